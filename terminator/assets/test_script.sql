@@ -1,11 +1,215 @@
+-- Rows that refer to each other look their IDs up by title, text or code rather than assuming
+-- what AUTOINCREMENT will hand out, so the script can be run on a database that already has data.
+
 -- Users Table
-INSERT INTO Users (Username, Password) VALUES ("Jake", "PASSWORD");
+INSERT OR IGNORE INTO Users (Username, Password) VALUES ("Jake", "PASSWORD");
 
 -- Exams Table
 INSERT INTO Exams (Title, Description) VALUES ("EXAM1", "Test Exam...");
 
 -- ExamCreations Table
-INSERT INTO ExamCreation (ExamID, CreatorUsername, DateCreated) VALUES (1, "Jake", "03NOV23");
+INSERT INTO ExamCreation (ExamID, CreatorUsername, DateCreated) VALUES ((SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), "Jake", "03NOV23");
+
+-- Options written the old way, as one newline-joined string, are moved into QuestionOptions by
+-- create_database after this script runs.
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID) VALUES
+    ("Test question prompt:", "A.) 1\nB.) 2\nC.) 3", "B.) 2", "Because I said so...", (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'));
+
+-- Shell questions are graded by comparing the simulated system against the ExpectedState
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID, QuestionType) VALUES
+    ('The script ~/backup.sh should be readable, writable and executable by its owner, readable and executable by its group, and inaccessible to everyone else. Set its permissions.',
+     '', 'chmod 750 ~/backup.sh',
+     'Owner rwx = 7, group r-x = 5, others --- = 0. chmod u=rwx,g=rx,o= ~/backup.sh does the same thing.',
+     (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'Shell');
+
+INSERT INTO CommandQuestions (QuestionID, InitialState, ExpectedState) VALUES
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'The script ~/backup.sh%'),
+     'user student
+file /home/student/backup.sh 644 student:student "#!/bin/bash\ntar -czf /tmp/home.tar.gz /home/student\n"
+file /home/student/notes.txt 644 student:student "Remember to run the backup on Fridays.\n"',
+     'file /home/student/backup.sh 750 student:student');
+
+-- Command questions are graded by comparing the program, flags and arguments, so `ls -al`,
+-- `ls -l -a` and `ls --all -l` are all accepted here. Each line of CorrectAnswer is an
+-- acceptable answer, and CommandFlagRules can make flags required, optional or forbidden.
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID, QuestionType) VALUES
+    ('Enter a command that lists every file in the current directory, including hidden files, in long format.',
+     '', 'ls -la',
+     '-l gives the long listing and -a includes entries starting with a dot. -h is fine to add for human-readable sizes.',
+     (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'Command');
+
+INSERT INTO CommandFlagRules (QuestionID, Program, Flag, Rule) VALUES
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Enter a command that lists every file%'), 'ls', '-h', 'Optional'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Enter a command that lists every file%'), 'ls', '-R', 'Forbidden');
+
+-- IOS questions start the device with the InitialState config applied, and the ExpectedState is
+-- applied on top of the InitialState to get the running-config the learner should end up with.
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID, QuestionType) VALUES
+    ('Configure R1''s GigabitEthernet0/1 interface with the address 192.168.10.1/24 and bring it up.',
+     '', 'enable, configure terminal, interface g0/1, ip address 192.168.10.1 255.255.255.0, no shutdown',
+     'Interfaces are configured from interface configuration mode. Router interfaces are shut down by default, so no shutdown is needed to bring it up.',
+     (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'Ios');
+
+INSERT INTO CommandQuestions (QuestionID, InitialState, ExpectedState) VALUES
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Configure R1''s GigabitEthernet0/1%'),
+     'hostname R1
+interface GigabitEthernet0/0
+ ip address 10.0.0.1 255.255.255.252
+ no shutdown',
+     'interface GigabitEthernet0/1
+ ip address 192.168.10.1 255.255.255.0
+ no shutdown');
+
+-- Cmd and PowerShell questions show the simulated output of what the learner types, then grade
+-- it. Switches and names are compared without regard to case, and `/x` is the same as `-x`.
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID, QuestionType) VALUES
+    ('Show every connection and listening port by number, along with the PID of the process that owns it.',
+     '', 'netstat -ano',
+     '-a shows all connections and listening ports, -n skips name resolution, and -o adds the owning process ID.',
+     (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'Cmd');
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID, QuestionType) VALUES
+    ('Using PowerShell, list every file under the Documents folder, including those in subfolders.',
+     '', 'Get-ChildItem -Path C:\Users\student\Documents -Recurse
+Get-ChildItem -Path Documents -Recurse',
+     'Get-ChildItem (alias gci, ls or dir) lists a folder, and -Recurse includes its subfolders.',
+     (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'PowerShell');
+
+-- Labs are multi-step scenarios in the simulated shell. Each step is done once the system
+-- matches its GoalCheck, which uses the same format as a command question's ExpectedState.
+INSERT INTO Labs (ExamID, Title, Description, InitialState) VALUES
+    ((SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'Web server down',
+     'The web server on web01 can''t be reached. Find out why and fix it.',
+     'user admin
+hostname web01
+service nginx inactive disabled
+service ssh active enabled
+process 1 root /sbin/init
+process 412 root /usr/sbin/sshd
+file /etc/nginx/nginx.conf 644 root:root "user www-data;\nhttp {\n    server {\n        listen 80;\n        root /var/www/html;\n    }\n}\n"
+file /var/www/html/index.html 600 root:root "<h1>Welcome to web01</h1>\n"');
+
+INSERT INTO LabTasks (LabID, StepNumber, Instructions, GoalCheck, Hint) VALUES
+    ((SELECT MAX(LabID) FROM Labs WHERE Title = 'Web server down'), 1, 'curl localhost is refused. Find out which web server is installed and start it.',
+     'service nginx active', 'systemctl status nginx shows whether it is running. Starting it needs sudo.'),
+    ((SELECT MAX(LabID) FROM Labs WHERE Title = 'Web server down'), 2, 'Make sure the web server will still be running after the next reboot.',
+     'service nginx active enabled', 'systemctl enable starts a service at boot.'),
+    ((SELECT MAX(LabID) FROM Labs WHERE Title = 'Web server down'), 3, 'curl localhost now gets a 403 Forbidden. Fix the page so the web server can read it.',
+     'file /var/www/html/index.html 644', 'The web server runs as www-data, so others need read permission on the page.');
 
+-- New multiple choice questions list their options in QuestionOptions instead. They're shown in a
+-- random order, so the option text shouldn't refer to another option's letter.
 INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID) VALUES
-    ("Test question prompt:", "A.) 1\nB.) 2\nC.) 3", "B.) 2", "Because I said so...", 1);
\ No newline at end of file
+    ('Which port does HTTPS use by default?', '', '443',
+     'HTTPS is HTTP over TLS on TCP port 443. Port 80 is plain HTTP, 22 is SSH and 8443 is a common alternate.', (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'));
+
+INSERT INTO QuestionOptions (QuestionID, Position, OptionText, IsCorrect) VALUES
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Which port does HTTPS%'), 1, '22', 0),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Which port does HTTPS%'), 2, '80', 0),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Which port does HTTPS%'), 3, '443', 1),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Which port does HTTPS%'), 4, '8443', 0);
+
+-- Multiple response questions mark every correct option, and are answered with e.g. `A,C`.
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID, QuestionType) VALUES
+    ('Which of the following protocols are used to send or retrieve email? (Select TWO)', '', 'SMTP, IMAP',
+     'SMTP (25/587) sends mail and IMAP (143/993) retrieves it. SNMP manages network devices and SFTP transfers files.',
+     (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'MultipleResponse');
+
+INSERT INTO QuestionOptions (QuestionID, Position, OptionText, IsCorrect) VALUES
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Which of the following protocols are used to send%'), 1, 'SMTP', 1),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Which of the following protocols are used to send%'), 2, 'SNMP', 0),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Which of the following protocols are used to send%'), 3, 'IMAP', 1),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Which of the following protocols are used to send%'), 4, 'SFTP', 0);
+
+-- Matching questions pair each Prompt with its Match. A row without a Prompt only adds an extra
+-- item to the list of matches.
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID, QuestionType) VALUES
+    ('Match each port to the protocol that uses it by default.', '', '22 SSH, 53 DNS, 3389 RDP, 161 SNMP',
+     'SSH is TCP 22, DNS is 53 (UDP and TCP), RDP is TCP 3389 and SNMP is UDP 161. Telnet (23) is not used.',
+     (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'Matching');
+
+INSERT INTO MatchingPairs (QuestionID, Position, Prompt, Match) VALUES
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Match each port%'), 1, '22', 'SSH'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Match each port%'), 2, '53', 'DNS'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Match each port%'), 3, '3389', 'RDP'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Match each port%'), 4, '161', 'SNMP'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Match each port%'), 5, NULL, 'Telnet');
+
+-- Ordering questions list their OrderingItems in the correct order. OrderingQuestions sets the
+-- ScoringRule; without a row, only the exact order earns credit.
+INSERT INTO Questions (QuestionText, Options, CorrectAnswer, Explanation, ExamID, QuestionType) VALUES
+    ('Put the steps of the CompTIA troubleshooting methodology in order.', '', '',
+     'Theory comes before testing, and a plan is made before it is implemented. Verify before documenting.',
+     (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 'Ordering');
+
+INSERT INTO OrderingQuestions (QuestionID, ScoringRule) VALUES
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Put the steps of the CompTIA%'), 'Kendall');
+
+INSERT INTO OrderingItems (QuestionID, Position, ItemText) VALUES
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Put the steps of the CompTIA%'), 1, 'Identify the problem'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Put the steps of the CompTIA%'), 2, 'Establish a theory of probable cause'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Put the steps of the CompTIA%'), 3, 'Test the theory to determine the cause'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Put the steps of the CompTIA%'), 4, 'Establish a plan of action and implement the solution'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Put the steps of the CompTIA%'), 5, 'Verify full system functionality and implement preventive measures'),
+    ((SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE 'Put the steps of the CompTIA%'), 6, 'Document findings, actions and outcomes');
+
+-- The exam's catalog: its Domains with their weighting in percent, and the Objectives within each.
+-- EXAM1 uses the CompTIA Network+ (N10-008) objectives.
+INSERT INTO Domains (ExamID, Code, Name, Weight) VALUES
+    ((SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), '1.0', 'Networking Fundamentals', 24),
+    ((SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), '2.0', 'Network Implementations', 19),
+    ((SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), '3.0', 'Network Operations', 16),
+    ((SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), '4.0', 'Network Security', 19),
+    ((SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), '5.0', 'Network Troubleshooting', 22);
+
+INSERT INTO Objectives (DomainID, Code, Title) VALUES
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '1.0'), '1.1', 'Compare and contrast the OSI model layers and encapsulation concepts'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '1.0'), '1.2', 'Explain the characteristics of network topologies and network types'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '1.0'), '1.3', 'Summarize the types of cables and connectors and explain which is the appropriate type for a solution'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '1.0'), '1.4', 'Given a scenario, configure a subnet and use appropriate IP addressing schemes'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '1.0'), '1.5', 'Explain common ports and protocols, their application, and encrypted alternatives'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '1.0'), '1.6', 'Explain the use and purpose of network services'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '1.0'), '1.7', 'Explain basic corporate and datacenter network architecture'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '1.0'), '1.8', 'Summarize cloud concepts and connectivity options'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '2.0'), '2.1', 'Compare and contrast various devices, their features, and their appropriate placement on the network'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '2.0'), '2.2', 'Compare and contrast routing technologies and bandwidth management concepts'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '2.0'), '2.3', 'Given a scenario, configure and deploy common Ethernet switching features'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '2.0'), '2.4', 'Given a scenario, install and configure the appropriate wireless standards and technologies'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '3.0'), '3.1', 'Given a scenario, use the appropriate statistics and sensors to ensure network availability'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '3.0'), '3.2', 'Explain the purpose of organizational documents and policies'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '3.0'), '3.3', 'Explain high availability and disaster recovery concepts and summarize which is the best solution'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '4.0'), '4.1', 'Explain common security concepts'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '4.0'), '4.2', 'Compare and contrast common types of attacks'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '4.0'), '4.3', 'Given a scenario, apply network hardening techniques'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '4.0'), '4.4', 'Compare and contrast remote access methods and security implications'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '4.0'), '4.5', 'Explain the importance of physical security'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '5.0'), '5.1', 'Explain the network troubleshooting methodology'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '5.0'), '5.2', 'Given a scenario, troubleshoot common cable connectivity issues and select the appropriate tools'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '5.0'), '5.3', 'Given a scenario, use the appropriate network software tools and commands'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '5.0'), '5.4', 'Given a scenario, troubleshoot common wireless connectivity issues'),
+    ((SELECT DomainID FROM Domains WHERE ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1') AND Code = '5.0'), '5.5', 'Given a scenario, troubleshoot general networking issues');
+
+-- QuestionObjectives tags questions with the objectives they cover, matched here by the start of
+-- the question's text and the objective's code.
+INSERT INTO QuestionObjectives (QuestionID, ObjectiveID)
+SELECT Questions.QuestionID, Objectives.ObjectiveID
+FROM (VALUES
+    ('Test question prompt:', '1.1'),
+    ('The script ~/backup.sh', '4.3'),
+    ('Enter a command that lists every file', '5.3'),
+    ('Configure R1''s GigabitEthernet0/1', '2.2'),
+    ('Show every connection and listening port', '5.3'),
+    ('Using PowerShell, list every file', '5.3'),
+    ('Which port does HTTPS', '1.5'),
+    ('Which of the following protocols are used to send', '1.5'),
+    ('Match each port', '1.5'),
+    ('Put the steps of the CompTIA', '5.1')
+) AS Tagged
+JOIN Questions ON Questions.QuestionID = (
+    SELECT MAX(QuestionID) FROM Questions WHERE QuestionText LIKE Tagged.column1 || '%'
+)
+JOIN Domains ON Domains.ExamID = (SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1')
+JOIN Objectives ON Objectives.DomainID = Domains.DomainID AND Objectives.Code = Tagged.column2;
+
+-- ExamBlueprints says how mock exams are put together. The questions are split by domain weight.
+INSERT INTO ExamBlueprints (ExamID, QuestionCount, TimeLimitMinutes, PassingScore) VALUES
+    ((SELECT MAX(ExamID) FROM Exams WHERE Title = 'EXAM1'), 8, 20, 720);
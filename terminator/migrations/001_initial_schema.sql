@@ -0,0 +1,272 @@
+-- Migration 1: the schema as it stood before migrations were tracked. Every statement uses
+-- IF NOT EXISTS so that databases made by older versions of create_database are adopted; any of
+-- their tables in an older shape have already been rebuilt to match these by the time this runs.
+
+CREATE TABLE IF NOT EXISTS Users (
+    Username TEXT NOT NULL UNIQUE,
+    Password TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS Exams (
+    ExamID INTEGER PRIMARY KEY AUTOINCREMENT,
+    Title TEXT NOT NULL,
+    Description TEXT
+);
+
+CREATE TABLE IF NOT EXISTS ExamCreation (
+    ExamID INTEGER NOT NULL,
+    CreatorUsername TEXT NOT NULL,
+    DateCreated TEXT NOT NULL,
+    PRIMARY KEY (ExamID, CreatorUsername),
+    FOREIGN KEY (ExamID) REFERENCES Exams(ExamID),
+    FOREIGN KEY (CreatorUsername) REFERENCES Users(Username)
+);
+
+CREATE TABLE IF NOT EXISTS Questions (
+    QuestionID INTEGER PRIMARY KEY AUTOINCREMENT,
+    ExamID INTEGER NOT NULL,
+    QuestionText TEXT NOT NULL,
+    Options TEXT NOT NULL,
+    CorrectAnswer TEXT NOT NULL,
+    Explanation TEXT NOT NULL,
+    QuestionType TEXT NOT NULL DEFAULT 'MultipleChoice',
+    FOREIGN KEY (ExamID) REFERENCES Exams(ExamID)
+);
+
+CREATE TABLE IF NOT EXISTS QuestionOptions (
+    OptionID INTEGER PRIMARY KEY AUTOINCREMENT,
+    QuestionID INTEGER NOT NULL,
+    Position INTEGER NOT NULL,
+    OptionText TEXT NOT NULL,
+    IsCorrect INTEGER NOT NULL DEFAULT 0,
+    UNIQUE (QuestionID, Position),
+    FOREIGN KEY (QuestionID) REFERENCES Questions(QuestionID)
+);
+
+CREATE TABLE IF NOT EXISTS MatchingPairs (
+    PairID INTEGER PRIMARY KEY AUTOINCREMENT,
+    QuestionID INTEGER NOT NULL,
+    Position INTEGER NOT NULL,
+    Prompt TEXT,
+    Match TEXT NOT NULL,
+    UNIQUE (QuestionID, Position),
+    FOREIGN KEY (QuestionID) REFERENCES Questions(QuestionID)
+);
+
+CREATE TABLE IF NOT EXISTS OrderingQuestions (
+    QuestionID INTEGER PRIMARY KEY,
+    ScoringRule TEXT NOT NULL DEFAULT 'Exact' CHECK (ScoringRule IN ('Exact', 'Kendall')),
+    FOREIGN KEY (QuestionID) REFERENCES Questions(QuestionID)
+);
+
+CREATE TABLE IF NOT EXISTS OrderingItems (
+    ItemID INTEGER PRIMARY KEY AUTOINCREMENT,
+    QuestionID INTEGER NOT NULL,
+    Position INTEGER NOT NULL,
+    ItemText TEXT NOT NULL,
+    UNIQUE (QuestionID, Position),
+    FOREIGN KEY (QuestionID) REFERENCES Questions(QuestionID)
+);
+
+-- The domains of an exam's published objectives, e.g., '1.0', with their share of the exam
+-- in percent
+CREATE TABLE IF NOT EXISTS Domains (
+    DomainID INTEGER PRIMARY KEY AUTOINCREMENT,
+    ExamID INTEGER NOT NULL,
+    Code TEXT NOT NULL,
+    Name TEXT NOT NULL,
+    Weight INTEGER NOT NULL CHECK (Weight BETWEEN 0 AND 100),
+    UNIQUE (ExamID, Code),
+    FOREIGN KEY (ExamID) REFERENCES Exams(ExamID)
+);
+
+CREATE TABLE IF NOT EXISTS Objectives (
+    ObjectiveID INTEGER PRIMARY KEY AUTOINCREMENT,
+    DomainID INTEGER NOT NULL,
+    Code TEXT NOT NULL,
+    Title TEXT NOT NULL,
+    UNIQUE (DomainID, Code),
+    FOREIGN KEY (DomainID) REFERENCES Domains(DomainID)
+);
+
+-- Tags questions with the objectives they cover. A question may cover several.
+CREATE TABLE IF NOT EXISTS QuestionObjectives (
+    QuestionID INTEGER NOT NULL,
+    ObjectiveID INTEGER NOT NULL,
+    PRIMARY KEY (QuestionID, ObjectiveID),
+    FOREIGN KEY (QuestionID) REFERENCES Questions(QuestionID),
+    FOREIGN KEY (ObjectiveID) REFERENCES Objectives(ObjectiveID)
+);
+
+CREATE TABLE IF NOT EXISTS CommandQuestions (
+    QuestionID INTEGER PRIMARY KEY,
+    InitialState TEXT NOT NULL,
+    ExpectedState TEXT NOT NULL,
+    FOREIGN KEY (QuestionID) REFERENCES Questions(QuestionID)
+);
+
+CREATE TABLE IF NOT EXISTS CommandFlagRules (
+    RuleID INTEGER PRIMARY KEY AUTOINCREMENT,
+    QuestionID INTEGER NOT NULL,
+    Program TEXT,
+    Flag TEXT NOT NULL,
+    Rule TEXT NOT NULL CHECK (Rule IN ('Required', 'Optional', 'Forbidden')),
+    FOREIGN KEY (QuestionID) REFERENCES Questions(QuestionID)
+);
+
+-- Generated questions aren't in the Questions table, so their responses are linked to the
+-- saved parameters in GeneratedQuestions instead.
+CREATE TABLE IF NOT EXISTS GeneratedQuestions (
+    GeneratedID INTEGER PRIMARY KEY AUTOINCREMENT,
+    Generator TEXT NOT NULL,
+    Parameters TEXT NOT NULL,
+    Difficulty TEXT CHECK (Difficulty IN ('Easy', 'Medium', 'Hard')),
+    DateCreated TEXT
+);
+
+-- One row per sitting of an exam. EndTime is NULL until the attempt is finished, and the
+-- scaled score columns are only filled in for mock exams.
+CREATE TABLE IF NOT EXISTS ExamAttempts (
+    AttemptID INTEGER PRIMARY KEY AUTOINCREMENT,
+    Username TEXT NOT NULL,
+    ExamID INTEGER NOT NULL,
+    Mode TEXT NOT NULL CHECK (Mode IN ('Practice', 'Adaptive', 'Mock')),
+    StartTime TEXT NOT NULL,
+    EndTime TEXT,
+    NumQuestions INTEGER NOT NULL DEFAULT 0,
+    Score REAL NOT NULL DEFAULT 0,
+    ScaledScore INTEGER,
+    PassingScore INTEGER,
+    Passed INTEGER,
+    FOREIGN KEY (Username) REFERENCES Users (Username),
+    FOREIGN KEY (ExamID) REFERENCES Exams (ExamID)
+);
+
+CREATE TABLE IF NOT EXISTS UserQuestionResponses (
+    ResponseID INTEGER PRIMARY KEY AUTOINCREMENT,
+    Username TEXT NOT NULL,
+    QuestionID INTEGER,
+    GeneratedID INTEGER,
+    AttemptID INTEGER,
+    IsCorrect INTEGER DEFAULT 0,
+    Score REAL NOT NULL DEFAULT 0 CHECK (Score BETWEEN 0 AND 1),
+    ResponseText TEXT,
+    Timestamp TEXT,
+    CHECK ((QuestionID IS NULL) <> (GeneratedID IS NULL)),
+    FOREIGN KEY (Username) REFERENCES Users (Username),
+    FOREIGN KEY (QuestionID) REFERENCES Questions (QuestionID),
+    FOREIGN KEY (GeneratedID) REFERENCES GeneratedQuestions (GeneratedID),
+    FOREIGN KEY (AttemptID) REFERENCES ExamAttempts (AttemptID)
+);
+
+CREATE TABLE IF NOT EXISTS UserResponseOptions (
+    ResponseID INTEGER NOT NULL,
+    OptionID INTEGER NOT NULL,
+    PRIMARY KEY (ResponseID, OptionID),
+    FOREIGN KEY (ResponseID) REFERENCES UserQuestionResponses (ResponseID),
+    FOREIGN KEY (OptionID) REFERENCES QuestionOptions (OptionID)
+);
+
+CREATE TABLE IF NOT EXISTS UserResponsePairs (
+    ResponseID INTEGER NOT NULL,
+    PairID INTEGER NOT NULL,
+    ChosenPairID INTEGER,
+    IsCorrect INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (ResponseID, PairID),
+    FOREIGN KEY (ResponseID) REFERENCES UserQuestionResponses (ResponseID),
+    FOREIGN KEY (PairID) REFERENCES MatchingPairs (PairID),
+    FOREIGN KEY (ChosenPairID) REFERENCES MatchingPairs (PairID)
+);
+
+CREATE TABLE IF NOT EXISTS Labs (
+    LabID INTEGER PRIMARY KEY AUTOINCREMENT,
+    ExamID INTEGER NOT NULL,
+    Title TEXT NOT NULL,
+    Description TEXT,
+    InitialState TEXT NOT NULL,
+    FOREIGN KEY (ExamID) REFERENCES Exams(ExamID)
+);
+
+CREATE TABLE IF NOT EXISTS LabTasks (
+    TaskID INTEGER PRIMARY KEY AUTOINCREMENT,
+    LabID INTEGER NOT NULL,
+    StepNumber INTEGER NOT NULL,
+    Instructions TEXT NOT NULL,
+    GoalCheck TEXT NOT NULL,
+    Hint TEXT,
+    UNIQUE (LabID, StepNumber),
+    FOREIGN KEY (LabID) REFERENCES Labs(LabID)
+);
+
+CREATE TABLE IF NOT EXISTS UserLabProgress (
+    Username TEXT NOT NULL,
+    LabID INTEGER NOT NULL,
+    StepsCompleted INTEGER NOT NULL DEFAULT 0,
+    State TEXT NOT NULL,
+    Completed INTEGER NOT NULL DEFAULT 0,
+    Timestamp TEXT,
+    PRIMARY KEY (Username, LabID),
+    FOREIGN KEY (Username) REFERENCES Users (Username),
+    FOREIGN KEY (LabID) REFERENCES Labs (LabID)
+);
+
+-- Difficulty is NULL for drills that don't have levels
+CREATE TABLE IF NOT EXISTS DrillStreaks (
+    StreakID INTEGER PRIMARY KEY AUTOINCREMENT,
+    Username TEXT NOT NULL,
+    Generator TEXT NOT NULL,
+    Difficulty TEXT CHECK (Difficulty IN ('Easy', 'Medium', 'Hard')),
+    Length INTEGER NOT NULL,
+    Seconds REAL NOT NULL,
+    Timestamp TEXT,
+    FOREIGN KEY (Username) REFERENCES Users (Username)
+);
+
+-- Card is the protocol's short name in the built-in port reference table, and DatasetVersion
+-- is the version of that table the card was asked from.
+CREATE TABLE IF NOT EXISTS FlashcardHistory (
+    HistoryID INTEGER PRIMARY KEY AUTOINCREMENT,
+    Username TEXT NOT NULL,
+    Card TEXT NOT NULL,
+    Direction TEXT NOT NULL CHECK (Direction IN ('PortToProtocol', 'ProtocolToPort')),
+    IsCorrect INTEGER NOT NULL DEFAULT 0,
+    DatasetVersion INTEGER NOT NULL,
+    Timestamp TEXT,
+    FOREIGN KEY (Username) REFERENCES Users (Username)
+);
+
+-- The SM-2 state for each question a user has answered. It's worked out from their
+-- UserQuestionResponses, up to and including LastResponseID.
+CREATE TABLE IF NOT EXISTS ReviewSchedule (
+    Username TEXT NOT NULL,
+    QuestionID INTEGER NOT NULL,
+    Ease REAL NOT NULL DEFAULT 2.5 CHECK (Ease >= 1.3),
+    IntervalDays INTEGER NOT NULL DEFAULT 0,
+    Repetitions INTEGER NOT NULL DEFAULT 0,
+    DueDate TEXT NOT NULL,
+    LastResponseID INTEGER NOT NULL,
+    PRIMARY KEY (Username, QuestionID),
+    FOREIGN KEY (Username) REFERENCES Users (Username),
+    FOREIGN KEY (QuestionID) REFERENCES Questions (QuestionID),
+    FOREIGN KEY (LastResponseID) REFERENCES UserQuestionResponses (ResponseID)
+);
+
+-- How a mock exam is put together. Exams without a row use 90 questions in 90 minutes.
+CREATE TABLE IF NOT EXISTS ExamBlueprints (
+    ExamID INTEGER PRIMARY KEY,
+    QuestionCount INTEGER NOT NULL CHECK (QuestionCount > 0),
+    TimeLimitMinutes INTEGER NOT NULL CHECK (TimeLimitMinutes > 0),
+    PassingScore INTEGER NOT NULL CHECK (PassingScore BETWEEN 100 AND 900),
+    FOREIGN KEY (ExamID) REFERENCES Exams (ExamID)
+);
+
+-- How a mock exam attempt went in each domain of the exam
+CREATE TABLE IF NOT EXISTS AttemptDomainScores (
+    AttemptID INTEGER NOT NULL,
+    DomainID INTEGER NOT NULL,
+    Score REAL NOT NULL,
+    NumQuestions INTEGER NOT NULL,
+    PRIMARY KEY (AttemptID, DomainID),
+    FOREIGN KEY (AttemptID) REFERENCES ExamAttempts (AttemptID),
+    FOREIGN KEY (DomainID) REFERENCES Domains (DomainID)
+);
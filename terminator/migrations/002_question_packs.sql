@@ -0,0 +1,51 @@
+-- Migration 2: stable IDs for exams and questions so question packs can be imported again without
+-- making copies, plus the tags and references a pack can give each question.
+
+ALTER TABLE Exams ADD COLUMN UUID TEXT;
+ALTER TABLE Questions ADD COLUMN UUID TEXT;
+
+-- A random version 4 UUID for each existing row
+UPDATE Exams SET UUID = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' ||
+    substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+    substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)));
+UPDATE Questions SET UUID = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' ||
+    substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+    substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)));
+
+CREATE UNIQUE INDEX ExamsUUID ON Exams (UUID);
+CREATE UNIQUE INDEX QuestionsUUID ON Questions (UUID);
+
+-- Rows added without a UUID (e.g., by a SQL script or the port importer) are given one, so every
+-- exam and question can be exported
+CREATE TRIGGER ExamsDefaultUUID AFTER INSERT ON Exams WHEN NEW.UUID IS NULL
+BEGIN
+    UPDATE Exams SET UUID = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' ||
+        substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+        substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))
+    WHERE ExamID = NEW.ExamID;
+END;
+
+CREATE TRIGGER QuestionsDefaultUUID AFTER INSERT ON Questions WHEN NEW.UUID IS NULL
+BEGIN
+    UPDATE Questions SET UUID = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' ||
+        substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+        substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))
+    WHERE QuestionID = NEW.QuestionID;
+END;
+
+-- Free-form labels for finding questions, e.g., 'ports' or 'subnetting'
+CREATE TABLE QuestionTags (
+    QuestionID INTEGER NOT NULL,
+    Tag TEXT NOT NULL,
+    PRIMARY KEY (QuestionID, Tag),
+    FOREIGN KEY (QuestionID) REFERENCES Questions (QuestionID)
+);
+
+-- Where to read more about a question's topic, e.g., an RFC or a man page
+CREATE TABLE QuestionReferences (
+    QuestionID INTEGER NOT NULL,
+    Position INTEGER NOT NULL,
+    Reference TEXT NOT NULL,
+    PRIMARY KEY (QuestionID, Position),
+    FOREIGN KEY (QuestionID) REFERENCES Questions (QuestionID)
+);
@@ -0,0 +1,33 @@
+-- Migration 3: stable IDs for the options and pairs of questions, so that importing a question
+-- pack again can tell an edited option from a new one, and the responses that chose it stay
+-- attached to it.
+
+ALTER TABLE QuestionOptions ADD COLUMN UUID TEXT;
+ALTER TABLE MatchingPairs ADD COLUMN UUID TEXT;
+
+-- A random version 4 UUID for each existing row
+UPDATE QuestionOptions SET UUID = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' ||
+    substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+    substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)));
+UPDATE MatchingPairs SET UUID = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' ||
+    substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+    substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)));
+
+CREATE UNIQUE INDEX QuestionOptionsUUID ON QuestionOptions (UUID);
+CREATE UNIQUE INDEX MatchingPairsUUID ON MatchingPairs (UUID);
+
+CREATE TRIGGER QuestionOptionsDefaultUUID AFTER INSERT ON QuestionOptions WHEN NEW.UUID IS NULL
+BEGIN
+    UPDATE QuestionOptions SET UUID = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' ||
+        substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+        substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))
+    WHERE OptionID = NEW.OptionID;
+END;
+
+CREATE TRIGGER MatchingPairsDefaultUUID AFTER INSERT ON MatchingPairs WHEN NEW.UUID IS NULL
+BEGIN
+    UPDATE MatchingPairs SET UUID = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' ||
+        substr(hex(randomblob(2)), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+        substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6)))
+    WHERE PairID = NEW.PairID;
+END;
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use rand::Rng;
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rusqlite::{params, Connection};
+
+/// The share of picks that ignore the user's accuracy, unless they choose otherwise.
+pub const DEFAULT_EXPLORATION: f64 = 0.2;
+
+/// How the user has done on one exam objective so far: the credit earned and the number of
+/// answers, across every question tagged with it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Accuracy {
+    pub score: f64,
+    pub answered: usize,
+}
+
+impl Accuracy {
+    /// The accuracy, smoothed towards 50% so that a single answer doesn't decide it, and so that
+    /// objectives without any answers count as 50%.
+    pub fn estimate(&self) -> f64 {
+        (self.score + 1.0) / (self.answered as f64 + 2.0)
+    }
+
+    /// The estimate along with the raw accuracy it was smoothed from, e.g.,
+    /// `estimated 40% (33% over 4 answer(s))`.
+    pub fn describe(&self) -> String {
+        if self.answered == 0 {
+            format!("estimated {:.0}% (no answers yet)", self.estimate() * 100.0)
+        } else {
+            format!(
+                "estimated {:.0}% ({:.0}% over {} answer(s))",
+                self.estimate() * 100.0,
+                self.score / self.answered as f64 * 100.0,
+                self.answered,
+            )
+        }
+    }
+}
+
+/// Why a question was picked for an adaptive session.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    /// Picked from `objective` because of its low accuracy.
+    Weak { objective: String, estimate: f64 },
+    /// Picked at random, so that strong objectives (and untagged questions) still come up.
+    Explore,
+}
+
+/// Queries `UserQuestionResponses` for the user's accuracy on each objective of the exam with the
+/// given `exam_id`. A response counts toward every objective its question is tagged with.
+pub fn objective_accuracy(conn: &Connection, username: &str, exam_id: i32) -> rusqlite::Result<HashMap<String, Accuracy>> {
+    let mut stmt = conn.prepare(
+        "SELECT o.Code, r.Score FROM UserQuestionResponses r \
+        JOIN QuestionObjectives qo ON qo.QuestionID = r.QuestionID \
+        JOIN Objectives o ON o.ObjectiveID = qo.ObjectiveID \
+        JOIN Questions q ON q.QuestionID = r.QuestionID \
+        WHERE r.Username = ?1 AND q.ExamID = ?2"
+    )?;
+    let rows = stmt.query_map(params![username, exam_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+    let mut accuracy: HashMap<String, Accuracy> = HashMap::new();
+    for row in rows {
+        let (objective, score) = row?;
+        let entry: &mut Accuracy = accuracy.entry(objective).or_default();
+        entry.score += score;
+        entry.answered += 1;
+    }
+    Ok(accuracy)
+}
+
+/// Picks up to `size` questions for an adaptive session. `objectives` holds the objectives each
+/// question is tagged with, in the same order as the exam's questions. For each pick, with
+/// probability `exploration` any remaining question is chosen at random; otherwise an objective
+/// is drawn with weight `1 - estimate` (so weaker objectives come up more often) and one of its
+/// remaining questions is chosen. Returns the index of each question picked and why.
+pub fn plan(objectives: &[Vec<String>], accuracy: &HashMap<String, Accuracy>, size: usize, exploration: f64,
+            rng: &mut impl Rng) -> Vec<(usize, Reason)> {
+    let mut remaining: Vec<usize> = (0..objectives.len()).collect();
+    let mut picks: Vec<(usize, Reason)> = Vec::new();
+    while picks.len() < size && !remaining.is_empty() {
+        // Only objectives that still have questions left can be drawn
+        let mut candidates: Vec<&String> = remaining.iter().flat_map(|i| objectives[*i].iter()).collect();
+        candidates.sort();
+        candidates.dedup();
+
+        let pick: (usize, Reason) = if candidates.is_empty() || rng.gen_bool(exploration.clamp(0.0, 1.0)) {
+            (remaining[rng.gen_range(0..remaining.len())], Reason::Explore)
+        } else {
+            let estimates: Vec<f64> = candidates.iter()
+                .map(|objective| accuracy.get(*objective).copied().unwrap_or_default().estimate())
+                .collect();
+            let weights: Vec<f64> = estimates.iter().map(|estimate| 1.0 - estimate).collect();
+            let chosen: usize = WeightedIndex::new(&weights).expect("Accuracy estimates are below 100%").sample(rng);
+            let objective: &String = candidates[chosen];
+            let tagged: Vec<usize> = remaining.iter().copied().filter(|i| objectives[*i].contains(objective)).collect();
+            let question: usize = tagged[rng.gen_range(0..tagged.len())];
+            (question, Reason::Weak { objective: objective.clone(), estimate: estimates[chosen] })
+        };
+        remaining.retain(|i| *i != pick.0);
+        picks.push(pick);
+    }
+    picks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// `weak` questions on objective 1.1, which every answer so far got wrong, then `strong`
+    /// questions on 1.2, which every answer got right, then `untagged` questions.
+    fn exam(weak: usize, strong: usize, untagged: usize) -> (Vec<Vec<String>>, HashMap<String, Accuracy>) {
+        let mut objectives: Vec<Vec<String>> = vec![vec![String::from("1.1")]; weak];
+        objectives.extend(vec![vec![String::from("1.2")]; strong]);
+        objectives.extend(vec![Vec::new(); untagged]);
+        let accuracy: HashMap<String, Accuracy> = HashMap::from([
+            (String::from("1.1"), Accuracy { score: 0.0, answered: 20 }),
+            (String::from("1.2"), Accuracy { score: 20.0, answered: 20 }),
+        ]);
+        (objectives, accuracy)
+    }
+
+    #[test]
+    fn smooths_accuracy_towards_half() {
+        assert_eq!(Accuracy::default().estimate(), 0.5);
+        assert_eq!(Accuracy { score: 1.0, answered: 1 }.estimate(), 2.0 / 3.0);
+        assert_eq!(Accuracy { score: 0.0, answered: 4 }.estimate(), 1.0 / 6.0);
+        assert_eq!(Accuracy { score: 2.5, answered: 3 }.estimate(), 0.7);
+        assert_eq!(Accuracy::default().describe(), "estimated 50% (no answers yet)");
+        assert_eq!(Accuracy { score: 1.0, answered: 3 }.describe(), "estimated 40% (33% over 3 answer(s))");
+    }
+
+    #[test]
+    fn draws_weak_objectives_more_often() {
+        let (objectives, accuracy) = exam(50, 50, 0);
+        let picks: Vec<(usize, Reason)> = plan(&objectives, &accuracy, 20, 0.0, &mut StdRng::seed_from_u64(7));
+        assert_eq!(picks.len(), 20);
+        let weak: usize = picks.iter().filter(|(i, _)| objectives[*i][0] == "1.1").count();
+        assert!(weak >= 15, "only {} of 20 picks were on the weak objective", weak);
+        for (i, reason) in &picks {
+            let estimate: f64 = accuracy[&objectives[*i][0]].estimate();
+            assert_eq!(*reason, Reason::Weak { objective: objectives[*i][0].clone(), estimate });
+        }
+    }
+
+    #[test]
+    fn explores_at_the_given_rate() {
+        let (objectives, accuracy) = exam(5, 5, 5);
+        let picks: Vec<(usize, Reason)> = plan(&objectives, &accuracy, 10, 1.0, &mut StdRng::seed_from_u64(7));
+        assert_eq!(picks.len(), 10);
+        assert!(picks.iter().all(|(_, reason)| *reason == Reason::Explore));
+
+        // Without exploration, untagged questions only come up once the tagged ones run out
+        let picks: Vec<(usize, Reason)> = plan(&objectives, &accuracy, 15, 0.0, &mut StdRng::seed_from_u64(7));
+        assert!(picks[..10].iter().all(|(i, reason)| *i < 10 && matches!(reason, Reason::Weak { .. })));
+        assert!(picks[10..].iter().all(|(i, reason)| *i >= 10 && *reason == Reason::Explore));
+    }
+
+    #[test]
+    fn never_picks_a_question_twice() {
+        let (objectives, accuracy) = exam(3, 2, 1);
+        for exploration in [0.0, 0.5, 1.0] {
+            let mut picked: Vec<usize> = plan(&objectives, &accuracy, 90, exploration, &mut StdRng::seed_from_u64(7))
+                .into_iter()
+                .map(|(i, _)| i)
+                .collect();
+            picked.sort();
+            assert_eq!(picked, (0..6).collect::<Vec<usize>>());
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use chrono::{DateTime, Local, Utc};
+use rusqlite::{params, Connection};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use crate::catalog::code_order;
+use crate::term_io::read_choice;
+
+/// How an exam was sat, as stored in `ExamAttempts.Mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Every question of the exam, in order, with feedback after each.
+    Practice,
+    /// A session that targets the user's weakest objectives.
+    Adaptive,
+    /// A timed mock exam, graded when it's submitted.
+    Mock,
+}
+
+impl Mode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Mode::Practice => "Practice",
+            Mode::Adaptive => "Adaptive",
+            Mode::Mock => "Mock",
+        }
+    }
+}
+
+impl FromSql for Mode {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "Practice" => Ok(Mode::Practice),
+            "Adaptive" => Ok(Mode::Adaptive),
+            "Mock" => Ok(Mode::Mock),
+            other => Err(FromSqlError::Other(format!("Unknown attempt mode: {}", other).into())),
+        }
+    }
+}
+
+/// A time stored by `to_rfc3339` in `ExamAttempts`. One that can't be read is an error rather than
+/// a guess, so the history never shows made-up dates or durations.
+struct Timestamp(DateTime<Utc>);
+
+impl FromSql for Timestamp {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text: &str = value.as_str()?;
+        DateTime::parse_from_rfc3339(text)
+            .map(|time| Timestamp(time.with_timezone(&Utc)))
+            .map_err(|_| FromSqlError::Other(format!("Invalid timestamp: {}", text).into()))
+    }
+}
+
+/// A single row from the `ExamAttempts` table: one sitting of an exam, in any mode. `end` is
+/// `None` for an attempt that was never finished (e.g., the program was closed part way).
+#[derive(Debug)]
+pub struct Attempt {
+    pub attempt_id: i64,
+    pub exam_title: String,
+    pub mode: Mode,
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub num_questions: usize,
+    /// The credit earned, out of `num_questions`.
+    pub score: f64,
+    /// The score on the 100-900 scale and the score needed to pass, for mock exams.
+    pub scaled_score: Option<u32>,
+    pub passing_score: Option<u32>,
+    pub passed: Option<bool>,
+}
+
+impl Attempt {
+    /// Queries the database for every attempt the user has made, newest first.
+    pub fn get_for_user(conn: &Connection, username: &str) -> rusqlite::Result<Vec<Attempt>> {
+        let mut stmt = conn.prepare(
+            "SELECT a.AttemptID, e.Title, a.Mode, a.StartTime, a.EndTime, a.NumQuestions, a.Score, \
+            a.ScaledScore, a.PassingScore, a.Passed \
+            FROM ExamAttempts a JOIN Exams e ON e.ExamID = a.ExamID \
+            WHERE a.Username = ?1 ORDER BY a.StartTime DESC, a.AttemptID DESC"
+        )?;
+        let attempts = stmt.query_map([username], |row| {
+            Ok(Attempt {
+                attempt_id: row.get(0)?,
+                exam_title: row.get(1)?,
+                mode: row.get(2)?,
+                start: row.get::<_, Timestamp>(3)?.0,
+                end: row.get::<_, Option<Timestamp>>(4)?.map(|end| end.0),
+                num_questions: row.get(5)?,
+                score: row.get(6)?,
+                scaled_score: row.get(7)?,
+                passing_score: row.get(8)?,
+                passed: row.get(9)?,
+            })
+        })?;
+        attempts.collect()
+    }
+
+    /// A one-line summary, e.g., `7.00 of 8 (88%), 800 PASS`.
+    fn summary(&self) -> String {
+        if self.end.is_none() {
+            return String::from("not finished");
+        }
+        let mut summary: String = format!(
+            "{:.2} of {} ({:.0}%)",
+            self.score,
+            self.num_questions,
+            self.score / self.num_questions.max(1) as f64 * 100.0,
+        );
+        if let (Some(scaled_score), Some(passed)) = (self.scaled_score, self.passed) {
+            summary.push_str(&format!(", {} {}", scaled_score, if passed { "PASS" } else { "FAIL" }));
+        }
+        summary
+    }
+}
+
+/// Adds a row to the `ExamAttempts` table for a sitting that began at `start`. Returns its
+/// `AttemptID`, which the sitting's responses are recorded with.
+pub fn start(conn: &Connection, username: &str, exam_id: i32, mode: Mode, start: DateTime<Utc>) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO ExamAttempts (Username, ExamID, Mode, StartTime, NumQuestions, Score) VALUES (?1, ?2, ?3, ?4, 0, 0)",
+        params![username, exam_id, mode.as_str(), start.to_rfc3339()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Marks the attempt with the given `attempt_id` as finished now, with the `score` earned over
+/// `num_questions`. `scaled` is the scaled score and the passing score for a mock exam.
+pub fn finish(conn: &Connection, attempt_id: i64, num_questions: usize, score: f64,
+              scaled: Option<(u32, u32)>) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE ExamAttempts SET EndTime = ?2, NumQuestions = ?3, Score = ?4, ScaledScore = ?5, \
+        PassingScore = ?6, Passed = ?7 WHERE AttemptID = ?1",
+        params![
+            attempt_id,
+            Utc::now().to_rfc3339(),
+            num_questions,
+            score,
+            scaled.map(|(scaled_score, _)| scaled_score),
+            scaled.map(|(_, passing_score)| passing_score),
+            scaled.map(|(scaled_score, passing_score)| scaled_score >= passing_score),
+        ],
+    )?;
+    Ok(())
+}
+
+/// One answer given during an attempt, with what was chosen written out.
+#[derive(Debug)]
+struct Answer {
+    question_text: String,
+    score: f64,
+    text: Option<String>,
+    options: Vec<String>,
+    /// Each prompt of a matching question and the match it was paired with, if any.
+    pairs: Vec<(String, Option<String>)>,
+}
+
+impl Answer {
+    /// What the user answered, written out, e.g., `SMTP, IMAP` or `22 -> SSH, 53 -> DNS`.
+    fn describe(&self) -> String {
+        if !self.options.is_empty() {
+            return self.options.join(", ");
+        }
+        if !self.pairs.is_empty() {
+            let pairs: Vec<String> = self.pairs.iter()
+                .map(|(prompt, chosen)| format!("{} -> {}", prompt, chosen.as_deref().unwrap_or("(none)")))
+                .collect();
+            return pairs.join(", ");
+        }
+        self.text.clone().unwrap_or_else(|| String::from("(nothing entered)"))
+    }
+}
+
+/// Queries `UserQuestionResponses` for every answer given during the attempt, in the order they
+/// were given, along with the options and pairs chosen.
+fn answers(conn: &Connection, attempt_id: i64) -> rusqlite::Result<Vec<Answer>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.ResponseID, q.QuestionText, r.Score, r.ResponseText FROM UserQuestionResponses r \
+        JOIN Questions q ON q.QuestionID = r.QuestionID WHERE r.AttemptID = ?1 ORDER BY r.ResponseID"
+    )?;
+    let rows = stmt.query_map([attempt_id], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+    let rows: Vec<(i64, String, f64, Option<String>)> = rows.collect::<rusqlite::Result<_>>()?;
+
+    let mut options_stmt = conn.prepare(
+        "SELECT o.OptionText FROM UserResponseOptions r JOIN QuestionOptions o ON o.OptionID = r.OptionID \
+        WHERE r.ResponseID = ?1 ORDER BY o.Position"
+    )?;
+    let mut pairs_stmt = conn.prepare(
+        "SELECT p.Prompt, c.Match FROM UserResponsePairs r JOIN MatchingPairs p ON p.PairID = r.PairID \
+        LEFT JOIN MatchingPairs c ON c.PairID = r.ChosenPairID WHERE r.ResponseID = ?1 ORDER BY p.Position"
+    )?;
+    let mut answers: Vec<Answer> = Vec::new();
+    for (response_id, question_text, score, text) in rows {
+        let options = options_stmt.query_map([response_id], |row| row.get(0))?;
+        let pairs = pairs_stmt.query_map([response_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        answers.push(Answer {
+            question_text,
+            score,
+            text,
+            options: options.collect::<rusqlite::Result<Vec<String>>>()?,
+            pairs: pairs.collect::<rusqlite::Result<Vec<(String, Option<String>)>>>()?,
+        });
+    }
+    Ok(answers)
+}
+
+/// Queries `AttemptDomainScores` for how the attempt went in each domain, in the catalog's order.
+fn domain_scores(conn: &Connection, attempt_id: i64) -> rusqlite::Result<Vec<(String, f64, usize)>> {
+    let mut stmt = conn.prepare(
+        "SELECT d.Code, d.Name, s.Score, s.NumQuestions FROM AttemptDomainScores s \
+        JOIN Domains d ON d.DomainID = s.DomainID WHERE s.AttemptID = ?1"
+    )?;
+    let rows = stmt.query_map([attempt_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?, row.get(3)?))
+    })?;
+    let mut rows: Vec<(String, String, f64, usize)> = rows.collect::<rusqlite::Result<_>>()?;
+    rows.sort_by(|a, b| code_order(&a.0, &b.0));
+    Ok(rows.into_iter().map(|(code, name, score, num_questions)| (format!("{} {}", code, name), score, num_questions)).collect())
+}
+
+/// Shows every answer given during the `attempt`, along with its score by domain if it has one.
+fn show_attempt(conn: &Connection, attempt: &Attempt) -> rusqlite::Result<()> {
+    println!(
+        "\n=== {} - {} on {} ===",
+        attempt.exam_title,
+        attempt.mode.as_str(),
+        attempt.start.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+    );
+    if let Some(end) = attempt.end {
+        let seconds: i64 = (end - attempt.start).num_seconds().max(0);
+        println!("Took {}:{:02}. Result: {}", seconds / 60, seconds % 60, attempt.summary());
+    } else {
+        println!("This attempt wasn't finished.");
+    }
+    if let Some(passing_score) = attempt.passing_score {
+        println!("Passing score: {}", passing_score);
+    }
+
+    let domains: Vec<(String, f64, usize)> = domain_scores(conn, attempt.attempt_id)?;
+    if !domains.is_empty() {
+        println!("\nBy domain:");
+        for (label, score, num_questions) in domains {
+            println!(
+                "  {} - {:.2} of {} ({:.0}%)",
+                label, score, num_questions, score / num_questions.max(1) as f64 * 100.0,
+            );
+        }
+    }
+
+    let answers: Vec<Answer> = answers(conn, attempt.attempt_id)?;
+    if answers.is_empty() {
+        println!("\nNo answers were recorded.");
+    } else {
+        println!("\nAnswers:");
+    }
+    for (i, answer) in answers.iter().enumerate() {
+        let verdict: String = if answer.score >= 1.0 {
+            String::from("correct")
+        } else if answer.score > 0.0 {
+            format!("partly correct ({:.0}% credit)", answer.score * 100.0)
+        } else {
+            String::from("incorrect")
+        };
+        println!("  {}. {}", i + 1, answer.question_text);
+        println!("     Your answer: {} - {}", answer.describe(), verdict);
+    }
+    Ok(())
+}
+
+/// Lists the user's past attempts, newest first, and shows every answer of the one they pick,
+/// until they back out.
+pub fn history(conn: &Connection, username: &str) -> rusqlite::Result<()> {
+    loop {
+        let attempts: Vec<Attempt> = Attempt::get_for_user(conn, username)?;
+        if attempts.is_empty() {
+            println!("You haven't sat any exams yet.");
+            return Ok(());
+        }
+        println!("\n=== Attempt history ===");
+        for (i, attempt) in attempts.iter().enumerate() {
+            println!(
+                "  {}. {}  {}  {} - {}",
+                i + 1,
+                attempt.start.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                attempt.exam_title,
+                attempt.mode.as_str(),
+                attempt.summary(),
+            );
+        }
+        match read_choice("Select an attempt to see its answers (or 'q' to go back): ", attempts.len()) {
+            Some(index) => show_attempt(conn, &attempts[index])?,
+            None => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE Exams (ExamID INTEGER PRIMARY KEY, Title TEXT);
+            CREATE TABLE ExamAttempts (AttemptID INTEGER PRIMARY KEY, Username TEXT, ExamID INTEGER, Mode TEXT,
+                StartTime TEXT, EndTime TEXT, NumQuestions INTEGER, Score REAL, ScaledScore INTEGER,
+                PassingScore INTEGER, Passed INTEGER);
+            CREATE TABLE Domains (DomainID INTEGER PRIMARY KEY, ExamID INTEGER, Code TEXT, Name TEXT, Weight INTEGER);
+            CREATE TABLE AttemptDomainScores (AttemptID INTEGER, DomainID INTEGER, Score REAL, NumQuestions INTEGER);
+            INSERT INTO Exams (Title) VALUES ('Network+');"
+        ).unwrap();
+        conn
+    }
+
+    #[test]
+    fn reads_back_the_times_it_writes() {
+        let conn = database();
+        let started: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-03-01T09:30:00+00:00").unwrap().with_timezone(&Utc);
+        let attempt_id: i64 = start(&conn, "alice", 1, Mode::Mock, started).unwrap();
+        finish(&conn, attempt_id, 4, 3.0, Some((750, 720))).unwrap();
+        start(&conn, "alice", 1, Mode::Practice, started).unwrap();
+
+        let attempts: Vec<Attempt> = Attempt::get_for_user(&conn, "alice").unwrap();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].start, started);
+        assert_eq!(attempts[0].summary(), "not finished");
+        assert_eq!(attempts[1].start, started);
+        assert!(attempts[1].end.is_some());
+        assert_eq!(attempts[1].summary(), "3.00 of 4 (75%), 750 PASS");
+    }
+
+    #[test]
+    fn refuses_times_it_cannot_read() {
+        let conn = database();
+        conn.execute(
+            "INSERT INTO ExamAttempts (Username, ExamID, Mode, StartTime, NumQuestions, Score) \
+            VALUES ('alice', 1, 'Practice', 'yesterday', 0, 0)",
+            [],
+        ).unwrap();
+        let error: String = Attempt::get_for_user(&conn, "alice").unwrap_err().to_string();
+        assert!(error.contains("Invalid timestamp: yesterday"), "{}", error);
+
+        conn.execute("UPDATE ExamAttempts SET StartTime = ?1, EndTime = 'later'", [Utc::now().to_rfc3339()]).unwrap();
+        let error: String = Attempt::get_for_user(&conn, "alice").unwrap_err().to_string();
+        assert!(error.contains("Invalid timestamp: later"), "{}", error);
+    }
+
+    #[test]
+    fn orders_domain_scores_by_code() {
+        let conn = database();
+        conn.execute_batch(
+            "INSERT INTO Domains (ExamID, Code, Name, Weight) VALUES (1, '10.0', 'Later', 10), (1, '2.0', 'Earlier', 90);
+            INSERT INTO AttemptDomainScores (AttemptID, DomainID, Score, NumQuestions) VALUES (1, 1, 1.0, 2), (1, 2, 3.5, 4);"
+        ).unwrap();
+        assert_eq!(
+            domain_scores(&conn, 1).unwrap(),
+            vec![(String::from("2.0 Earlier"), 3.5, 4), (String::from("10.0 Later"), 1.0, 2)],
+        );
+    }
+}
@@ -1,202 +1,796 @@
-use bcrypt::DEFAULT_COST;
-use chrono::{DateTime, Datelike, Utc};
-use rusqlite::Connection;
-use std::{env, fs};
-use std::fs::{create_dir, read_to_string};
-use std::io::{ErrorKind, stdin, stdout, Write};
+use chrono::Local;
+use regex::Regex;
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::{copy, create_dir_all, metadata, read_to_string, write};
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use serde::Serialize;
+use terminator::lint::{self, Finding, Lines, Severity};
+use terminator::migrations;
+use terminator::pack::{self, Pack};
 
 const ASSET_DIR_NAME: &str = "assets";
 const DATABASE_NAME: &str = "terminator.db";
-const USERS_TABLE: &str = "users.sql";
-const EXAMS_TABLE: &str = "exams.sql";
-const EXAM_CREATION_TABLE: &str = "exam_creation.sql";
-const QUESTIONS_TABLE: &str = "questions.sql";
 
-#[doc(hidden)]
-/// Creates the database, `terminator.db`, and the tables for `terminator.db`.
-/// The schemas for each of the tables are as follows:
-///
-/// - Users (**Username**: Text, Password: Text)
-/// - Exams (**ExamID**: Int, Title: Text, Description: Text)
-/// - ExamCreation (_**ExamID**_: Int, _**CreatorUsername**_: Text, DateCreated: Text)
-/// - Questions (**QuestionID**: Int, QuestionText: Text, Options: Text, CorrectAnswer: Text, Explanation: Text, *ExamID*: Int)
-/// - UserQuestionResponses (ResponseID, )
-fn create_database_and_tables(db_path: &Path) -> rusqlite::Result<()> {
-    // Creates the database if it doesn't exists, and opens it for updating.
-    let conn = Connection::open(db_path)?;
-
-    // TODO: Delete the drop statements and the code to execute it when done testing
-    println!("Dropping tables...");
-    let drop_usr_q_res = "DROP TABLE IF EXISTS UserQuestionResponses";
-    let drop_questions = "DROP TABLE IF EXISTS Questions";
-    let drop_exam_cre = "DROP TABLE IF EXISTS ExamCreation";
-    let drop_exams = "DROP TABLE IF EXISTS Exams";
-    let drop_users = "DROP TABLE IF EXISTS Users";
-
-    conn.execute(drop_usr_q_res, [])?;
-    conn.execute(drop_questions, [])?;
-    conn.execute(drop_exam_cre, [])?;
-    conn.execute(drop_exams, [])?;
-    conn.execute(drop_users, [])?;
-    // TODO: End of delete for testing
-
-    let users_sql =
-        "CREATE TABLE IF NOT EXISTS Users (
-           Username TEXT NOT NULL UNIQUE,
-           Password TEXT NOT NULL
-        )";
-    conn.execute(users_sql, [])?;
-
-    let exams_sql =
-    "CREATE  TABLE IF NOT EXISTS Exams (
-       ExamID INTEGER PRIMARY KEY AUTOINCREMENT,
-       Title TEXT NOT NULL,
-       Description TEXT
-    )";
-    conn.execute(exams_sql, [])?;
-
-    let exam_creat_sql =
-    "CREATE TABLE IF NOT EXISTS ExamCreation (
-       ExamID INTEGER NOT NULL,
-       CreatorUsername TEXT NOT NULL,
-       DateCreated TEXT NOT NULL,
-       PRIMARY KEY (ExamID, CreatorUsername),
-       FOREIGN KEY (ExamID) REFERENCES Exams(ExamID),
-       FOREIGN KEY (CreatorUsername) REFERENCES Users(Username)
-    )";
-    conn.execute(exam_creat_sql, [])?;
-
-    let questions_sql =
-    "CREATE TABLE IF NOT EXISTS Questions (
-       QuestionID INTEGER PRIMARY KEY AUTOINCREMENT,
-       ExamID INTEGER NOT NULL,
-       QuestionText TEXT NOT NULL,
-       Options TEXT NOT NULL,
-       CorrectAnswer TEXT NOT NULL,
-       Explanation TEXT NOT NULL,
-       FOREIGN KEY (ExamID) REFERENCES Exams(ExamID)
-    )";
-    conn.execute(questions_sql, [])?;
-
-    let user_question_responses_sql =
-    "CREATE TABLE IF NOT EXISTS UserQuestionResponses (
-       ResponseID INTEGER PRIMARY KEY AUTOINCREMENT,
-       Username TEXT NOT NULL,
-       QuestionID INTEGER NOT NULL,
-       IsCorrect INTEGER DEFAULT 0,
-       Timestamp TEXT,
-       FOREIGN KEY (Username) REFERENCES Users (Username),
-       FOREIGN KEY (QuestionID) REFERENCES Questions (QuestionID)
-    )";
-    conn.execute(user_question_responses_sql, [])?;
+/// The command finished successfully.
+const EXIT_OK: i32 = 0;
+/// The command failed, e.g., the database couldn't be opened or a script had an error.
+const EXIT_FAILURE: i32 = 1;
+/// The command line couldn't be understood.
+const EXIT_USAGE: i32 = 2;
+/// `check` or `lint` ran, but found problems.
+const EXIT_CHECK_FAILED: i32 = 3;
 
-    Ok(())
+const USAGE: &str = "\
+Usage: create_database [--db PATH] [--dry-run] COMMAND
+
+Commands:
+  init             Create a new database with the latest schema
+  migrate          Apply any schema migrations the database is missing
+  seed FILE        Run the legacy SQL script FILE against the database
+  import PACK      Import the question pack PACK, updating questions imported before
+  export EXAM [FILE]
+                   Export the exam with the ID or UUID EXAM as a question pack to FILE
+                   (default: stdout)
+  backup [FILE]    Copy the database to FILE (default: next to the database, timestamped)
+  restore FILE     Replace the database with the backup FILE
+  check            Check the database's integrity, foreign keys and schema
+  stats            Show the schema version and the number of rows in each table
+  lint [PACK...]   Check the question packs PACK, or every exam in the database if none are
+                   given, for wrong answers, missing explanations, near-duplicates and the like
+
+Options:
+  --db PATH        The database to use (default: assets/terminator.db in the current directory)
+  --dry-run        Show what would change without changing anything
+  --json           Write lint findings as JSON
+  -h, --help       Show this message
+
+Exit codes: 0 success, 1 failure, 2 bad command line, 3 check or lint found problems";
+
+#[derive(Debug)]
+struct UsageError {
+    message: String,
+}
+
+impl Display for UsageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for UsageError {}
+
+#[derive(Debug)]
+struct CheckFailed {
+    problems: usize,
+}
+
+impl Display for CheckFailed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CheckFailed - {} problem(s) found", self.problems)
+    }
+}
+
+impl Error for CheckFailed {}
+
+#[derive(Debug)]
+struct NoDatabase {
+    path: PathBuf,
+}
+
+impl Display for NoDatabase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NoDatabase - there is no database at {}; run `create_database init` first", self.path.display())
+    }
+}
+
+impl Error for NoDatabase {}
+
+#[derive(Debug)]
+struct OutdatedSchema {
+    found: u32,
+}
+
+impl Display for OutdatedSchema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "OutdatedSchema - the database is at schema version {} but needs version {}; run `create_database migrate` first",
+            self.found, migrations::SCHEMA_VERSION,
+        )
+    }
+}
+
+impl Error for OutdatedSchema {}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Init,
+    Migrate,
+    Seed(PathBuf),
+    Import(PathBuf),
+    Export(String, Option<PathBuf>),
+    Backup(Option<PathBuf>),
+    Restore(PathBuf),
+    Check,
+    Stats,
+    Lint(Vec<PathBuf>),
+}
+
+/// The parsed command line.
+#[derive(Debug)]
+struct Options {
+    db: PathBuf,
+    dry_run: bool,
+    json: bool,
+    command: Command,
+}
+
+impl Options {
+    /// Parses the arguments that follow the program name. Returns `Ok(None)` if help was asked
+    /// for.
+    fn parse(args: &[String]) -> Result<Option<Options>, UsageError> {
+        let usage = |message: String| UsageError { message };
+        let mut db: Option<PathBuf> = None;
+        let mut dry_run: bool = false;
+        let mut json: bool = false;
+        let mut positional: Vec<&str> = Vec::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--dry-run" => dry_run = true,
+                "--json" => json = true,
+                "--db" => match args.next() {
+                    Some(path) => db = Some(PathBuf::from(path)),
+                    None => return Err(usage(String::from("--db needs a path"))),
+                },
+                arg if arg.starts_with("--db=") => db = Some(PathBuf::from(&arg["--db=".len()..])),
+                arg if arg.starts_with('-') && arg.len() > 1 => return Err(usage(format!("Unknown option: {}", arg))),
+                arg => positional.push(arg),
+            }
+        }
+
+        let (name, rest) = match positional.split_first() {
+            Some((name, rest)) => (*name, rest),
+            None => return Err(usage(String::from("No command given"))),
+        };
+        let command: Command = match (name, rest) {
+            ("init", []) => Command::Init,
+            ("migrate", []) => Command::Migrate,
+            ("seed", [file]) => Command::Seed(PathBuf::from(file)),
+            ("import", [pack]) => Command::Import(PathBuf::from(pack)),
+            ("export", [exam]) => Command::Export(exam.to_string(), None),
+            ("export", [exam, file]) => Command::Export(exam.to_string(), Some(PathBuf::from(file))),
+            ("backup", []) => Command::Backup(None),
+            ("backup", [file]) => Command::Backup(Some(PathBuf::from(file))),
+            ("restore", [file]) => Command::Restore(PathBuf::from(file)),
+            ("check", []) => Command::Check,
+            ("stats", []) => Command::Stats,
+            ("lint", packs) => Command::Lint(packs.iter().map(PathBuf::from).collect()),
+            ("init" | "migrate" | "seed" | "import" | "export" | "backup" | "restore" | "check" | "stats", _) => {
+                return Err(usage(format!("Wrong number of arguments for {}", name)));
+            },
+            _ => return Err(usage(format!("Unknown command: {}", name))),
+        };
+
+        let db: PathBuf = db.unwrap_or_else(|| {
+            let mut path: PathBuf = env::current_dir().expect("Unable to get current directory...");
+            path.push(ASSET_DIR_NAME);
+            path.push(DATABASE_NAME);
+            path
+        });
+        Ok(Some(Options { db, dry_run, json, command }))
+    }
+}
+
+/// A statement of a SQL script, along with the line of the script it starts on.
+#[derive(Debug)]
+struct ScriptStatement<'a> {
+    line: usize,
+    sql: &'a str,
+}
+
+/// Splits a SQL script into its statements, leaving out comments between them. Semicolons in
+/// strings, quoted names and comments don't end a statement, and neither do the ones in the body
+/// of a `CREATE TRIGGER`.
+fn split_statements(script: &str) -> Vec<ScriptStatement<'_>> {
+    let mut statements: Vec<ScriptStatement> = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut line: usize = 1;
+    // The character that closes the quote, or the comment, the scanner is in
+    let mut closing: Option<&str> = None;
+    let mut chars = script.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if let Some(close) = closing {
+            if script[i..].starts_with(close) {
+                if close == "*/" {
+                    chars.next();
+                }
+                closing = None;
+            }
+            continue;
+        }
+        match c {
+            '-' if script[i..].starts_with("--") => {
+                closing = Some("\n");
+                // The newline that ends the comment still needs to be counted
+                if let Some(end) = script[i..].find('\n') {
+                    while chars.peek().is_some_and(|(j, _)| *j < i + end) {
+                        chars.next();
+                    }
+                }
+                continue;
+            },
+            '/' if script[i..].starts_with("/*") => {
+                chars.next();
+                closing = Some("*/");
+                continue;
+            },
+            _ => {},
+        }
+        if start.is_none() && !c.is_whitespace() {
+            start = Some((i, line));
+        }
+        match c {
+            '\'' => closing = Some("'"),
+            '"' => closing = Some("\""),
+            '`' => closing = Some("`"),
+            '[' => closing = Some("]"),
+            ';' => {
+                let (begin, begin_line) = start.expect("A statement starts before its semicolon.");
+                let sql: &str = &script[begin..=i];
+                if is_complete(sql) {
+                    statements.push(ScriptStatement { line: begin_line, sql });
+                    start = None;
+                }
+            },
+            _ => {},
+        }
+    }
+    if let Some((begin, begin_line)) = start {
+        statements.push(ScriptStatement { line: begin_line, sql: script[begin..].trim_end() });
+    }
+    statements
+}
+
+/// Whether `sql`, which ends with a semicolon, is a whole statement rather than one statement in
+/// the body of a `CREATE TRIGGER`. The body's `BEGIN` and any `CASE` in it each close with an
+/// `END`, so the trigger is whole once they've all been closed.
+fn is_complete(sql: &str) -> bool {
+    let words: Vec<String> = sql.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_uppercase())
+        .collect();
+    let is_trigger: bool = words.first().is_some_and(|word| word == "CREATE")
+        && words.iter().take(4).any(|word| word == "TRIGGER");
+    let count = |wanted: &str| words.iter().filter(|word| *word == wanted).count();
+    !is_trigger || (words.last().is_some_and(|word| word == "END") && count("END") >= count("BEGIN") + count("CASE"))
+}
+
+/// What running a SQL script changed.
+#[derive(Debug, Default)]
+struct ScriptSummary {
+    statements: usize,
+    /// The number of rows inserted, updated or deleted in each table.
+    rows: BTreeMap<String, usize>,
+}
+
+#[derive(Debug)]
+struct ScriptError {
+    line: usize,
+    statement: String,
+    source: rusqlite::Error,
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut statement: String = self.statement.lines().next().unwrap_or("").to_string();
+        if statement.chars().count() > 80 || self.statement.lines().count() > 1 {
+            statement = format!("{}...", statement.chars().take(80).collect::<String>());
+        }
+        write!(f, "ScriptError - the statement at line {} failed: {}\n    {}", self.line, self.source, statement)
+    }
+}
+
+impl Error for ScriptError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
 }
 
 #[doc(hidden)]
 /// Executes SQL commands from a `.sql` file for performing functions on tables within the
-/// database passed via `db_path`.
+/// database given via `conn`, one statement at a time. This is kept for legacy scripts like
+/// `test_script.sql`; new questions should be written as a question pack and imported instead.
+///
+/// Run it inside a transaction, with `PRAGMA foreign_keys` on, so that a script that fails part
+/// way can be rolled back and a broken reference fails the statement that made it. The script
+/// itself shouldn't use `BEGIN` or `COMMIT`.
 ///
 /// # Arguments
-/// * `db_path` - a reference to the `Path` that leads to the database (e.g., `.db` file).
-/// * `sql_file_path` - a reference to the `PathBuf` that leads to the SQL file for adjusting some
-/// table within the database located at `db_path`.
+/// * `conn` - a reference to the `Connection` to the database (or a transaction on it).
+/// * `sql_file_path` - a reference to the `Path` that leads to the SQL file for adjusting some
+/// table within the database.
 ///
 /// **Note: the `Exams` and `Users` tables must have their commands executed before calling an
 /// `ExamCreation` adjustment since the `ExamCreation` table requires information from both the
 /// `Users` table (for the `CreatorUsername`) and the `Exams` table (for the `ExamID`) foreign
 /// keys.**
-fn execute_sql_from_file(db_path: &Path, sql_file_path: &PathBuf) {
-    // Open the database connection
-    let conn: Connection = Connection::open(db_path).expect("Unable to open connection to db.");
-
-    if let Ok(sql) = read_to_string(sql_file_path) {
-        match conn.execute_batch(&sql) {
-            Ok(_) => println!("Batch sql execution successful."),
-            Err(e) => eprintln!("Unable to execute batch sql: {}", e),
+fn execute_sql_from_file(conn: &Connection, sql_file_path: &Path) -> Result<ScriptSummary, Box<dyn Error>> {
+    let script: String = read_to_string(sql_file_path)?;
+    let table_regex = Regex::new(
+        r#"(?i)^\s*(?:INSERT(?:\s+OR\s+\w+)?\s+INTO|REPLACE\s+INTO|UPDATE(?:\s+OR\s+\w+)?|DELETE\s+FROM)\s+["`\[]?(\w+)"#
+    ).unwrap();
+
+    let mut summary: ScriptSummary = ScriptSummary::default();
+    for statement in split_statements(&script) {
+        let failed = |source: rusqlite::Error| ScriptError {
+            line: statement.line,
+            statement: statement.sql.to_string(),
+            source,
+        };
+        let mut stmt = conn.prepare(statement.sql).map_err(failed)?;
+        if stmt.column_count() > 0 {
+            // e.g., a SELECT or a PRAGMA that returns its value; its rows aren't needed
+            let mut rows = stmt.query([]).map_err(failed)?;
+            while rows.next().map_err(failed)?.is_some() {}
+        } else {
+            let changed: usize = stmt.execute([]).map_err(failed)?;
+            if let Some(caps) = table_regex.captures(statement.sql) {
+                *summary.rows.entry(caps[1].to_string()).or_insert(0) += changed;
+            }
+        }
+        summary.statements += 1;
+    }
+    Ok(summary)
+}
+
+#[doc(hidden)]
+/// Moves the options of multiple choice questions that still use the newline-joined
+/// `Questions.Options` column into the `QuestionOptions` table, one row per option with its label
+/// (e.g., `A.)`) removed. An option is marked correct if the question's `CorrectAnswer` matches the
+/// option in full, its label or its text. Questions that already have `QuestionOptions` rows are
+/// left alone, as is the `Options` column itself. Run it inside a transaction; it doesn't start
+/// one of its own.
+fn migrate_question_options(conn: &Connection) -> rusqlite::Result<usize> {
+    let label_regex = Regex::new(r"^\s*([A-Za-z0-9]{1,2})\s*[.)]+\s*(.*)$").unwrap();
+
+    let legacy: Vec<(i32, String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT QuestionID, Options, CorrectAnswer FROM Questions q \
+            WHERE QuestionType = 'MultipleChoice' AND TRIM(Options) <> '' \
+            AND NOT EXISTS (SELECT 1 FROM QuestionOptions o WHERE o.QuestionID = q.QuestionID)"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<Vec<(i32, String, String)>>>()?
+    };
+
+    for (question_id, options, answer) in &legacy {
+        let answer: &str = answer.trim();
+        // SQLite doesn't unescape `\n` in string literals, so scripts leave a literal `\n`
+        let options = options.lines()
+            .flat_map(|line| line.split("\\n"))
+            .map(|option| option.trim())
+            .filter(|option| !option.is_empty());
+        for (position, option) in options.enumerate() {
+            let (label, text) = match label_regex.captures(option) {
+                Some(caps) => (caps.get(1).map(|label| label.as_str()), caps.get(2).map_or("", |text| text.as_str())),
+                None => (None, option),
+            };
+            let is_correct: bool = option.eq_ignore_ascii_case(answer)
+                || text.eq_ignore_ascii_case(answer)
+                || label.is_some_and(|label| label.eq_ignore_ascii_case(answer));
+            conn.execute(
+                "INSERT INTO QuestionOptions (QuestionID, Position, OptionText, IsCorrect) VALUES (?1, ?2, ?3, ?4)",
+                params![question_id, position as i64 + 1, text, is_correct],
+            )?;
         }
+    }
+    Ok(legacy.len())
+}
+
+/// Opens the existing database at `db`. Unlike `Connection::open`, this won't create an empty
+/// database if there isn't one.
+fn open_existing(db: &Path) -> Result<Connection, Box<dyn Error>> {
+    if !db.is_file() {
+        return Err(Box::new(NoDatabase { path: db.to_path_buf() }));
+    }
+    Ok(Connection::open(db)?)
+}
+
+/// Opens the existing database at `db`, which must already be at the latest schema version.
+fn open_current(db: &Path) -> Result<Connection, Box<dyn Error>> {
+    let conn: Connection = open_existing(db)?;
+    let found: u32 = migrations::schema_version(&conn)?;
+    if found < migrations::SCHEMA_VERSION {
+        return Err(Box::new(OutdatedSchema { found }));
+    }
+    migrations::pending(&conn)?;
+    Ok(conn)
+}
+
+/// Creates a new database at `db` with the latest schema, along with the directory it's in.
+fn init(db: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    if db.exists() {
+        return Err(format!("{} already exists; use `migrate` to update it", db.display()).into());
+    }
+    if dry_run {
+        println!("Would create {} at schema version {}.", db.display(), migrations::SCHEMA_VERSION);
+        return Ok(());
+    }
+    if let Some(dir) = db.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        create_dir_all(dir)?;
+    }
+    let mut conn: Connection = Connection::open(db)?;
+    migrations::migrate(&mut conn)?;
+    println!("Created {} at schema version {}.", db.display(), migrations::SCHEMA_VERSION);
+    Ok(())
+}
+
+/// Applies the migrations the database at `db` hasn't had yet.
+fn migrate(db: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let mut conn: Connection = open_existing(db)?;
+    let found: u32 = migrations::schema_version(&conn)?;
+    let pending = if dry_run { migrations::pending(&conn)? } else { migrations::migrate(&mut conn)? };
+    if pending.is_empty() {
+        println!("The database is up to date (schema version {}).", found);
+    }
+    for migration in pending {
+        let verb: &str = if dry_run { "Would apply" } else { "Applied" };
+        println!("{} migration {}: {}", verb, migration.version, migration.description);
+    }
+    Ok(())
+}
+
+/// Runs the SQL script `file` against the database, then moves any options it wrote the old way
+/// into `QuestionOptions`. The script and the move are done in one transaction with foreign keys
+/// enforced, which is rolled back if any statement fails, or on a dry run.
+fn seed(db: &Path, file: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let mut conn: Connection = open_current(db)?;
+    // This has no effect inside a transaction, so it's turned on first
+    conn.pragma_update(None, "foreign_keys", true)?;
+    let tx = conn.transaction()?;
+    let summary: ScriptSummary = match execute_sql_from_file(&tx, file) {
+        Ok(summary) => summary,
+        Err(e) => {
+            tx.rollback()?;
+            return Err(format!("Unable to run {}; nothing was changed.\n{}", file.display(), e).into());
+        },
+    };
+    let moved: usize = migrate_question_options(&tx)?;
+    if dry_run {
+        tx.rollback()?;
+        println!("{} ran without errors ({} statement(s)); nothing was changed.", file.display(), summary.statements);
     } else {
-        eprintln!("Couldn't convert sql batch script path to string...");
+        tx.commit()?;
+        println!("Ran {} ({} statement(s)).", file.display(), summary.statements);
+    }
+    for (table, rows) in &summary.rows {
+        println!("  {:<24}{:>6} row(s)", table, rows);
     }
+    if moved > 0 {
+        println!("Moved the options of {} question(s) into QuestionOptions.", moved);
+    }
+    Ok(())
 }
 
+/// Imports the question pack `file` in one transaction, which is rolled back on a dry run.
+fn import(db: &Path, file: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let pack: Pack = Pack::read(file)?;
+    let mut conn: Connection = open_current(db)?;
+    // As in `seed`, this has no effect inside a transaction, so it's turned on first
+    conn.pragma_update(None, "foreign_keys", true)?;
+    let tx = conn.transaction()?;
+    let summary = pack::import(&tx, &pack)?;
+    if dry_run {
+        tx.rollback()?;
+        println!("{} can be imported; nothing was changed.", file.display());
+    } else {
+        tx.commit()?;
+        println!("Imported {}.", file.display());
+    }
+    println!(
+        "Exam {} ({}): {}, {} question(s) added, {} updated.",
+        summary.exam_id,
+        pack.exam.title,
+        if summary.exam_created { "new" } else { "updated" },
+        summary.questions_added,
+        summary.questions_updated,
+    );
+    for username in &summary.skipped_creators {
+        println!("Left out creator {}, who isn't a user of this database.", username);
+    }
+    Ok(())
+}
 
-fn main() {
-    let mut base_path: PathBuf = env::current_dir().expect("Unable to get current directory...");
-    base_path.pop();
-    base_path.pop();
-    base_path.push(ASSET_DIR_NAME);
-
-    match create_dir(&base_path) {
-        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
-            println!("The {} directory already exists; no need to create it...", ASSET_DIR_NAME);
+/// Exports the exam with the ID or UUID `exam` as a question pack, to the file `to` or to stdout.
+fn export(db: &Path, exam: &str, to: Option<&Path>, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let conn: Connection = open_current(db)?;
+    let exam_id: i64 = match pack::find_exam(&conn, exam)? {
+        Some(exam_id) => exam_id,
+        None => return Err(format!("There is no exam with the ID or UUID {}", exam).into()),
+    };
+    let pack: Pack = pack::export(&conn, exam_id)?;
+    let text: String = pack.to_toml()?;
+    match to {
+        Some(to) if dry_run => println!(
+            "Would export {} question(s) of {} to {}.", pack.questions.len(), pack.exam.title, to.display(),
+        ),
+        Some(to) => {
+            write(to, text)?;
+            println!("Exported {} question(s) of {} to {}.", pack.questions.len(), pack.exam.title, to.display());
         },
-        Err(e) => {
-            eprintln!("Error occurred and caught by me: {}", e);
-            exit(1);
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Copies the database at `db` to `to`, or to a timestamped file next to it, e.g.,
+/// `terminator.db.20240101-120000.bak`. Returns where the copy went.
+fn backup(db: &Path, to: Option<&Path>, dry_run: bool) -> Result<PathBuf, Box<dyn Error>> {
+    let conn: Connection = open_existing(db)?;
+    let to: PathBuf = match to {
+        Some(to) => to.to_path_buf(),
+        None => {
+            let stamp: String = Local::now().format("%Y%m%d-%H%M%S").to_string();
+            // Two backups in the same second (e.g., `backup` then `restore`) get a counter
+            (0..).map(|n| {
+                let mut name = db.as_os_str().to_os_string();
+                match n {
+                    0 => name.push(format!(".{}.bak", stamp)),
+                    n => name.push(format!(".{}-{}.bak", stamp, n)),
+                }
+                PathBuf::from(name)
+            }).find(|path| !path.exists()).expect("Unable to name the backup.")
         },
-        Ok(()) => println!("Created {} directory.", ASSET_DIR_NAME),
+    };
+    if to.exists() {
+        return Err(format!("{} already exists", to.display()).into());
+    }
+    if dry_run {
+        println!("Would back up {} to {}.", db.display(), to.display());
+        return Ok(to);
+    }
+    // Unlike copying the file, VACUUM INTO gives a consistent copy even if the game is running
+    conn.execute("VACUUM INTO ?1", [to.to_string_lossy()])?;
+    println!("Backed up {} to {}.", db.display(), to.display());
+    Ok(to)
+}
+
+/// Replaces the database at `db` with the backup `from`, after checking that it's a sound
+/// terminator database this binary can use. The database being replaced is backed up first.
+fn restore(db: &Path, from: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    if !from.is_file() {
+        return Err(format!("There is no backup at {}", from.display()).into());
+    }
+    {
+        let backup: Connection = Connection::open_with_flags(from, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let integrity: String = backup.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+        if integrity != "ok" {
+            return Err(format!("{} is damaged: {}", from.display(), integrity).into());
+        }
+        if migrations::schema_version(&backup)? == 0 {
+            return Err(format!("{} isn't a terminator database", from.display()).into());
+        }
+        migrations::pending(&backup)?;
+    }
 
+    if db.exists() {
+        backup(db, None, dry_run)?;
+    }
+    if dry_run {
+        println!("Would restore {} from {}.", db.display(), from.display());
+        return Ok(());
     }
+    copy(from, db)?;
+    let mut conn: Connection = Connection::open(db)?;
+    migrations::migrate(&mut conn)?;
+    println!("Restored {} from {}.", db.display(), from.display());
+    Ok(())
+}
 
-    // Database path
-    let mut db_path: PathBuf = PathBuf::from(&base_path);
-    db_path = db_path.join(DATABASE_NAME);
-    println!("db_path:\t{:?}", db_path);
+/// Checks the database's integrity, its foreign keys, its schema version and that its tables
+/// have the columns that version should have, printing each problem found. Returns a `CheckFailed` error if there were any.
+fn check(db: &Path) -> Result<(), Box<dyn Error>> {
+    let conn: Connection = open_existing(db)?;
+    let mut problems: usize = 0;
 
-    /*
-    // Establish paths to DB and .sql files for each DB Table
-    // Database path
-    let mut db_path: PathBuf = PathBuf::from(&base_path);
-    db_path.push(DATABASE_NAME);
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let integrity = stmt.query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    for message in integrity.iter().filter(|message| *message != "ok") {
+        println!("Integrity: {}", message);
+        problems += 1;
+    }
 
-    // Users table path
-    let mut users_path: PathBuf = PathBuf::from(&base_path);
-    users_path.push(USERS_TABLE);
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let violations = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, String>(2)?)))?
+        .collect::<rusqlite::Result<Vec<(String, Option<i64>, String)>>>()?;
+    for (table, rowid, parent) in &violations {
+        match rowid {
+            Some(rowid) => println!("Foreign key: row {} of {} refers to a missing {} row", rowid, table, parent),
+            None => println!("Foreign key: a row of {} refers to a missing {} row", table, parent),
+        }
+        problems += 1;
+    }
 
-    // Exams table path
-    let mut exams_path: PathBuf = PathBuf::from(&base_path);
-    exams_path.push(EXAMS_TABLE);
+    let found: u32 = migrations::schema_version(&conn)?;
+    if found < migrations::SCHEMA_VERSION {
+        println!("Schema: at version {} of {}; run `create_database migrate`", found, migrations::SCHEMA_VERSION);
+        problems += 1;
+    } else if found > migrations::SCHEMA_VERSION {
+        println!("Schema: at version {}, which is newer than this binary (version {})", found, migrations::SCHEMA_VERSION);
+        problems += 1;
+    } else {
+        // The version only says which migrations ran, so the tables themselves are compared too
+        for difference in migrations::schema_differences(&conn)? {
+            println!("Schema: {}", difference);
+            problems += 1;
+        }
+    }
 
-    // ExamCreation table path
-    let mut exam_creation_path: PathBuf = PathBuf::from(&base_path);
-    exam_creation_path.push(EXAM_CREATION_TABLE);
+    if problems > 0 {
+        return Err(Box::new(CheckFailed { problems }));
+    }
+    println!("No problems found in {}.", db.display());
+    Ok(())
+}
 
-    // Questions table path
-    let mut questions_path: PathBuf = PathBuf::from(&base_path);
-    questions_path.push(QUESTIONS_TABLE);
-     */
+/// Shows the database's size and schema version, how many rows each table has and how many
+/// questions each exam has.
+fn stats(db: &Path) -> Result<(), Box<dyn Error>> {
+    let conn: Connection = open_existing(db)?;
+    println!("Database: {} ({} KiB)", db.display(), metadata(db)?.len() / 1024);
+    println!("Schema version: {} (this binary: {})", migrations::schema_version(&conn)?, migrations::SCHEMA_VERSION);
 
-    match create_database_and_tables(&db_path) {
-        Err(e) => {
-            eprintln!("An error occurred setting up the database/tables: {}", e);
-            exit(1);
-        },
-        _ => println!("Database and tables created successfully..."),
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+    )?;
+    let tables = stmt.query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    println!("\nRows by table:");
+    for table in &tables {
+        // Table names can't be bound as parameters, but these come from sqlite_master itself
+        let count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| row.get(0))?;
+        println!("  {:<24}{:>8}", table, count);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT e.ExamID, e.Title, COUNT(q.QuestionID) FROM Exams e \
+        LEFT JOIN Questions q ON q.ExamID = e.ExamID GROUP BY e.ExamID ORDER BY e.ExamID"
+    )?;
+    let exams = stmt.query_map([], |row| Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?)))?
+        .collect::<rusqlite::Result<Vec<(i32, String, i64)>>>()?;
+    if !exams.is_empty() {
+        println!("\nQuestions by exam:");
     }
+    for (exam_id, title, count) in exams {
+        println!("  {:>3}. {:<40}{:>6}", exam_id, title, count);
+    }
+    Ok(())
+}
 
+/// A lint finding along with where the content came from, e.g., a pack's path.
+#[derive(Debug, Serialize)]
+struct Located<'a> {
+    source: String,
+    #[serde(flatten)]
+    finding: &'a Finding,
+}
 
+/// Lints the question packs `packs`, or every exam in the database at `db` if there are none, and
+/// prints what was found. Returns a `CheckFailed` error if there were any errors; warnings alone
+/// don't fail.
+fn lint(db: &Path, packs: &[PathBuf], json: bool) -> Result<(), Box<dyn Error>> {
+    let mut sources: Vec<(String, Vec<Finding>)> = Vec::new();
+    if packs.is_empty() {
+        let conn: Connection = open_current(db)?;
+        let mut stmt = conn.prepare("SELECT ExamID, Title FROM Exams ORDER BY ExamID")?;
+        let exams = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<Vec<(i64, String)>>>()?;
+        for (exam_id, title) in exams {
+            let pack: Pack = pack::export(&conn, exam_id)?;
+            sources.push((format!("{} (exam {}, {})", db.display(), exam_id, title), lint::lint(&pack, &Lines::default())));
+        }
+    }
+    for path in packs {
+        let text: String = read_to_string(path)?;
+        let findings: Vec<Finding> = match Pack::from_toml(&text) {
+            Ok(pack) => lint::lint(&pack, &Lines::find(&text)),
+            Err(pack::PackError::Parse(e)) => vec![Finding {
+                severity: Severity::Error,
+                rule: "parse",
+                line: e.span().map(|span| lint::line_of(&text, span.start)),
+                question: None,
+                message: e.message().trim().replace('\n', " "),
+            }],
+            Err(e) => return Err(Box::new(e)),
+        };
+        sources.push((path.display().to_string(), findings));
+    }
 
-    let mut test_sql_batch = PathBuf::from(&base_path);
-    test_sql_batch = test_sql_batch.join("test_script.sql");
+    let located: Vec<Located> = sources.iter()
+        .flat_map(|(source, findings)| findings.iter().map(|finding| Located { source: source.clone(), finding }))
+        .collect();
+    let errors: usize = located.iter().filter(|located| located.finding.severity == Severity::Error).count();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&located)?);
+    } else {
+        for Located { source, finding } in &located {
+            let place: String = match finding.line {
+                Some(line) => format!("{}:{}", source, line),
+                None => source.clone(),
+            };
+            let question: String = finding.question.as_ref().map_or(String::new(), |id| format!(" (question {})", id));
+            println!("{}: {} [{}] {}{}", place, finding.severity.as_str(), finding.rule, finding.message, question);
+        }
+        println!(
+            "{} error(s), {} warning(s) in {} source(s).",
+            errors, located.len() - errors, sources.len(),
+        );
+    }
+    if errors > 0 {
+        return Err(Box::new(CheckFailed { problems: errors }));
+    }
+    Ok(())
+}
 
-    execute_sql_from_file(&db_path, &test_sql_batch);
+/// Runs the parsed command.
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let db: &Path = &options.db;
+    match &options.command {
+        Command::Init => init(db, options.dry_run),
+        Command::Migrate => migrate(db, options.dry_run),
+        Command::Seed(file) => seed(db, file, options.dry_run),
+        Command::Import(file) => import(db, file, options.dry_run),
+        Command::Export(exam, to) => export(db, exam, to.as_deref(), options.dry_run),
+        Command::Backup(to) => backup(db, to.as_deref(), options.dry_run).map(|_| ()),
+        Command::Restore(from) => restore(db, from, options.dry_run),
+        Command::Check => check(db),
+        Command::Stats => stats(db),
+        Command::Lint(packs) => lint(db, packs, options.json),
+    }
+}
 
-    //let DATABASE: PathBuf = PathBuf::from(format!("{}{}", "./", DATABASE_NAME));
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options: Options = match Options::parse(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            exit(EXIT_OK);
+        },
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            exit(exit_code(&e));
+        },
+    };
 
-    /*
-    create_database_tables(&test).expect("Unable to run create_database_tables()...");
-    get_and_insert_user(&test).expect("Unable to run get_and_insert_user");
-     */
+    if let Err(e) = run(&options) {
+        eprintln!("{}", e);
+        exit(exit_code(e.as_ref()));
+    }
+}
 
-    // let DATABASE_PATH: PathBuf = PathBuf::from("../../assets/");
-    // create_database_tables()?;
-    // execute_sql_from_file(&DATABASE_PATH, format!("{}{}", SQL_FILE_PATH, "TBD.sql"))?;
-    // insert_username()?;
-    // execute_sql_from_file(DATABASE_PATH, "../../exam_creation.sql")?;
-    // execute_sql_from_file(DATABASE_PATH, "../../questions.sql")?;
+/// The code to exit with after the error `e`.
+fn exit_code(e: &(dyn Error + 'static)) -> i32 {
+    if e.is::<UsageError>() {
+        EXIT_USAGE
+    } else if e.is::<CheckFailed>() {
+        EXIT_CHECK_FAILED
+    } else {
+        EXIT_FAILURE
+    }
 }
 
 
@@ -398,4 +992,162 @@ fn get_and_insert_user(db_path: &Path) -> rusqlite::Result<()> {
 
     Ok(())
 }
- */
\ No newline at end of file
+ */
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs::{remove_file, write};
+    use std::process::id;
+    use super::*;
+
+    /// Parses `args`, as they'd follow the program name.
+    fn parse(args: &[&str]) -> Result<Option<Options>, UsageError> {
+        Options::parse(&args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>())
+    }
+
+    /// The message and exit code of the usage error that parsing `args` gives.
+    fn usage_error(args: &[&str]) -> (String, i32) {
+        let error: UsageError = parse(args).unwrap_err();
+        let code: i32 = exit_code(&error);
+        (error.message, code)
+    }
+
+    #[test]
+    fn parses_each_command() {
+        let cases: Vec<(&[&str], Command)> = vec![
+            (&["init"], Command::Init),
+            (&["migrate"], Command::Migrate),
+            (&["seed", "extra.sql"], Command::Seed(PathBuf::from("extra.sql"))),
+            (&["import", "pack.toml"], Command::Import(PathBuf::from("pack.toml"))),
+            (&["export", "3"], Command::Export(String::from("3"), None)),
+            (&["export", "3", "out.toml"], Command::Export(String::from("3"), Some(PathBuf::from("out.toml")))),
+            (&["backup"], Command::Backup(None)),
+            (&["backup", "copy.db"], Command::Backup(Some(PathBuf::from("copy.db")))),
+            (&["restore", "copy.db"], Command::Restore(PathBuf::from("copy.db"))),
+            (&["check"], Command::Check),
+            (&["stats"], Command::Stats),
+            (&["lint"], Command::Lint(Vec::new())),
+            (&["lint", "a.toml", "b.toml"], Command::Lint(vec![PathBuf::from("a.toml"), PathBuf::from("b.toml")])),
+        ];
+        for (args, command) in cases {
+            let options: Options = parse(args).unwrap().unwrap();
+            assert_eq!(options.command, command, "{:?}", args);
+            assert!(!options.dry_run && !options.json);
+            assert!(options.db.ends_with(Path::new(ASSET_DIR_NAME).join(DATABASE_NAME)));
+        }
+    }
+
+    #[test]
+    fn options_go_before_or_after_the_command() {
+        for args in [
+            &["--db", "class.db", "--dry-run", "import", "pack.toml"][..],
+            &["import", "pack.toml", "--db", "class.db", "--dry-run"],
+            &["--dry-run", "import", "--db=class.db", "pack.toml"],
+        ] {
+            let options: Options = parse(args).unwrap().unwrap();
+            assert_eq!(options.db, PathBuf::from("class.db"), "{:?}", args);
+            assert!(options.dry_run);
+            assert_eq!(options.command, Command::Import(PathBuf::from("pack.toml")));
+        }
+        assert!(parse(&["lint", "--json"]).unwrap().unwrap().json);
+        assert!(parse(&["--help"]).unwrap().is_none());
+        assert!(parse(&["seed", "-h"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn bad_command_lines_are_usage_errors() {
+        assert_eq!(usage_error(&["init", "--force"]), (String::from("Unknown option: --force"), EXIT_USAGE));
+        assert_eq!(usage_error(&["check", "--db"]), (String::from("--db needs a path"), EXIT_USAGE));
+        assert_eq!(usage_error(&["--dry-run"]), (String::from("No command given"), EXIT_USAGE));
+        assert_eq!(usage_error(&["drop"]), (String::from("Unknown command: drop"), EXIT_USAGE));
+        assert_eq!(usage_error(&["seed"]), (String::from("Wrong number of arguments for seed"), EXIT_USAGE));
+        assert_eq!(usage_error(&["import"]), (String::from("Wrong number of arguments for import"), EXIT_USAGE));
+        assert_eq!(usage_error(&["init", "now"]), (String::from("Wrong number of arguments for init"), EXIT_USAGE));
+        // A lone `-` is a file name, as it is for most tools
+        assert_eq!(parse(&["import", "-"]).unwrap().unwrap().command, Command::Import(PathBuf::from("-")));
+    }
+
+    #[test]
+    fn errors_map_to_exit_codes() {
+        assert_eq!(exit_code(&CheckFailed { problems: 2 }), EXIT_CHECK_FAILED);
+        assert_eq!(exit_code(&NoDatabase { path: PathBuf::from("missing.db") }), EXIT_FAILURE);
+        assert_eq!(exit_code(&OutdatedSchema { found: 1 }), EXIT_FAILURE);
+        let options: Options = parse(&["--db", "/nonexistent/terminator.db", "check"]).unwrap().unwrap();
+        let error: Box<dyn Error> = run(&options).unwrap_err();
+        assert!(error.is::<NoDatabase>());
+        assert_eq!(exit_code(error.as_ref()), EXIT_FAILURE);
+    }
+
+    /// The line and SQL of each statement of `script`.
+    fn split(script: &str) -> Vec<(usize, &str)> {
+        split_statements(script).into_iter().map(|statement| (statement.line, statement.sql)).collect()
+    }
+
+    #[test]
+    fn splits_statements_with_their_lines() {
+        let script: &str = "INSERT INTO Users VALUES ('a', 'b');\n\nINSERT INTO Users\n    VALUES ('c', 'd'); DELETE FROM Users;\n";
+        assert_eq!(split(script), vec![
+            (1, "INSERT INTO Users VALUES ('a', 'b');"),
+            (3, "INSERT INTO Users\n    VALUES ('c', 'd');"),
+            (4, "DELETE FROM Users;"),
+        ]);
+    }
+
+    #[test]
+    fn keeps_the_last_statement_without_a_semicolon() {
+        assert_eq!(split("SELECT 1;\nSELECT 2\n\n"), vec![(1, "SELECT 1;"), (2, "SELECT 2")]);
+        assert!(split("  \n-- just a comment; nothing else\n").is_empty());
+    }
+
+    #[test]
+    fn ignores_semicolons_in_comments() {
+        let script: &str = "-- Users; the people who sign in\nSELECT 1; /* not; a\nstatement */ SELECT 2;\nSELECT 3 -- trailing; comment\n;";
+        assert_eq!(split(script), vec![
+            (2, "SELECT 1;"),
+            (3, "SELECT 2;"),
+            (4, "SELECT 3 -- trailing; comment\n;"),
+        ]);
+    }
+
+    #[test]
+    fn ignores_semicolons_in_strings_and_names() {
+        let script: &str = "INSERT INTO t VALUES ('a;b', 'it''s; fine', \"c;d\", `e;f`, [g;h]);\nSELECT ';\n;';\nSELECT 4;";
+        assert_eq!(split(script), vec![
+            (1, "INSERT INTO t VALUES ('a;b', 'it''s; fine', \"c;d\", `e;f`, [g;h]);"),
+            (2, "SELECT ';\n;';"),
+            (4, "SELECT 4;"),
+        ]);
+    }
+
+    #[test]
+    fn keeps_trigger_bodies_together() {
+        let trigger: &str = "CREATE TRIGGER Stamp AFTER INSERT ON t\nBEGIN\n    UPDATE t SET a = 1;\n    DELETE FROM u;\nEND;";
+        let script: String = format!("{}\nSELECT 1;", trigger);
+        assert_eq!(split(&script), vec![(1, trigger), (6, "SELECT 1;")]);
+
+        let with_case: &str = "CREATE TEMP TRIGGER IF NOT EXISTS Score AFTER INSERT ON t BEGIN\n    \
+            UPDATE t SET s = CASE WHEN NEW.a THEN 1 ELSE 0 END;\nEND;";
+        assert_eq!(split(with_case), vec![(1, with_case)]);
+    }
+
+    #[test]
+    fn knows_when_a_trigger_is_complete() {
+        assert!(is_complete("INSERT INTO t VALUES (1);"));
+        assert!(!is_complete("CREATE TRIGGER x AFTER INSERT ON t BEGIN UPDATE t SET a = 1;"));
+        assert!(is_complete("create trigger x after insert on t begin update t set a = 1; end;"));
+        assert!(!is_complete("CREATE TRIGGER x AFTER INSERT ON t BEGIN UPDATE t SET a = CASE WHEN 1 THEN 2 END;"));
+    }
+
+    #[test]
+    fn reports_the_line_of_the_statement_that_failed() {
+        let path: PathBuf = temp_dir().join(format!("terminator-script-{}.sql", id()));
+        write(&path, "CREATE TABLE t (a INTEGER NOT NULL);\n-- a; comment\nINSERT INTO t VALUES (1);\n\nINSERT INTO t\nVALUES (NULL);\n").unwrap();
+        let conn: Connection = Connection::open_in_memory().unwrap();
+        let result = execute_sql_from_file(&conn, &path);
+        remove_file(&path).unwrap();
+        let error: Box<dyn Error> = result.unwrap_err();
+        let error: &ScriptError = error.downcast_ref().expect("Unable to read the error as a ScriptError");
+        assert_eq!(error.line, 5);
+        assert!(error.statement.starts_with("INSERT INTO t\nVALUES (NULL)"));
+    }
+}
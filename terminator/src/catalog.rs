@@ -0,0 +1,115 @@
+use std::cmp::Ordering;
+use rusqlite::Connection;
+
+/// A single row from the `Objectives` table: one numbered objective within a domain, e.g.,
+/// `1.5 Explain common ports and protocols...`.
+#[derive(Debug, Clone)]
+pub struct Objective {
+    pub code: String,
+    pub title: String,
+}
+
+/// A single row from the `Domains` table, along with its `Objectives` once they've been loaded.
+/// `weight` is the domain's share of the exam in percent, as published in the exam objectives.
+#[derive(Debug, Clone)]
+pub struct Domain {
+    pub domain_id: i32,
+    pub code: String,
+    pub name: String,
+    pub weight: u32,
+    pub objectives: Vec<Objective>,
+}
+
+impl Domain {
+    /// Queries the database for every domain of the exam with the given `exam_id`, including the
+    /// objectives for each, in the order the exam objectives list them.
+    pub fn get_for_exam(conn: &Connection, exam_id: i32) -> rusqlite::Result<Vec<Domain>> {
+        let mut stmt = conn.prepare(
+            "SELECT DomainID, Code, Name, Weight FROM Domains WHERE ExamID = ?1 ORDER BY Code"
+        )?;
+        let domains = stmt.query_map([exam_id], |row| {
+            Ok(Domain {
+                domain_id: row.get(0)?,
+                code: row.get(1)?,
+                name: row.get(2)?,
+                weight: row.get(3)?,
+                objectives: Vec::new(),
+            })
+        })?;
+        let mut domains: Vec<Domain> = domains.collect::<rusqlite::Result<Vec<Domain>>>()?;
+        domains.sort_by(|a, b| code_order(&a.code, &b.code));
+        for domain in domains.iter_mut() {
+            domain.load_objectives(conn)?;
+        }
+        Ok(domains)
+    }
+
+    /// Loads the objectives of this domain from the `Objectives` table.
+    pub fn load_objectives(&mut self, conn: &Connection) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            "SELECT Code, Title FROM Objectives WHERE DomainID = ?1 ORDER BY Code"
+        )?;
+        let objectives = stmt.query_map([self.domain_id], |row| {
+            Ok(Objective {
+                code: row.get(0)?,
+                title: row.get(1)?,
+            })
+        })?;
+        self.objectives = objectives.collect::<rusqlite::Result<Vec<Objective>>>()?;
+        self.objectives.sort_by(|a, b| code_order(&a.code, &b.code));
+        Ok(())
+    }
+
+    /// Whether the objective with the given `code` (e.g., `1.5`) belongs to this domain.
+    pub fn contains(&self, code: &str) -> bool {
+        self.objectives.iter().any(|objective| objective.code == code)
+    }
+}
+
+/// Compares two domain or objective codes part by part, so that `1.2` comes before `1.10`. Parts
+/// that aren't numbers are compared as text, after the numbers.
+pub fn code_order(a: &str, b: &str) -> Ordering {
+    let key = |code: &str| -> Vec<(bool, u32, String)> {
+        code.split('.')
+            .map(|part| match part.trim().parse::<u32>() {
+                Ok(number) => (false, number, String::new()),
+                Err(_) => (true, 0, part.to_string()),
+            })
+            .collect()
+    };
+    key(a).cmp(&key(b))
+}
+
+/// The domain that the objective with the given `code` belongs to, if it's in the catalog.
+pub fn domain_of<'a>(domains: &'a [Domain], code: &str) -> Option<&'a Domain> {
+    domains.iter().find(|domain| domain.contains(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_codes_by_their_numbers() {
+        let mut codes: Vec<&str> = vec!["1.10", "2.0", "1.2", "10.1", "1.1", "1.b", "1.a", "1"];
+        codes.sort_by(|a, b| code_order(a, b));
+        assert_eq!(codes, vec!["1", "1.1", "1.2", "1.10", "1.a", "1.b", "2.0", "10.1"]);
+    }
+
+    #[test]
+    fn loads_the_catalog_in_the_exam_objectives_order() {
+        let conn: Connection = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE Domains (DomainID INTEGER PRIMARY KEY, ExamID INTEGER, Code TEXT, Name TEXT, Weight INTEGER);
+            CREATE TABLE Objectives (ObjectiveID INTEGER PRIMARY KEY, DomainID INTEGER, Code TEXT, Title TEXT);
+            INSERT INTO Domains (ExamID, Code, Name, Weight) VALUES (1, '10.0', 'Later', 10), (1, '2.0', 'Earlier', 90);
+            INSERT INTO Objectives (DomainID, Code, Title) VALUES (2, '2.10', 'Tenth'), (2, '2.9', 'Ninth'), (2, '2.1', 'First');"
+        ).unwrap();
+        let domains: Vec<Domain> = Domain::get_for_exam(&conn, 1).unwrap();
+        let codes: Vec<&str> = domains.iter().map(|domain| domain.code.as_str()).collect();
+        assert_eq!(codes, vec!["2.0", "10.0"]);
+        let codes: Vec<&str> = domains[0].objectives.iter().map(|objective| objective.code.as_str()).collect();
+        assert_eq!(codes, vec!["2.1", "2.9", "2.10"]);
+        assert_eq!(domain_of(&domains, "2.10").map(|domain| domain.name.as_str()), Some("Earlier"));
+    }
+}
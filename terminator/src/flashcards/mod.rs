@@ -0,0 +1,272 @@
+pub mod ports;
+
+use std::collections::HashMap;
+use chrono::Utc;
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::seq::SliceRandom;
+use rand::rngs::ThreadRng;
+use rand::thread_rng;
+use rusqlite::{params, Connection};
+use crate::flashcards::ports::{PortEntry, PORTS, VERSION};
+use crate::term_io::read_line;
+
+/// Which way round a flashcard is asked, as stored in `FlashcardHistory.Direction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Shows the port and asks for the protocol, e.g., `443?`.
+    PortToProtocol,
+    /// Shows the protocol and asks for the port, e.g., `LDAPS port?`.
+    ProtocolToPort,
+}
+
+impl Direction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::PortToProtocol => "PortToProtocol",
+            Direction::ProtocolToPort => "ProtocolToPort",
+        }
+    }
+}
+
+/// A single card: one entry of the reference table, asked in one direction.
+#[derive(Debug, Clone, Copy)]
+struct Card {
+    entry: &'static PortEntry,
+    direction: Direction,
+}
+
+impl Card {
+    fn prompt(&self) -> String {
+        match self.direction {
+            Direction::PortToProtocol => format!("{}?", self.entry.port_label()),
+            Direction::ProtocolToPort => format!("{} port?", self.entry.protocol),
+        }
+    }
+
+    /// Whether `response` answers the card. A protocol may be given by its short name, full name
+    /// or an alias. For a protocol that uses several ports, any of them is accepted.
+    fn grade(&self, response: &str) -> bool {
+        match self.direction {
+            Direction::PortToProtocol => {
+                let response: String = normalize(response);
+                [self.entry.protocol, self.entry.name].iter().chain(self.entry.aliases.iter())
+                    .any(|name| normalize(name) == response)
+            },
+            Direction::ProtocolToPort => {
+                let ports: Vec<&str> = response.split(|c: char| !c.is_ascii_digit()).filter(|port| !port.is_empty()).collect();
+                !ports.is_empty() && ports.iter().all(|port| {
+                    port.parse::<u16>().is_ok_and(|port| self.entry.ports.contains(&port))
+                })
+            },
+        }
+    }
+
+    /// The full answer, shown after every card, e.g., `LDAPS (LDAP over TLS) uses TCP 636`.
+    fn answer(&self) -> String {
+        let name: String = if self.entry.name == self.entry.protocol {
+            String::from(self.entry.protocol)
+        } else {
+            format!("{} ({})", self.entry.protocol, self.entry.name)
+        };
+        format!("{} uses {} {}", name, self.entry.transport.as_str(), self.entry.port_label())
+    }
+}
+
+/// Lowercases a protocol name and drops everything but letters and digits, so that `ms-sql`,
+/// `MS SQL` and `mssql` all match.
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_lowercase()
+}
+
+/// Every card in the reference table, in both directions.
+fn cards() -> Vec<Card> {
+    PORTS.iter()
+        .flat_map(|entry| [Direction::PortToProtocol, Direction::ProtocolToPort].map(|direction| Card { entry, direction }))
+        .collect()
+}
+
+/// Queries `FlashcardHistory` for how many times the user has answered each card correctly and
+/// in total, keyed by the protocol and direction.
+fn history(conn: &Connection, username: &str) -> rusqlite::Result<HashMap<(String, String), (usize, usize)>> {
+    let mut stmt = conn.prepare(
+        "SELECT Card, Direction, SUM(IsCorrect), COUNT(*) FROM FlashcardHistory WHERE Username = ?1 \
+        GROUP BY Card, Direction"
+    )?;
+    let rows = stmt.query_map([username], |row| Ok(((row.get(0)?, row.get(1)?), (row.get(2)?, row.get(3)?))))?;
+    rows.collect()
+}
+
+/// Drills the user on the port reference table until they enter `q`. Cards are picked at random,
+/// but the ones the user has missed more often than they've gotten right come up more often, and
+/// each answer is saved in `FlashcardHistory`.
+pub fn port_flashcards(conn: &Connection, username: &str) -> rusqlite::Result<()> {
+    let cards: Vec<Card> = cards();
+    let mut stats: HashMap<(String, String), (usize, usize)> = history(conn, username)?;
+    let mut rng: ThreadRng = thread_rng();
+
+    println!("\n=== Port flashcards ({} protocols, reference version {}) ===", PORTS.len(), VERSION);
+    println!("Answer with a protocol name or a port number. Enter 'q' to stop.");
+    let (mut num_correct, mut total): (usize, usize) = (0, 0);
+    let mut last: Option<usize> = None;
+    loop {
+        let weights: Vec<f64> = cards.iter().enumerate()
+            .map(|(i, card)| {
+                if Some(i) == last {
+                    return 0.0;
+                }
+                let key: (String, String) = (String::from(card.entry.protocol), String::from(card.direction.as_str()));
+                let (correct, seen) = stats.get(&key).copied().unwrap_or((0, 0));
+                (seen - correct + 1) as f64 / (correct + 1) as f64
+            })
+            .collect();
+        let index: usize = WeightedIndex::new(&weights).expect("Some card should have a weight").sample(&mut rng);
+        let card: Card = cards[index];
+        last = Some(index);
+
+        println!("\n{}", card.prompt());
+        let response: String = loop {
+            let entry: String = read_line("Your answer: ");
+            if entry.is_empty() {
+                println!("Entry must not be empty!");
+            } else {
+                break entry;
+            }
+        };
+        if response.eq_ignore_ascii_case("q") {
+            break;
+        }
+
+        let is_correct: bool = card.grade(&response);
+        conn.execute(
+            "INSERT INTO FlashcardHistory (Username, Card, Direction, IsCorrect, DatasetVersion, Timestamp) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![username, card.entry.protocol, card.direction.as_str(), is_correct, VERSION, Utc::now().to_rfc3339()],
+        )?;
+        let stat = stats.entry((String::from(card.entry.protocol), String::from(card.direction.as_str())))
+            .or_insert((0, 0));
+        stat.1 += 1;
+        total += 1;
+        if is_correct {
+            stat.0 += 1;
+            num_correct += 1;
+            println!("Correct! {}.", card.answer());
+        } else {
+            println!("Incorrect. {}.", card.answer());
+        }
+        println!("(You've gotten this card right {} of {} times.)", stat.0, stat.1);
+    }
+    if total > 0 {
+        println!("\nYou answered {} of {} cards correctly.", num_correct, total);
+    }
+    Ok(())
+}
+
+/// Imports the port reference table into the exam with the given `exam_id` as ordinary multiple
+/// choice questions, one per entry in each direction, with three wrong options drawn from the
+/// other entries. Questions the exam already has (by their text) are skipped, so importing again
+/// after the table is updated only adds what's new. Returns the number of questions added.
+pub fn import_ports(conn: &mut Connection, exam_id: i32) -> rusqlite::Result<usize> {
+    let mut rng: ThreadRng = thread_rng();
+    let tx = conn.transaction()?;
+    let mut added: usize = 0;
+    for entry in PORTS {
+        let others: Vec<&PortEntry> = PORTS.iter().filter(|other| other.protocol != entry.protocol).collect();
+        let distractors: Vec<&&PortEntry> = others.choose_multiple(&mut rng, 3).collect();
+        let explanation: String = format!(
+            "{} ({}) uses {} {}. (Port reference version {}.)",
+            entry.protocol, entry.name, entry.transport.as_str(), entry.port_label(), VERSION,
+        );
+        let questions: [(String, String, Vec<String>); 2] = [
+            (
+                format!("Which port does {} use by default?", entry.protocol),
+                entry.port_label(),
+                distractors.iter().map(|other| other.port_label()).collect(),
+            ),
+            (
+                format!(
+                    "Which protocol uses {} {} {} by default?",
+                    entry.transport.as_str(), if entry.ports.len() > 1 { "ports" } else { "port" }, entry.port_label(),
+                ),
+                String::from(entry.protocol),
+                distractors.iter().map(|other| String::from(other.protocol)).collect(),
+            ),
+        ];
+        for (text, answer, wrong) in questions {
+            let exists: bool = tx.query_row(
+                "SELECT COUNT(*) FROM Questions WHERE ExamID = ?1 AND QuestionText = ?2",
+                params![exam_id, &text],
+                |row| row.get::<_, i64>(0),
+            )? > 0;
+            if exists {
+                continue;
+            }
+            tx.execute(
+                "INSERT INTO Questions (ExamID, QuestionText, Options, CorrectAnswer, Explanation, QuestionType) \
+                VALUES (?1, ?2, '', ?3, ?4, 'MultipleChoice')",
+                params![exam_id, &text, &answer, &explanation],
+            )?;
+            let question_id: i64 = tx.last_insert_rowid();
+            let mut options: Vec<(String, bool)> = wrong.into_iter().map(|option| (option, false)).collect();
+            options.push((answer, true));
+            options.shuffle(&mut rng);
+            for (position, (option, is_correct)) in options.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO QuestionOptions (QuestionID, Position, OptionText, IsCorrect) VALUES (?1, ?2, ?3, ?4)",
+                    params![question_id, position + 1, option, is_correct],
+                )?;
+            }
+            added += 1;
+        }
+    }
+    tx.commit()?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(protocol: &str, direction: Direction) -> Card {
+        let entry: &PortEntry = PORTS.iter().find(|entry| entry.protocol == protocol).expect("The protocol is in the table");
+        Card { entry, direction }
+    }
+
+    #[test]
+    fn accepts_any_spelling_of_a_protocol() {
+        let card: Card = card("MS SQL", Direction::PortToProtocol);
+        assert_eq!(card.prompt(), "1433?");
+        for response in ["ms-sql", "MS SQL", "mssql", " MsSql ", "SQL Server", "microsoft sql server"] {
+            assert!(card.grade(response), "{}", response);
+        }
+        for response in ["mysql", "sql", "1433"] {
+            assert!(!card.grade(response), "{}", response);
+        }
+    }
+
+    #[test]
+    fn accepts_any_of_a_protocols_ports() {
+        let card: Card = card("FTP", Direction::ProtocolToPort);
+        assert_eq!(card.prompt(), "FTP port?");
+        for response in ["20 21", "20/21", "21", "20", "port 20, 21"] {
+            assert!(card.grade(response), "{}", response);
+        }
+        for response in ["20 22", "22/21", "ftp", "", "2021", "99999"] {
+            assert!(!card.grade(response), "{}", response);
+        }
+        assert_eq!(card.answer(), "FTP (File Transfer Protocol) uses TCP 20/21");
+    }
+
+    #[test]
+    fn asks_every_entry_both_ways() {
+        let cards: Vec<Card> = cards();
+        assert_eq!(cards.len(), PORTS.len() * 2);
+        for card in cards {
+            let response: String = match card.direction {
+                Direction::PortToProtocol => String::from(card.entry.protocol),
+                Direction::ProtocolToPort => card.entry.port_label(),
+            };
+            assert!(card.grade(&response), "{} doesn't accept {}", card.prompt(), response);
+        }
+    }
+}
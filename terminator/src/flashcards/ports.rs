@@ -0,0 +1,112 @@
+/// The version of the `PORTS` reference table. Bump it whenever an entry is added, removed or
+/// corrected, so that flashcard history and imported questions can be traced to the data they
+/// were based on.
+pub const VERSION: u32 = 1;
+
+/// The transport protocol(s) a well-known port is used with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Tcp,
+    Udp,
+    Both,
+}
+
+impl Transport {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Transport::Tcp => "TCP",
+            Transport::Udp => "UDP",
+            Transport::Both => "TCP/UDP",
+        }
+    }
+}
+
+/// A single entry in the port reference table.
+#[derive(Debug)]
+pub struct PortEntry {
+    /// The short name the protocol is usually known by, e.g., `HTTPS`. These are unique.
+    pub protocol: &'static str,
+    pub name: &'static str,
+    /// Other names that are accepted as answers, including protocols that share the port.
+    pub aliases: &'static [&'static str],
+    pub ports: &'static [u16],
+    pub transport: Transport,
+}
+
+impl PortEntry {
+    /// The ports written the way study guides do, e.g., `20/21` or `137-139`.
+    pub fn port_label(&self) -> String {
+        let is_range: bool = self.ports.len() > 2 && self.ports.windows(2).all(|pair| pair[1] == pair[0] + 1);
+        if is_range {
+            format!("{}-{}", self.ports[0], self.ports[self.ports.len() - 1])
+        } else {
+            self.ports.iter().map(|port| port.to_string()).collect::<Vec<String>>().join("/")
+        }
+    }
+}
+
+/// The well-known ports covered by the CompTIA A+, Network+ and Security+ objectives.
+pub const PORTS: &[PortEntry] = &[
+    PortEntry { protocol: "FTP", name: "File Transfer Protocol", aliases: &[], ports: &[20, 21], transport: Transport::Tcp },
+    PortEntry { protocol: "SSH", name: "Secure Shell", aliases: &["SFTP", "SCP"], ports: &[22], transport: Transport::Tcp },
+    PortEntry { protocol: "Telnet", name: "Telnet", aliases: &[], ports: &[23], transport: Transport::Tcp },
+    PortEntry { protocol: "SMTP", name: "Simple Mail Transfer Protocol", aliases: &[], ports: &[25], transport: Transport::Tcp },
+    PortEntry { protocol: "TACACS+", name: "Terminal Access Controller Access-Control System Plus", aliases: &["TACACS"], ports: &[49], transport: Transport::Tcp },
+    PortEntry { protocol: "DNS", name: "Domain Name System", aliases: &[], ports: &[53], transport: Transport::Both },
+    PortEntry { protocol: "DHCP", name: "Dynamic Host Configuration Protocol", aliases: &["BOOTP"], ports: &[67, 68], transport: Transport::Udp },
+    PortEntry { protocol: "TFTP", name: "Trivial File Transfer Protocol", aliases: &[], ports: &[69], transport: Transport::Udp },
+    PortEntry { protocol: "HTTP", name: "Hypertext Transfer Protocol", aliases: &[], ports: &[80], transport: Transport::Tcp },
+    PortEntry { protocol: "Kerberos", name: "Kerberos", aliases: &[], ports: &[88], transport: Transport::Both },
+    PortEntry { protocol: "POP3", name: "Post Office Protocol version 3", aliases: &["POP"], ports: &[110], transport: Transport::Tcp },
+    PortEntry { protocol: "NTP", name: "Network Time Protocol", aliases: &[], ports: &[123], transport: Transport::Udp },
+    PortEntry { protocol: "NetBIOS", name: "NetBIOS over TCP/IP", aliases: &["NetBT"], ports: &[137, 138, 139], transport: Transport::Both },
+    PortEntry { protocol: "IMAP", name: "Internet Message Access Protocol", aliases: &["IMAP4"], ports: &[143], transport: Transport::Tcp },
+    PortEntry { protocol: "SNMP", name: "Simple Network Management Protocol", aliases: &[], ports: &[161, 162], transport: Transport::Udp },
+    PortEntry { protocol: "LDAP", name: "Lightweight Directory Access Protocol", aliases: &[], ports: &[389], transport: Transport::Both },
+    PortEntry { protocol: "HTTPS", name: "HTTP Secure (HTTP over TLS)", aliases: &[], ports: &[443], transport: Transport::Tcp },
+    PortEntry { protocol: "SMB", name: "Server Message Block", aliases: &["CIFS"], ports: &[445], transport: Transport::Tcp },
+    PortEntry { protocol: "SMTPS", name: "SMTP over TLS", aliases: &["SMTP submission"], ports: &[465, 587], transport: Transport::Tcp },
+    PortEntry { protocol: "IKE", name: "Internet Key Exchange (IPsec)", aliases: &["ISAKMP", "IPsec"], ports: &[500], transport: Transport::Udp },
+    PortEntry { protocol: "Syslog", name: "Syslog", aliases: &[], ports: &[514], transport: Transport::Udp },
+    PortEntry { protocol: "LDAPS", name: "LDAP over TLS", aliases: &[], ports: &[636], transport: Transport::Tcp },
+    PortEntry { protocol: "FTPS", name: "FTP over TLS (implicit)", aliases: &[], ports: &[989, 990], transport: Transport::Tcp },
+    PortEntry { protocol: "IMAPS", name: "IMAP over TLS", aliases: &[], ports: &[993], transport: Transport::Tcp },
+    PortEntry { protocol: "POP3S", name: "POP3 over TLS", aliases: &[], ports: &[995], transport: Transport::Tcp },
+    PortEntry { protocol: "MS SQL", name: "Microsoft SQL Server", aliases: &["SQL Server", "MSSQL"], ports: &[1433], transport: Transport::Tcp },
+    PortEntry { protocol: "Oracle", name: "Oracle Database listener", aliases: &["Oracle DB"], ports: &[1521], transport: Transport::Tcp },
+    PortEntry { protocol: "L2TP", name: "Layer 2 Tunneling Protocol", aliases: &[], ports: &[1701], transport: Transport::Udp },
+    PortEntry { protocol: "H.323", name: "H.323 call signaling", aliases: &["H323"], ports: &[1720], transport: Transport::Tcp },
+    PortEntry { protocol: "PPTP", name: "Point-to-Point Tunneling Protocol", aliases: &[], ports: &[1723], transport: Transport::Tcp },
+    PortEntry { protocol: "RADIUS", name: "Remote Authentication Dial-In User Service", aliases: &[], ports: &[1812, 1813], transport: Transport::Udp },
+    PortEntry { protocol: "MySQL", name: "MySQL", aliases: &["MariaDB"], ports: &[3306], transport: Transport::Tcp },
+    PortEntry { protocol: "RDP", name: "Remote Desktop Protocol", aliases: &["Remote Desktop"], ports: &[3389], transport: Transport::Tcp },
+    PortEntry { protocol: "SIP", name: "Session Initiation Protocol", aliases: &[], ports: &[5060, 5061], transport: Transport::Both },
+    PortEntry { protocol: "PostgreSQL", name: "PostgreSQL", aliases: &["Postgres"], ports: &[5432], transport: Transport::Tcp },
+    PortEntry { protocol: "VNC", name: "Virtual Network Computing", aliases: &["RFB"], ports: &[5900], transport: Transport::Tcp },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(protocol: &str) -> &'static PortEntry {
+        PORTS.iter().find(|entry| entry.protocol == protocol).expect("The protocol is in the table")
+    }
+
+    #[test]
+    fn labels_ports_like_study_guides() {
+        assert_eq!(entry("HTTPS").port_label(), "443");
+        assert_eq!(entry("FTP").port_label(), "20/21");
+        assert_eq!(entry("SNMP").port_label(), "161/162");
+        assert_eq!(entry("SMTPS").port_label(), "465/587");
+        assert_eq!(entry("NetBIOS").port_label(), "137-139");
+    }
+
+    #[test]
+    fn lists_each_protocol_once() {
+        for (i, entry) in PORTS.iter().enumerate() {
+            assert!(!entry.ports.is_empty(), "{} has no ports", entry.protocol);
+            assert!(PORTS[..i].iter().all(|other| other.protocol != entry.protocol), "{} is listed twice", entry.protocol);
+        }
+    }
+}
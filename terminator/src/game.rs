@@ -0,0 +1,1176 @@
+use std::collections::HashMap;
+use chrono::{Local, Utc};
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::GameError;
+use crate::adaptive::{objective_accuracy, plan, Accuracy, Reason, DEFAULT_EXPLORATION};
+use crate::attempt::{self, Mode};
+use crate::catalog::{code_order, domain_of, Domain};
+use crate::flashcards::port_flashcards;
+use crate::generate::practice;
+use crate::ios::Device;
+use crate::ios::config;
+use crate::lab::{Lab, LabProgress};
+use crate::mock::{self, Blueprint, MockResult};
+use crate::review::{self, Schedule};
+use crate::shell::Shell;
+use crate::shell::grader::{grade, FlagRule};
+use crate::shell::state::{check, parse_expectations, Expectation};
+use crate::term_io::{read_choice, read_line};
+use crate::term_user::User;
+use crate::windows::{syntax, Console, Interpreter};
+use terminator::question::{QuestionType, ScoringRule};
+
+/// A single row from the `QuestionOptions` table: one of the options of a multiple choice or
+/// multiple response question.
+#[derive(Debug)]
+pub struct AnswerOption {
+    pub option_id: i32,
+    pub text: String,
+    pub is_correct: bool,
+}
+
+/// A single row from the `MatchingPairs` table. The `prompt` should be paired with the
+/// `match_text`; a pair without a prompt only adds an extra, unused item to the list of matches.
+#[derive(Debug)]
+pub struct MatchingPair {
+    pub pair_id: i32,
+    pub prompt: Option<String>,
+    pub match_text: String,
+}
+
+impl MatchingPair {
+    /// Whether pairing this pair's prompt with `chosen` is correct. Matches can repeat (e.g.,
+    /// several ports that all use TCP), so the text is compared rather than the pair.
+    pub fn is_matched_by(&self, chosen: &MatchingPair) -> bool {
+        chosen.match_text == self.match_text
+    }
+}
+
+/// What the user answered for one prompt of a matching question: the `PairID` of the prompt, and
+/// the `PairID` of the match they paired it with, if any.
+#[derive(Debug)]
+pub struct PairAnswer {
+    pub pair_id: i32,
+    pub chosen_pair_id: Option<i32>,
+    pub is_correct: bool,
+}
+
+/// The options and pairs the user chose for a question, which are recorded alongside their
+/// response so that the most commonly chosen wrong answers can be found.
+#[derive(Debug, Default)]
+pub struct Selections {
+    pub options: Vec<i32>,
+    pub pairs: Vec<PairAnswer>,
+    /// What was typed for answers that aren't options or pairs: the command for a command
+    /// question, the commands entered in a simulator, or the items of an ordering question in the
+    /// order given.
+    pub text: Option<String>,
+}
+
+/// Writes the `order` (indices into the `items` of an ordering question) as the items themselves,
+/// e.g., `Identify the problem > Establish a theory...`.
+pub fn order_text(items: &[String], order: &[usize]) -> String {
+    order.iter().map(|i| items[*i].as_str()).collect::<Vec<&str>>().join(" > ")
+}
+
+/// A single row from the `Questions` table, along with its `CommandQuestions` row if it has one,
+/// its `CommandFlagRules`, its `QuestionOptions`, its `MatchingPairs` and its `OrderingItems`.
+#[derive(Debug)]
+pub struct Question {
+    pub question_id: i32,
+    pub question_text: String,
+    pub answer: String,
+    pub explanation: String,
+    pub question_type: QuestionType,
+    pub initial_state: Option<String>,
+    pub expected_state: Option<String>,
+    pub flag_rules: Vec<FlagRule>,
+    pub options: Vec<AnswerOption>,
+    pub pairs: Vec<MatchingPair>,
+    /// The items of an ordering question, in the correct order.
+    pub items: Vec<String>,
+    pub scoring_rule: ScoringRule,
+    /// The exam objectives the question is tagged with, e.g., `1.2`.
+    pub objectives: Vec<String>,
+}
+
+/// Finds the option that the `response` refers to, either by the label it was shown with (e.g.,
+/// `B` or `B.)`) or by its text. `shown` is the options in the order they were displayed.
+/// Comparisons ignore case and surrounding whitespace.
+pub fn chosen_option<'a>(shown: &[&'a AnswerOption], response: &str) -> Option<&'a AnswerOption> {
+    let response: &str = response.trim();
+    let label: &str = response.trim_end_matches(['.', ')']).trim();
+    shown.iter()
+        .enumerate()
+        .find(|(i, option)| label.eq_ignore_ascii_case(&option_label(*i)) || response.eq_ignore_ascii_case(option.text.trim()))
+        .map(|(_, option)| *option)
+}
+
+/// Parses a multiple response answer such as `A,C` or `c a` into the indices of the options in
+/// `shown` that were selected, without duplicates. Each selection is an option's label.
+pub fn parse_selections(shown: &[&AnswerOption], response: &str) -> Result<Vec<usize>, String> {
+    let mut selected: Vec<usize> = Vec::new();
+    for token in response.split([',', ' ', '\t']).map(|token| token.trim_end_matches(['.', ')'])) {
+        if token.is_empty() {
+            continue;
+        }
+        match (0..shown.len()).find(|i| token.eq_ignore_ascii_case(&option_label(*i))) {
+            Some(i) if !selected.contains(&i) => selected.push(i),
+            Some(_) => {},
+            None => return Err(format!("'{}' isn't one of the options.", token)),
+        }
+    }
+    Ok(selected)
+}
+
+/// Parses a matching answer such as `1-c 2-a 3-d` (or `1c, 2a`) into the index of each prompt and
+/// the index of the match it was paired with. Prompts are numbered from `1` to `num_prompts` and
+/// matches are lettered from `a` for the first of the `num_matches`.
+pub fn parse_pairings(response: &str, num_prompts: usize, num_matches: usize) -> Result<Vec<(usize, usize)>, String> {
+    let pair_regex = Regex::new(r"^(\d+)\s*[-:=.)]?\s*([A-Za-z]+)$").unwrap();
+    let mut pairings: Vec<(usize, usize)> = Vec::new();
+    for token in response.split([',', ';']).flat_map(|part| part.split_whitespace()) {
+        let caps = pair_regex.captures(token)
+            .ok_or_else(|| format!("'{}' isn't a pairing such as 1-a.", token))?;
+        let prompt: usize = match caps[1].parse::<usize>() {
+            Ok(num) if num >= 1 && num <= num_prompts => num - 1,
+            _ => return Err(format!("There's no item {}.", &caps[1])),
+        };
+        let answer: usize = (0..num_matches)
+            .find(|i| caps[2].eq_ignore_ascii_case(&option_label(*i)))
+            .ok_or_else(|| format!("There's no match '{}'.", &caps[2]))?;
+        if pairings.iter().any(|(p, _)| *p == prompt) {
+            return Err(format!("Item {} was paired more than once.", prompt + 1));
+        }
+        pairings.push((prompt, answer));
+    }
+    Ok(pairings)
+}
+
+/// Parses an ordering answer such as `c a b d`, `c,a,b,d` or `cabd` into the indices of the
+/// `num_items` items in the order given. Items are labelled as by `option_label`, so past the
+/// 26th they're numbered, as in `z 27 28`. Every item must be given once.
+pub fn parse_order(response: &str, num_items: usize) -> Result<Vec<usize>, String> {
+    // Each letter is a label on its own, but digits run together into one number
+    let mut labels: Vec<String> = Vec::new();
+    let mut number: bool = false;
+    for c in response.chars() {
+        if c.is_whitespace() || matches!(c, ',' | '-' | '>') {
+            number = false;
+            continue;
+        }
+        match labels.last_mut() {
+            Some(last) if number && c.is_ascii_digit() => last.push(c),
+            _ => {
+                number = c.is_ascii_digit();
+                labels.push(c.to_string());
+            },
+        }
+    }
+    let mut order: Vec<usize> = Vec::new();
+    for label in labels {
+        let item: usize = (0..num_items)
+            .find(|i| label.eq_ignore_ascii_case(&option_label(*i)))
+            .ok_or_else(|| format!("There's no item '{}'.", label))?;
+        if order.contains(&item) {
+            return Err(format!("Item '{}' was given more than once.", label));
+        }
+        order.push(item);
+    }
+    if order.len() != num_items {
+        let example: Vec<String> = (0..num_items).map(|i| option_label(i).to_lowercase()).collect();
+        return Err(format!("Put all {} items in order, e.g., {}.", num_items, example.join(" ")));
+    }
+    Ok(order)
+}
+
+/// The credit for the `selected` options of a multiple response question, out of the options
+/// `shown`. Each correct selection earns an equal share of the credit and each wrong one takes a
+/// share away, down to zero.
+pub fn selection_credit(shown: &[&AnswerOption], selected: &[usize]) -> f64 {
+    let num_correct: usize = shown.iter().filter(|option| option.is_correct).count();
+    let right: usize = selected.iter().filter(|i| shown[**i].is_correct).count();
+    let wrong: usize = selected.len() - right;
+    right.saturating_sub(wrong) as f64 / num_correct.max(1) as f64
+}
+
+/// Grades the `response` to a `Command`, `Cmd` or `PowerShell` question against its correct
+/// answers (and, for a `Command` question, its flag rules). Returns what's wrong with it if it's
+/// incorrect.
+pub fn grade_command(question: &Question, response: &str) -> Result<(), Vec<String>> {
+    match question.question_type {
+        QuestionType::Cmd => syntax::grade(&question.answer, response, Interpreter::Cmd),
+        QuestionType::PowerShell => syntax::grade(&question.answer, response, Interpreter::PowerShell),
+        _ => grade(&question.answer, response, &question.flag_rules),
+    }
+}
+
+/// The simulator of a `Shell` or `Ios` question, along with what it's compared against when the
+/// question is graded: the expected state of the system, or the device with the expected config.
+#[derive(Debug)]
+pub enum Simulator {
+    Shell(Shell, Vec<Expectation>),
+    Ios(Box<(Device, Device)>),
+}
+
+impl Simulator {
+    /// Sets up the simulator for a `Shell` or `Ios` `question` from its `CommandQuestions` entry,
+    /// or returns why it can't be. An IOS device starts with the `InitialState` config applied,
+    /// and the expected config is the `InitialState` with the `ExpectedState` applied on top.
+    pub fn new(question: &Question) -> Result<Simulator, String> {
+        let (initial, expected) = question.initial_state.as_deref().zip(question.expected_state.as_deref())
+            .ok_or_else(|| String::from("it's missing its CommandQuestions entry"))?;
+        if question.question_type == QuestionType::Shell {
+            let shell: Shell = Shell::from_state(initial).map_err(|e| format!("its state is invalid ({})", e))?;
+            let expectations: Vec<Expectation> = parse_expectations(expected)
+                .map_err(|e| format!("its state is invalid ({})", e))?;
+            Ok(Simulator::Shell(shell, expectations))
+        } else {
+            let configs = Device::from_config(initial).and_then(|device| {
+                let mut target: Device = device.clone();
+                target.apply_config(expected)?;
+                Ok((device, target))
+            });
+            let devices: (Device, Device) = configs.map_err(|e| format!("its config is invalid ({})", e))?;
+            Ok(Simulator::Ios(Box::new(devices)))
+        }
+    }
+
+    /// Lets the user work in the simulator until they leave it. Returns the commands entered.
+    pub fn interact(&mut self) -> Vec<String> {
+        match self {
+            Simulator::Shell(shell, _) => shell.interact(),
+            Simulator::Ios(devices) => devices.0.interact(),
+        }
+    }
+
+    /// How the simulator differs from what's expected, which is empty if the question has been
+    /// answered correctly.
+    pub fn problems(&self) -> Vec<String> {
+        match self {
+            Simulator::Shell(shell, expectations) => check(shell, expectations),
+            Simulator::Ios(devices) => config::check(&devices.0.running, &devices.1.running),
+        }
+    }
+}
+
+/// The credit given for an answer that is either right or wrong.
+pub fn credit(is_correct: bool) -> f64 {
+    if is_correct { 1.0 } else { 0.0 }
+}
+
+/// How many options to select, written the way exam questions do (e.g., `TWO`).
+pub fn count_word(count: usize) -> String {
+    const WORDS: &[&str] = &["ZERO", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE", "TEN"];
+    WORDS.get(count).map_or_else(|| count.to_string(), |word| word.to_string())
+}
+
+/// The label that the option at `index` is shown with: `A` for the first, `B` for the second and
+/// so on.
+pub fn option_label(index: usize) -> String {
+    match u8::try_from(index).ok().filter(|index| *index < 26) {
+        Some(index) => char::from(b'A' + index).to_string(),
+        None => (index + 1).to_string(),
+    }
+}
+
+/// A single row from the `Exams` table, along with its `Questions` once they've been loaded.
+#[derive(Debug)]
+pub struct Exam {
+    pub exam_id: i32,
+    pub exam_title: String,
+    pub exam_desc: Option<String>,
+    pub questions: Vec<Question>,
+}
+
+impl Exam {
+    /// Queries the database for every exam. The questions for each exam are not loaded; use
+    /// `load_questions` once an exam has been selected.
+    pub fn get_all(conn: &Connection) -> rusqlite::Result<Vec<Exam>> {
+        let mut stmt = conn.prepare(
+            "SELECT ExamID, Title, Description FROM Exams ORDER BY ExamID"
+        )?;
+        let exams = stmt.query_map([], |row| {
+            Ok(Exam {
+                exam_id: row.get(0)?,
+                exam_title: row.get(1)?,
+                exam_desc: row.get(2)?,
+                questions: Vec::new(),
+            })
+        })?;
+        exams.collect()
+    }
+
+    /// Loads the questions that belong to this exam from the `Questions` table, along with the
+    /// flag rules for any command questions, the options for any multiple choice or multiple
+    /// response questions, the pairs for any matching questions, the items for any ordering
+    /// questions and the objectives every question is tagged with.
+    pub fn load_questions(&mut self, conn: &Connection) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            "SELECT q.QuestionID, q.QuestionText, q.CorrectAnswer, q.Explanation, \
+            q.QuestionType, c.InitialState, c.ExpectedState \
+            FROM Questions q LEFT JOIN CommandQuestions c ON q.QuestionID = c.QuestionID \
+            WHERE q.ExamID = ?1 ORDER BY q.QuestionID"
+        )?;
+        let questions = stmt.query_map([self.exam_id], |row| {
+            Ok(Question {
+                question_id: row.get(0)?,
+                question_text: row.get(1)?,
+                answer: row.get(2)?,
+                explanation: row.get(3)?,
+                question_type: row.get(4)?,
+                initial_state: row.get(5)?,
+                expected_state: row.get(6)?,
+                flag_rules: Vec::new(),
+                options: Vec::new(),
+                pairs: Vec::new(),
+                items: Vec::new(),
+                scoring_rule: ScoringRule::default(),
+                objectives: Vec::new(),
+            })
+        })?;
+        self.questions = questions.collect::<rusqlite::Result<Vec<Question>>>()?;
+
+        let mut rules_stmt = conn.prepare(
+            "SELECT Program, Flag, Rule FROM CommandFlagRules WHERE QuestionID = ?1 ORDER BY RuleID"
+        )?;
+        for question in self.questions.iter_mut().filter(|q| q.question_type == QuestionType::Command) {
+            let rules = rules_stmt.query_map([question.question_id], |row| {
+                Ok(FlagRule { program: row.get(0)?, flag: row.get(1)?, kind: row.get(2)? })
+            })?;
+            question.flag_rules = rules.collect::<rusqlite::Result<Vec<FlagRule>>>()?;
+        }
+
+        let mut options_stmt = conn.prepare(
+            "SELECT OptionID, OptionText, IsCorrect FROM QuestionOptions WHERE QuestionID = ?1 ORDER BY Position"
+        )?;
+        let has_options = |q: &&mut Question| {
+            matches!(q.question_type, QuestionType::MultipleChoice | QuestionType::MultipleResponse)
+        };
+        for question in self.questions.iter_mut().filter(has_options) {
+            let options = options_stmt.query_map([question.question_id], |row| {
+                Ok(AnswerOption { option_id: row.get(0)?, text: row.get(1)?, is_correct: row.get(2)? })
+            })?;
+            question.options = options.collect::<rusqlite::Result<Vec<AnswerOption>>>()?;
+        }
+
+        let mut pairs_stmt = conn.prepare(
+            "SELECT PairID, Prompt, Match FROM MatchingPairs WHERE QuestionID = ?1 ORDER BY Position"
+        )?;
+        for question in self.questions.iter_mut().filter(|q| q.question_type == QuestionType::Matching) {
+            let pairs = pairs_stmt.query_map([question.question_id], |row| {
+                Ok(MatchingPair { pair_id: row.get(0)?, prompt: row.get(1)?, match_text: row.get(2)? })
+            })?;
+            question.pairs = pairs.collect::<rusqlite::Result<Vec<MatchingPair>>>()?;
+        }
+
+        let mut items_stmt = conn.prepare(
+            "SELECT ItemText FROM OrderingItems WHERE QuestionID = ?1 ORDER BY Position"
+        )?;
+        let mut rule_stmt = conn.prepare(
+            "SELECT ScoringRule FROM OrderingQuestions WHERE QuestionID = ?1"
+        )?;
+        for question in self.questions.iter_mut().filter(|q| q.question_type == QuestionType::Ordering) {
+            let items = items_stmt.query_map([question.question_id], |row| row.get(0))?;
+            question.items = items.collect::<rusqlite::Result<Vec<String>>>()?;
+            question.scoring_rule = rule_stmt.query_row([question.question_id], |row| row.get(0))
+                .optional()?
+                .unwrap_or_default();
+        }
+
+        let mut objectives_stmt = conn.prepare(
+            "SELECT o.Code FROM QuestionObjectives qo JOIN Objectives o ON o.ObjectiveID = qo.ObjectiveID \
+            WHERE qo.QuestionID = ?1 ORDER BY o.Code"
+        )?;
+        for question in self.questions.iter_mut() {
+            let objectives = objectives_stmt.query_map([question.question_id], |row| row.get(0))?;
+            question.objectives = objectives.collect::<rusqlite::Result<Vec<String>>>()?;
+            question.objectives.sort_by(|a, b| code_order(a, b));
+        }
+        Ok(())
+    }
+}
+
+/// The study session for a logged-in `User`.
+pub struct Game {
+    conn: Connection,
+    user: User,
+}
+
+impl Game {
+    /// Creates a new `Game` for the given `user`, using `conn` for reading exams and recording
+    /// responses.
+    pub fn new(conn: Connection, user: User) -> Self {
+        Game { conn, user }
+    }
+
+    /// Runs the game loop: the user picks an exam, answers each of its questions (or works
+    /// through one of its labs), and is shown their score, practices with generated questions or
+    /// port flashcards, reviews the questions that are due, or looks back over past attempts.
+    /// This repeats until the user quits from the exam selection.
+    pub fn play(&mut self) -> Result<(), GameError> {
+        println!("Welcome, {}!", self.user.username);
+        loop {
+            let exams: Vec<Exam> = Exam::get_all(&self.conn)?;
+            if exams.is_empty() {
+                println!("\nThere are no exams available yet, but the practice drills and flashcards are.");
+            } else {
+                println!("\nAvailable exams:");
+            }
+            for (i, exam) in exams.iter().enumerate() {
+                match &exam.exam_desc {
+                    Some(desc) if !desc.is_empty() => println!("  {}. {} - {}", i + 1, exam.exam_title, desc),
+                    _ => println!("  {}. {}", i + 1, exam.exam_title),
+                }
+            }
+            println!("  {}. Practice drills (generated questions)", exams.len() + 1);
+            println!("  {}. Port flashcards", exams.len() + 2);
+            review::sync(&self.conn, &self.user.username)?;
+            let num_due: usize = review::due(&self.conn, &self.user.username)?.len();
+            println!("  {}. Review due items ({} due)", exams.len() + 3, num_due);
+            println!("  {}. Attempt history", exams.len() + 4);
+
+            let choice = read_choice("Select an exam (or 'q' to quit): ", exams.len() + 4);
+            match choice {
+                Some(index) if index == exams.len() => {
+                    practice(&self.conn, &self.user.username)?;
+                },
+                Some(index) if index == exams.len() + 1 => {
+                    port_flashcards(&self.conn, &self.user.username)?;
+                },
+                Some(index) if index == exams.len() + 2 => self.review_due()?,
+                Some(index) if index == exams.len() + 3 => {
+                    attempt::history(&self.conn, &self.user.username)?;
+                },
+                Some(index) => {
+                    let mut exam: Exam = exams.into_iter().nth(index).expect("Exam choice out of range");
+                    exam.load_questions(&self.conn)?;
+                    let labs: Vec<Lab> = Lab::get_for_exam(&self.conn, exam.exam_id)?;
+                    self.exam_menu(&exam, &labs)?;
+                },
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Lets the user choose between answering the `exam`'s questions, an adaptive practice
+    /// session, viewing its objectives, a mock exam and working through one of its `labs`, until
+    /// they back out.
+    fn exam_menu(&self, exam: &Exam, labs: &[Lab]) -> Result<(), GameError> {
+        loop {
+            println!("\n=== {} ===", exam.exam_title);
+            println!("  1. Answer the exam's questions");
+            println!("  2. Adaptive practice (targets your weakest objectives)");
+            println!("  3. Objectives and coverage");
+            println!("  4. Mock exam (timed, graded when you submit)");
+            for (i, lab) in labs.iter().enumerate() {
+                let progress: Option<LabProgress> = lab.load_progress(&self.conn, &self.user.username)?;
+                println!("  {}. Lab: {} ({})", i + 5, lab.title, lab.status(&progress));
+            }
+
+            match read_choice("Select an activity (or 'q' to go back): ", labs.len() + 4) {
+                Some(0) => self.take_exam(exam)?,
+                Some(1) => self.adaptive_practice(exam)?,
+                Some(2) => self.show_coverage(exam)?,
+                Some(3) => self.mock_exam(exam)?,
+                Some(index) => labs[index - 4].run(&self.conn, &self.user.username)?,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Lists the `exam`'s domains and objectives from the catalog. Each domain shows its official
+    /// weighting next to its share of the exam's questions, and each objective shows how many
+    /// questions cover it and the user's accuracy on them, so gaps in either stand out.
+    fn show_coverage(&self, exam: &Exam) -> Result<(), GameError> {
+        let domains: Vec<Domain> = Domain::get_for_exam(&self.conn, exam.exam_id)?;
+        if domains.is_empty() {
+            println!("{} doesn't have an objectives catalog yet...", exam.exam_title);
+            return Ok(());
+        }
+        let accuracy: HashMap<String, Accuracy> = objective_accuracy(&self.conn, &self.user.username, exam.exam_id)?;
+
+        println!("\n=== {} - Objectives ===", exam.exam_title);
+        for domain in &domains {
+            let in_domain: usize = exam.questions.iter()
+                .filter(|question| question.objectives.iter().any(|code| domain.contains(code)))
+                .count();
+            println!(
+                "\n{} {} - {}% of the exam, {} question(s) ({:.0}% of the question bank)",
+                domain.code,
+                domain.name,
+                domain.weight,
+                in_domain,
+                in_domain as f64 / exam.questions.len().max(1) as f64 * 100.0,
+            );
+            for objective in &domain.objectives {
+                let covering: usize = exam.questions.iter()
+                    .filter(|question| question.objectives.contains(&objective.code))
+                    .count();
+                let status: String = match (covering, accuracy.get(&objective.code)) {
+                    (0, _) => String::from("no questions yet"),
+                    (_, Some(accuracy)) => format!("{} question(s), {}", covering, accuracy.describe()),
+                    (_, None) => format!("{} question(s), not answered yet", covering),
+                };
+                println!("  {} {}", objective.code, objective.title);
+                println!("      {}", status);
+            }
+        }
+
+        let untagged: usize = exam.questions.iter()
+            .filter(|question| question.objectives.iter().all(|code| domain_of(&domains, code).is_none()))
+            .count();
+        if untagged > 0 {
+            println!("\n{} question(s) aren't tagged with any of these objectives.", untagged);
+        }
+        let total_weight: u32 = domains.iter().map(|domain| domain.weight).sum();
+        if total_weight != 100 {
+            println!("(The domain weightings add up to {}%, not 100%.)", total_weight);
+        }
+        Ok(())
+    }
+
+    /// Sits a mock exam on the `exam` following its blueprint. Once it's submitted, every answer is
+    /// recorded like any other response, and the attempt is saved with its score by domain.
+    fn mock_exam(&self, exam: &Exam) -> Result<(), GameError> {
+        let blueprint: Blueprint = Blueprint::get_for_exam(&self.conn, exam.exam_id)?;
+        let domains: Vec<Domain> = Domain::get_for_exam(&self.conn, exam.exam_id)?;
+        let result: MockResult = match mock::sit(exam, &domains, &blueprint) {
+            Some(result) => result,
+            None => return Ok(()),
+        };
+
+        let attempt_id: i64 = attempt::start(&self.conn, &self.user.username, exam.exam_id, Mode::Mock, result.start)?;
+        for (index, graded) in &result.graded {
+            if let Some((score, selections)) = graded {
+                self.record_response(&exam.questions[*index], *score, selections, Some(attempt_id))?;
+            }
+        }
+        result.save(&self.conn, attempt_id)?;
+        result.report(exam);
+        Ok(())
+    }
+
+    /// Runs a practice session on the `exam` that picks more questions from the objectives the
+    /// user is weakest on, going by their past answers. A share of the picks (the exploration
+    /// rate, which the user can change) is random so that strong objectives still come up. The
+    /// reasoning behind each pick is shown at the end.
+    fn adaptive_practice(&self, exam: &Exam) -> Result<(), GameError> {
+        if exam.questions.is_empty() {
+            println!("{} doesn't have any questions yet...", exam.exam_title);
+            return Ok(());
+        }
+
+        let size: usize = loop {
+            let entry: String = read_line(&format!("Number of questions [{}]: ", exam.questions.len().min(10)));
+            if entry.is_empty() {
+                break exam.questions.len().min(10);
+            }
+            match entry.parse::<usize>() {
+                Ok(size) if size > 0 => break size.min(exam.questions.len()),
+                _ => println!("Please enter a positive number."),
+            }
+        };
+        let exploration: f64 = loop {
+            let prompt: String = format!(
+                "Exploration rate, the percentage of random picks [{:.0}]: ", DEFAULT_EXPLORATION * 100.0,
+            );
+            let entry: String = read_line(&prompt);
+            if entry.is_empty() {
+                break DEFAULT_EXPLORATION;
+            }
+            match entry.trim_end_matches('%').trim().parse::<f64>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => break percent / 100.0,
+                _ => println!("Please enter a percentage from 0 to 100."),
+            }
+        };
+
+        let accuracy: HashMap<String, Accuracy> = objective_accuracy(&self.conn, &self.user.username, exam.exam_id)?;
+        let objectives: Vec<Vec<String>> = exam.questions.iter().map(|question| question.objectives.clone()).collect();
+        let picks: Vec<(usize, Reason)> = plan(&objectives, &accuracy, size, exploration, &mut thread_rng());
+
+        println!("\n=== {} - Adaptive practice ===", exam.exam_title);
+        let attempt_id: i64 = attempt::start(&self.conn, &self.user.username, exam.exam_id, Mode::Adaptive, Utc::now())?;
+        let mut results: Vec<Option<f64>> = Vec::new();
+        for (i, (index, _)) in picks.iter().enumerate() {
+            let question: &Question = &exam.questions[*index];
+            println!("\nQuestion {} of {}:", i + 1, picks.len());
+            println!("{}", question.question_text);
+            let (score, selections) = match Self::ask_question(question) {
+                Some(answer) => answer,
+                None => {
+                    results.push(None);
+                    continue;
+                },
+            };
+            println!("Explanation: {}", question.explanation);
+            self.record_response(question, score, &selections, Some(attempt_id))?;
+            results.push(Some(score));
+        }
+
+        let answered: Vec<f64> = results.iter().flatten().copied().collect();
+        let total_score: f64 = answered.iter().fold(0.0, |total, score| total + score);
+        attempt::finish(&self.conn, attempt_id, answered.len(), total_score, None)?;
+        println!(
+            "\nYou answered {} of {} questions correctly.",
+            answered.iter().filter(|score| **score >= 1.0).count(),
+            answered.len(),
+        );
+
+        println!("\nWhy these questions were picked ({:.0}% exploration rate):", exploration * 100.0);
+        let mut ranked: Vec<&String> = objectives.iter().flatten().collect();
+        ranked.sort();
+        ranked.dedup();
+        ranked.sort_by(|a, b| {
+            let estimate = |objective: &String| accuracy.get(objective).copied().unwrap_or_default().estimate();
+            estimate(a).total_cmp(&estimate(b)).then(a.cmp(b))
+        });
+        if ranked.is_empty() {
+            println!("  None of this exam's questions are tagged with objectives, so every pick was random.");
+        } else {
+            println!("  Your accuracy by objective before this session, weakest first:");
+            for objective in ranked {
+                println!("    {}: {}", objective, accuracy.get(objective).copied().unwrap_or_default().describe());
+            }
+        }
+        for (i, ((index, reason), result)) in picks.iter().zip(&results).enumerate() {
+            let question_text: &str = &exam.questions[*index].question_text;
+            let text: String = if question_text.chars().count() > 50 {
+                format!("{}...", question_text.chars().take(47).collect::<String>())
+            } else {
+                String::from(question_text)
+            };
+            let why: String = match reason {
+                Reason::Weak { objective, estimate } => {
+                    format!("targeted objective {} (estimated {:.0}%)", objective, estimate * 100.0)
+                },
+                Reason::Explore => String::from("random pick, so strong areas still come up"),
+            };
+            let outcome: &str = match result {
+                Some(score) if *score >= 1.0 => "correct",
+                Some(score) if *score > 0.0 => "partly correct",
+                Some(_) => "incorrect",
+                None => "skipped",
+            };
+            println!("  {}. {} - {} ({})", i + 1, text, why, outcome);
+        }
+        Ok(())
+    }
+
+    /// Asks each question in the `exam`, grading and recording every response, and then shows
+    /// the user their score.
+    fn take_exam(&self, exam: &Exam) -> Result<(), GameError> {
+        if exam.questions.is_empty() {
+            println!("{} doesn't have any questions yet...", exam.exam_title);
+            return Ok(());
+        }
+
+        println!("\n=== {} ===", exam.exam_title);
+        let attempt_id: i64 = attempt::start(&self.conn, &self.user.username, exam.exam_id, Mode::Practice, Utc::now())?;
+        let total: usize = exam.questions.len();
+        // Skipped questions weren't answered, so they don't count toward the score
+        let mut num_answered: usize = 0;
+        let mut num_correct: usize = 0;
+        let mut total_score: f64 = 0.0;
+        for (i, question) in exam.questions.iter().enumerate() {
+            println!("\nQuestion {} of {}:", i + 1, total);
+            println!("{}", question.question_text);
+
+            let (score, selections) = match Self::ask_question(question) {
+                Some(answer) => answer,
+                None => continue,
+            };
+            num_answered += 1;
+            if score >= 1.0 {
+                num_correct += 1;
+            }
+            total_score += score;
+            println!("Explanation: {}", question.explanation);
+
+            self.record_response(question, score, &selections, Some(attempt_id))?;
+        }
+        attempt::finish(&self.conn, attempt_id, num_answered, total_score, None)?;
+
+        if num_answered == 0 {
+            println!("\nNone of the questions could be asked.");
+            return Ok(());
+        }
+        println!(
+            "\nYou answered {} of {} questions correctly ({:.0}%).",
+            num_correct,
+            num_answered,
+            num_correct as f64 / num_answered as f64 * 100.0,
+        );
+        if total_score > num_correct as f64 {
+            println!(
+                "With partial credit, you scored {:.2} of {} ({:.0}%).",
+                total_score,
+                num_answered,
+                total_score / num_answered as f64 * 100.0,
+            );
+        }
+        if num_answered < total {
+            println!("{} question(s) were skipped and aren't counted.", total - num_answered);
+        }
+        Ok(())
+    }
+
+    /// Asks every question that is due for review, most overdue first, and reschedules each one
+    /// from the user's answer. Questions are scheduled from every answer the user has given, in
+    /// exams as well as reviews.
+    fn review_due(&self) -> Result<(), GameError> {
+        let due: Vec<Schedule> = review::due(&self.conn, &self.user.username)?;
+        if due.is_empty() {
+            let upcoming: Vec<Schedule> = review::load(&self.conn, &self.user.username)?;
+            match upcoming.first() {
+                Some(next) => println!(
+                    "Nothing is due for review. The next item is due {}.",
+                    next.due.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                ),
+                None => println!("Nothing is due for review. Answer some exam questions to start a schedule."),
+            }
+            return Ok(());
+        }
+
+        // Questions are loaded an exam at a time
+        let mut questions: HashMap<i32, Question> = HashMap::new();
+        for mut exam in Exam::get_all(&self.conn)? {
+            exam.load_questions(&self.conn)?;
+            questions.extend(exam.questions.into_iter().map(|question| (question.question_id, question)));
+        }
+
+        println!("\n=== Review ({} due) ===", due.len());
+        let total: usize = due.len();
+        for (i, schedule) in due.iter().enumerate() {
+            // The question may have been deleted since it was answered
+            let question: &Question = match questions.get(&schedule.question_id) {
+                Some(question) => question,
+                None => continue,
+            };
+            println!("\nReview {} of {}:", i + 1, total);
+            println!("{}", question.question_text);
+            let (score, selections) = match Self::ask_question(question) {
+                Some(answer) => answer,
+                None => continue,
+            };
+            println!("Explanation: {}", question.explanation);
+
+            self.record_response(question, score, &selections, None)?;
+            review::sync(&self.conn, &self.user.username)?;
+            let next: Option<Schedule> = review::load(&self.conn, &self.user.username)?
+                .into_iter()
+                .find(|next| next.question_id == question.question_id);
+            if let Some(next) = next {
+                println!("Next review in {} day(s).", next.interval_days);
+            }
+        }
+        println!("\nYou're done reviewing for now.");
+        Ok(())
+    }
+
+    /// Asks a single `question` the way its type calls for and grades the answer. Returns the
+    /// credit earned, from `0.0` to `1.0`, and the options or pairs the user chose, or `None` if
+    /// the question couldn't be asked (e.g., its initial state is invalid) and should be skipped.
+    fn ask_question(question: &Question) -> Option<(f64, Selections)> {
+        let mut selections: Selections = Selections::default();
+        let score: f64 = match question.question_type {
+            QuestionType::MultipleChoice => {
+                let (is_correct, option_id) = Self::ask_multiple_choice(question)?;
+                selections.options.push(option_id);
+                credit(is_correct)
+            },
+            QuestionType::MultipleResponse => {
+                let (score, option_ids) = Self::ask_multiple_response(question)?;
+                selections.options = option_ids;
+                score
+            },
+            QuestionType::Ordering => {
+                let (score, order) = Self::ask_ordering(question)?;
+                selections.text = Some(order_text(&question.items, &order));
+                score
+            },
+            QuestionType::Matching => {
+                let (score, pairs) = Self::ask_matching(question)?;
+                selections.pairs = pairs;
+                score
+            },
+            QuestionType::Shell | QuestionType::Ios => {
+                let (is_correct, history) = Self::ask_simulator(question)?;
+                if !history.is_empty() {
+                    selections.text = Some(history.join("; "));
+                }
+                credit(is_correct)
+            },
+            QuestionType::Command | QuestionType::Cmd | QuestionType::PowerShell => {
+                let (is_correct, response) = Self::ask_command(question);
+                selections.text = Some(response);
+                credit(is_correct)
+            },
+        };
+        Some((score, selections))
+    }
+
+    /// Shows the options for a multiple choice `question` in a random order and grades the user's
+    /// answer. Returns whether it was correct and the `OptionID` of the option that was chosen,
+    /// or `None` if the question has no options and was skipped.
+    fn ask_multiple_choice(question: &Question) -> Option<(bool, i32)> {
+        if question.options.is_empty() {
+            println!("This question doesn't have any options yet. Skipping...");
+            return None;
+        }
+        let mut shown: Vec<&AnswerOption> = question.options.iter().collect();
+        shown.shuffle(&mut thread_rng());
+        for (i, option) in shown.iter().enumerate() {
+            println!("  {}.) {}", option_label(i), option.text);
+        }
+
+        let chosen: &AnswerOption = loop {
+            let entry: String = read_line("Your answer: ");
+            if entry.is_empty() {
+                println!("Entry must not be empty!");
+                continue;
+            }
+            match chosen_option(&shown, &entry) {
+                Some(option) => break option,
+                None => println!("Please enter the letter of one of the options."),
+            }
+        };
+
+        if chosen.is_correct {
+            println!("Correct!");
+        } else {
+            let correct: Vec<String> = shown.iter()
+                .enumerate()
+                .filter(|(_, option)| option.is_correct)
+                .map(|(i, option)| format!("{}.) {}", option_label(i), option.text))
+                .collect();
+            println!("Incorrect. The correct answer is: {}", correct.join(" or "));
+        }
+        Some((chosen.is_correct, chosen.option_id))
+    }
+
+    /// Shows the options for a multiple response `question` in a random order and grades the
+    /// user's selections, which may be given as `A,C` or `c a` (see `selection_credit` for how
+    /// they're scored). Returns the credit and the `OptionID`s of the options that were chosen, or
+    /// `None` if the question has no correct options and was skipped.
+    fn ask_multiple_response(question: &Question) -> Option<(f64, Vec<i32>)> {
+        let num_correct: usize = question.options.iter().filter(|option| option.is_correct).count();
+        if num_correct == 0 {
+            println!("This question doesn't have any correct options yet. Skipping...");
+            return None;
+        }
+        let mut shown: Vec<&AnswerOption> = question.options.iter().collect();
+        shown.shuffle(&mut thread_rng());
+        // Questions written in the exam's style already say how many to select
+        if !question.question_text.to_ascii_lowercase().contains("select") {
+            println!("(Select {})", count_word(num_correct));
+        }
+        for (i, option) in shown.iter().enumerate() {
+            println!("  {}.) {}", option_label(i), option.text);
+        }
+
+        let selected: Vec<usize> = loop {
+            let entry: String = read_line("Your answers: ");
+            match parse_selections(&shown, &entry) {
+                Ok(selected) if selected.is_empty() => println!("Entry must not be empty!"),
+                Ok(selected) => break selected,
+                Err(e) => println!("{}", e),
+            }
+        };
+
+        let score: f64 = selection_credit(&shown, &selected);
+        if score >= 1.0 {
+            println!("Correct!");
+        } else {
+            if score > 0.0 {
+                println!("Partially correct ({:.0}% credit):", score * 100.0);
+            } else {
+                println!("Incorrect:");
+            }
+            for (i, option) in shown.iter().enumerate() {
+                let verdict: &str = match (selected.contains(&i), option.is_correct) {
+                    (true, true) => "correct",
+                    (true, false) => "wrong, this shouldn't be selected",
+                    (false, true) => "missed, this should be selected",
+                    (false, false) => continue,
+                };
+                println!("  {}.) {} - {}", option_label(i), option.text, verdict);
+            }
+        }
+        Some((score, selected.iter().map(|i| shown[*i].option_id).collect()))
+    }
+
+    /// Shows the prompts of a matching `question` in order alongside its matches in a random order,
+    /// and grades the user's pairings, which are given as e.g. `1-c 2-a 3-d`. Each prompt paired
+    /// correctly earns an equal share of the credit. Returns the credit and what was chosen for
+    /// each prompt, or `None` if the question has no prompts and was skipped.
+    fn ask_matching(question: &Question) -> Option<(f64, Vec<PairAnswer>)> {
+        let prompts: Vec<(&MatchingPair, &str)> = question.pairs.iter()
+            .filter_map(|pair| pair.prompt.as_deref().map(|prompt| (pair, prompt)))
+            .collect();
+        if prompts.is_empty() {
+            println!("This question doesn't have any pairs yet. Skipping...");
+            return None;
+        }
+        let mut matches: Vec<&MatchingPair> = question.pairs.iter().collect();
+        matches.shuffle(&mut thread_rng());
+
+        let width: usize = prompts.iter().map(|(_, prompt)| prompt.chars().count()).max().unwrap_or(0);
+        for row in 0..prompts.len().max(matches.len()) {
+            let left: String = match prompts.get(row) {
+                Some((_, prompt)) => format!("{:>2}. {:<width$}", row + 1, prompt, width = width),
+                None => " ".repeat(width + 4),
+            };
+            match matches.get(row) {
+                Some(pair) => println!("  {}    {}. {}", left, option_label(row).to_lowercase(), pair.match_text),
+                None => println!("  {}", left.trim_end()),
+            }
+        }
+
+        let pairings: Vec<(usize, usize)> = loop {
+            let entry: String = read_line("Your pairings (e.g., 1-a 2-b): ");
+            match parse_pairings(&entry, prompts.len(), matches.len()) {
+                Ok(pairings) if pairings.is_empty() => println!("Entry must not be empty!"),
+                Ok(pairings) => break pairings,
+                Err(e) => println!("{}", e),
+            }
+        };
+
+        let mut answers: Vec<PairAnswer> = Vec::new();
+        let mut feedback: Vec<String> = Vec::new();
+        for (i, (pair, prompt)) in prompts.iter().enumerate() {
+            let chosen: Option<&MatchingPair> = pairings.iter()
+                .find(|(p, _)| *p == i)
+                .map(|(_, m)| matches[*m]);
+            let is_correct: bool = chosen.is_some_and(|chosen| pair.is_matched_by(chosen));
+            let verdict: String = match chosen {
+                _ if is_correct => String::from("correct"),
+                Some(chosen) => format!("wrong, you chose {}; it's {}", chosen.match_text, pair.match_text),
+                None => format!("not answered; it's {}", pair.match_text),
+            };
+            feedback.push(format!("  {}. {} - {}", i + 1, prompt, verdict));
+            answers.push(PairAnswer { pair_id: pair.pair_id, chosen_pair_id: chosen.map(|c| c.pair_id), is_correct });
+        }
+
+        let num_correct: usize = answers.iter().filter(|answer| answer.is_correct).count();
+        let score: f64 = num_correct as f64 / prompts.len() as f64;
+        if score >= 1.0 {
+            println!("Correct!");
+        } else {
+            println!("{} of {} pairs are correct:", num_correct, prompts.len());
+            for line in feedback {
+                println!("{}", line);
+            }
+        }
+        Some((score, answers))
+    }
+
+    /// Shows the items of an ordering `question` in a random order and grades the order the user
+    /// puts them in, which is given as e.g. `c a b d`, using the question's scoring rule. The
+    /// correct order is always shown afterwards. Returns the credit and the order given, as
+    /// indices into the question's items, or `None` if the question has fewer than two items and
+    /// was skipped.
+    fn ask_ordering(question: &Question) -> Option<(f64, Vec<usize>)> {
+        if question.items.len() < 2 {
+            println!("This question doesn't have enough items yet. Skipping...");
+            return None;
+        }
+        let mut shown: Vec<usize> = (0..question.items.len()).collect();
+        shown.shuffle(&mut thread_rng());
+        for (i, item) in shown.iter().enumerate() {
+            println!("  {}. {}", option_label(i).to_lowercase(), question.items[*item]);
+        }
+
+        let order: Vec<usize> = loop {
+            let entry: String = read_line("Your order (first to last): ");
+            match parse_order(&entry, shown.len()) {
+                Ok(order) => break order.into_iter().map(|i| shown[i]).collect(),
+                Err(e) => println!("{}", e),
+            }
+        };
+
+        let score: f64 = question.scoring_rule.score(&order);
+        if score >= 1.0 {
+            println!("Correct!");
+        } else if score > 0.0 {
+            println!("Partially correct ({:.0}% credit).", score * 100.0);
+        } else {
+            println!("Incorrect.");
+        }
+        println!("The correct order is:");
+        for (i, item) in question.items.iter().enumerate() {
+            println!("  {}. {}", i + 1, item);
+        }
+        Some((score, order))
+    }
+
+    /// Drops the user into the simulator of a `Shell` or `Ios` question, then compares the
+    /// resulting system or running-config against the expected one. Any command sequence that
+    /// reaches the expected state is correct. Returns whether it was correct and the commands
+    /// entered, or `None` if the simulator can't be set up.
+    fn ask_simulator(question: &Question) -> Option<(bool, Vec<String>)> {
+        let mut simulator: Simulator = match Simulator::new(question) {
+            Ok(simulator) => simulator,
+            Err(e) => {
+                println!("This question can't be run; {}. Skipping...", e);
+                return None;
+            },
+        };
+
+        let history: Vec<String> = simulator.interact();
+        let problems: Vec<String> = simulator.problems();
+        if problems.is_empty() {
+            println!("Correct!");
+            return Some((true, history));
+        }
+        match simulator {
+            Simulator::Shell(..) => println!("Incorrect:"),
+            Simulator::Ios(_) => println!("Incorrect. The running-config doesn't match:"),
+        }
+        for problem in problems {
+            println!("  - {}", problem);
+        }
+        println!("One way to solve this: {}", question.answer);
+        Some((false, history))
+    }
+
+    /// Reads a single command line for a `Command`, `Cmd` or `PowerShell` question and grades it
+    /// with `grade_command`. A Windows command is entered at a simulated prompt, which shows its
+    /// output. Returns whether it was correct and the command entered.
+    fn ask_command(question: &Question) -> (bool, String) {
+        let mut console: Option<Console> = match question.question_type {
+            QuestionType::Cmd => Some(Console::new(Interpreter::Cmd)),
+            QuestionType::PowerShell => Some(Console::new(Interpreter::PowerShell)),
+            _ => None,
+        };
+        let prompt: String = console.as_ref().map_or_else(|| String::from("$ "), Console::prompt);
+        let response: String = loop {
+            let entry: String = read_line(&prompt);
+            if entry.is_empty() {
+                println!("Entry must not be empty!");
+            } else {
+                break entry;
+            }
+        };
+        if let Some(console) = console.as_mut() {
+            print!("{}", console.execute(&response));
+        }
+
+        match grade_command(question, &response) {
+            Ok(()) => {
+                println!("Correct!");
+                (true, response)
+            },
+            Err(problems) => {
+                println!("Incorrect:");
+                for problem in problems {
+                    println!("  - {}", problem);
+                }
+                let answer: &str = question.answer.lines().next().unwrap_or("").trim();
+                println!("The correct answer is: {}", answer);
+                (false, response)
+            },
+        }
+    }
+
+    /// Adds a row to the `UserQuestionResponses` table for the current user's answer to `question`,
+    /// along with a `UserResponseOptions` row for each option they chose and a `UserResponsePairs`
+    /// row for each pair they matched. The `score` is the credit earned, from `0.0` to `1.0`; only
+    /// full credit counts as correct. `attempt_id` is the `ExamAttempts` row of the sitting the
+    /// answer was given in, if any.
+    fn record_response(&self, question: &Question, score: f64, selections: &Selections,
+                       attempt_id: Option<i64>) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO UserQuestionResponses (Username, QuestionID, AttemptID, IsCorrect, Score, ResponseText, Timestamp) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                &self.user.username,
+                question.question_id,
+                attempt_id,
+                score >= 1.0,
+                score,
+                selections.text,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        let response_id: i64 = self.conn.last_insert_rowid();
+        for option_id in &selections.options {
+            self.conn.execute(
+                "INSERT INTO UserResponseOptions (ResponseID, OptionID) VALUES (?1, ?2)",
+                params![response_id, option_id],
+            )?;
+        }
+        for pair in &selections.pairs {
+            self.conn.execute(
+                "INSERT INTO UserResponsePairs (ResponseID, PairID, ChosenPairID, IsCorrect) VALUES (?1, ?2, ?3, ?4)",
+                params![response_id, pair.pair_id, pair.chosen_pair_id, pair.is_correct],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_orders_with_or_without_separators() {
+        assert_eq!(parse_order("c a b d", 4), Ok(vec![2, 0, 1, 3]));
+        assert_eq!(parse_order("C,A,B,D", 4), Ok(vec![2, 0, 1, 3]));
+        assert_eq!(parse_order("cabd", 4), Ok(vec![2, 0, 1, 3]));
+        assert_eq!(parse_order("c -> a -> b -> d", 4), Ok(vec![2, 0, 1, 3]));
+        assert_eq!(parse_order("c a b", 4), Err(String::from("Put all 4 items in order, e.g., a b c d.")));
+        assert_eq!(parse_order("c a a d", 4), Err(String::from("Item 'a' was given more than once.")));
+        assert_eq!(parse_order("c a b e", 4), Err(String::from("There's no item 'e'.")));
+    }
+
+    #[test]
+    fn parses_numbered_items_past_z() {
+        let labels: Vec<String> = (0..28).map(option_label).collect();
+        assert_eq!(labels[25..], [String::from("Z"), String::from("27"), String::from("28")]);
+
+        let mut expected: Vec<usize> = (0..28).collect();
+        expected.swap(26, 27);
+        let response: String = format!("{} 28,27", labels[..26].join(""));
+        assert_eq!(parse_order(&response, 28), Ok(expected));
+        assert_eq!(parse_order(&format!("{} 2728", labels[..26].join("")), 28), Err(String::from("There's no item '2728'.")));
+        assert_eq!(parse_order("1 b", 2), Err(String::from("There's no item '1'.")));
+    }
+
+    fn command_question(question_type: QuestionType, answer: &str) -> Question {
+        Question {
+            question_id: 1,
+            question_text: String::new(),
+            answer: String::from(answer),
+            explanation: String::new(),
+            question_type,
+            initial_state: None,
+            expected_state: None,
+            flag_rules: Vec::new(),
+            options: Vec::new(),
+            pairs: Vec::new(),
+            items: Vec::new(),
+            scoring_rule: ScoringRule::default(),
+            objectives: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn grades_each_kind_of_command_by_its_own_rules() {
+        let question: Question = command_question(QuestionType::Command, "ls -la");
+        assert_eq!(grade_command(&question, "ls -al"), Ok(()));
+        assert!(grade_command(&question, "LS -la").is_err());
+
+        let question: Question = command_question(QuestionType::Cmd, "ipconfig /all");
+        assert_eq!(grade_command(&question, "IPCONFIG /ALL"), Ok(()));
+        assert!(grade_command(&question, "ipconfig").is_err());
+
+        let question: Question = command_question(QuestionType::PowerShell, "Get-Service");
+        assert_eq!(grade_command(&question, "get-service"), Ok(()));
+        assert!(grade_command(&question, "Get-Process").is_err());
+    }
+}
@@ -0,0 +1,710 @@
+use std::collections::HashMap;
+use std::net::Ipv6Addr;
+use rand::Rng;
+use rand::rngs::ThreadRng;
+use crate::generate::{format_params, param, parse_params, Difficulty, Problem};
+
+/// A number base used by base conversion problems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+    Binary,
+    Octal,
+    Decimal,
+    Hex,
+}
+
+impl Base {
+    const ALL: [Base; 4] = [Base::Binary, Base::Octal, Base::Decimal, Base::Hex];
+
+    fn radix(self) -> u32 {
+        match self {
+            Base::Binary => 2,
+            Base::Octal => 8,
+            Base::Decimal => 10,
+            Base::Hex => 16,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Base::Binary => "binary",
+            Base::Octal => "octal",
+            Base::Decimal => "decimal",
+            Base::Hex => "hexadecimal",
+        }
+    }
+
+    /// The name used in a problem's parameters.
+    fn key(self) -> &'static str {
+        match self {
+            Base::Binary => "bin",
+            Base::Octal => "oct",
+            Base::Decimal => "dec",
+            Base::Hex => "hex",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Base> {
+        Base::ALL.into_iter().find(|base| base.key() == key)
+    }
+
+    /// Writes `value` in this base. Binary is split into groups of four bits to make it readable.
+    fn format(self, value: u32) -> String {
+        match self {
+            Base::Binary => {
+                let digits: String = format!("{:b}", value);
+                let width: usize = digits.len().div_ceil(4) * 4;
+                let padded: String = format!("{:0>width$}", digits, width = width);
+                padded.as_bytes().chunks(4)
+                    .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            },
+            Base::Octal => format!("{:o}", value),
+            Base::Decimal => value.to_string(),
+            Base::Hex => format!("{:X}", value),
+        }
+    }
+
+    /// Parses a value written in this base. The usual prefixes (`0b`, `0o`, `0x`), a trailing `h`
+    /// on hex, and spaces or underscores between digits are all accepted.
+    fn parse(self, text: &str) -> Option<u32> {
+        let text: String = text.trim().to_ascii_lowercase().replace([' ', '_', ','], "");
+        let digits: &str = match self {
+            Base::Binary => text.strip_prefix("0b").unwrap_or(&text),
+            Base::Octal => text.strip_prefix("0o").unwrap_or(&text),
+            Base::Decimal => &text,
+            Base::Hex => text.strip_prefix("0x").or_else(|| text.strip_suffix('h')).unwrap_or(&text),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        u32::from_str_radix(digits, self.radix()).ok()
+    }
+}
+
+/// How a MAC address is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacStyle {
+    /// `00:1a:2b:3c:4d:5e`, as shown by Linux and macOS.
+    Colon,
+    /// `00-1A-2B-3C-4D-5E`, as shown by Windows.
+    Hyphen,
+    /// `001a.2b3c.4d5e`, as shown by Cisco IOS.
+    Dotted,
+}
+
+impl MacStyle {
+    const ALL: [MacStyle; 3] = [MacStyle::Colon, MacStyle::Hyphen, MacStyle::Dotted];
+
+    fn key(self) -> &'static str {
+        match self {
+            MacStyle::Colon => "colon",
+            MacStyle::Hyphen => "hyphen",
+            MacStyle::Dotted => "dotted",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<MacStyle> {
+        MacStyle::ALL.into_iter().find(|style| style.key() == key)
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            MacStyle::Colon => "Linux format (xx:xx:xx:xx:xx:xx)",
+            MacStyle::Hyphen => "Windows format (XX-XX-XX-XX-XX-XX)",
+            MacStyle::Dotted => "Cisco format (xxxx.xxxx.xxxx)",
+        }
+    }
+
+    fn format(self, mac: &[u8; 6]) -> String {
+        match self {
+            MacStyle::Colon => mac.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<String>>().join(":"),
+            MacStyle::Hyphen => mac.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<String>>().join("-"),
+            MacStyle::Dotted => mac.chunks(2)
+                .map(|pair| format!("{:02x}{:02x}", pair[0], pair[1]))
+                .collect::<Vec<String>>()
+                .join("."),
+        }
+    }
+}
+
+/// Units of data and their size in bytes.
+const DATA_UNITS: &[(&str, f64)] = &[
+    ("B", 1.0),
+    ("KB", 1e3),
+    ("MB", 1e6),
+    ("GB", 1e9),
+    ("TB", 1e12),
+    ("KiB", 1024.0),
+    ("MiB", 1048576.0),
+    ("GiB", 1073741824.0),
+    ("TiB", 1099511627776.0),
+];
+
+/// Units of throughput and their size in bits per second.
+const RATE_UNITS: &[(&str, f64)] = &[("Kbps", 1e3), ("Mbps", 1e6), ("Gbps", 1e9), ("MB/s", 8e6), ("GB/s", 8e9)];
+
+/// The size of the data unit `name` in bytes, or of the throughput unit `name` in bits per second.
+fn unit_size(units: &[(&str, f64)], name: &str) -> Option<f64> {
+    units.iter().find(|(unit, _)| *unit == name).map(|(_, size)| *size)
+}
+
+/// Writes a number with at most two decimal places, dropping them if it's a whole number.
+fn format_number(value: f64) -> String {
+    let rounded: String = format!("{:.2}", value);
+    rounded.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Parses a numeric answer, ignoring thousands separators and any unit written after it (e.g.,
+/// `1,024 KiB` or `40s`).
+fn parse_number(text: &str) -> Option<f64> {
+    let text: String = text.trim().replace(',', "");
+    let end: usize = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    text[..end].parse().ok()
+}
+
+/// Whether `response` is `expected` once both are rounded to two decimal places.
+fn grade_number(response: &str, expected: f64) -> bool {
+    parse_number(response).is_some_and(|value| (value - expected).abs() <= 0.005 + expected.abs() * 1e-9)
+}
+
+/// The hex digits of a MAC address or OUI with any separators removed, in lowercase.
+fn hex_digits(text: &str) -> String {
+    text.chars().filter(|c| !matches!(c, ':' | '-' | '.' | ' ')).collect::<String>().to_ascii_lowercase()
+}
+
+/// The modified EUI-64 interface ID for `mac`: `fffe` goes in the middle and the
+/// universal/local bit (the 7th bit of the first byte) is flipped.
+fn eui64(mac: &[u8; 6]) -> [u16; 4] {
+    let bytes: [u8; 8] = [mac[0] ^ 0x02, mac[1], mac[2], 0xff, 0xfe, mac[3], mac[4], mac[5]];
+    [0, 2, 4, 6].map(|i| u16::from_be_bytes([bytes[i], bytes[i + 1]]))
+}
+
+/// A generated conversion problem.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionProblem {
+    /// Convert `value` from one base to another.
+    Base { value: u32, from: Base, to: Base },
+    /// Rewrite a MAC address in another style.
+    MacFormat { mac: [u8; 6], from: MacStyle, to: MacStyle },
+    /// The vendor part (the first three bytes) of a MAC address.
+    Oui { mac: [u8; 6], style: MacStyle },
+    /// The modified EUI-64 interface ID that SLAAC derives from a MAC address.
+    Eui64 { mac: [u8; 6] },
+    /// Convert `value` of the data unit `from` to the unit `to`.
+    DataUnit { value: f64, from: &'static str, to: &'static str },
+    /// Convert a throughput of `value` in the unit `from` to the unit `to`.
+    Rate { value: f64, from: &'static str, to: &'static str },
+    /// How many seconds it takes to transfer `size` of `size_unit` at `rate` of `rate_unit`.
+    TransferTime { size: f64, size_unit: &'static str, rate: f64, rate_unit: &'static str },
+}
+
+/// Picks a random element of `items`.
+fn pick<T: Copy>(rng: &mut ThreadRng, items: &[T]) -> T {
+    items[rng.gen_range(0..items.len())]
+}
+
+/// Picks two different bases from `bases`, where one of the two must be in `with`.
+fn base_pair(rng: &mut ThreadRng, bases: &[Base], with: &[Base]) -> (Base, Base) {
+    loop {
+        let (from, to) = (pick(rng, bases), pick(rng, bases));
+        if from != to && (with.contains(&from) || with.contains(&to)) {
+            return (from, to);
+        }
+    }
+}
+
+/// A random unicast MAC address with a universally administered OUI.
+fn random_mac(rng: &mut ThreadRng) -> [u8; 6] {
+    let mut mac: [u8; 6] = rng.gen();
+    mac[0] &= 0xfc;
+    mac
+}
+
+/// Picks two different units from `units` (decimal or binary) that are at most `steps` apart.
+fn unit_pair(rng: &mut ThreadRng, units: &[&'static str], steps: usize) -> (&'static str, &'static str) {
+    loop {
+        let (i, j) = (rng.gen_range(0..units.len()), rng.gen_range(0..units.len()));
+        if i != j && i.abs_diff(j) <= steps {
+            return (units[i], units[j]);
+        }
+    }
+}
+
+/// Creates a random conversion problem for the `difficulty`. Easy problems use 8-bit numbers and
+/// neighbouring units; hard ones use 32-bit numbers and EUI-64, and mix decimal and binary units.
+pub fn generate(rng: &mut ThreadRng, difficulty: Difficulty) -> Box<dyn Problem> {
+    let units: &[&str] = if rng.gen_bool(0.5) { &["B", "KB", "MB", "GB", "TB"] } else { &["B", "KiB", "MiB", "GiB", "TiB"] };
+    let problem: ConversionProblem = match (difficulty, rng.gen_range(0..4)) {
+        (Difficulty::Easy, 0) => {
+            let (from, to) = base_pair(rng, &[Base::Binary, Base::Decimal, Base::Hex], &[Base::Decimal]);
+            ConversionProblem::Base { value: rng.gen_range(1..=255), from, to }
+        },
+        (Difficulty::Easy, 1) => {
+            let (from, to) = loop {
+                let (from, to) = (pick(rng, &MacStyle::ALL), pick(rng, &MacStyle::ALL));
+                if from != to {
+                    break (from, to);
+                }
+            };
+            ConversionProblem::MacFormat { mac: random_mac(rng), from, to }
+        },
+        (Difficulty::Easy, _) => {
+            // From a larger unit to the next smaller one, so the answer is whole
+            let i: usize = rng.gen_range(1..units.len());
+            ConversionProblem::DataUnit { value: rng.gen_range(1..=64) as f64, from: units[i], to: units[i - 1] }
+        },
+        (Difficulty::Medium, 0) => {
+            let (from, to) = base_pair(rng, &[Base::Binary, Base::Decimal, Base::Hex], &Base::ALL);
+            ConversionProblem::Base { value: rng.gen_range(256..=65535), from, to }
+        },
+        (Difficulty::Medium, 1) => ConversionProblem::Oui { mac: random_mac(rng), style: pick(rng, &MacStyle::ALL) },
+        (Difficulty::Medium, 2) => {
+            let (from, to) = unit_pair(rng, units, 2);
+            let ratio: f64 = unit_size(DATA_UNITS, to).unwrap_or(1.0) / unit_size(DATA_UNITS, from).unwrap_or(1.0);
+            // Going to a larger unit, pick the answer first so it comes out whole
+            let value: f64 = if ratio > 1.0 { rng.gen_range(1..=512) as f64 * ratio } else { rng.gen_range(1..=64) as f64 };
+            ConversionProblem::DataUnit { value, from, to }
+        },
+        (Difficulty::Medium, _) => {
+            let (from, to) = pick(rng, &[("Mbps", "MB/s"), ("MB/s", "Mbps"), ("Gbps", "MB/s"), ("Gbps", "Mbps")]);
+            let value: f64 = pick(rng, &[10.0, 20.0, 40.0, 50.0, 100.0, 200.0, 250.0, 400.0, 500.0, 800.0]);
+            ConversionProblem::Rate { value: if from == "Gbps" { value / 100.0 } else { value }, from, to }
+        },
+        (Difficulty::Hard, 0) => {
+            let (from, to) = base_pair(rng, &Base::ALL, &Base::ALL);
+            // Keep binary to 24 bits so that it can still be typed
+            let value: u32 = if from == Base::Binary || to == Base::Binary {
+                rng.gen_range(0x10000..=0xFFFFFF)
+            } else {
+                rng.gen_range(0x1000000..=u32::MAX)
+            };
+            ConversionProblem::Base { value, from, to }
+        },
+        (Difficulty::Hard, 1) => ConversionProblem::Eui64 { mac: random_mac(rng) },
+        (Difficulty::Hard, 2) => {
+            let (from, to) = pick(rng, &[("GB", "GiB"), ("TB", "TiB"), ("TB", "GiB"), ("GiB", "MB"), ("MiB", "KB")]);
+            ConversionProblem::DataUnit { value: pick(rng, &[1.0, 2.0, 4.0, 8.0, 16.0, 128.0, 250.0, 500.0]), from, to }
+        },
+        (Difficulty::Hard, _) => {
+            let size_unit: &str = pick(rng, &["MB", "GB", "MiB", "GiB"]);
+            let size: f64 = if size_unit.starts_with('M') {
+                pick(rng, &[100.0, 250.0, 500.0, 700.0])
+            } else {
+                pick(rng, &[1.5, 4.0, 8.0, 20.0])
+            };
+            let rate_unit: &str = pick(rng, &["Mbps", "Gbps"]);
+            let rate: f64 = if rate_unit == "Mbps" { pick(rng, &[10.0, 20.0, 50.0, 100.0, 300.0]) } else { pick(rng, &[1.0, 2.5, 10.0]) };
+            ConversionProblem::TransferTime { size, size_unit, rate, rate_unit }
+        },
+    };
+    Box::new(problem)
+}
+
+/// Recreates a conversion problem from the parameters written by `Problem::params`.
+pub fn replay(params: &str) -> Result<Box<dyn Problem>, String> {
+    let params: HashMap<&str, &str> = parse_params(params);
+    let base = |key: &str| -> Result<Base, String> {
+        params.get(key).and_then(|value| Base::from_key(value)).ok_or_else(|| format!("invalid parameter '{}'", key))
+    };
+    let style = |key: &str| -> Result<MacStyle, String> {
+        params.get(key).and_then(|value| MacStyle::from_key(value)).ok_or_else(|| format!("invalid parameter '{}'", key))
+    };
+    let mac = || -> Result<[u8; 6], String> {
+        let digits: String = param(&params, "mac")?;
+        let value: u64 = u64::from_str_radix(&digits, 16).ok()
+            .filter(|_| digits.len() == 12)
+            .ok_or_else(|| format!("invalid MAC address '{}'", digits))?;
+        let bytes: [u8; 8] = value.to_be_bytes();
+        Ok([bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]])
+    };
+    let unit = |units: &[(&'static str, f64)], key: &str| -> Result<&'static str, String> {
+        let name: &str = params.get(key).copied().unwrap_or("");
+        units.iter().find(|(unit, _)| *unit == name).map(|(unit, _)| *unit)
+            .ok_or_else(|| format!("invalid unit '{}'", name))
+    };
+    let number = |key: &str| -> Result<f64, String> {
+        param::<f64>(&params, key).and_then(|value| {
+            if value.is_finite() && value > 0.0 { Ok(value) } else { Err(format!("invalid value for parameter '{}'", key)) }
+        })
+    };
+
+    let problem: ConversionProblem = match params.get("kind").copied() {
+        Some("base") => ConversionProblem::Base { value: param(&params, "value")?, from: base("from")?, to: base("to")? },
+        Some("mac-format") => ConversionProblem::MacFormat { mac: mac()?, from: style("from")?, to: style("to")? },
+        Some("oui") => ConversionProblem::Oui { mac: mac()?, style: style("style")? },
+        Some("eui64") => ConversionProblem::Eui64 { mac: mac()? },
+        Some("data-unit") => ConversionProblem::DataUnit {
+            value: number("value")?,
+            from: unit(DATA_UNITS, "from")?,
+            to: unit(DATA_UNITS, "to")?,
+        },
+        Some("rate") => ConversionProblem::Rate {
+            value: number("value")?,
+            from: unit(RATE_UNITS, "from")?,
+            to: unit(RATE_UNITS, "to")?,
+        },
+        Some("transfer-time") => ConversionProblem::TransferTime {
+            size: number("size")?,
+            size_unit: unit(DATA_UNITS, "size_unit")?,
+            rate: number("rate")?,
+            rate_unit: unit(RATE_UNITS, "rate_unit")?,
+        },
+        Some(kind) => return Err(format!("unknown kind of conversion problem '{}'", kind)),
+        None => return Err(String::from("missing parameter 'kind'")),
+    };
+    Ok(Box::new(problem))
+}
+
+impl ConversionProblem {
+    /// The exact answer to a numeric problem.
+    fn value(&self) -> f64 {
+        match self {
+            ConversionProblem::DataUnit { value, from, to } => {
+                value * unit_size(DATA_UNITS, from).unwrap_or(1.0) / unit_size(DATA_UNITS, to).unwrap_or(1.0)
+            },
+            ConversionProblem::Rate { value, from, to } => {
+                value * unit_size(RATE_UNITS, from).unwrap_or(1.0) / unit_size(RATE_UNITS, to).unwrap_or(1.0)
+            },
+            ConversionProblem::TransferTime { size, size_unit, rate, rate_unit } => {
+                size * unit_size(DATA_UNITS, size_unit).unwrap_or(1.0) * 8.0
+                    / (rate * unit_size(RATE_UNITS, rate_unit).unwrap_or(1.0))
+            },
+            _ => 0.0,
+        }
+    }
+}
+
+impl Problem for ConversionProblem {
+    fn params(&self) -> String {
+        let mac_digits = |mac: &[u8; 6]| mac.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        match self {
+            ConversionProblem::Base { value, from, to } => format_params(&[
+                ("kind", String::from("base")),
+                ("value", value.to_string()),
+                ("from", String::from(from.key())),
+                ("to", String::from(to.key())),
+            ]),
+            ConversionProblem::MacFormat { mac, from, to } => format_params(&[
+                ("kind", String::from("mac-format")),
+                ("mac", mac_digits(mac)),
+                ("from", String::from(from.key())),
+                ("to", String::from(to.key())),
+            ]),
+            ConversionProblem::Oui { mac, style } => format_params(&[
+                ("kind", String::from("oui")), ("mac", mac_digits(mac)), ("style", String::from(style.key())),
+            ]),
+            ConversionProblem::Eui64 { mac } => format_params(&[("kind", String::from("eui64")), ("mac", mac_digits(mac))]),
+            ConversionProblem::DataUnit { value, from, to } => format_params(&[
+                ("kind", String::from("data-unit")),
+                ("value", value.to_string()),
+                ("from", String::from(*from)),
+                ("to", String::from(*to)),
+            ]),
+            ConversionProblem::Rate { value, from, to } => format_params(&[
+                ("kind", String::from("rate")),
+                ("value", value.to_string()),
+                ("from", String::from(*from)),
+                ("to", String::from(*to)),
+            ]),
+            ConversionProblem::TransferTime { size, size_unit, rate, rate_unit } => format_params(&[
+                ("kind", String::from("transfer-time")),
+                ("size", size.to_string()),
+                ("size_unit", String::from(*size_unit)),
+                ("rate", rate.to_string()),
+                ("rate_unit", String::from(*rate_unit)),
+            ]),
+        }
+    }
+
+    fn prompt(&self) -> String {
+        match self {
+            ConversionProblem::Base { value, from, to } => {
+                format!("Convert the {} value {} to {}.", from.name(), from.format(*value), to.name())
+            },
+            ConversionProblem::MacFormat { mac, from, to } => {
+                format!("Write the MAC address {} in {}.", from.format(mac), to.description())
+            },
+            ConversionProblem::Oui { mac, style } => {
+                format!("What is the OUI (vendor ID) of the MAC address {}?", style.format(mac))
+            },
+            ConversionProblem::Eui64 { mac } => format!(
+                "An interface with the MAC address {} uses SLAAC. What is its modified EUI-64 interface ID \
+                (the last 64 bits of its IPv6 address)?",
+                MacStyle::Colon.format(mac),
+            ),
+            ConversionProblem::DataUnit { value, from, to } => format!(
+                "How many {} are in {} {}? (Round to 2 decimal places.)", to, format_number(*value), from,
+            ),
+            ConversionProblem::Rate { value, from, to } => {
+                format!("A link transfers {} {}. What is that in {}?", format_number(*value), from, to)
+            },
+            ConversionProblem::TransferTime { size, size_unit, rate, rate_unit } => format!(
+                "How many seconds does it take to transfer a {} {} file over a {} {} link at full speed? \
+                (Round to 2 decimal places.)",
+                format_number(*size), size_unit, format_number(*rate), rate_unit,
+            ),
+        }
+    }
+
+    fn grade(&self, response: &str) -> bool {
+        match self {
+            ConversionProblem::Base { value, to, .. } => to.parse(response) == Some(*value),
+            ConversionProblem::MacFormat { mac, to, .. } => response.trim().eq_ignore_ascii_case(&to.format(mac)),
+            ConversionProblem::Oui { mac, .. } => hex_digits(response) == hex_digits(&MacStyle::Colon.format(mac))[..6],
+            ConversionProblem::Eui64 { mac } => {
+                let response: &str = response.trim();
+                let groups: Option<[u16; 4]> = match response.parse::<Ipv6Addr>() {
+                    Ok(addr) => {
+                        let segments: [u16; 8] = addr.segments();
+                        Some([segments[4], segments[5], segments[6], segments[7]])
+                    },
+                    Err(_) => {
+                        let groups: Vec<u16> = response.split(':')
+                            .filter_map(|group| u16::from_str_radix(group, 16).ok())
+                            .collect();
+                        <[u16; 4]>::try_from(groups).ok().filter(|_| response.split(':').count() == 4)
+                    },
+                };
+                groups == Some(eui64(mac))
+            },
+            _ => grade_number(response, self.value()),
+        }
+    }
+
+    fn answer(&self) -> String {
+        match self {
+            ConversionProblem::Base { value, to, .. } => to.format(*value),
+            ConversionProblem::MacFormat { mac, to, .. } => to.format(mac),
+            ConversionProblem::Oui { mac, style } => {
+                let full: String = style.format(mac);
+                String::from(&full[..if *style == MacStyle::Dotted { 7 } else { 8 }])
+            },
+            ConversionProblem::Eui64 { mac } => {
+                eui64(mac).iter().map(|group| format!("{:x}", group)).collect::<Vec<String>>().join(":")
+            },
+            ConversionProblem::DataUnit { to, .. } | ConversionProblem::Rate { to, .. } => {
+                format!("{} {}", format_number(self.value()), to)
+            },
+            ConversionProblem::TransferTime { .. } => format!("{} seconds", format_number(self.value())),
+        }
+    }
+
+    fn explanation(&self) -> String {
+        match self {
+            ConversionProblem::Base { value, from, to } => {
+                let mut steps: Vec<String> = Vec::new();
+                let digits: String = from.format(*value).replace(' ', "");
+                // Binary converts to and from hex or octal a digit at a time
+                let other: Option<Base> = match (from, to) {
+                    (Base::Binary, Base::Hex | Base::Octal) => Some(*to),
+                    (Base::Hex | Base::Octal, Base::Binary) => Some(*from),
+                    _ => None,
+                };
+                if let Some(other) = other {
+                    let width: usize = if other == Base::Hex { 4 } else { 3 };
+                    let groups: Vec<String> = other.format(*value).chars()
+                        .map(|digit| {
+                            let bits: u32 = digit.to_digit(other.radix()).unwrap_or(0);
+                            format!("{}={:0width$b}", digit, bits, width = width)
+                        })
+                        .collect();
+                    steps.push(format!(
+                        "Each {} digit is exactly {} bits, so convert one digit at a time (padding the binary \
+                        with leading zeros to a multiple of {} bits): {}.",
+                        other.name(), width, width, groups.join(", "),
+                    ));
+                } else if *from != Base::Decimal {
+                    let terms: Vec<String> = digits.chars().enumerate()
+                        .filter(|(_, digit)| *digit != '0')
+                        .map(|(i, digit)| format!("{}×{}^{}", digit, from.radix(), digits.len() - 1 - i))
+                        .collect();
+                    steps.push(format!(
+                        "Multiply each digit by its place value (a power of {}) and add them up: {} = {}.",
+                        from.radix(), terms.join(" + "), value,
+                    ));
+                }
+                if other.is_none() && *to != Base::Decimal {
+                    let mut divisions: Vec<String> = Vec::new();
+                    let mut rest: u32 = *value;
+                    while rest > 0 {
+                        divisions.push(format!("{} ÷ {} = {} r {:X}", rest, to.radix(), rest / to.radix(), rest % to.radix()));
+                        rest /= to.radix();
+                    }
+                    if divisions.len() > 8 {
+                        steps.push(format!(
+                            "Divide by {} repeatedly and read the remainders from the last to the first.", to.radix(),
+                        ));
+                    } else {
+                        steps.push(format!(
+                            "Divide by {} repeatedly and read the remainders from the last to the first: {}.",
+                            to.radix(), divisions.join(", "),
+                        ));
+                    }
+                }
+                steps.push(format!("So {} in {} is {}.", from.format(*value), to.name(), to.format(*value)));
+                steps.iter().enumerate().map(|(i, step)| format!("{}. {}", i + 1, step)).collect::<Vec<String>>().join("\n")
+            },
+            ConversionProblem::MacFormat { mac, to, .. } => format!(
+                "1. A MAC address is 48 bits, written as 12 hex digits: {}.\n\
+                2. Only the grouping and separators change between formats. In {} that's {}.",
+                hex_digits(&MacStyle::Colon.format(mac)), to.description(), to.format(mac),
+            ),
+            ConversionProblem::Oui { mac, style } => format!(
+                "1. The first 24 bits (6 hex digits) of a MAC address are the OUI, which identifies the vendor.\n\
+                2. The remaining 24 bits are assigned by the vendor.\n3. So the OUI of {} is {}.",
+                style.format(mac), self.answer(),
+            ),
+            ConversionProblem::Eui64 { mac } => {
+                let flipped: u8 = mac[0] ^ 0x02;
+                format!(
+                    "1. Split the MAC address in half: {:02x}{:02x}{:02x} | {:02x}{:02x}{:02x}.\n\
+                    2. Insert fffe in the middle: {:02x}{:02x}{:02x}fffe{:02x}{:02x}{:02x}.\n\
+                    3. Flip the 7th bit of the first byte (the universal/local bit): {:02x} = {:08b} becomes \
+                    {:08b} = {:02x}.\n4. Group into four 16-bit fields, dropping leading zeros: {}.",
+                    mac[0], mac[1], mac[2], mac[3], mac[4], mac[5],
+                    mac[0], mac[1], mac[2], mac[3], mac[4], mac[5],
+                    mac[0], mac[0], flipped, flipped, self.answer(),
+                )
+            },
+            ConversionProblem::DataUnit { value, from, to } => {
+                let (from_bytes, to_bytes): (f64, f64) = (
+                    unit_size(DATA_UNITS, from).unwrap_or(1.0), unit_size(DATA_UNITS, to).unwrap_or(1.0),
+                );
+                format!(
+                    "1. Decimal units step by 1000 (KB, MB, GB); binary units step by 1024 (KiB, MiB, GiB).\n\
+                    2. 1 {} = {} B and 1 {} = {} B.\n3. {} × {} ÷ {} = {}.",
+                    from, from_bytes, to, to_bytes, format_number(*value), from_bytes, to_bytes, self.answer(),
+                )
+            },
+            ConversionProblem::Rate { value, from, to } => format!(
+                "1. Lowercase b is bits and uppercase B is bytes; there are 8 bits in a byte.\n\
+                2. 1 {} = {} bits/s and 1 {} = {} bits/s.\n3. {} × {} ÷ {} = {}.",
+                from, unit_size(RATE_UNITS, from).unwrap_or(1.0), to, unit_size(RATE_UNITS, to).unwrap_or(1.0),
+                format_number(*value), unit_size(RATE_UNITS, from).unwrap_or(1.0),
+                unit_size(RATE_UNITS, to).unwrap_or(1.0), self.answer(),
+            ),
+            ConversionProblem::TransferTime { size, size_unit, rate, rate_unit } => {
+                let bits: f64 = size * unit_size(DATA_UNITS, size_unit).unwrap_or(1.0) * 8.0;
+                let bps: f64 = rate * unit_size(RATE_UNITS, rate_unit).unwrap_or(1.0);
+                format!(
+                    "1. Convert the size to bits: {} {} × 8 = {} bits.\n2. Convert the speed to bits per second: \
+                    {} {} = {} bits/s.\n3. Divide: {} ÷ {} = {}.",
+                    format_number(*size), size_unit, bits, format_number(*rate), rate_unit, bps, bits, bps, self.answer(),
+                )
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAC: [u8; 6] = [0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e];
+
+    #[test]
+    fn converts_between_bases() {
+        let problem = ConversionProblem::Base { value: 192, from: Base::Decimal, to: Base::Binary };
+        assert_eq!(problem.answer(), "1100 0000");
+        for response in ["11000000", "1100 0000", "0b1100_0000"] {
+            assert!(problem.grade(response), "{}", response);
+        }
+        assert!(!problem.grade("1100 0001"));
+
+        let problem = ConversionProblem::Base { value: 0xBEEF, from: Base::Decimal, to: Base::Hex };
+        assert_eq!(problem.answer(), "BEEF");
+        for response in ["BEEF", "beef", "0xbeef", "BEEFh"] {
+            assert!(problem.grade(response), "{}", response);
+        }
+        assert!(!problem.grade("0x"));
+        assert!(ConversionProblem::Base { value: 0o755, from: Base::Binary, to: Base::Octal }.grade("0o755"));
+        assert!(ConversionProblem::Base { value: 48879, from: Base::Hex, to: Base::Decimal }.grade("48,879"));
+    }
+
+    #[test]
+    fn formats_mac_addresses() {
+        assert_eq!(MacStyle::Colon.format(&MAC), "00:1a:2b:3c:4d:5e");
+        assert_eq!(MacStyle::Hyphen.format(&MAC), "00-1A-2B-3C-4D-5E");
+        assert_eq!(MacStyle::Dotted.format(&MAC), "001a.2b3c.4d5e");
+
+        let problem = ConversionProblem::MacFormat { mac: MAC, from: MacStyle::Colon, to: MacStyle::Hyphen };
+        assert!(problem.grade("00-1a-2b-3c-4d-5e"));
+        assert!(!problem.grade("00:1a:2b:3c:4d:5e"));
+
+        let problem = ConversionProblem::Oui { mac: MAC, style: MacStyle::Dotted };
+        assert_eq!(problem.answer(), "001a.2b");
+        for response in ["001a.2b", "00:1A:2B", "00-1a-2b", "001A2B"] {
+            assert!(problem.grade(response), "{}", response);
+        }
+        assert!(!problem.grade("1a:2b:3c"));
+    }
+
+    #[test]
+    fn derives_eui64_interface_ids() {
+        let problem = ConversionProblem::Eui64 { mac: MAC };
+        assert_eq!(problem.answer(), "21a:2bff:fe3c:4d5e");
+        for response in ["21a:2bff:fe3c:4d5e", "021A:2BFF:FE3C:4D5E", "fe80::21a:2bff:fe3c:4d5e"] {
+            assert!(problem.grade(response), "{}", response);
+        }
+        // Without flipping the universal/local bit
+        assert!(!problem.grade("1a:2bff:fe3c:4d5e"));
+        assert!(!problem.grade("21a:2bff:fe3c"));
+    }
+
+    #[test]
+    fn converts_decimal_and_binary_units() {
+        let problem = ConversionProblem::DataUnit { value: 1.0, from: "GiB", to: "MB" };
+        assert_eq!(problem.answer(), "1073.74 MB");
+        for response in ["1073.74", "1,073.74 MB", "1073.741824"] {
+            assert!(problem.grade(response), "{}", response);
+        }
+        assert!(!problem.grade("1024"));
+        assert_eq!(ConversionProblem::DataUnit { value: 2.0, from: "KiB", to: "B" }.answer(), "2048 B");
+        assert_eq!(ConversionProblem::DataUnit { value: 1.0, from: "TB", to: "GiB" }.answer(), "931.32 GiB");
+        assert_eq!(ConversionProblem::DataUnit { value: 3.0, from: "MB", to: "KB" }.answer(), "3000 KB");
+    }
+
+    #[test]
+    fn converts_throughput_and_transfer_times() {
+        let problem = ConversionProblem::Rate { value: 100.0, from: "Mbps", to: "MB/s" };
+        assert_eq!(problem.answer(), "12.5 MB/s");
+        assert!(problem.grade("12.5MB/s"));
+        assert!(!problem.grade("100"));
+        assert_eq!(ConversionProblem::Rate { value: 2.5, from: "Gbps", to: "Mbps" }.answer(), "2500 Mbps");
+
+        let problem = ConversionProblem::TransferTime { size: 500.0, size_unit: "MB", rate: 100.0, rate_unit: "Mbps" };
+        assert_eq!(problem.answer(), "40 seconds");
+        assert!(problem.grade("40s"));
+        assert!(problem.grade("40.00"));
+        let problem = ConversionProblem::TransferTime { size: 1.5, size_unit: "GiB", rate: 1.0, rate_unit: "Gbps" };
+        assert_eq!(problem.answer(), "12.88 seconds");
+        assert!(problem.grade("12.88"));
+        assert!(!problem.grade("12.9"));
+    }
+
+    #[test]
+    fn params_replay_the_same_problem() {
+        let problems: Vec<ConversionProblem> = vec![
+            ConversionProblem::Base { value: 0xFFFFFF, from: Base::Binary, to: Base::Hex },
+            ConversionProblem::MacFormat { mac: MAC, from: MacStyle::Dotted, to: MacStyle::Colon },
+            ConversionProblem::Oui { mac: [0xfc, 0xff, 0, 0, 0, 1], style: MacStyle::Hyphen },
+            ConversionProblem::Eui64 { mac: MAC },
+            ConversionProblem::DataUnit { value: 262144.0, from: "KiB", to: "MiB" },
+            ConversionProblem::Rate { value: 0.4, from: "Gbps", to: "MB/s" },
+            ConversionProblem::TransferTime { size: 1.5, size_unit: "GB", rate: 2.5, rate_unit: "Gbps" },
+        ];
+        for problem in problems {
+            let replayed: Box<dyn Problem> = replay(&problem.params()).unwrap();
+            assert_eq!(replayed.params(), problem.params());
+            assert_eq!(replayed.prompt(), problem.prompt());
+            assert_eq!(replayed.answer(), problem.answer());
+        }
+        assert!(replay("kind=base;value=12;from=bin;to=roman").is_err());
+        assert!(replay("kind=eui64;mac=001a2b3c4d").is_err());
+        assert!(replay("kind=data-unit;value=-1;from=MB;to=KB").is_err());
+        assert!(replay("kind=rate;value=NaN;from=Mbps;to=MB/s").is_err());
+        assert!(replay("kind=transfer-time;size=1;size_unit=MB;rate=1;rate_unit=baud").is_err());
+    }
+}
@@ -0,0 +1,409 @@
+pub mod conversion;
+pub mod permissions;
+pub mod subnet;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use rand::rngs::ThreadRng;
+use rand::thread_rng;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use rusqlite::{params, Connection};
+use crate::term_io::{read_choice, read_line};
+
+/// How hard generated problems should be, as stored in `GeneratedQuestions.Difficulty`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl FromSql for Difficulty {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "Easy" => Ok(Difficulty::Easy),
+            "Medium" => Ok(Difficulty::Medium),
+            "Hard" => Ok(Difficulty::Hard),
+            other => Err(FromSqlError::Other(format!("Unknown difficulty: {}", other).into())),
+        }
+    }
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// How long each answer may take during a timed streak. Harder problems take more working out.
+    pub fn time_limit(self) -> Duration {
+        match self {
+            Difficulty::Easy => Duration::from_secs(20),
+            Difficulty::Medium => Duration::from_secs(30),
+            Difficulty::Hard => Duration::from_secs(60),
+        }
+    }
+}
+
+/// A generated question. Every problem can be written out as its parameters, which are saved in
+/// the `GeneratedQuestions` table so that the exact problem can be asked again later.
+pub trait Problem {
+    /// The parameters that `Generator::replay` turns back into this problem, e.g.,
+    /// `kind=network;addr=10.1.2.3;prefix=22`.
+    fn params(&self) -> String;
+
+    /// The question shown to the user.
+    fn prompt(&self) -> String;
+
+    /// Whether the `response` is a correct answer. Equivalent ways of writing the answer (e.g.,
+    /// `/24` and `24`) are all accepted.
+    fn grade(&self, response: &str) -> bool;
+
+    /// The correct answer, as it's shown when the user gets it wrong.
+    fn answer(&self) -> String;
+
+    /// A step-by-step explanation of how the answer is worked out.
+    fn explanation(&self) -> String;
+}
+
+/// A kind of generated question, as stored in `GeneratedQuestions.Generator`.
+pub struct Generator {
+    pub name: &'static str,
+    pub title: &'static str,
+    /// Whether the generator makes easier or harder problems depending on the `Difficulty`. The
+    /// user is only asked to pick one if so.
+    pub levels: bool,
+    /// Creates a new, random problem.
+    pub generate: fn(&mut ThreadRng, Difficulty) -> Box<dyn Problem>,
+    /// Recreates a problem from its saved parameters.
+    pub replay: fn(&str) -> Result<Box<dyn Problem>, String>,
+}
+
+/// Every kind of question that can be generated, in the order they're offered.
+pub const GENERATORS: &[Generator] = &[
+    Generator {
+        name: "subnetting",
+        title: "IPv4 and IPv6 subnetting",
+        levels: false,
+        generate: subnet::generate,
+        replay: subnet::replay,
+    },
+    Generator {
+        name: "permissions",
+        title: "Linux file permissions",
+        levels: false,
+        generate: permissions::generate,
+        replay: permissions::replay,
+    },
+    Generator {
+        name: "conversions",
+        title: "Number bases, MAC addresses and data units",
+        levels: true,
+        generate: conversion::generate,
+        replay: conversion::replay,
+    },
+];
+
+/// Looks up the generator with the given `name`.
+pub fn generator(name: &str) -> Option<&'static Generator> {
+    GENERATORS.iter().find(|generator| generator.name == name)
+}
+
+/// Joins `pairs` into the `key=value;key=value` format used for a problem's parameters.
+pub fn format_params(pairs: &[(&str, String)]) -> String {
+    pairs.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<String>>().join(";")
+}
+
+/// Splits parameters written by `format_params` back into their keys and values.
+pub fn parse_params(params: &str) -> HashMap<&str, &str> {
+    params.split(';').filter_map(|pair| pair.split_once('=')).collect()
+}
+
+/// Looks up the parameter `key` and parses it, describing what's wrong if it can't be.
+pub fn param<T: std::str::FromStr>(params: &HashMap<&str, &str>, key: &str) -> Result<T, String> {
+    let value: &str = params.get(key).ok_or_else(|| format!("missing parameter '{}'", key))?;
+    value.parse().map_err(|_| format!("invalid value '{}' for parameter '{}'", value, key))
+}
+
+/// Lets the user pick a drill and answer generated questions until they stop, replay the
+/// generated questions they got wrong most recently, or look over their results.
+pub fn practice(conn: &Connection, username: &str) -> rusqlite::Result<()> {
+    loop {
+        println!("\nPractice drills:");
+        for (i, generator) in GENERATORS.iter().enumerate() {
+            println!("  {}. {}", i + 1, generator.title);
+        }
+        println!("  {}. Replay problems you missed", GENERATORS.len() + 1);
+        println!("  {}. Your drill results", GENERATORS.len() + 2);
+
+        match read_choice("Select a drill (or 'q' to go back): ", GENERATORS.len() + 2) {
+            Some(index) if index < GENERATORS.len() => start_drill(conn, username, &GENERATORS[index])?,
+            Some(index) if index == GENERATORS.len() => replay_missed(conn, username)?,
+            Some(_) => results(conn, username)?,
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Asks for the difficulty (if the `generator` has levels) and whether to practice at the user's
+/// own pace or against the clock, then starts the drill.
+fn start_drill(conn: &Connection, username: &str, generator: &Generator) -> rusqlite::Result<()> {
+    let difficulty: Difficulty = if generator.levels {
+        println!("\nDifficulty:");
+        for (i, difficulty) in Difficulty::ALL.iter().enumerate() {
+            println!("  {}. {}", i + 1, difficulty.as_str());
+        }
+        match read_choice("Select a difficulty (or 'q' to go back): ", Difficulty::ALL.len()) {
+            Some(index) => Difficulty::ALL[index],
+            None => return Ok(()),
+        }
+    } else {
+        Difficulty::Medium
+    };
+
+    println!("\nMode:");
+    println!("  1. Practice at your own pace");
+    println!("  2. Timed streak ({} seconds per answer)", difficulty.time_limit().as_secs());
+    match read_choice("Select a mode (or 'q' to go back): ", 2) {
+        Some(0) => drill(conn, username, generator, difficulty),
+        Some(_) => streak(conn, username, generator, difficulty),
+        None => Ok(()),
+    }
+}
+
+/// Asks fresh problems from the `generator` until the user enters `q`.
+fn drill(conn: &Connection, username: &str, generator: &Generator, difficulty: Difficulty) -> rusqlite::Result<()> {
+    println!("\n=== {} ===", generator.title);
+    println!("Enter 'q' at any question to stop.");
+    let mut rng: ThreadRng = thread_rng();
+    let (mut num_correct, mut total): (usize, usize) = (0, 0);
+    loop {
+        let problem: Box<dyn Problem> = (generator.generate)(&mut rng, difficulty);
+        let is_correct: bool = match ask(problem.as_ref()) {
+            Some(is_correct) => is_correct,
+            None => break,
+        };
+        record(conn, username, None, generator, generator.levels.then_some(difficulty), problem.as_ref(), is_correct)?;
+        total += 1;
+        if is_correct {
+            num_correct += 1;
+        }
+    }
+    if total > 0 {
+        println!("\nYou answered {} of {} problems correctly.", num_correct, total);
+    }
+    Ok(())
+}
+
+/// Asks fresh problems from the `generator` until the user gets one wrong, takes longer than the
+/// difficulty's time limit to answer, or enters `q`. The length of the streak is saved in
+/// `DrillStreaks` and compared to the user's best.
+fn streak(conn: &Connection, username: &str, generator: &Generator, difficulty: Difficulty) -> rusqlite::Result<()> {
+    let limit: Duration = difficulty.time_limit();
+    println!("\n=== {} - Timed streak ===", generator.title);
+    println!(
+        "Answer as many problems in a row as you can. Each answer must be right and take no more than {} seconds.",
+        limit.as_secs(),
+    );
+    let mut rng: ThreadRng = thread_rng();
+    let started: Instant = Instant::now();
+    let (mut length, mut total): (usize, usize) = (0, 0);
+    loop {
+        let problem: Box<dyn Problem> = (generator.generate)(&mut rng, difficulty);
+        let asked: Instant = Instant::now();
+        let is_correct: bool = match ask(problem.as_ref()) {
+            Some(is_correct) => is_correct,
+            None => break,
+        };
+        total += 1;
+        let elapsed: Duration = asked.elapsed();
+        record(conn, username, None, generator, generator.levels.then_some(difficulty), problem.as_ref(), is_correct)?;
+        if !is_correct {
+            break;
+        }
+        if elapsed > limit {
+            println!("Too slow! That took {:.1} seconds.", elapsed.as_secs_f64());
+            break;
+        }
+        length += 1;
+        println!("Streak: {}", length);
+    }
+    if total == 0 {
+        return Ok(());
+    }
+
+    let level: Option<&str> = generator.levels.then(|| difficulty.as_str());
+    let best: Option<usize> = conn.query_row(
+        "SELECT MAX(Length) FROM DrillStreaks WHERE Username = ?1 AND Generator = ?2 AND Difficulty IS ?3",
+        params![username, generator.name, level],
+        |row| row.get(0),
+    )?;
+    conn.execute(
+        "INSERT INTO DrillStreaks (Username, Generator, Difficulty, Length, Seconds, Timestamp) \
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![username, generator.name, level, length, started.elapsed().as_secs_f64(), Utc::now().to_rfc3339()],
+    )?;
+    println!("\nYour streak: {} in {:.0} seconds.", length, started.elapsed().as_secs_f64());
+    match best {
+        Some(best) if best >= length => println!("Your best streak is {}.", best),
+        Some(_) => println!("That's a new best streak!"),
+        None => {},
+    }
+    Ok(())
+}
+
+/// Shows how the user has done on each kind of generated problem, weakest first, along with
+/// their best timed streaks.
+fn results(conn: &Connection, username: &str) -> rusqlite::Result<()> {
+    let responses: Vec<(String, String, Option<Difficulty>, bool)> = {
+        let mut stmt = conn.prepare(
+            "SELECT g.Generator, g.Parameters, g.Difficulty, r.IsCorrect FROM UserQuestionResponses r \
+            JOIN GeneratedQuestions g ON g.GeneratedID = r.GeneratedID \
+            WHERE r.Username = ?1"
+        )?;
+        let rows = stmt.query_map([username], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<rusqlite::Result<Vec<(String, String, Option<Difficulty>, bool)>>>()?
+    };
+    if responses.is_empty() {
+        println!("You haven't answered any generated problems yet.");
+        return Ok(());
+    }
+
+    // Counts the correct answers and the total for each generator, kind of problem and difficulty
+    type Topic = (String, String, Option<&'static str>);
+    let mut tallies: HashMap<Topic, (usize, usize)> = HashMap::new();
+    for (name, params, difficulty, is_correct) in &responses {
+        let title: &str = generator(name).map_or(name.as_str(), |generator| generator.title);
+        let kind: &str = parse_params(params).get("kind").copied().unwrap_or("other");
+        let tally = tallies.entry((String::from(title), String::from(kind), difficulty.map(Difficulty::as_str)))
+            .or_insert((0, 0));
+        if *is_correct {
+            tally.0 += 1;
+        }
+        tally.1 += 1;
+    }
+    let mut tallies: Vec<(Topic, (usize, usize))> = tallies.into_iter().collect();
+    tallies.sort_by(|(a_key, (a_correct, a_total)), (b_key, (b_correct, b_total))| {
+        (a_correct * b_total).cmp(&(b_correct * a_total)).then(b_total.cmp(a_total)).then(a_key.cmp(b_key))
+    });
+
+    println!("\nYour drill results (weakest first):");
+    for ((title, kind, difficulty), (num_correct, total)) in &tallies {
+        let level: String = difficulty.map(|difficulty| format!(", {}", difficulty)).unwrap_or_default();
+        println!(
+            "  {} - {}{}: {} of {} correct ({:.0}%)",
+            title, kind, level, num_correct, total, *num_correct as f64 / *total as f64 * 100.0,
+        );
+    }
+
+    let streaks: Vec<(String, Option<String>, usize)> = {
+        let mut stmt = conn.prepare(
+            "SELECT Generator, Difficulty, MAX(Length) FROM DrillStreaks WHERE Username = ?1 \
+            GROUP BY Generator, Difficulty ORDER BY Generator, Difficulty"
+        )?;
+        let rows = stmt.query_map([username], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<Vec<(String, Option<String>, usize)>>>()?
+    };
+    if !streaks.is_empty() {
+        println!("\nYour best timed streaks:");
+        for (name, difficulty, best) in streaks {
+            let title: &str = generator(&name).map_or(name.as_str(), |generator| generator.title);
+            let level: String = difficulty.map(|difficulty| format!(" ({})", difficulty)).unwrap_or_default();
+            println!("  {}{}: {}", title, level, best);
+        }
+    }
+    Ok(())
+}
+
+/// Asks again each generated problem whose most recent answer by the user was wrong.
+fn replay_missed(conn: &Connection, username: &str) -> rusqlite::Result<()> {
+    let missed: Vec<(i64, String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT g.GeneratedID, g.Generator, g.Parameters FROM GeneratedQuestions g \
+            WHERE (SELECT r.IsCorrect FROM UserQuestionResponses r \
+                   WHERE r.GeneratedID = g.GeneratedID AND r.Username = ?1 \
+                   ORDER BY r.ResponseID DESC LIMIT 1) = 0 \
+            ORDER BY g.GeneratedID"
+        )?;
+        let rows = stmt.query_map([username], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<Vec<(i64, String, String)>>>()?
+    };
+    if missed.is_empty() {
+        println!("You don't have any missed problems to replay.");
+        return Ok(());
+    }
+
+    println!("\nReplaying {} missed problem(s). Enter 'q' at any question to stop.", missed.len());
+    for (generated_id, name, params) in missed {
+        let problem = generator(&name).ok_or_else(|| format!("unknown generator '{}'", name))
+            .and_then(|generator| (generator.replay)(&params).map(|problem| (generator, problem)));
+        let (generator, problem) = match problem {
+            Ok(found) => found,
+            Err(e) => {
+                println!("Problem {} can't be replayed ({}). Skipping...", generated_id, e);
+                continue;
+            },
+        };
+        match ask(problem.as_ref()) {
+            Some(is_correct) => record(conn, username, Some(generated_id), generator, None, problem.as_ref(), is_correct)?,
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+/// Asks a single `problem` and shows whether the answer was right along with the worked
+/// explanation. Returns `None` if the user entered `q` instead of an answer.
+fn ask(problem: &dyn Problem) -> Option<bool> {
+    println!("\n{}", problem.prompt());
+    let response: String = loop {
+        let entry: String = read_line("Your answer: ");
+        if entry.is_empty() {
+            println!("Entry must not be empty!");
+        } else {
+            break entry;
+        }
+    };
+    if response.eq_ignore_ascii_case("q") {
+        return None;
+    }
+
+    let is_correct: bool = problem.grade(&response);
+    if is_correct {
+        println!("Correct!");
+    } else {
+        println!("Incorrect. The correct answer is: {}", problem.answer());
+    }
+    println!("Explanation:\n{}", problem.explanation());
+    Some(is_correct)
+}
+
+/// Saves the user's answer to a generated `problem` in `UserQuestionResponses`. A new problem is
+/// first added to `GeneratedQuestions`, along with its `difficulty` if the generator has levels; a
+/// replayed one is recorded under its `generated_id`.
+fn record(conn: &Connection, username: &str, generated_id: Option<i64>, generator: &Generator,
+          difficulty: Option<Difficulty>, problem: &dyn Problem, is_correct: bool) -> rusqlite::Result<()> {
+    let now: String = Utc::now().to_rfc3339();
+    let generated_id: i64 = match generated_id {
+        Some(generated_id) => generated_id,
+        None => {
+            conn.execute(
+                "INSERT INTO GeneratedQuestions (Generator, Parameters, Difficulty, DateCreated) VALUES (?1, ?2, ?3, ?4)",
+                params![generator.name, problem.params(), difficulty.map(Difficulty::as_str), &now],
+            )?;
+            conn.last_insert_rowid()
+        },
+    };
+    conn.execute(
+        "INSERT INTO UserQuestionResponses (Username, GeneratedID, IsCorrect, Score, Timestamp) \
+        VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![username, generated_id, is_correct, if is_correct { 1.0 } else { 0.0 }, &now],
+    )?;
+    Ok(())
+}
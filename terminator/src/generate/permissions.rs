@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+use rand::Rng;
+use rand::rngs::ThreadRng;
+use crate::generate::{format_params, param, parse_params, Difficulty, Problem};
+use crate::shell::{Output, Shell};
+use crate::shell::vfs::{file_name, mode_to_symbolic, parent, symbolic_to_mode};
+
+/// Files that generated problems are about, with the owner and group shown by `ls -l`.
+const FILES: &[(&str, &str, &str)] = &[
+    ("/home/alice/notes.txt", "alice", "alice"),
+    ("/home/alice/backup.sh", "alice", "devs"),
+    ("/srv/app/config.yml", "root", "app"),
+    ("/var/log/app.log", "app", "adm"),
+    ("/usr/local/bin/report", "root", "staff"),
+];
+
+/// Directories that generated problems are about, including the usual homes of the special bits.
+const DIRS: &[(&str, &str, &str)] = &[
+    ("/srv/shared", "root", "devs"),
+    ("/srv/drop", "root", "root"),
+    ("/home/alice/projects", "alice", "devs"),
+];
+
+/// Modes that make sense on a file or directory, to pick problems from. Random bits would produce
+/// modes such as `-w---x-w-` that nobody uses.
+const FILE_MODES: &[u32] = &[0o600, 0o640, 0o644, 0o660, 0o664, 0o666, 0o400, 0o440, 0o444, 0o700, 0o744, 0o750, 0o754, 0o755, 0o775];
+const DIR_MODES: &[u32] = &[0o700, 0o750, 0o755, 0o770, 0o775, 0o777, 0o711, 0o751];
+
+/// Who is trying to access the file in a `ReadLs` problem, relative to its owner and group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Who {
+    Owner,
+    Group,
+    Other,
+}
+
+/// A generated permissions problem. `from` and `to` are full modes, including any setuid, setgid
+/// or sticky bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionProblem {
+    /// The octal mode of a file shown by `ls -l` with `mode`.
+    SymbolicToOctal { mode: u32, is_dir: bool },
+    /// How `ls -l` shows a file with `mode`.
+    OctalToSymbolic { mode: u32, is_dir: bool },
+    /// The mode of a new file or directory created under `umask`.
+    Umask { umask: u32, is_dir: bool },
+    /// The `chmod` that changes the file at index `file` of `FILES` (or `DIRS`) from `from` to `to`.
+    Chmod { file: usize, is_dir: bool, from: u32, to: u32 },
+    /// Whether someone can `access` (`r`, `w` or `x`) the file at index `file` with `mode`.
+    ReadLs { file: usize, is_dir: bool, mode: u32, who: Who, access: char },
+}
+
+/// The path, owner and group of the file at `index` of `FILES`, or of `DIRS` for a directory.
+fn file(index: usize, is_dir: bool) -> (&'static str, &'static str, &'static str) {
+    let files: &[(&str, &str, &str)] = if is_dir { DIRS } else { FILES };
+    files[index % files.len()]
+}
+
+/// The line `ls -l` shows for a file.
+fn ls_line(index: usize, is_dir: bool, mode: u32) -> String {
+    let (path, owner, group) = file(index, is_dir);
+    let (links, size): (u32, u32) = if is_dir { (2, 4096) } else { (1, 1204) };
+    format!(
+        "{}{} {} {} {} {:>5} Mar  3 10:15 {}",
+        if is_dir { 'd' } else { '-' }, mode_to_symbolic(mode), links, owner, group, size, file_name(path),
+    )
+}
+
+/// Writes a mode in octal, with the fourth digit only if any special bits are set.
+fn octal(mode: u32) -> String {
+    if mode & 0o7000 != 0 { format!("{:04o}", mode) } else { format!("{:03o}", mode) }
+}
+
+/// Describes the special bits of `mode`, e.g., `setgid (2)`.
+fn special_bits(mode: u32) -> Vec<&'static str> {
+    [(0o4000, "setuid (4)"), (0o2000, "setgid (2)"), (0o1000, "sticky (1)")].iter()
+        .filter(|(bit, _)| mode & bit != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Explains how each class's permissions add up to an octal digit.
+fn digit_steps(mode: u32) -> Vec<String> {
+    let mut steps: Vec<String> = Vec::new();
+    let symbolic: String = mode_to_symbolic(mode & 0o777);
+    for (i, class) in ["Owner", "Group", "Others"].iter().enumerate() {
+        let bits: u32 = (mode >> (6 - 3 * i)) & 0o7;
+        let parts: Vec<String> = [(0o4, 'r'), (0o2, 'w'), (0o1, 'x')].iter()
+            .filter(|(bit, _)| bits & bit != 0)
+            .map(|(bit, c)| format!("{}={}", c, bit))
+            .collect();
+        let sum: String = if parts.is_empty() { String::from("nothing = 0") } else { format!("{} = {}", parts.join(" + "), bits) };
+        steps.push(format!("{} {}: {}", class, &symbolic[3 * i..3 * i + 3], sum));
+    }
+    let specials: Vec<&str> = special_bits(mode);
+    if !specials.is_empty() {
+        steps.push(format!(
+            "An s or t in the execute position is a special bit: {}. Lowercase means execute is also set, \
+            uppercase means it isn't. The special bits add up to a leading digit: {}.",
+            specials.join(", "), mode >> 9,
+        ));
+    }
+    steps
+}
+
+/// Grades an answer that should produce `to`: an octal mode, the nine (or ten) characters shown
+/// by `ls -l`, or a full `chmod` command, which is run in a simulated shell on a file with the
+/// mode `from` and graded by the mode it leaves behind.
+fn grade_mode(response: &str, path: &str, is_dir: bool, from: u32, to: u32) -> bool {
+    let response: &str = response.trim();
+    let command: &str = response.strip_prefix("sudo ").unwrap_or(response).trim_start();
+    if command.starts_with("chmod ") {
+        let kind: &str = if is_dir { "dir" } else { "file" };
+        let spec: String = format!("cwd {}\n{} {} {:o}", parent(path), kind, path, from);
+        let mut shell: Shell = Shell::from_state(&spec).expect("Generated states should be valid");
+        let output: Output = shell.execute(command);
+        return output.status == 0 && shell.vfs.get(path).is_some_and(|node| node.mode == to);
+    }
+    if !response.is_empty() && response.len() <= 4 && response.chars().all(|c| c.is_digit(8)) {
+        return u32::from_str_radix(response, 8) == Ok(to);
+    }
+    symbolic_to_mode(response).is_some_and(|mode| mode == to)
+        && (response.len() == 9 || response.starts_with(if is_dir { 'd' } else { '-' }))
+}
+
+/// Creates a random permissions problem. The generator isn't levelled, so the difficulty is ignored.
+pub fn generate(rng: &mut ThreadRng, _difficulty: Difficulty) -> Box<dyn Problem> {
+    let is_dir: bool = rng.gen_bool(0.3);
+    let modes: &[u32] = if is_dir { DIR_MODES } else { FILE_MODES };
+    let mut mode: u32 = modes[rng.gen_range(0..modes.len())];
+    // Special bits turn up on executables and directories
+    if rng.gen_bool(0.25) && mode & 0o111 != 0 {
+        mode |= if is_dir { [0o2000, 0o1000][rng.gen_range(0..2)] } else { 0o4000 };
+    }
+    let file: usize = rng.gen_range(0..if is_dir { DIRS.len() } else { FILES.len() });
+
+    let problem: PermissionProblem = match rng.gen_range(0..5) {
+        0 => PermissionProblem::SymbolicToOctal { mode, is_dir },
+        1 => PermissionProblem::OctalToSymbolic { mode, is_dir },
+        2 => PermissionProblem::Umask { umask: [0o022, 0o002, 0o027, 0o077, 0o007, 0o037][rng.gen_range(0..6)], is_dir },
+        3 => {
+            let from: u32 = loop {
+                let from: u32 = modes[rng.gen_range(0..modes.len())];
+                if from != mode {
+                    break from;
+                }
+            };
+            PermissionProblem::Chmod { file, is_dir, from, to: mode }
+        },
+        _ => PermissionProblem::ReadLs {
+            file,
+            is_dir,
+            mode: mode & 0o777,
+            who: [Who::Owner, Who::Group, Who::Other][rng.gen_range(0..3)],
+            access: ['r', 'w', 'x'][rng.gen_range(0..3)],
+        },
+    };
+    Box::new(problem)
+}
+
+/// Recreates a permissions problem from the parameters written by `Problem::params`.
+pub fn replay(params: &str) -> Result<Box<dyn Problem>, String> {
+    let params: HashMap<&str, &str> = parse_params(params);
+    let mode = |key: &str| -> Result<u32, String> {
+        let value: String = param(&params, key)?;
+        u32::from_str_radix(&value, 8).ok()
+            .filter(|mode| *mode <= 0o7777)
+            .ok_or_else(|| format!("invalid mode '{}'", value))
+    };
+    let is_dir: bool = param::<String>(&params, "type")? == "dir";
+    let problem: PermissionProblem = match params.get("kind").copied() {
+        Some("symbolic-to-octal") => PermissionProblem::SymbolicToOctal { mode: mode("mode")?, is_dir },
+        Some("octal-to-symbolic") => PermissionProblem::OctalToSymbolic { mode: mode("mode")?, is_dir },
+        Some("umask") => PermissionProblem::Umask { umask: mode("umask")? & 0o777, is_dir },
+        Some("chmod") => PermissionProblem::Chmod { file: param(&params, "file")?, is_dir, from: mode("from")?, to: mode("to")? },
+        Some("read-ls") => PermissionProblem::ReadLs {
+            file: param(&params, "file")?,
+            is_dir,
+            mode: mode("mode")?,
+            who: match params.get("who").copied() {
+                Some("owner") => Who::Owner,
+                Some("group") => Who::Group,
+                Some("other") => Who::Other,
+                _ => return Err(String::from("invalid parameter 'who'")),
+            },
+            access: param::<char>(&params, "access").ok()
+                .filter(|access| matches!(access, 'r' | 'w' | 'x'))
+                .ok_or_else(|| String::from("invalid parameter 'access'"))?,
+        },
+        Some(kind) => return Err(format!("unknown kind of permissions problem '{}'", kind)),
+        None => return Err(String::from("missing parameter 'kind'")),
+    };
+    Ok(Box::new(problem))
+}
+
+impl PermissionProblem {
+    /// Whether the person in a `ReadLs` problem has the access asked about.
+    fn has_access(mode: u32, who: Who, access: char) -> bool {
+        let shift: u32 = match who {
+            Who::Owner => 6,
+            Who::Group => 3,
+            Who::Other => 0,
+        };
+        let bit: u32 = match access {
+            'r' => 0o4,
+            'w' => 0o2,
+            _ => 0o1,
+        };
+        (mode >> shift) & bit != 0
+    }
+
+    /// What a `ReadLs` access means for a file or directory, e.g., "list the contents of".
+    fn access_name(access: char, is_dir: bool) -> &'static str {
+        match (access, is_dir) {
+            ('r', false) => "read",
+            ('w', false) => "write to",
+            ('x', false) => "execute",
+            ('r', true) => "list the contents of",
+            ('w', true) => "create or delete files in",
+            _ => "cd into",
+        }
+    }
+}
+
+impl Problem for PermissionProblem {
+    fn params(&self) -> String {
+        let kind = |is_dir: &bool| String::from(if *is_dir { "dir" } else { "file" });
+        match self {
+            PermissionProblem::SymbolicToOctal { mode, is_dir } => format_params(&[
+                ("kind", String::from("symbolic-to-octal")), ("type", kind(is_dir)), ("mode", format!("{:o}", mode)),
+            ]),
+            PermissionProblem::OctalToSymbolic { mode, is_dir } => format_params(&[
+                ("kind", String::from("octal-to-symbolic")), ("type", kind(is_dir)), ("mode", format!("{:o}", mode)),
+            ]),
+            PermissionProblem::Umask { umask, is_dir } => format_params(&[
+                ("kind", String::from("umask")), ("type", kind(is_dir)), ("umask", format!("{:03o}", umask)),
+            ]),
+            PermissionProblem::Chmod { file, is_dir, from, to } => format_params(&[
+                ("kind", String::from("chmod")),
+                ("type", kind(is_dir)),
+                ("file", file.to_string()),
+                ("from", format!("{:o}", from)),
+                ("to", format!("{:o}", to)),
+            ]),
+            PermissionProblem::ReadLs { file, is_dir, mode, who, access } => format_params(&[
+                ("kind", String::from("read-ls")),
+                ("type", kind(is_dir)),
+                ("file", file.to_string()),
+                ("mode", format!("{:o}", mode)),
+                ("who", String::from(match who { Who::Owner => "owner", Who::Group => "group", Who::Other => "other" })),
+                ("access", access.to_string()),
+            ]),
+        }
+    }
+
+    fn prompt(&self) -> String {
+        match self {
+            PermissionProblem::SymbolicToOctal { mode, is_dir } => format!(
+                "ls -l shows {}{}. What is its mode in octal?",
+                if *is_dir { 'd' } else { '-' }, mode_to_symbolic(*mode),
+            ),
+            PermissionProblem::OctalToSymbolic { mode, is_dir } => format!(
+                "A {} has the mode {}. How does ls -l show its permissions (e.g., rwxr-xr-x)?",
+                if *is_dir { "directory" } else { "file" }, octal(*mode),
+            ),
+            PermissionProblem::Umask { umask, is_dir } => format!(
+                "With a umask of {:03o}, what mode does a newly created {} get? (Answer in octal.)",
+                umask, if *is_dir { "directory" } else { "file" },
+            ),
+            PermissionProblem::Chmod { file: index, is_dir, from, to } => {
+                let (path, _, _) = file(*index, *is_dir);
+                format!(
+                    "{}\nChange {} so ls -l shows {}{}. Enter the chmod command, or just the octal mode.",
+                    ls_line(*index, *is_dir, *from), path, if *is_dir { 'd' } else { '-' }, mode_to_symbolic(*to),
+                )
+            },
+            PermissionProblem::ReadLs { file: index, is_dir, mode, who, access } => {
+                let (_, owner, group) = file(*index, *is_dir);
+                let person: String = match who {
+                    Who::Owner => format!("the user {}", owner),
+                    Who::Group => format!("a user (not {}) who is in the group {}", owner, group),
+                    Who::Other => format!("a user who is neither {} nor in the group {}", owner, group),
+                };
+                format!(
+                    "{}\nCan {} {} it? (yes or no)",
+                    ls_line(*index, *is_dir, *mode), person, Self::access_name(*access, *is_dir),
+                )
+            },
+        }
+    }
+
+    fn grade(&self, response: &str) -> bool {
+        match self {
+            PermissionProblem::SymbolicToOctal { mode, is_dir } | PermissionProblem::OctalToSymbolic { mode, is_dir } => {
+                grade_mode(response, file(0, *is_dir).0, *is_dir, 0, *mode)
+            },
+            PermissionProblem::Umask { umask, is_dir } => {
+                let base: u32 = if *is_dir { 0o777 } else { 0o666 };
+                grade_mode(response, file(0, *is_dir).0, *is_dir, base, base & !umask)
+            },
+            PermissionProblem::Chmod { file: index, is_dir, from, to } => {
+                grade_mode(response, file(*index, *is_dir).0, *is_dir, *from, *to)
+            },
+            PermissionProblem::ReadLs { mode, who, access, .. } => {
+                let expected: bool = Self::has_access(*mode, *who, *access);
+                match response.trim().to_ascii_lowercase().as_str() {
+                    "yes" | "y" => expected,
+                    "no" | "n" => !expected,
+                    _ => false,
+                }
+            },
+        }
+    }
+
+    fn answer(&self) -> String {
+        match self {
+            PermissionProblem::SymbolicToOctal { mode, .. } => octal(*mode),
+            PermissionProblem::OctalToSymbolic { mode, .. } => mode_to_symbolic(*mode),
+            PermissionProblem::Umask { umask, is_dir } => {
+                let base: u32 = if *is_dir { 0o777 } else { 0o666 };
+                format!("{} ({})", octal(base & !umask), mode_to_symbolic(base & !umask))
+            },
+            PermissionProblem::Chmod { file: index, is_dir, to, .. } => {
+                format!("chmod {} {}", octal(*to), file(*index, *is_dir).0)
+            },
+            PermissionProblem::ReadLs { mode, who, access, .. } => {
+                String::from(if Self::has_access(*mode, *who, *access) { "yes" } else { "no" })
+            },
+        }
+    }
+
+    fn explanation(&self) -> String {
+        match self {
+            PermissionProblem::SymbolicToOctal { mode, .. } | PermissionProblem::OctalToSymbolic { mode, .. } => {
+                let mut steps: Vec<String> = vec![String::from(
+                    "Each class (owner, group, others) gets one octal digit, adding r=4, w=2 and x=1.",
+                )];
+                steps.extend(digit_steps(*mode));
+                steps.push(format!("Together: {} is {}.", mode_to_symbolic(*mode), octal(*mode)));
+                steps.join("\n")
+            },
+            PermissionProblem::Umask { umask, is_dir } => {
+                let (base, kind) = if *is_dir { (0o777, "Directories") } else { (0o666, "Files") };
+                format!(
+                    "1. {} start from {:03o}{}.\n2. The umask removes bits: {:03o} with {:03o} removed is {:03o}.\n\
+                    3. In symbolic form that's {}.",
+                    kind, base, if *is_dir { "" } else { "; new files never get execute permission" },
+                    base, umask, base & !umask, mode_to_symbolic(base & !umask),
+                )
+            },
+            PermissionProblem::Chmod { from, to, file: index, is_dir } => {
+                let mut steps: Vec<String> = vec![format!(
+                    "It is {} now; the target is {}. An octal mode sets every bit at once:",
+                    mode_to_symbolic(*from), mode_to_symbolic(*to),
+                )];
+                steps.extend(digit_steps(*to));
+                steps.push(format!(
+                    "So chmod {} {} does it. A symbolic mode such as u=...,g=...,o=... works as well.",
+                    octal(*to), file(*index, *is_dir).0,
+                ));
+                steps.join("\n")
+            },
+            PermissionProblem::ReadLs { mode, who, access, is_dir, .. } => {
+                let (class, range) = match who {
+                    Who::Owner => ("owner", 0..3),
+                    Who::Group => ("group", 3..6),
+                    Who::Other => ("others", 6..9),
+                };
+                let symbolic: String = mode_to_symbolic(*mode);
+                let has: bool = Self::has_access(*mode, *who, *access);
+                format!(
+                    "1. Linux checks only one class: the owner's bits if you own the file, otherwise the group's if \
+                    you're in its group, otherwise the others' bits.\n2. This person falls under {}, whose bits are {}.\n\
+                    3. {} '{}' is {}, so they {} {} it.",
+                    class, &symbolic[range], if *is_dir && *access == 'x' { "Execute (search)" } else { "The permission" },
+                    access, if has { "set" } else { "not set" }, if has { "can" } else { "can't" },
+                    Self::access_name(*access, *is_dir),
+                )
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn octal_and_symbolic_answers_are_graded_by_mode() {
+        let problem = PermissionProblem::SymbolicToOctal { mode: 0o750, is_dir: false };
+        for response in ["750", "0750", " 750 ", "rwxr-x---", "-rwxr-x---"] {
+            assert!(problem.grade(response), "{}", response);
+        }
+        for response in ["755", "7500", "drwxr-x---", "rwxr-x--", "seven fifty", ""] {
+            assert!(!problem.grade(response), "{}", response);
+        }
+        let problem = PermissionProblem::OctalToSymbolic { mode: 0o750, is_dir: true };
+        assert!(problem.grade("drwxr-x---"));
+        assert!(!problem.grade("-rwxr-x---"));
+    }
+
+    #[test]
+    fn chmod_answers_are_run_and_graded_by_the_mode_they_leave() {
+        let problem = PermissionProblem::Chmod { file: 0, is_dir: false, from: 0o644, to: 0o750 };
+        for response in [
+            "750",
+            "chmod 750 notes.txt",
+            "chmod 0750 /home/alice/notes.txt",
+            "sudo chmod u=rwx,g=rx,o= /home/alice/notes.txt",
+            "chmod u+x,g+x,o-r ./notes.txt",
+        ] {
+            assert!(problem.grade(response), "{}", response);
+        }
+        for response in ["chmod 755 notes.txt", "chmod 750 other.txt", "chmod 750", "chmod -R notes.txt"] {
+            assert!(!problem.grade(response), "{}", response);
+        }
+    }
+
+    #[test]
+    fn special_bits_are_graded() {
+        let problem = PermissionProblem::Chmod { file: 4, is_dir: false, from: 0o755, to: 0o4755 };
+        for response in ["4755", "rwsr-xr-x", "chmod 4755 /usr/local/bin/report", "chmod u+s report"] {
+            assert!(problem.grade(response), "{}", response);
+        }
+        assert!(!problem.grade("755"));
+        assert!(!problem.grade("rwSr-xr-x"));
+        assert_eq!(problem.answer(), "chmod 4755 /usr/local/bin/report");
+
+        let problem = PermissionProblem::Chmod { file: 1, is_dir: true, from: 0o777, to: 0o1777 };
+        assert!(problem.grade("chmod +t /srv/drop"));
+        assert!(problem.grade("drwxrwxrwt"));
+    }
+
+    #[test]
+    fn umask_and_read_ls_answers() {
+        let problem = PermissionProblem::Umask { umask: 0o027, is_dir: false };
+        assert!(problem.grade("640"));
+        assert!(problem.grade("rw-r-----"));
+        assert!(!problem.grade("750"));
+        assert!(PermissionProblem::Umask { umask: 0o027, is_dir: true }.grade("750"));
+
+        let problem = PermissionProblem::ReadLs { file: 0, is_dir: false, mode: 0o640, who: Who::Group, access: 'r' };
+        assert!(problem.grade("Yes"));
+        assert!(!problem.grade("no"));
+        let problem = PermissionProblem::ReadLs { file: 0, is_dir: false, mode: 0o640, who: Who::Other, access: 'r' };
+        assert!(problem.grade("n"));
+    }
+
+    #[test]
+    fn every_file_and_mode_can_be_loaded_into_a_shell() {
+        for (files, modes, is_dir) in [(FILES, FILE_MODES, false), (DIRS, DIR_MODES, true)] {
+            for (index, (path, _, _)) in files.iter().enumerate() {
+                for &from in modes {
+                    let to: u32 = from | if is_dir { 0o1000 } else { 0o4000 };
+                    let problem = PermissionProblem::Chmod { file: index, is_dir, from, to };
+                    assert!(problem.grade(&format!("chmod {:o} {}", to, path)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn params_replay_the_same_problem() {
+        let problems: Vec<PermissionProblem> = vec![
+            PermissionProblem::SymbolicToOctal { mode: 0o2755, is_dir: true },
+            PermissionProblem::OctalToSymbolic { mode: 0o640, is_dir: false },
+            PermissionProblem::Umask { umask: 0o077, is_dir: false },
+            PermissionProblem::Chmod { file: 2, is_dir: false, from: 0o600, to: 0o4755 },
+            PermissionProblem::ReadLs { file: 1, is_dir: true, mode: 0o751, who: Who::Other, access: 'x' },
+        ];
+        for problem in problems {
+            let replayed: Box<dyn Problem> = replay(&problem.params()).unwrap();
+            assert_eq!(replayed.params(), problem.params());
+            assert_eq!(replayed.prompt(), problem.prompt());
+            assert_eq!(replayed.answer(), problem.answer());
+        }
+        assert!(replay("kind=chmod;type=file;file=0;from=644;to=17777").is_err());
+        assert!(replay("kind=read-ls;type=file;file=0;mode=644;who=owner;access=q").is_err());
+        assert!(replay("kind=setfacl;type=file").is_err());
+    }
+}
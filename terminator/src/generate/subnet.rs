@@ -0,0 +1,496 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use rand::Rng;
+use rand::rngs::ThreadRng;
+use crate::generate::{format_params, param, parse_params, Difficulty, Problem};
+
+/// A generated subnetting problem. IPv4 addresses are drawn from the private ranges, and IPv6
+/// addresses from the `2001:db8::/32` documentation range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubnetProblem {
+    /// The network address of `addr/prefix`.
+    Network { addr: Ipv4Addr, prefix: u8 },
+    /// The broadcast address of `addr/prefix`.
+    Broadcast { addr: Ipv4Addr, prefix: u8 },
+    /// The number of usable hosts in a `/prefix`.
+    Hosts { prefix: u8 },
+    /// The dotted-decimal mask for a `/prefix`.
+    CidrToMask { prefix: u8 },
+    /// The prefix length for the mask of a `/prefix`.
+    MaskToCidr { prefix: u8 },
+    /// Which `/subnet_prefix` subnet of `network/prefix` the `host` is in.
+    WhichSubnet { network: Ipv4Addr, prefix: u8, subnet_prefix: u8, host: Ipv4Addr },
+    /// The subnets allocated from `network/prefix` for LANs needing `hosts` hosts, largest first.
+    Vlsm { network: Ipv4Addr, prefix: u8, hosts: Vec<u32> },
+    /// The IPv6 prefix that `addr/prefix` belongs to.
+    Ipv6Prefix { addr: Ipv6Addr, prefix: u8 },
+    /// How many `/subnet_prefix` IPv6 subnets fit in a `/prefix`.
+    Ipv6Count { prefix: u8, subnet_prefix: u8 },
+}
+
+/// The mask for a `/prefix`, as an integer.
+fn mask(prefix: u8) -> u32 {
+    u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0)
+}
+
+/// The mask for a `/prefix`, in dotted-decimal form.
+fn mask_addr(prefix: u8) -> Ipv4Addr {
+    Ipv4Addr::from(mask(prefix))
+}
+
+/// The network address of `addr/prefix`.
+fn network(addr: Ipv4Addr, prefix: u8) -> Ipv4Addr {
+    Ipv4Addr::from(u32::from(addr) & mask(prefix))
+}
+
+/// The number of addresses in a `/prefix`.
+fn block(prefix: u8) -> u64 {
+    1u64 << (32 - u32::from(prefix))
+}
+
+/// The number of `/subnet_prefix` subnets in a `/prefix`, or `None` if there are too many to count
+/// (or `subnet_prefix` is shorter than `prefix`).
+fn subnet_count(prefix: u8, subnet_prefix: u8) -> Option<u128> {
+    1u128.checked_shl(u32::from(subnet_prefix.checked_sub(prefix)?))
+}
+
+/// The smallest prefix whose subnet has room for `hosts` usable hosts.
+fn prefix_for(hosts: u32) -> u8 {
+    (1..=30).rev().find(|prefix| block(*prefix) - 2 >= u64::from(hosts)).unwrap_or(1)
+}
+
+/// The subnets that VLSM allocates from `network/prefix`, one per entry of `hosts` in the same
+/// order. `hosts` must already be sorted from largest to smallest, so every subnet lands on its
+/// own boundary.
+fn allocate(network: Ipv4Addr, hosts: &[u32]) -> Vec<(Ipv4Addr, u8)> {
+    let mut next: u64 = u64::from(u32::from(network));
+    hosts.iter()
+        .map(|hosts| {
+            let prefix: u8 = prefix_for(*hosts);
+            let subnet: Ipv4Addr = Ipv4Addr::from(next as u32);
+            next += block(prefix);
+            (subnet, prefix)
+        })
+        .collect()
+}
+
+/// The octet that a `/prefix` mask ends in, counting from `1`, and that octet's value.
+fn interesting_octet(prefix: u8) -> (usize, u8) {
+    let index: usize = (usize::from(prefix.max(1)) - 1) / 8;
+    (index + 1, mask_addr(prefix).octets()[index])
+}
+
+/// A random address from one of the private IPv4 ranges.
+fn private_addr(rng: &mut ThreadRng) -> Ipv4Addr {
+    match rng.gen_range(0..3) {
+        0 => Ipv4Addr::new(10, rng.gen(), rng.gen(), rng.gen()),
+        1 => Ipv4Addr::new(172, rng.gen_range(16..32), rng.gen(), rng.gen()),
+        _ => Ipv4Addr::new(192, 168, rng.gen(), rng.gen()),
+    }
+}
+
+/// Creates a random subnetting problem, mixing every kind of problem at any difficulty.
+pub fn generate(rng: &mut ThreadRng, _difficulty: Difficulty) -> Box<dyn Problem> {
+    let problem: SubnetProblem = match rng.gen_range(0..9) {
+        0 => SubnetProblem::Network { addr: private_addr(rng), prefix: rng.gen_range(9..=30) },
+        1 => SubnetProblem::Broadcast { addr: private_addr(rng), prefix: rng.gen_range(9..=30) },
+        2 => SubnetProblem::Hosts { prefix: rng.gen_range(8..=30) },
+        3 => SubnetProblem::CidrToMask { prefix: rng.gen_range(8..=30) },
+        4 => SubnetProblem::MaskToCidr { prefix: rng.gen_range(8..=30) },
+        5 => {
+            let prefix: u8 = rng.gen_range(16..=26);
+            let subnet_prefix: u8 = rng.gen_range(prefix + 2..=(prefix + 6).min(30));
+            let network: Ipv4Addr = network(private_addr(rng), prefix);
+            let offset: u32 = rng.gen_range(1..block(prefix) - 1) as u32;
+            let host: Ipv4Addr = Ipv4Addr::from(u32::from(network) + offset);
+            SubnetProblem::WhichSubnet { network, prefix, subnet_prefix, host }
+        },
+        6 => {
+            let prefix: u8 = rng.gen_range(22..=24);
+            let network: Ipv4Addr = network(private_addr(rng), prefix);
+            // Draw LAN sizes until they fit in the block
+            let hosts: Vec<u32> = loop {
+                let mut hosts: Vec<u32> = (0..rng.gen_range(3..=4)).map(|_| rng.gen_range(2..=120)).collect();
+                hosts.sort_unstable_by(|a, b| b.cmp(a));
+                let needed: u64 = hosts.iter().map(|hosts| block(prefix_for(*hosts))).sum();
+                if needed <= block(prefix) {
+                    break hosts;
+                }
+            };
+            SubnetProblem::Vlsm { network, prefix, hosts }
+        },
+        7 => {
+            let mut segments: [u16; 8] = [0x2001, 0x0db8, rng.gen(), rng.gen(), rng.gen(), 0, 0, rng.gen()];
+            if rng.gen_bool(0.5) {
+                segments[4] = rng.gen();
+            }
+            let prefix: u8 = [40, 48, 52, 56, 60][rng.gen_range(0..5)];
+            SubnetProblem::Ipv6Prefix { addr: Ipv6Addr::from(segments), prefix }
+        },
+        _ => {
+            let prefix: u8 = [32, 40, 48, 56][rng.gen_range(0..4)];
+            let subnet_prefix: u8 = [48, 52, 56, 60, 64].into_iter()
+                .filter(|subnet_prefix| *subnet_prefix > prefix)
+                .nth(rng.gen_range(0..2))
+                .unwrap_or(64);
+            SubnetProblem::Ipv6Count { prefix, subnet_prefix }
+        },
+    };
+    Box::new(problem)
+}
+
+/// Recreates a subnetting problem from the parameters written by `Problem::params`.
+pub fn replay(params: &str) -> Result<Box<dyn Problem>, String> {
+    let params: HashMap<&str, &str> = parse_params(params);
+    let prefix = || param::<u8>(&params, "prefix").and_then(|prefix| {
+        if prefix <= 32 { Ok(prefix) } else { Err(format!("invalid prefix /{}", prefix)) }
+    });
+    let problem: SubnetProblem = match params.get("kind").copied() {
+        Some("network") => SubnetProblem::Network { addr: param(&params, "addr")?, prefix: prefix()? },
+        Some("broadcast") => SubnetProblem::Broadcast { addr: param(&params, "addr")?, prefix: prefix()? },
+        Some("hosts") => SubnetProblem::Hosts { prefix: prefix()? },
+        Some("cidr-to-mask") => SubnetProblem::CidrToMask { prefix: prefix()? },
+        Some("mask-to-cidr") => SubnetProblem::MaskToCidr { prefix: prefix()? },
+        Some("which-subnet") => SubnetProblem::WhichSubnet {
+            network: param(&params, "network")?,
+            prefix: prefix()?,
+            subnet_prefix: param(&params, "subnet-prefix")?,
+            host: param(&params, "host")?,
+        },
+        Some("vlsm") => SubnetProblem::Vlsm {
+            network: param(&params, "network")?,
+            prefix: prefix()?,
+            hosts: param::<String>(&params, "hosts")?
+                .split(',')
+                .map(|hosts| hosts.parse().map_err(|_| format!("invalid host count '{}'", hosts)))
+                .collect::<Result<Vec<u32>, String>>()?,
+        },
+        Some("ipv6-prefix") => SubnetProblem::Ipv6Prefix { addr: param(&params, "addr")?, prefix: param(&params, "prefix")? },
+        Some("ipv6-count") => SubnetProblem::Ipv6Count {
+            prefix: param(&params, "prefix")?,
+            subnet_prefix: param(&params, "subnet-prefix")?,
+        },
+        Some(kind) => return Err(format!("unknown kind of subnetting problem '{}'", kind)),
+        None => return Err(String::from("missing parameter 'kind'")),
+    };
+    let valid: bool = match &problem {
+        SubnetProblem::Hosts { prefix } => *prefix <= 30,
+        SubnetProblem::WhichSubnet { prefix, subnet_prefix, .. } => prefix < subnet_prefix && *subnet_prefix <= 32,
+        SubnetProblem::Vlsm { network: start, prefix, hosts } => {
+            // Every LAN has to fit in its subnet, and every subnet in the block, or the allocation
+            // would run past the end of the address space
+            !hosts.is_empty()
+                && hosts.windows(2).all(|pair| pair[0] >= pair[1])
+                && network(*start, *prefix) == *start
+                && hosts.iter().all(|hosts| block(prefix_for(*hosts)) - 2 >= u64::from(*hosts))
+                && hosts.iter().map(|hosts| block(prefix_for(*hosts))).sum::<u64>() <= block(*prefix)
+        },
+        SubnetProblem::Ipv6Prefix { prefix, .. } => *prefix <= 128,
+        SubnetProblem::Ipv6Count { prefix, subnet_prefix } => {
+            prefix < subnet_prefix && *subnet_prefix <= 128 && subnet_count(*prefix, *subnet_prefix).is_some()
+        },
+        _ => true,
+    };
+    if !valid {
+        return Err(String::from("the parameters don't describe a valid problem"));
+    }
+    Ok(Box::new(problem))
+}
+
+/// The number of `/subnet_prefix` subnets in a `/prefix` for showing to the user.
+fn count_text(prefix: u8, subnet_prefix: u8) -> String {
+    subnet_count(prefix, subnet_prefix).map_or_else(|| String::from("too many to count"), |count| count.to_string())
+}
+
+/// Parses an IPv4 address, optionally followed by a `/prefix` that must equal `prefix`.
+fn parse_v4(response: &str, prefix: Option<u8>) -> Option<Ipv4Addr> {
+    let (addr, given) = match response.trim().split_once('/') {
+        Some((addr, given)) => (addr, Some(given.trim().parse::<u8>().ok()?)),
+        None => (response.trim(), None),
+    };
+    if given.is_some() && given != prefix {
+        return None;
+    }
+    addr.trim().parse().ok()
+}
+
+/// Parses a count, which may be written with thousands separators or as a power of two (`2^16`).
+fn parse_count(response: &str) -> Option<u128> {
+    let response: String = response.trim().replace([',', '_', ' '], "");
+    match response.split_once('^') {
+        Some(("2", exponent)) => exponent.parse::<u32>().ok().and_then(|exponent| 2u128.checked_pow(exponent)),
+        Some(_) => None,
+        None => response.parse().ok(),
+    }
+}
+
+impl Problem for SubnetProblem {
+    fn params(&self) -> String {
+        match self {
+            SubnetProblem::Network { addr, prefix } => {
+                format_params(&[("kind", String::from("network")), ("addr", addr.to_string()), ("prefix", prefix.to_string())])
+            },
+            SubnetProblem::Broadcast { addr, prefix } => {
+                format_params(&[("kind", String::from("broadcast")), ("addr", addr.to_string()), ("prefix", prefix.to_string())])
+            },
+            SubnetProblem::Hosts { prefix } => format_params(&[("kind", String::from("hosts")), ("prefix", prefix.to_string())]),
+            SubnetProblem::CidrToMask { prefix } => {
+                format_params(&[("kind", String::from("cidr-to-mask")), ("prefix", prefix.to_string())])
+            },
+            SubnetProblem::MaskToCidr { prefix } => {
+                format_params(&[("kind", String::from("mask-to-cidr")), ("prefix", prefix.to_string())])
+            },
+            SubnetProblem::WhichSubnet { network, prefix, subnet_prefix, host } => format_params(&[
+                ("kind", String::from("which-subnet")),
+                ("network", network.to_string()),
+                ("prefix", prefix.to_string()),
+                ("subnet-prefix", subnet_prefix.to_string()),
+                ("host", host.to_string()),
+            ]),
+            SubnetProblem::Vlsm { network, prefix, hosts } => format_params(&[
+                ("kind", String::from("vlsm")),
+                ("network", network.to_string()),
+                ("prefix", prefix.to_string()),
+                ("hosts", hosts.iter().map(|hosts| hosts.to_string()).collect::<Vec<String>>().join(",")),
+            ]),
+            SubnetProblem::Ipv6Prefix { addr, prefix } => {
+                format_params(&[("kind", String::from("ipv6-prefix")), ("addr", addr.to_string()), ("prefix", prefix.to_string())])
+            },
+            SubnetProblem::Ipv6Count { prefix, subnet_prefix } => format_params(&[
+                ("kind", String::from("ipv6-count")),
+                ("prefix", prefix.to_string()),
+                ("subnet-prefix", subnet_prefix.to_string()),
+            ]),
+        }
+    }
+
+    fn prompt(&self) -> String {
+        match self {
+            SubnetProblem::Network { addr, prefix } => format!("What is the network address of {}/{}?", addr, prefix),
+            SubnetProblem::Broadcast { addr, prefix } => format!("What is the broadcast address of {}/{}?", addr, prefix),
+            SubnetProblem::Hosts { prefix } => format!("How many usable host addresses does a /{} subnet have?", prefix),
+            SubnetProblem::CidrToMask { prefix } => format!("What is the subnet mask for a /{} in dotted-decimal form?", prefix),
+            SubnetProblem::MaskToCidr { prefix } => format!("What prefix length (CIDR) is the mask {}?", mask_addr(*prefix)),
+            SubnetProblem::WhichSubnet { network, prefix, subnet_prefix, host } => format!(
+                "The network {}/{} is divided into /{} subnets. Which subnet (network address) is the host {} in?",
+                network, prefix, subnet_prefix, host,
+            ),
+            SubnetProblem::Vlsm { network, prefix, hosts } => {
+                let lans: Vec<String> = hosts.iter()
+                    .enumerate()
+                    .map(|(i, hosts)| format!("LAN {} needs {} hosts", i + 1, hosts))
+                    .collect();
+                format!(
+                    "Using VLSM, allocate subnets from {}/{} in order, starting at the beginning of the block: \
+                    {}. List each LAN's subnet as address/prefix, separated by commas.",
+                    network, prefix, lans.join(", "),
+                )
+            },
+            SubnetProblem::Ipv6Prefix { addr, prefix } => {
+                format!("What is the /{} prefix that {} belongs to? (Give the network address.)", prefix, addr)
+            },
+            SubnetProblem::Ipv6Count { prefix, subnet_prefix } => {
+                format!("How many /{} subnets can be made from a /{} IPv6 allocation?", subnet_prefix, prefix)
+            },
+        }
+    }
+
+    fn grade(&self, response: &str) -> bool {
+        match self {
+            SubnetProblem::Network { addr, prefix } => parse_v4(response, Some(*prefix)) == Some(network(*addr, *prefix)),
+            SubnetProblem::Broadcast { addr, prefix } => {
+                let broadcast: Ipv4Addr = Ipv4Addr::from(u32::from(network(*addr, *prefix)) | !mask(*prefix));
+                parse_v4(response, Some(*prefix)) == Some(broadcast)
+            },
+            SubnetProblem::Hosts { prefix } => parse_count(response) == Some(u128::from(block(*prefix) - 2)),
+            SubnetProblem::CidrToMask { prefix } => parse_v4(response, None) == Some(mask_addr(*prefix)),
+            SubnetProblem::MaskToCidr { prefix } => response.trim().trim_start_matches('/').parse::<u8>() == Ok(*prefix),
+            SubnetProblem::WhichSubnet { subnet_prefix, host, .. } => {
+                parse_v4(response, Some(*subnet_prefix)) == Some(network(*host, *subnet_prefix))
+            },
+            SubnetProblem::Vlsm { network, hosts, .. } => {
+                let given: Vec<&str> = response.split([',', ' ']).filter(|part| !part.is_empty()).collect();
+                let expected: Vec<(Ipv4Addr, u8)> = allocate(*network, hosts);
+                given.len() == expected.len() && given.iter().zip(&expected).all(|(given, (subnet, prefix))| {
+                    given.contains('/') && parse_v4(given, Some(*prefix)) == Some(*subnet)
+                })
+            },
+            SubnetProblem::Ipv6Prefix { addr, prefix } => {
+                let expected: Ipv6Addr = Ipv6Addr::from(u128::from(*addr) & u128::MAX.checked_shl(128 - u32::from(*prefix)).unwrap_or(0));
+                let (given, given_prefix) = match response.trim().split_once('/') {
+                    Some((given, given_prefix)) => (given, Some(given_prefix.trim())),
+                    None => (response.trim(), None),
+                };
+                given_prefix.is_none_or(|given_prefix| given_prefix == prefix.to_string())
+                    && given.parse::<Ipv6Addr>() == Ok(expected)
+            },
+            SubnetProblem::Ipv6Count { prefix, subnet_prefix } => {
+                subnet_count(*prefix, *subnet_prefix).is_some_and(|count| parse_count(response) == Some(count))
+            },
+        }
+    }
+
+    fn answer(&self) -> String {
+        match self {
+            SubnetProblem::Network { addr, prefix } => format!("{}/{}", network(*addr, *prefix), prefix),
+            SubnetProblem::Broadcast { addr, prefix } => {
+                Ipv4Addr::from(u32::from(network(*addr, *prefix)) | !mask(*prefix)).to_string()
+            },
+            SubnetProblem::Hosts { prefix } => (block(*prefix) - 2).to_string(),
+            SubnetProblem::CidrToMask { prefix } => mask_addr(*prefix).to_string(),
+            SubnetProblem::MaskToCidr { prefix } => format!("/{}", prefix),
+            SubnetProblem::WhichSubnet { subnet_prefix, host, .. } => format!("{}/{}", network(*host, *subnet_prefix), subnet_prefix),
+            SubnetProblem::Vlsm { network, hosts, .. } => allocate(*network, hosts).iter()
+                .map(|(subnet, prefix)| format!("{}/{}", subnet, prefix))
+                .collect::<Vec<String>>()
+                .join(", "),
+            SubnetProblem::Ipv6Prefix { addr, prefix } => format!(
+                "{}/{}",
+                Ipv6Addr::from(u128::from(*addr) & u128::MAX.checked_shl(128 - u32::from(*prefix)).unwrap_or(0)),
+                prefix,
+            ),
+            SubnetProblem::Ipv6Count { prefix, subnet_prefix } => {
+                format!("{} (2^{})", count_text(*prefix, *subnet_prefix), subnet_prefix.saturating_sub(*prefix))
+            },
+        }
+    }
+
+    fn explanation(&self) -> String {
+        match self {
+            SubnetProblem::Network { addr, prefix } | SubnetProblem::Broadcast { addr, prefix } => {
+                let (octet, value) = interesting_octet(*prefix);
+                let size: u16 = 256 - u16::from(value);
+                let network: Ipv4Addr = network(*addr, *prefix);
+                let broadcast: Ipv4Addr = Ipv4Addr::from(u32::from(network) | !mask(*prefix));
+                let start: u8 = network.octets()[octet - 1];
+                let mut steps: Vec<String> = vec![
+                    format!("1. /{} is the mask {}; it ends in octet {} with the value {}.", prefix, mask_addr(*prefix), octet, value),
+                    format!("2. The block size in that octet is 256 - {} = {}.", value, size),
+                    format!(
+                        "3. Octet {} of the address is {}, which falls in the block starting at {} ({} is a multiple of {}).",
+                        octet, addr.octets()[octet - 1], start, start, size,
+                    ),
+                    format!("4. Keep the octets before it, set the octets after it to 0: the network is {}.", network),
+                ];
+                if matches!(self, SubnetProblem::Broadcast { .. }) {
+                    steps.push(format!(
+                        "5. The broadcast is the last address before the next block: set every host bit to 1 to get {}.",
+                        broadcast,
+                    ));
+                }
+                steps.join("\n")
+            },
+            SubnetProblem::Hosts { prefix } => format!(
+                "1. A /{} leaves 32 - {} = {} host bits.\n2. That's 2^{} = {} addresses.\n\
+                3. Subtract the network and broadcast addresses: {} - 2 = {} usable hosts.",
+                prefix, prefix, 32 - prefix, 32 - prefix, block(*prefix), block(*prefix), block(*prefix) - 2,
+            ),
+            SubnetProblem::CidrToMask { prefix } | SubnetProblem::MaskToCidr { prefix } => {
+                let octets: Vec<String> = mask_addr(*prefix).octets().iter().map(|octet| format!("{:08b}", octet)).collect();
+                format!(
+                    "1. /{} means the first {} bits of the mask are 1s and the rest are 0s.\n\
+                    2. In binary, that's {}.\n3. Converting each octet to decimal gives {}.",
+                    prefix, prefix, octets.join("."), mask_addr(*prefix),
+                )
+            },
+            SubnetProblem::WhichSubnet { network: parent, prefix, subnet_prefix, host } => {
+                let (octet, value) = interesting_octet(*subnet_prefix);
+                let size: u16 = 256 - u16::from(value);
+                format!(
+                    "1. Borrowing {} bits from the /{} makes 2^{} = {} subnets of /{} each.\n\
+                    2. A /{} mask is {}, so the subnets go up in blocks of {} in octet {}, starting at {}.\n\
+                    3. Octet {} of {} is {}; the block it falls in starts at {}.\n\
+                    4. The host is in {}/{}.",
+                    subnet_prefix - prefix, prefix, subnet_prefix - prefix, 1u64 << (subnet_prefix - prefix), subnet_prefix,
+                    subnet_prefix, mask_addr(*subnet_prefix), size, octet, parent,
+                    octet, host, host.octets()[octet - 1], network(*host, *subnet_prefix).octets()[octet - 1],
+                    network(*host, *subnet_prefix), subnet_prefix,
+                )
+            },
+            SubnetProblem::Vlsm { network, prefix, hosts } => {
+                let mut steps: Vec<String> = vec![format!(
+                    "1. Allocate the largest LAN first so each subnet starts on its own boundary. The block {}/{} has {} addresses.",
+                    network, prefix, block(*prefix),
+                )];
+                for (i, (hosts, (subnet, subnet_prefix))) in hosts.iter().zip(allocate(*network, hosts)).enumerate() {
+                    let last: String = u32::from(subnet)
+                        .checked_add((block(subnet_prefix) - 1) as u32)
+                        .map_or_else(|| String::from("past 255.255.255.255"), |last| Ipv4Addr::from(last).to_string());
+                    steps.push(format!(
+                        "{}. LAN {} needs {} hosts + 2 = {} addresses, so it gets a block of {} (/{}): {}/{} ({} - {}).",
+                        i + 2, i + 1, hosts, hosts + 2, block(subnet_prefix), subnet_prefix, subnet, subnet_prefix, subnet, last,
+                    ));
+                }
+                steps.join("\n")
+            },
+            SubnetProblem::Ipv6Prefix { addr, prefix } => {
+                let full: Vec<String> = addr.segments().iter().map(|segment| format!("{:04x}", segment)).collect();
+                format!(
+                    "1. Written out in full, the address is {}.\n2. Each group is 16 bits, so /{} keeps the first {} groups \
+                    and {} bits of the next.\n3. Zero every bit after the first {} and compress the zeros: {}.",
+                    full.join(":"), prefix, prefix / 16, prefix % 16, prefix, self.answer(),
+                )
+            },
+            SubnetProblem::Ipv6Count { prefix, subnet_prefix } => format!(
+                "1. Going from /{} to /{} borrows {} - {} = {} bits.\n2. {} bits make 2^{} = {} subnets.",
+                prefix, subnet_prefix, subnet_prefix, prefix, subnet_prefix.saturating_sub(*prefix),
+                subnet_prefix.saturating_sub(*prefix), subnet_prefix.saturating_sub(*prefix), count_text(*prefix, *subnet_prefix),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use super::*;
+
+    #[test]
+    fn masks_at_the_ends_of_the_range() {
+        assert_eq!(mask_addr(0), Ipv4Addr::new(0, 0, 0, 0));
+        assert_eq!(mask_addr(32), Ipv4Addr::new(255, 255, 255, 255));
+        assert_eq!(block(0), 1 << 32);
+        assert_eq!(block(32), 1);
+    }
+
+    #[test]
+    fn network_and_broadcast_of_slash_0_and_slash_32() {
+        let addr: Ipv4Addr = Ipv4Addr::new(10, 1, 2, 3);
+        let whole = SubnetProblem::Broadcast { addr, prefix: 0 };
+        assert_eq!(whole.answer(), "255.255.255.255");
+        assert!(SubnetProblem::Network { addr, prefix: 0 }.grade("0.0.0.0/0"));
+        let host = SubnetProblem::Broadcast { addr, prefix: 32 };
+        assert_eq!(host.answer(), "10.1.2.3");
+        assert!(SubnetProblem::Network { addr, prefix: 32 }.grade("10.1.2.3"));
+        // The explanations walk through the octet the mask ends in, which mustn't go out of range
+        whole.explanation();
+        host.explanation();
+    }
+
+    #[test]
+    fn ipv6_counts_up_to_the_limit() {
+        let most = SubnetProblem::Ipv6Count { prefix: 1, subnet_prefix: 128 };
+        assert!(most.grade("2^127"));
+        assert_eq!(most.answer(), format!("{} (2^127)", 1u128 << 127));
+        assert!(replay("kind=ipv6-count;prefix=1;subnet-prefix=128").is_ok());
+        assert!(replay("kind=ipv6-count;prefix=0;subnet-prefix=128").is_err());
+        assert!(replay("kind=ipv6-count;prefix=64;subnet-prefix=48").is_err());
+        assert!(replay("kind=ipv6-prefix;addr=2001:db8::1;prefix=129").is_err());
+    }
+
+    #[test]
+    fn replay_rejects_crafted_prefixes() {
+        assert!(replay("kind=network;addr=10.0.0.1;prefix=33").is_err());
+        assert!(replay("kind=hosts;prefix=31").is_err());
+        assert!(replay("kind=which-subnet;network=10.0.0.0;prefix=24;subnet-prefix=33;host=10.0.0.1").is_err());
+        assert!(replay("kind=which-subnet;network=10.0.0.0;prefix=24;subnet-prefix=24;host=10.0.0.1").is_err());
+    }
+
+    #[test]
+    fn replay_rejects_vlsm_that_runs_past_the_block() {
+        assert!(replay("kind=vlsm;network=192.168.0.0;prefix=24;hosts=100,50,20").is_ok());
+        assert!(replay("kind=vlsm;network=192.168.0.0;prefix=24;hosts=200,100").is_err());
+        assert!(replay("kind=vlsm;network=255.255.255.0;prefix=24;hosts=4294967295").is_err());
+        assert!(replay("kind=vlsm;network=192.168.0.1;prefix=24;hosts=100").is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use std::net::Ipv4Addr;
+
+/// The interface types the simulated device understands, in the order `show running-config`
+/// lists them.
+const INTERFACE_TYPES: &[&str] = &[
+    "FastEthernet", "GigabitEthernet", "TenGigabitEthernet", "Ethernet", "Serial", "Loopback", "Vlan",
+];
+
+/// Expands an interface name as typed (e.g., `g0/1`, `gig 0/1` or `GigabitEthernet0/1`) into its
+/// full name. Returns `None` if the type is unknown or ambiguous, or the number is malformed.
+pub fn expand_interface_name(typed: &str) -> Option<String> {
+    let typed: String = typed.split_whitespace().collect();
+    let split: usize = typed.find(|c: char| c.is_ascii_digit())?;
+    let (kind, number) = typed.split_at(split);
+    if kind.is_empty() {
+        return None;
+    }
+    let digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    let (base, sub) = match number.split_once('.') {
+        Some((base, sub)) => (base, Some(sub)),
+        None => (number, None),
+    };
+    if !base.split('/').all(digits) || !sub.is_none_or(digits) {
+        return None;
+    }
+
+    let matches: Vec<&&str> = INTERFACE_TYPES.iter()
+        .filter(|name| name.to_ascii_lowercase().starts_with(&kind.to_ascii_lowercase()))
+        .collect();
+    match matches.as_slice() {
+        [name] => Some(format!("{}{}", name, number)),
+        _ => None,
+    }
+}
+
+/// Whether the interface can be created by configuring it, rather than having to exist on the
+/// device already (loopbacks, VLAN interfaces and subinterfaces).
+pub fn is_virtual(name: &str) -> bool {
+    name.starts_with("Loopback") || name.starts_with("Vlan") || name.contains('.')
+}
+
+/// Whether `mask` is a valid subnet mask, i.e., its one bits are contiguous.
+pub fn is_valid_mask(mask: Ipv4Addr) -> bool {
+    let bits: u32 = u32::from(mask);
+    bits.leading_ones() + bits.trailing_zeros() == 32
+}
+
+/// How a switch port carries VLANs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchportMode {
+    Access,
+    Trunk,
+}
+
+/// The configuration of a single interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    pub description: Option<String>,
+    pub address: Option<(Ipv4Addr, Ipv4Addr)>,
+    pub shutdown: bool,
+    pub switchport_mode: Option<SwitchportMode>,
+    pub access_vlan: Option<u16>,
+}
+
+impl Interface {
+    /// A new, unconfigured interface. Physical interfaces start shut down; loopbacks and other
+    /// virtual interfaces start up.
+    pub fn new(name: &str) -> Self {
+        Interface {
+            name: name.to_string(),
+            description: None,
+            address: None,
+            shutdown: !is_virtual(name),
+            switchport_mode: None,
+            access_vlan: None,
+        }
+    }
+
+    /// The lines under this interface in the running-config, without their leading space.
+    fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        if let Some(description) = &self.description {
+            lines.push(format!("description {}", description));
+        }
+        if let Some(mode) = self.switchport_mode {
+            if let Some(vlan) = self.access_vlan {
+                lines.push(format!("switchport access vlan {}", vlan));
+            }
+            lines.push(String::from(match mode {
+                SwitchportMode::Access => "switchport mode access",
+                SwitchportMode::Trunk => "switchport mode trunk",
+            }));
+        } else if let Some(vlan) = self.access_vlan {
+            lines.push(format!("switchport access vlan {}", vlan));
+        }
+        match self.address {
+            Some((address, mask)) => lines.push(format!("ip address {} {}", address, mask)),
+            None => lines.push(String::from("no ip address")),
+        }
+        if self.shutdown {
+            lines.push(String::from("shutdown"));
+        }
+        lines
+    }
+}
+
+/// A static route, as configured by `ip route NETWORK MASK NEXT-HOP`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Route {
+    pub network: Ipv4Addr,
+    pub mask: Ipv4Addr,
+    /// The next-hop address or exit interface, as it was typed.
+    pub next_hop: String,
+}
+
+/// The running (or startup) configuration of a simulated device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub hostname: String,
+    pub enable_secret: Option<String>,
+    pub domain_name: Option<String>,
+    pub domain_lookup: bool,
+    pub interfaces: Vec<Interface>,
+    pub default_gateway: Option<Ipv4Addr>,
+    pub routes: Vec<Route>,
+}
+
+impl Default for Config {
+    /// A router fresh out of the box, with two gigabit and two serial interfaces.
+    fn default() -> Self {
+        Config {
+            hostname: String::from("Router"),
+            enable_secret: None,
+            domain_name: None,
+            domain_lookup: true,
+            interfaces: ["GigabitEthernet0/0", "GigabitEthernet0/1", "Serial0/0/0", "Serial0/0/1"]
+                .iter()
+                .map(|name| Interface::new(name))
+                .collect(),
+            default_gateway: None,
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn interface(&self, name: &str) -> Option<&Interface> {
+        self.interfaces.iter().find(|interface| interface.name == name)
+    }
+
+    pub fn interface_mut(&mut self, name: &str) -> Option<&mut Interface> {
+        self.interfaces.iter_mut().find(|interface| interface.name == name)
+    }
+
+    /// Adds an interface, keeping the interfaces in the order IOS lists them: by type, then by
+    /// number.
+    pub fn add_interface(&mut self, name: &str) {
+        fn sort_key(name: &str) -> (usize, Vec<u32>) {
+            let split: usize = name.find(|c: char| c.is_ascii_digit()).unwrap_or(name.len());
+            let kind: usize = INTERFACE_TYPES.iter().position(|t| *t == &name[..split]).unwrap_or(usize::MAX);
+            let numbers: Vec<u32> = name[split..].split(['/', '.']).filter_map(|n| n.parse().ok()).collect();
+            (kind, numbers)
+        }
+        let key = sort_key(name);
+        let index: usize = self.interfaces.iter().position(|i| sort_key(&i.name) > key).unwrap_or(self.interfaces.len());
+        self.interfaces.insert(index, Interface::new(name));
+    }
+
+    /// The configuration as `(section, line)` pairs, where the section is the `interface` line
+    /// that a line belongs under (or empty for global lines). This is what's compared when
+    /// checking a device against its expected configuration.
+    pub fn lines(&self) -> Vec<(String, String)> {
+        let mut lines: Vec<(String, String)> = Vec::new();
+        let global = |line: String| (String::new(), line);
+        lines.push(global(format!("hostname {}", self.hostname)));
+        if let Some(secret) = &self.enable_secret {
+            lines.push(global(format!("enable secret {}", secret)));
+        }
+        if !self.domain_lookup {
+            lines.push(global(String::from("no ip domain-lookup")));
+        }
+        if let Some(domain) = &self.domain_name {
+            lines.push(global(format!("ip domain-name {}", domain)));
+        }
+        for interface in &self.interfaces {
+            let section: String = format!("interface {}", interface.name);
+            lines.push((section.clone(), String::new()));
+            for line in interface.lines() {
+                lines.push((section.clone(), line));
+            }
+        }
+        if let Some(gateway) = self.default_gateway {
+            lines.push(global(format!("ip default-gateway {}", gateway)));
+        }
+        for route in &self.routes {
+            lines.push(global(format!("ip route {} {} {}", route.network, route.mask, route.next_hop)));
+        }
+        lines
+    }
+
+    /// Renders the configuration as `show running-config` does.
+    pub fn render(&self) -> String {
+        let mut body: String = String::from("!\nversion 15.2\n!\n");
+        let mut section: String = String::new();
+        for (line_section, line) in self.lines() {
+            if line_section != section {
+                body.push_str("!\n");
+                section = line_section;
+            }
+            if line.is_empty() {
+                body.push_str(&section);
+            } else if section.is_empty() {
+                body.push_str(&line);
+            } else {
+                body.push(' ');
+                body.push_str(&line);
+            }
+            body.push('\n');
+        }
+        body.push_str("!\nend\n");
+        format!("Building configuration...\n\nCurrent configuration : {} bytes\n{}", body.len(), body)
+    }
+}
+
+/// Compares a device's configuration against the `expected` one, returning a description of
+/// every difference. An empty result means the configurations match.
+pub fn check(actual: &Config, expected: &Config) -> Vec<String> {
+    let actual_lines: Vec<(String, String)> = actual.lines();
+    let expected_lines: Vec<(String, String)> = expected.lines();
+    let describe = |(section, line): &(String, String)| match (section.is_empty(), line.is_empty()) {
+        (true, _) => line.clone(),
+        (false, true) => section.clone(),
+        (false, false) => format!("{} > {}", section, line),
+    };
+
+    let mut problems: Vec<String> = Vec::new();
+    for line in expected_lines.iter().filter(|line| !actual_lines.contains(line)) {
+        problems.push(format!("missing: {}", describe(line)));
+    }
+    for line in actual_lines.iter().filter(|line| !expected_lines.contains(line)) {
+        problems.push(format!("unexpected: {}", describe(line)));
+    }
+    problems
+}
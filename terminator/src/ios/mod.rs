@@ -0,0 +1,555 @@
+pub mod config;
+
+use std::net::Ipv4Addr;
+use crate::ios::config::{expand_interface_name, is_valid_mask, is_virtual, Config, Route, SwitchportMode};
+use crate::term_io::read_line;
+
+const INVALID_INPUT: &str = "% Invalid input detected at '^' marker.";
+const INCOMPLETE: &str = "% Incomplete command.";
+
+/// The CLI modes of the simulated device, each with its own prompt and commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// User EXEC mode: `Router>`
+    User,
+    /// Privileged EXEC mode, entered with `enable`: `Router#`
+    Privileged,
+    /// Global configuration mode, entered with `configure terminal`: `Router(config)#`
+    Global,
+    /// Interface configuration mode for the named interface: `Router(config-if)#`
+    Interface(String),
+}
+
+/// Resolves a possibly abbreviated `word` against the `keywords` accepted at that point in a
+/// command, as IOS does: an exact match or a unique prefix is accepted, so `conf t` is
+/// `configure terminal`.
+fn keyword(words: &[&str], i: usize, keywords: &[&'static str]) -> Result<&'static str, String> {
+    let word: String = match words.get(i) {
+        Some(word) => word.to_ascii_lowercase(),
+        None => return Err(String::from(INCOMPLETE)),
+    };
+    if let Some(exact) = keywords.iter().find(|k| **k == word) {
+        return Ok(exact);
+    }
+    let matches: Vec<&&str> = keywords.iter().filter(|k| k.starts_with(&word)).collect();
+    match matches.as_slice() {
+        [only] => Ok(only),
+        [] => Err(String::from(INVALID_INPUT)),
+        _ => Err(format!("% Ambiguous command:  \"{}\"", words[..=i].join(" "))),
+    }
+}
+
+/// Parses the IPv4 address at `words[i]`.
+fn address(words: &[&str], i: usize) -> Result<Ipv4Addr, String> {
+    match words.get(i) {
+        Some(word) => word.parse().map_err(|_| String::from(INVALID_INPUT)),
+        None => Err(String::from(INCOMPLETE)),
+    }
+}
+
+/// A simulated Cisco IOS device, used for questions that are answered by configuring it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    pub running: Config,
+    /// The saved configuration, or `None` if it has never been saved.
+    pub startup: Option<Config>,
+    pub mode: Mode,
+    /// Whether the next line entered is the password for `enable`.
+    awaiting_password: bool,
+    /// Whether a configuration is being loaded, in which case any interface may be created.
+    loading: bool,
+}
+
+impl Default for Device {
+    fn default() -> Self {
+        Device {
+            running: Config::default(),
+            startup: None,
+            mode: Mode::User,
+            awaiting_password: false,
+            loading: false,
+        }
+    }
+}
+
+impl Device {
+    /// Builds a device by applying `config` to a new router; see `apply_config`.
+    pub fn from_config(config: &str) -> Result<Self, String> {
+        let mut device: Device = Device::default();
+        device.apply_config(config)?;
+        Ok(device)
+    }
+
+    /// Applies each line of `config` as a global configuration command, as if it were pasted in
+    /// after `configure terminal`. Output from `show running-config` can be used as-is, since
+    /// `!` lines, indentation and the surrounding boilerplate are ignored. Interfaces that are
+    /// configured are added to the device if it doesn't already have them.
+    pub fn apply_config(&mut self, config: &str) -> Result<(), String> {
+        self.mode = Mode::Global;
+        self.loading = true;
+        let mut result: Result<(), String> = Ok(());
+        for (i, line) in config.lines().enumerate() {
+            let line: &str = line.trim();
+            let ignored: bool = line.is_empty() || line.starts_with('!') || line == "end"
+                || line.starts_with("version ") || line.starts_with("Building configuration")
+                || line.starts_with("Current configuration");
+            if ignored {
+                continue;
+            }
+            let output: String = self.execute(line);
+            if output.starts_with('%') {
+                result = Err(format!("line {}: {} ({})", i + 1, output.trim(), line));
+                break;
+            }
+        }
+        self.mode = Mode::User;
+        self.loading = false;
+        result
+    }
+
+    /// The prompt shown before each command, such as `R1(config-if)#`.
+    pub fn prompt(&self) -> String {
+        let hostname: &str = &self.running.hostname;
+        match self.mode {
+            Mode::User => format!("{}>", hostname),
+            Mode::Privileged => format!("{}#", hostname),
+            Mode::Global => format!("{}(config)#", hostname),
+            Mode::Interface(_) => format!("{}(config-if)#", hostname),
+        }
+    }
+
+    /// Runs a single command in the current mode, returning its output.
+    pub fn execute(&mut self, line: &str) -> String {
+        if self.awaiting_password {
+            self.awaiting_password = false;
+            if self.running.enable_secret.as_deref() == Some(line) {
+                self.mode = Mode::Privileged;
+                return String::new();
+            }
+            return String::from("% Access denied\n");
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            return String::new();
+        }
+        let result: Result<String, String> = match self.mode.clone() {
+            Mode::User => self.exec(&words, false),
+            Mode::Privileged => self.exec(&words, true),
+            Mode::Global => self.global(&words),
+            Mode::Interface(name) => self.interface(&name, &words),
+        };
+        match result {
+            Ok(output) => output,
+            Err(e) => format!("{}\n", e),
+        }
+    }
+
+    /// Commands in user and privileged EXEC mode.
+    fn exec(&mut self, words: &[&str], privileged: bool) -> Result<String, String> {
+        let keywords: &[&str] = if privileged {
+            &["configure", "copy", "disable", "enable", "exit", "logout", "show", "write"]
+        } else {
+            &["enable", "exit", "logout", "show"]
+        };
+        match keyword(words, 0, keywords)? {
+            "enable" => {
+                if !privileged {
+                    match self.running.enable_secret {
+                        Some(_) => self.awaiting_password = true,
+                        None => self.mode = Mode::Privileged,
+                    }
+                }
+                Ok(String::new())
+            },
+            "disable" => {
+                self.mode = Mode::User;
+                Ok(String::new())
+            },
+            "exit" | "logout" => {
+                self.mode = Mode::User;
+                Ok(String::from("Type 'submit' when you're finished.\n"))
+            },
+            "configure" => {
+                if words.len() > 1 {
+                    keyword(words, 1, &["terminal"])?;
+                }
+                self.mode = Mode::Global;
+                Ok(String::from("Enter configuration commands, one per line.  End with CNTL/Z.\n"))
+            },
+            "copy" => {
+                keyword(words, 1, &["running-config"])?;
+                keyword(words, 2, &["startup-config"])?;
+                self.startup = Some(self.running.clone());
+                Ok(String::from("Destination filename [startup-config]? \nBuilding configuration...\n[OK]\n"))
+            },
+            "write" => {
+                if words.len() > 1 {
+                    keyword(words, 1, &["memory"])?;
+                }
+                self.startup = Some(self.running.clone());
+                Ok(String::from("Building configuration...\n[OK]\n"))
+            },
+            "show" => {
+                let keywords: &[&str] = if privileged {
+                    &["ip", "running-config", "startup-config"]
+                } else {
+                    &["ip"]
+                };
+                match keyword(words, 1, keywords)? {
+                    "running-config" => Ok(self.running.render()),
+                    "startup-config" => match &self.startup {
+                        Some(startup) => Ok(startup.render()),
+                        None => Ok(String::from("startup-config is not present\n")),
+                    },
+                    _ => {
+                        keyword(words, 2, &["interface"])?;
+                        keyword(words, 3, &["brief"])?;
+                        Ok(self.interface_brief())
+                    },
+                }
+            },
+            _ => unreachable!("Every EXEC keyword should be handled"),
+        }
+    }
+
+    /// The output of `show ip interface brief`.
+    fn interface_brief(&self) -> String {
+        let mut output: String = format!(
+            "{:<27}{:<16}{:<4}{:<7}{:<22}{}\n", "Interface", "IP-Address", "OK?", "Method", "Status", "Protocol"
+        );
+        for interface in &self.running.interfaces {
+            let (address, method) = match interface.address {
+                Some((address, _)) => (address.to_string(), "manual"),
+                None => (String::from("unassigned"), "unset"),
+            };
+            let (status, protocol) = if interface.shutdown {
+                ("administratively down", "down")
+            } else {
+                ("up", "up")
+            };
+            output.push_str(&format!(
+                "{:<27}{:<16}{:<4}{:<7}{:<22}{}\n", interface.name, address, "YES", method, status, protocol
+            ));
+        }
+        output
+    }
+
+    /// Commands in global configuration mode.
+    fn global(&mut self, words: &[&str]) -> Result<String, String> {
+        if words[0].eq_ignore_ascii_case("do") {
+            return self.exec(&words[1..], true);
+        }
+        let negate: bool = words[0].eq_ignore_ascii_case("no");
+        let words: &[&str] = if negate { &words[1..] } else { words };
+
+        match keyword(words, 0, &["end", "enable", "exit", "hostname", "interface", "ip"])? {
+            "end" | "exit" if !negate => self.mode = Mode::Privileged,
+            "hostname" if negate => self.running.hostname = Config::default().hostname,
+            "hostname" => match words.get(1) {
+                Some(name) if words.len() == 2 => self.running.hostname = name.to_string(),
+                Some(_) => return Err(String::from(INVALID_INPUT)),
+                None => return Err(String::from(INCOMPLETE)),
+            },
+            "enable" => {
+                keyword(words, 1, &["secret"])?;
+                if negate {
+                    self.running.enable_secret = None;
+                } else {
+                    match words.get(2) {
+                        Some(secret) => self.running.enable_secret = Some(secret.to_string()),
+                        None => return Err(String::from(INCOMPLETE)),
+                    }
+                }
+            },
+            "interface" => {
+                if words.len() < 2 {
+                    return Err(String::from(INCOMPLETE));
+                }
+                let name: String = expand_interface_name(&words[1..].join(" "))
+                    .ok_or_else(|| String::from(INVALID_INPUT))?;
+                let exists: bool = self.running.interface(&name).is_some();
+                if negate {
+                    if !exists || !is_virtual(&name) {
+                        return Err(String::from(INVALID_INPUT));
+                    }
+                    self.running.interfaces.retain(|interface| interface.name != name);
+                } else {
+                    if !exists {
+                        if !is_virtual(&name) && !self.loading {
+                            return Err(String::from(INVALID_INPUT));
+                        }
+                        self.running.add_interface(&name);
+                    }
+                    self.mode = Mode::Interface(name);
+                }
+            },
+            "ip" => self.global_ip(words, negate)?,
+            _ => return Err(String::from(INVALID_INPUT)),
+        }
+        Ok(String::new())
+    }
+
+    /// The `ip ...` commands in global configuration mode.
+    fn global_ip(&mut self, words: &[&str], negate: bool) -> Result<(), String> {
+        match keyword(words, 1, &["default-gateway", "domain-lookup", "domain-name", "route"])? {
+            "default-gateway" if negate => self.running.default_gateway = None,
+            "default-gateway" => self.running.default_gateway = Some(address(words, 2)?),
+            "domain-lookup" => self.running.domain_lookup = !negate,
+            "domain-name" if negate => self.running.domain_name = None,
+            "domain-name" => match words.get(2) {
+                Some(domain) => self.running.domain_name = Some(domain.to_string()),
+                None => return Err(String::from(INCOMPLETE)),
+            },
+            _ => {
+                let network: Ipv4Addr = address(words, 2)?;
+                let mask: Ipv4Addr = address(words, 3)?;
+                if !is_valid_mask(mask) || u32::from(network) & !u32::from(mask) != 0 {
+                    return Err(String::from("%Inconsistent address and mask"));
+                }
+                if negate {
+                    self.running.routes.retain(|route| {
+                        !(route.network == network && route.mask == mask
+                            && words.get(4).is_none_or(|hop| route.next_hop == *hop))
+                    });
+                    return Ok(());
+                }
+                let next_hop: String = match words.get(4) {
+                    Some(hop) if hop.parse::<Ipv4Addr>().is_ok() => hop.to_string(),
+                    Some(hop) => expand_interface_name(hop)
+                        .filter(|name| self.running.interface(name).is_some())
+                        .ok_or_else(|| String::from(INVALID_INPUT))?,
+                    None => return Err(String::from(INCOMPLETE)),
+                };
+                let route: Route = Route { network, mask, next_hop };
+                if !self.running.routes.contains(&route) {
+                    self.running.routes.push(route);
+                    self.running.routes.sort();
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Commands in interface configuration mode. Global configuration commands are also accepted,
+    /// and leave interface mode, as they do in IOS.
+    fn interface(&mut self, name: &str, words: &[&str]) -> Result<String, String> {
+        if words[0].eq_ignore_ascii_case("do") {
+            return self.exec(&words[1..], true);
+        }
+        let negate: bool = words[0].eq_ignore_ascii_case("no");
+        let command: &[&str] = if negate { &words[1..] } else { words };
+        let keywords: &[&str] = &["description", "end", "exit", "ip", "shutdown", "switchport"];
+        let first: &str = match keyword(command, 0, keywords) {
+            Ok(first) => first,
+            Err(e) => {
+                let mode: Mode = std::mem::replace(&mut self.mode, Mode::Global);
+                return self.global(words).inspect_err(|_| self.mode = mode).map_err(|_| e);
+            },
+        };
+        if first == "ip" && keyword(command, 1, &["address"]).is_err() {
+            self.mode = Mode::Global;
+            return self.global(words);
+        }
+
+        match first {
+            "end" if !negate => self.mode = Mode::Privileged,
+            "exit" if !negate => self.mode = Mode::Global,
+            "description" => {
+                let description: Option<String> = if negate {
+                    None
+                } else if command.len() < 2 {
+                    return Err(String::from(INCOMPLETE));
+                } else {
+                    Some(command[1..].join(" "))
+                };
+                self.current(name).description = description;
+            },
+            "ip" if negate => self.current(name).address = None,
+            "ip" => {
+                let address: Ipv4Addr = address(command, 2)?;
+                let mask: Ipv4Addr = address_mask(command)?;
+                let prefix: u32 = u32::from(mask).leading_ones();
+                let host: u32 = u32::from(address) & !u32::from(mask);
+                if prefix < 31 && (host == 0 || host == !u32::from(mask)) {
+                    return Err(format!("% Bad mask /{} for address {}", prefix, address));
+                }
+                let network: u32 = u32::from(address) & u32::from(mask);
+                let overlap = self.running.interfaces.iter()
+                    .filter(|interface| interface.name != name)
+                    .find(|interface| interface.address.is_some_and(|(other, other_mask)| {
+                        let shared: u32 = u32::from(mask) & u32::from(other_mask);
+                        u32::from(other) & shared == network & shared
+                    }));
+                if let Some(other) = overlap {
+                    return Err(format!("% {} overlaps with {}", Ipv4Addr::from(network), other.name));
+                }
+                self.current(name).address = Some((address, mask));
+            },
+            "shutdown" => self.current(name).shutdown = !negate,
+            "switchport" => match keyword(command, 1, &["access", "mode"])? {
+                "mode" if negate => self.current(name).switchport_mode = None,
+                "mode" => {
+                    let mode: SwitchportMode = match keyword(command, 2, &["access", "trunk"])? {
+                        "access" => SwitchportMode::Access,
+                        _ => SwitchportMode::Trunk,
+                    };
+                    self.current(name).switchport_mode = Some(mode);
+                },
+                _ => {
+                    keyword(command, 2, &["vlan"])?;
+                    if negate {
+                        self.current(name).access_vlan = None;
+                    } else {
+                        let vlan: u16 = match command.get(3).map(|vlan| vlan.parse::<u16>()) {
+                            Some(Ok(vlan)) if (1..=4094).contains(&vlan) => vlan,
+                            Some(_) => return Err(String::from(INVALID_INPUT)),
+                            None => return Err(String::from(INCOMPLETE)),
+                        };
+                        self.current(name).access_vlan = Some(vlan);
+                    }
+                },
+            },
+            _ => return Err(String::from(INVALID_INPUT)),
+        }
+        Ok(String::new())
+    }
+
+    /// The interface being configured.
+    fn current(&mut self, name: &str) -> &mut config::Interface {
+        self.running.interface_mut(name).expect("The interface being configured should exist")
+    }
+
+    /// Runs an interactive session on the device until the user enters `submit`. Every command
+    /// that was entered is returned, in order.
+    pub fn interact(&mut self) -> Vec<String> {
+        let mut history: Vec<String> = Vec::new();
+        println!("Enter IOS commands at the prompt. Type 'submit' when you're finished.");
+        loop {
+            let line: String = read_line(&self.prompt());
+            if line.is_empty() {
+                continue;
+            }
+            if line == "submit" {
+                return history;
+            }
+            print!("{}", self.execute(&line));
+            history.push(line);
+            if self.awaiting_password {
+                let password: String = read_line("Password: ");
+                print!("{}", self.execute(&password));
+            }
+        }
+    }
+}
+
+/// Parses the subnet mask of an `ip address ADDRESS MASK` command.
+fn address_mask(command: &[&str]) -> Result<Ipv4Addr, String> {
+    let mask: Ipv4Addr = address(command, 3)?;
+    if !is_valid_mask(mask) || command.len() > 4 {
+        return Err(String::from(INVALID_INPUT));
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ios::config::check;
+
+    const INITIAL: &str = "hostname R1\ninterface GigabitEthernet0/0\n ip address 10.0.0.1 255.255.255.252\n no shutdown";
+    const EXPECTED: &str = "interface GigabitEthernet0/1\n ip address 192.168.10.1 255.255.255.0\n no shutdown";
+
+    /// A device loaded with `INITIAL`, and the same device with `EXPECTED` applied, as a question
+    /// would set them up.
+    fn devices() -> (Device, Device) {
+        let device: Device = Device::from_config(INITIAL).unwrap();
+        let mut target: Device = device.clone();
+        target.apply_config(EXPECTED).unwrap();
+        (device, target)
+    }
+
+    /// Runs each of `lines` on the `device`, checking that none of them fails.
+    fn run(device: &mut Device, lines: &[&str]) {
+        for line in lines {
+            let output: String = device.execute(line);
+            assert!(!output.starts_with('%'), "{}: {}", line, output);
+        }
+    }
+
+    #[test]
+    fn full_and_abbreviated_commands_reach_the_expected_config() {
+        let (mut device, target) = devices();
+        run(&mut device, &[
+            "enable", "configure terminal", "interface GigabitEthernet0/1",
+            "ip address 192.168.10.1 255.255.255.0", "no shutdown", "end",
+        ]);
+        assert_eq!(device.mode, Mode::Privileged);
+        assert_eq!(check(&device.running, &target.running), Vec::<String>::new());
+
+        let (mut device, target) = devices();
+        run(&mut device, &["en", "conf t", "int g0/1", "ip add 192.168.10.1 255.255.255.0", "no shut", "EXIT"]);
+        assert_eq!(device.prompt(), "R1(config)#");
+        assert!(check(&device.running, &target.running).is_empty());
+    }
+
+    #[test]
+    fn differences_from_the_expected_config_are_listed() {
+        let (mut device, target) = devices();
+        run(&mut device, &["en", "conf t", "int g0/1", "ip address 192.168.10.1 255.255.255.128"]);
+        assert_eq!(check(&device.running, &target.running), vec![
+            String::from("missing: interface GigabitEthernet0/1 > ip address 192.168.10.1 255.255.255.0"),
+            String::from("unexpected: interface GigabitEthernet0/1 > ip address 192.168.10.1 255.255.255.128"),
+            String::from("unexpected: interface GigabitEthernet0/1 > shutdown"),
+        ]);
+    }
+
+    #[test]
+    fn ambiguous_and_unknown_commands_are_rejected() {
+        let (mut device, _) = devices();
+        assert_eq!(device.execute("e"), "% Ambiguous command:  \"e\"\n");
+        run(&mut device, &["en"]);
+        assert_eq!(device.execute("co t"), "% Ambiguous command:  \"co\"\n");
+        assert_eq!(device.execute("frobnicate"), format!("{}\n", INVALID_INPUT));
+        assert_eq!(device.execute("show"), format!("{}\n", INCOMPLETE));
+        run(&mut device, &["conf t"]);
+        assert_eq!(device.execute("i g0/1"), "% Ambiguous command:  \"i\"\n");
+        assert_eq!(device.execute("int g0/9"), format!("{}\n", INVALID_INPUT));
+        assert_eq!(device.mode, Mode::Global);
+    }
+
+    #[test]
+    fn modes_limit_the_commands_available() {
+        let (mut device, _) = devices();
+        assert_eq!(device.prompt(), "R1>");
+        assert_eq!(device.execute("configure terminal"), format!("{}\n", INVALID_INPUT));
+        assert_eq!(device.execute("show running-config"), format!("{}\n", INVALID_INPUT));
+        run(&mut device, &["enable", "conf t", "int g0/1"]);
+        assert_eq!(device.prompt(), "R1(config-if)#");
+        // Global commands still work from interface mode, and leave it
+        run(&mut device, &["hostname Edge"]);
+        assert_eq!(device.prompt(), "Edge(config)#");
+        assert!(device.execute("do show run").contains("hostname Edge\n"));
+    }
+
+    #[test]
+    fn invalid_addresses_are_refused() {
+        let (mut device, _) = devices();
+        run(&mut device, &["en", "conf t", "int g0/1"]);
+        assert_eq!(device.execute("ip address 192.168.10.0 255.255.255.0"), "% Bad mask /24 for address 192.168.10.0\n");
+        assert_eq!(device.execute("ip address 10.0.0.2 255.255.255.0"), "% 10.0.0.0 overlaps with GigabitEthernet0/0\n");
+        assert!(device.running.interface("GigabitEthernet0/1").is_some_and(|interface| interface.address.is_none()));
+    }
+
+    #[test]
+    fn expands_interface_names() {
+        assert_eq!(expand_interface_name("g0/1").as_deref(), Some("GigabitEthernet0/1"));
+        assert_eq!(expand_interface_name("gig 0/1").as_deref(), Some("GigabitEthernet0/1"));
+        assert_eq!(expand_interface_name("fa0/1.10").as_deref(), Some("FastEthernet0/1.10"));
+        assert_eq!(expand_interface_name("lo0").as_deref(), Some("Loopback0"));
+        // Of the types, only Ethernet itself starts with an e
+        assert_eq!(expand_interface_name("e0").as_deref(), Some("Ethernet0"));
+        assert_eq!(expand_interface_name("g0//1"), None);
+        assert_eq!(expand_interface_name("0/1"), None);
+    }
+}
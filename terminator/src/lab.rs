@@ -0,0 +1,216 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::shell::{Output, Shell};
+use crate::shell::state::{check, parse_expectations, serialize, Expectation};
+use crate::term_io::read_line;
+
+/// A single row from the `LabTasks` table: one step of a lab. The step is complete once the
+/// simulated system matches its `goal_check`, which uses the same format as a command question's
+/// expected state (see `state::parse_expectations`).
+#[derive(Debug)]
+pub struct LabTask {
+    pub step_number: i32,
+    pub instructions: String,
+    pub goal_check: String,
+    pub hint: Option<String>,
+}
+
+/// A single row from the `UserLabProgress` table. `state` is the simulated system as it was
+/// when the user last saved, so that the lab can be resumed from where they left off.
+#[derive(Debug)]
+pub struct LabProgress {
+    pub steps_completed: usize,
+    pub state: String,
+    pub completed: bool,
+}
+
+/// A single row from the `Labs` table, along with its `LabTasks` once they've been loaded. A lab
+/// is a multi-step scenario that is solved in a simulated shell, starting from `initial_state`.
+#[derive(Debug)]
+pub struct Lab {
+    pub lab_id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub initial_state: String,
+    pub tasks: Vec<LabTask>,
+}
+
+impl Lab {
+    /// Queries the database for every lab that belongs to the exam with the given `exam_id`,
+    /// including the tasks for each.
+    pub fn get_for_exam(conn: &Connection, exam_id: i32) -> rusqlite::Result<Vec<Lab>> {
+        let mut stmt = conn.prepare(
+            "SELECT LabID, Title, Description, InitialState FROM Labs WHERE ExamID = ?1 ORDER BY LabID"
+        )?;
+        let labs = stmt.query_map([exam_id], |row| {
+            Ok(Lab {
+                lab_id: row.get(0)?,
+                title: row.get(1)?,
+                description: row.get(2)?,
+                initial_state: row.get(3)?,
+                tasks: Vec::new(),
+            })
+        })?;
+        let mut labs: Vec<Lab> = labs.collect::<rusqlite::Result<Vec<Lab>>>()?;
+        for lab in labs.iter_mut() {
+            lab.load_tasks(conn)?;
+        }
+        Ok(labs)
+    }
+
+    /// Loads the steps of this lab from the `LabTasks` table, in order.
+    pub fn load_tasks(&mut self, conn: &Connection) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            "SELECT StepNumber, Instructions, GoalCheck, Hint FROM LabTasks WHERE LabID = ?1 ORDER BY StepNumber"
+        )?;
+        let tasks = stmt.query_map([self.lab_id], |row| {
+            Ok(LabTask {
+                step_number: row.get(0)?,
+                instructions: row.get(1)?,
+                goal_check: row.get(2)?,
+                hint: row.get(3)?,
+            })
+        })?;
+        self.tasks = tasks.collect::<rusqlite::Result<Vec<LabTask>>>()?;
+        Ok(())
+    }
+
+    /// Queries the `UserLabProgress` table for the user's saved progress through this lab.
+    pub fn load_progress(&self, conn: &Connection, username: &str) -> rusqlite::Result<Option<LabProgress>> {
+        conn.query_row(
+            "SELECT StepsCompleted, State, Completed FROM UserLabProgress WHERE Username = ?1 AND LabID = ?2",
+            params![username, self.lab_id],
+            |row| Ok(LabProgress { steps_completed: row.get(0)?, state: row.get(1)?, completed: row.get(2)? }),
+        ).optional()
+    }
+
+    /// Replaces the user's saved progress through this lab.
+    fn save_progress(&self, conn: &Connection, username: &str, progress: &LabProgress) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO UserLabProgress (Username, LabID, StepsCompleted, State, Completed, Timestamp) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                username,
+                self.lab_id,
+                progress.steps_completed,
+                &progress.state,
+                progress.completed,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// A short description of how far the user has gotten, for the lab menu.
+    pub fn status(&self, progress: &Option<LabProgress>) -> String {
+        match progress {
+            Some(progress) if progress.completed => String::from("completed"),
+            Some(progress) => format!("{} of {} steps done", progress.steps_completed, self.tasks.len()),
+            None => String::from("not started"),
+        }
+    }
+
+    /// Runs the lab in a simulated shell for the user with the given `username`. The user works
+    /// through the steps in order; each step is complete as soon as its goal check passes.
+    /// Progress is saved after every completed step and when the user quits, and a saved lab can
+    /// be resumed or started over.
+    pub fn run(&self, conn: &Connection, username: &str) -> rusqlite::Result<()> {
+        if self.tasks.is_empty() {
+            println!("{} doesn't have any steps yet...", self.title);
+            return Ok(());
+        }
+
+        let mut progress: LabProgress = match self.load_progress(conn, username)? {
+            Some(progress) if progress.completed => {
+                let entry: String = read_line("You've already completed this lab. Start it over? [y/N] ");
+                if !entry.eq_ignore_ascii_case("y") {
+                    return Ok(());
+                }
+                self.fresh_progress()
+            },
+            Some(progress) => {
+                let prompt: String = format!(
+                    "Resume where you left off ({} of {} steps done)? [Y/n] ",
+                    progress.steps_completed,
+                    self.tasks.len(),
+                );
+                if read_line(&prompt).eq_ignore_ascii_case("n") {
+                    self.fresh_progress()
+                } else {
+                    progress
+                }
+            },
+            None => self.fresh_progress(),
+        };
+
+        let mut shell: Shell = match Shell::from_state(&progress.state) {
+            Ok(shell) => shell,
+            Err(e) => {
+                println!("This lab can't be run; its state is invalid ({}).", e);
+                return Ok(());
+            },
+        };
+
+        println!("\n=== {} ===", self.title);
+        if let Some(desc) = self.description.as_deref().filter(|desc| !desc.is_empty()) {
+            println!("{}", desc);
+        }
+        println!("Enter commands at the prompt. Type 'check' to see what's left for the current step, \
+            'hint' for a hint, or 'quit' to save and leave.");
+
+        let total: usize = self.tasks.len();
+        for task in self.tasks.iter().skip(progress.steps_completed) {
+            let expectations: Vec<Expectation> = match parse_expectations(&task.goal_check) {
+                Ok(expectations) => expectations,
+                Err(e) => {
+                    println!("Step {} can't be checked; its goal is invalid ({}).", task.step_number, e);
+                    return Ok(());
+                },
+            };
+            println!("\nStep {} of {}: {}", progress.steps_completed + 1, total, task.instructions);
+
+            // The goal may already be met, e.g., if the user got ahead of the instructions
+            while !check(&shell, &expectations).is_empty() {
+                let line: String = read_line(&shell.prompt());
+                match line.as_str() {
+                    "" => {},
+                    "check" => {
+                        println!("Not done yet:");
+                        for problem in check(&shell, &expectations) {
+                            println!("  - {}", problem);
+                        }
+                    },
+                    "hint" => match &task.hint {
+                        Some(hint) if !hint.is_empty() => println!("Hint: {}", hint),
+                        _ => println!("There's no hint for this step."),
+                    },
+                    "quit" => {
+                        progress.state = serialize(&shell);
+                        self.save_progress(conn, username, &progress)?;
+                        println!("Your progress has been saved.");
+                        return Ok(());
+                    },
+                    _ => {
+                        let output: Output = shell.execute(&line);
+                        print!("{}", output.stdout);
+                        eprint!("{}", output.stderr);
+                    },
+                }
+            }
+
+            println!("Step {} complete!", progress.steps_completed + 1);
+            progress.steps_completed += 1;
+            progress.state = serialize(&shell);
+            progress.completed = progress.steps_completed == total;
+            self.save_progress(conn, username, &progress)?;
+        }
+
+        println!("\nYou've completed {}!", self.title);
+        Ok(())
+    }
+
+    /// The progress for a user who is starting this lab from the beginning.
+    fn fresh_progress(&self) -> LabProgress {
+        LabProgress { steps_completed: 0, state: self.initial_state.clone(), completed: false }
+    }
+}
@@ -0,0 +1,6 @@
+//! Code shared by the `terminator` game and the `create_database` tool.
+
+pub mod lint;
+pub mod migrations;
+pub mod pack;
+pub mod question;
@@ -0,0 +1,549 @@
+//! Checks question packs, and exams exported from the database, for content that would import or
+//! play wrong: answers that aren't among the options, missing explanations, near-duplicate
+//! questions, objectives that aren't in the catalog and domain weights that don't add up.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use toml::Spanned;
+use crate::pack::{Pack, Question};
+use crate::question::QuestionType;
+
+/// Questions whose text is at least this similar (by trigram Jaccard similarity) are reported as
+/// near-duplicates.
+pub const SIMILARITY_THRESHOLD: f64 = 0.85;
+
+/// Matches an answer that starts with an option's label, as in `B`, `B.) 2` or `b) 2`, capturing
+/// the label and any text after it.
+fn answer_label_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^\s*([A-Za-z])\s*(?:[.)]+\s*(.*))?$").unwrap())
+}
+
+/// Matches the label at the start of an option written the old way, as in `A.) 1`.
+fn legacy_label_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^\s*([A-Za-z0-9]{1,2})\s*[.)]+").unwrap())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The content is wrong and will be graded or imported wrong.
+    Error,
+    /// The content works, but probably isn't what the author meant.
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// A single problem found by the linter.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// A short name for the check that failed, e.g., `empty-explanation`.
+    pub rule: &'static str,
+    /// The line of the pack the problem is on, if the content came from a file.
+    pub line: Option<usize>,
+    /// The UUID of the question the problem is with, if it's with a question.
+    pub question: Option<String>,
+    pub message: String,
+}
+
+/// Where things are in the text of a pack, as line numbers starting at 1. Only what the checks
+/// point at is kept.
+#[derive(Debug, Default)]
+pub struct Lines {
+    domains: Vec<DomainLines>,
+    questions: Vec<QuestionLines>,
+}
+
+#[derive(Debug, Default)]
+struct DomainLines {
+    weight: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct QuestionLines {
+    start: Option<usize>,
+    answer: Option<usize>,
+    explanation: Option<usize>,
+    objectives: Vec<usize>,
+    options: Vec<Option<usize>>,
+}
+
+// The same shape as a pack, but with the span of each field the checks point at. Unknown fields
+// are ignored here; `Pack` itself rejects them.
+#[derive(Deserialize)]
+struct Located {
+    #[serde(default)]
+    domains: Vec<LocatedDomain>,
+    #[serde(default)]
+    questions: Vec<LocatedQuestion>,
+}
+
+#[derive(Deserialize)]
+struct LocatedDomain {
+    weight: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize)]
+struct LocatedQuestion {
+    id: Option<Spanned<toml::Value>>,
+    text: Option<Spanned<toml::Value>>,
+    answer: Option<Spanned<toml::Value>>,
+    explanation: Option<Spanned<toml::Value>>,
+    #[serde(default)]
+    objectives: Vec<Spanned<toml::Value>>,
+    #[serde(default)]
+    options: Vec<LocatedOption>,
+}
+
+#[derive(Deserialize)]
+struct LocatedOption {
+    text: Option<Spanned<toml::Value>>,
+}
+
+/// The line of `text` that the byte `offset` is on.
+pub fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+impl Lines {
+    /// Finds the lines of a pack's fields in its `text`. Returns empty `Lines` if the text
+    /// doesn't parse, in which case parsing it as a `Pack` gives the error.
+    pub fn find(text: &str) -> Lines {
+        let located: Located = match toml::from_str(text) {
+            Ok(located) => located,
+            Err(_) => return Lines::default(),
+        };
+        let line = |spanned: &Option<Spanned<toml::Value>>| spanned.as_ref().map(|value| line_of(text, value.span().start));
+        Lines {
+            domains: located.domains.iter().map(|domain| DomainLines { weight: line(&domain.weight) }).collect(),
+            questions: located.questions.iter().map(|question| QuestionLines {
+                start: line(&question.id).or_else(|| line(&question.text)),
+                answer: line(&question.answer),
+                explanation: line(&question.explanation),
+                objectives: question.objectives.iter().map(|objective| line_of(text, objective.span().start)).collect(),
+                options: question.options.iter().map(|option| line(&option.text)).collect(),
+            }).collect(),
+        }
+    }
+
+    fn question(&self, index: usize) -> Option<&QuestionLines> {
+        self.questions.get(index)
+    }
+}
+
+/// Collects the findings for one pack, pointing at lines from `lines`.
+struct Linter<'a> {
+    lines: &'a Lines,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, severity: Severity, rule: &'static str, line: Option<usize>, question: Option<&Question>, message: String) {
+        self.findings.push(Finding {
+            severity,
+            rule,
+            line,
+            question: question.map(|question| question.id.clone()),
+            message,
+        });
+    }
+
+    fn question_line(&self, index: usize) -> Option<usize> {
+        self.lines.question(index).and_then(|lines| lines.start)
+    }
+}
+
+/// Checks `pack` and returns what's wrong with it, in the order it appears. `lines` says where
+/// things are in the file the pack was read from; use `Lines::default()` for a pack that wasn't
+/// read from a file.
+pub fn lint(pack: &Pack, lines: &Lines) -> Vec<Finding> {
+    let mut linter: Linter = Linter { lines, findings: Vec::new() };
+
+    if !pack.domains.is_empty() {
+        let total: u32 = pack.domains.iter().map(|domain| domain.weight).sum();
+        if total != 100 {
+            let line: Option<usize> = lines.domains.first().and_then(|domain| domain.weight);
+            linter.report(Severity::Error, "domain-weights", line, None, format!(
+                "The domain weights add up to {}%, not 100%", total,
+            ));
+        }
+    }
+    let catalog: HashSet<&str> = pack.domains.iter()
+        .flat_map(|domain| domain.objectives.iter().map(|objective| objective.code.as_str()))
+        .collect();
+
+    let mut seen_ids: HashSet<&str> = HashSet::new();
+    for (index, question) in pack.questions.iter().enumerate() {
+        let line: Option<usize> = linter.question_line(index);
+        let question_lines: Option<&QuestionLines> = lines.question(index);
+
+        if question.id.trim().is_empty() {
+            linter.report(Severity::Error, "missing-id", line, None, format!(
+                "Question {} has no id, so it can't be imported", index + 1,
+            ));
+        } else if !seen_ids.insert(question.id.trim()) {
+            linter.report(Severity::Error, "duplicate-id", line, Some(question), String::from(
+                "Another question has the same id",
+            ));
+        }
+
+        if question.explanation.trim().is_empty() {
+            let explanation_line: Option<usize> = question_lines.and_then(|lines| lines.explanation).or(line);
+            linter.report(Severity::Warning, "empty-explanation", explanation_line, Some(question), String::from(
+                "The explanation is empty, so learners who get it wrong won't be told why",
+            ));
+        }
+
+        for (i, code) in question.objectives.iter().enumerate() {
+            if !catalog.contains(code.as_str()) {
+                let objective_line: Option<usize> = question_lines.and_then(|lines| lines.objectives.get(i).copied()).or(line);
+                linter.report(Severity::Error, "unknown-objective", objective_line, Some(question), format!(
+                    "Objective {} isn't in the exam's catalog", code,
+                ));
+            }
+        }
+
+        if matches!(question.kind, QuestionType::MultipleChoice | QuestionType::MultipleResponse) {
+            check_options(&mut linter, index, question);
+        }
+    }
+
+    check_near_duplicates(&mut linter, pack);
+    linter.findings.sort_by_key(|finding| finding.line.unwrap_or(0));
+    linter.findings
+}
+
+/// Checks the options of a multiple choice or multiple response question against each other and,
+/// for multiple choice, against its `answer`. A multiple response question is graded by which
+/// options are marked correct, so its `answer` is only shown to the learner and isn't checked.
+fn check_options(linter: &mut Linter, index: usize, question: &Question) {
+    let line: Option<usize> = linter.question_line(index);
+    let question_lines: Option<&QuestionLines> = linter.lines.question(index);
+    let option_line = |i: usize| question_lines.and_then(|lines| lines.options.get(i).copied().flatten()).or(line);
+    let answer_line: Option<usize> = question_lines.and_then(|lines| lines.answer).or(line);
+
+    if question.options.is_empty() {
+        if question.legacy_options.trim().is_empty() {
+            linter.report(Severity::Error, "no-options", line, Some(question), String::from(
+                "The question has no options to choose from",
+            ));
+        } else {
+            check_legacy_labels(linter, line, question);
+        }
+        return;
+    }
+
+    let mut seen: Vec<String> = Vec::new();
+    for (i, option) in question.options.iter().enumerate() {
+        let text: String = option.text.trim().to_lowercase();
+        if seen.contains(&text) {
+            linter.report(Severity::Error, "duplicate-option", option_line(i), Some(question), format!(
+                "The option \"{}\" is listed more than once", option.text.trim(),
+            ));
+        }
+        seen.push(text);
+    }
+
+    if !question.options.iter().any(|option| option.correct) {
+        linter.report(Severity::Error, "no-correct-option", line, Some(question), String::from(
+            "None of the options is marked correct",
+        ));
+    }
+
+    let answer: &str = question.answer.trim();
+    if question.kind != QuestionType::MultipleChoice || answer.is_empty() {
+        return;
+    }
+    match matching_option(question, answer) {
+        Some(i) if !question.options[i].correct => {
+            linter.report(Severity::Error, "answer-not-correct", answer_line, Some(question), format!(
+                "The answer \"{}\" is the option \"{}\", which isn't marked correct", answer, question.options[i].text,
+            ));
+        },
+        Some(_) => {},
+        None => linter.report(Severity::Error, "answer-not-an-option", answer_line, Some(question), format!(
+            "The answer \"{}\" doesn't match any of the options", answer,
+        )),
+    }
+}
+
+/// Finds the option that `answer` refers to, by its text, by a label like `B` (its position), or
+/// both, as in `B.) 2`. A letter only counts as a label on its own or followed by `.` or `)`, so
+/// `a cable` and `Telnet` are only matched by their text.
+fn matching_option(question: &Question, answer: &str) -> Option<usize> {
+    let by_text = |text: &str| question.options.iter().position(|option| option.text.trim().eq_ignore_ascii_case(text.trim()));
+    if let Some(i) = by_text(answer) {
+        return Some(i);
+    }
+    let caps = answer_label_regex().captures(answer)?;
+    let text: &str = caps.get(2).map_or("", |text| text.as_str());
+    if !text.is_empty() {
+        return by_text(text);
+    }
+    let label: u8 = caps[1].to_ascii_uppercase().as_bytes()[0];
+    let i: usize = (label - b'A') as usize;
+    (i < question.options.len()).then_some(i)
+}
+
+/// Checks the labels (e.g., `A.)`) of options written the old way, as one newline-joined string.
+fn check_legacy_labels(linter: &mut Linter, line: Option<usize>, question: &Question) {
+    let mut seen: Vec<String> = Vec::new();
+    let options = question.legacy_options.lines().flat_map(|line| line.split("\\n"));
+    for caps in options.filter_map(|option| legacy_label_regex().captures(option)) {
+        let label: String = caps[1].to_uppercase();
+        if seen.contains(&label) {
+            linter.report(Severity::Error, "duplicate-option", line, Some(question), format!(
+                "The option label {} is used more than once", label,
+            ));
+        }
+        seen.push(label);
+    }
+}
+
+/// The set of three-character pieces of `text`, lowercased and with punctuation and runs of
+/// spaces squeezed out, for comparing questions.
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let normalized: Vec<char> = text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .chars()
+        .collect();
+    normalized.windows(3).map(|window| [window[0], window[1], window[2]]).collect()
+}
+
+/// The Jaccard similarity of two sets of trigrams: 1.0 for the same text, 0.0 for nothing shared.
+fn similarity(a: &HashSet<[char; 3]>, b: &HashSet<[char; 3]>) -> f64 {
+    let union: usize = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Reports each question whose text is nearly the same as an earlier question's.
+fn check_near_duplicates(linter: &mut Linter, pack: &Pack) {
+    let shingles: Vec<HashSet<[char; 3]>> = pack.questions.iter().map(|question| trigrams(&question.text)).collect();
+    for (j, question) in pack.questions.iter().enumerate() {
+        for i in 0..j {
+            let score: f64 = similarity(&shingles[i], &shingles[j]);
+            if score >= SIMILARITY_THRESHOLD {
+                let earlier: String = match linter.question_line(i) {
+                    Some(line) => format!("question {} (line {})", i + 1, line),
+                    None => format!("question {} ({})", i + 1, pack.questions[i].id),
+                };
+                linter.report(Severity::Warning, "near-duplicate", linter.question_line(j), Some(question), format!(
+                    "The text is {:.0}% similar to {}", score * 100.0, earlier,
+                ));
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = r#"[exam]
+id = "exam"
+title = "Network+"
+
+[[domains]]
+code = "1.0"
+name = "Networking Fundamentals"
+weight = 100
+
+[[domains.objectives]]
+code = "1.5"
+title = "Explain common ports and protocols"
+"#;
+
+    /// The rule and line of each finding for a pack made of `HEADER` followed by `questions`.
+    fn findings(questions: &str) -> Vec<(&'static str, Option<usize>)> {
+        let text: String = format!("{}{}", HEADER, questions);
+        let pack: Pack = Pack::from_toml(&text).unwrap();
+        lint(&pack, &Lines::find(&text)).into_iter().map(|finding| (finding.rule, finding.line)).collect()
+    }
+
+    /// The line of the first `[[questions]]` appended to `HEADER`.
+    const FIRST: usize = 14;
+
+    #[test]
+    fn a_clean_pack_has_no_findings() {
+        let questions: &str = r#"
+[[questions]]
+id = "https"
+text = "Which port does HTTPS use by default?"
+answer = "C.) 443"
+explanation = "HTTPS is HTTP over TLS."
+objectives = ["1.5"]
+options = [{ text = "22" }, { text = "80" }, { text = "443", correct = true }]
+
+[[questions]]
+id = "email"
+type = "MultipleResponse"
+text = "Which of these protocols deliver email? (Select TWO)"
+answer = "SMTP, and IMAP, as in a cable"
+explanation = "SMTP sends mail and IMAP retrieves it."
+options = [{ text = "SMTP", correct = true }, { text = "IMAP", correct = true }, { text = "SNMP, v2" }]
+"#;
+        assert_eq!(findings(questions), vec![]);
+    }
+
+    #[test]
+    fn domain_weights_must_add_up_to_100() {
+        let text: String = HEADER.replace("weight = 100", "weight = 90");
+        let pack: Pack = Pack::from_toml(&text).unwrap();
+        let found: Vec<Finding> = lint(&pack, &Lines::find(&text));
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].rule, found[0].line), ("domain-weights", Some(8)));
+        assert!(found[0].message.contains("90%"));
+    }
+
+    #[test]
+    fn questions_need_unique_ids() {
+        let questions: &str = r#"
+[[questions]]
+id = " "
+type = "Command"
+text = "What does DNS do?"
+explanation = "It resolves names."
+
+[[questions]]
+id = "ports"
+type = "Command"
+text = "Which port does SSH use?"
+explanation = "SSH is TCP 22."
+
+[[questions]]
+id = "ports"
+type = "Command"
+text = "Which port does RDP use?"
+explanation = "RDP is TCP 3389."
+"#;
+        assert_eq!(findings(questions), vec![("missing-id", Some(FIRST + 1)), ("duplicate-id", Some(FIRST + 13))]);
+    }
+
+    #[test]
+    fn explanations_and_objectives_are_checked() {
+        let questions: &str = r#"
+[[questions]]
+id = "snmp"
+type = "Command"
+text = "Which port does SNMP use?"
+explanation = ""
+objectives = ["1.5",
+    "9.9"]
+"#;
+        assert_eq!(findings(questions), vec![("empty-explanation", Some(FIRST + 4)), ("unknown-objective", Some(FIRST + 6))]);
+    }
+
+    #[test]
+    fn options_are_checked() {
+        let questions: &str = r#"
+[[questions]]
+id = "none"
+text = "Which port does LDAP use?"
+explanation = "LDAP is TCP 389."
+
+[[questions]]
+id = "twice"
+text = "Which port does LDAPS use?"
+answer = "636"
+explanation = "LDAPS is TCP 636."
+
+[[questions.options]]
+text = "636"
+correct = true
+
+[[questions.options]]
+text = " 636"
+
+[[questions]]
+id = "unmarked"
+text = "Which port does Kerberos use?"
+explanation = "Kerberos is 88."
+options = [{ text = "88" }, { text = "464" }]
+
+[[questions]]
+id = "legacy"
+text = "Which port does NTP use?"
+explanation = "NTP is UDP 123."
+legacy_options = 'A.) 123\nA.) 161'
+"#;
+        assert_eq!(findings(questions), vec![
+            ("no-options", Some(FIRST + 1)),
+            ("duplicate-option", Some(FIRST + 16)),
+            ("no-correct-option", Some(FIRST + 19)),
+            ("duplicate-option", Some(FIRST + 25)),
+        ]);
+    }
+
+    #[test]
+    fn the_answer_must_be_a_correct_option() {
+        let questions: &str = r#"
+[[questions]]
+id = "smtp"
+text = "Which port does SMTP use for submission?"
+answer = "b"
+explanation = "Submission is TCP 587."
+options = [{ text = "587", correct = true }, { text = "25" }]
+
+[[questions]]
+id = "telnet"
+text = "Which protocol uses port 23?"
+answer = "Telnet"
+explanation = "Telnet is TCP 23."
+options = [{ text = "SSH" }, { text = "FTP" }, { text = "TFTP" }, { text = "DNS" }, { text = "elnet", correct = true }]
+
+[[questions]]
+id = "cable"
+text = "What connects a PC to a switch?"
+answer = "a cable"
+explanation = "A patch cable."
+options = [{ text = "cable", correct = true }, { text = "a cable" }]
+"#;
+        assert_eq!(findings(questions), vec![
+            ("answer-not-correct", Some(FIRST + 3)),
+            ("answer-not-an-option", Some(FIRST + 10)),
+            ("answer-not-correct", Some(FIRST + 17)),
+        ]);
+    }
+
+    #[test]
+    fn near_duplicates_point_at_the_earlier_question() {
+        let questions: &str = r#"
+[[questions]]
+id = "first"
+type = "Command"
+text = "Which port does HTTPS use by default?"
+explanation = "TCP 443."
+
+[[questions]]
+id = "second"
+type = "Command"
+text = "Which port does HTTPS use, by default?"
+explanation = "TCP 443."
+"#;
+        let text: String = format!("{}{}", HEADER, questions);
+        let pack: Pack = Pack::from_toml(&text).unwrap();
+        let found: Vec<Finding> = lint(&pack, &Lines::find(&text));
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].rule, found[0].line), ("near-duplicate", Some(FIRST + 7)));
+        assert!(found[0].message.contains(&format!("question 1 (line {})", FIRST + 1)));
+    }
+}
@@ -2,9 +2,27 @@ use std::error::Error;
 use std::env;
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::exit;
 use rusqlite::{Connection};
+use crate::game::Game;
+use crate::term_io::read_choice;
 use crate::term_user::User;
+use terminator::migrations;
+
+mod adaptive;
+mod attempt;
+mod catalog;
+mod flashcards;
+mod game;
+mod generate;
+mod ios;
+mod lab;
+mod mock;
+mod review;
+mod shell;
+mod term_io;
+mod windows;
 
 const LOGO: &str = "
 
@@ -18,6 +36,7 @@ const LOGO: &str = "
 ";
 const ASSETS_DIR: &str = "assets";
 const DB_NAME: &str = "terminator.db";
+const MAX_LOGIN_ATTEMPTS: u32 = 3;
 
 #[derive(Debug)]
 struct UserError {
@@ -36,6 +55,35 @@ impl Error for UserError {
     }
 }
 
+impl From<rusqlite::Error> for UserError {
+    fn from(e: rusqlite::Error) -> Self {
+        UserError { source: Box::new(e) }
+    }
+}
+
+#[derive(Debug)]
+struct GameError {
+    source: Box<dyn Error>,
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "An error occurred while playing the game. {}", self.source.deref())
+    }
+}
+
+impl Error for GameError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.deref())
+    }
+}
+
+impl From<rusqlite::Error> for GameError {
+    fn from(e: rusqlite::Error) -> Self {
+        GameError { source: Box::new(e) }
+    }
+}
+
 #[derive(Debug)]
 struct NoSuchUser;
 
@@ -81,13 +129,25 @@ impl Display for BadQuery {
 
 impl Error for BadQuery {}
 
+#[derive(Debug)]
+struct TooManyLoginAttempts;
+
+impl Display for TooManyLoginAttempts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TooManyLoginAttempts error - the password was entered incorrectly {} times", MAX_LOGIN_ATTEMPTS)
+    }
+}
+
+impl Error for TooManyLoginAttempts {}
+
 
 mod term_user {
     use std::io::{stdin, stdout, Write};
     use bcrypt::DEFAULT_COST;
     use regex::Regex;
     use rusqlite::Connection;
-    use crate::{UserError, UserExists};
+    use crate::{BadPassword, BadQuery, MAX_LOGIN_ATTEMPTS, NoSuchUser, TooManyLoginAttempts, UserError, UserExists};
+    use crate::term_io::read_line;
 
     #[derive(Debug)]
     pub struct User {
@@ -98,7 +158,7 @@ mod term_user {
     impl User {
         /// Creates a new `User` and adds them to them to the database given via `conn`.
         pub fn new(conn: &Connection) -> Result<Self, UserError> {
-            let username = Self::create_username(None, &conn);
+            let username = Self::create_username(None, conn);
             let password = Self::create_password(None);
             match username {
                 Ok(username) => Self::new_from_str(&username, &password, conn),
@@ -106,23 +166,39 @@ mod term_user {
             }
         }
         pub fn new_from_str(username: &str, password: &str, conn: &Connection) -> Result<Self, UserError> {
-            let username = Self::create_username(Some(username.to_string()), &conn);
+            let username = Self::create_username(Some(username.to_string()), conn);
             let password = Self::create_password(Some(password.to_string()));
             let password = bcrypt::hash(&password, DEFAULT_COST).expect("Unable to hash pw.");
             match username {
                 Ok(un) => {
-                    match conn.execute(
+                    conn.execute(
                         "INSERT INTO Users VALUES (?1, ?2)",
                         [&un, &password],
-                    ) {
-                        Ok(_) => Ok(User {username: un, password}),
-                        Err(e) => Err(UserError {source: Box::new(e)}),
-                    }
+                    )?;
+                    Ok(User {username: un, password})
                 },
                 Err(e) => Err(e),
             }
         }
 
+        /// Logs in an existing `User` by prompting for their username and password. If the username
+        /// isn't in the database, a `UserError` with `NoSuchUser` is returned right away; otherwise,
+        /// the password may be entered up to `MAX_LOGIN_ATTEMPTS` times before giving up.
+        pub fn login(conn: &Connection) -> Result<Self, UserError> {
+            let username: String = read_line("Username: ");
+            if !Self::username_exists(&username, conn)? {
+                return Err(UserError {source: Box::new(NoSuchUser)});
+            }
+            for attempt in 1..=MAX_LOGIN_ATTEMPTS {
+                let password: String = read_line("Password: ");
+                if let Some(user) = Self::get_user_from_str(&username, &password, conn) {
+                    return Ok(user);
+                }
+                println!("Incorrect password ({} of {} attempts).", attempt, MAX_LOGIN_ATTEMPTS);
+            }
+            Err(UserError {source: Box::new(TooManyLoginAttempts)})
+        }
+
         /// Queries the database for a `User` with the given `username` and `password`, if they exist.
         pub fn get_user_from_str(username: &str, password: &str, conn: &Connection) -> Option<User> {
             let mut stmt = conn.prepare(
@@ -154,22 +230,25 @@ mod term_user {
                 Some(name) => name,
                 None => Self::input("Enter your desired username: "),
             };
-            if let Ok(_) = conn.query_row(
-                "SELECT Username, Password FROM Users WHERE Username = ?1",
-                [&temp],
-                |row| {
-                    Ok(User {
-                        username: row.get(0)?,
-                        password: row.get(1)?,
-                    })
-                }
-            ) {
+            if Self::username_exists(&temp, conn)? {
                 Err(UserError {source: Box::new(UserExists)})
             } else {
                 Ok(temp)
             }
         }
 
+        /// Helper function that checks whether a `User` with the given `username` is in the database.
+        fn username_exists(username: &str, conn: &Connection) -> Result<bool, UserError> {
+            match conn.query_row(
+                "SELECT COUNT(*) FROM Users WHERE Username = ?1",
+                [username],
+                |row| row.get::<_, i64>(0),
+            ) {
+                Ok(count) => Ok(count > 0),
+                Err(_) => Err(UserError {source: Box::new(BadQuery)}),
+            }
+        }
+
         fn create_password(password: Option<String>) -> String {
             let prompt: &str = "Password must be at least 10 characters long, have at least one \
             uppercase character, at least one number, and at least one special character (!@#$%^&*)\n\
@@ -183,6 +262,7 @@ mod term_user {
                 result = temp;
             } else {
                 loop {
+                    println!("{}", BadPassword);
                     let temp = Self::input(prompt);
                     if Self::is_valid_password(&temp) {
                         result = temp;
@@ -239,71 +319,97 @@ mod term_user {
 }
 
 
-/*
-struct Question {
-    question_id: i32,
-    question_text: String,
-    options: String,
-    answer: String,
-    explanation: String,
-    exam_id: i32,
-}
-
-struct Exam {
-    exam_id: i32,
-    exam_title: String,
-    exam_desc: String,
-    questions: Vec<Question>,
-}
-
-struct Game {
-    conn: Connection,
-    user: User,
-    exam: Exam,
-}
-
-impl Game {
-
-    pub fn new() -> Self {
-        let mut db: PathBuf = env::current_dir().expect("Unable to get current directory");
-        db.pop();
-        db.pop();
-        db = db.join("/assets/terminator.db");
-        let conn: Connection = Connection::open(db).expect("Unable to open db");
-
-        Game {
-            conn,
-            user: User::new(),
+/// Shows the start menu, letting the user log in to an existing account or register a new one.
+/// Returns `None` if the user chooses to quit.
+fn start_menu(conn: &Connection) -> Option<User> {
+    loop {
+        println!("\n1. Log in\n2. Register\n3. Quit");
+        let result = match read_choice("Select an option: ", 3) {
+            Some(0) => User::login(conn),
+            Some(1) => User::new(conn),
+            _ => return None,
+        };
+        match result {
+            Ok(user) => return Some(user),
+            Err(e) => println!("{}", e),
         }
     }
-    pub fn new_game_with_existing_user(username: &str) -> Self {
-
+}
 
+/// Opens the database at `db` and applies any migrations it's missing. Exits if the database can't
+/// be opened or was made by a newer version of terminator, rather than risk damaging it.
+fn open_database(db: &Path) -> Connection {
+    let mut conn: Connection = match Connection::open(db) {
+        Ok(conn) => conn,
+        Err(e) => {
+            eprintln!("Unable to connect to database... {}", e);
+            exit(1);
+        },
+    };
+    match migrations::migrate(&mut conn) {
+        Ok(applied) => {
+            for migration in applied {
+                println!("Applied database migration {}: {}", migration.version, migration.description);
+            }
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1);
+        },
     }
+    conn
+}
 
-    pub fn new_game_with_new_user() -> Self {
-
+/// Imports the built-in port reference table into the exam with the ID given on the command
+/// line (`terminator --import-ports EXAM_ID`) as ordinary questions.
+fn import_ports(db: &Path, exam_id: Option<&String>) {
+    let exam_id: i32 = match exam_id.and_then(|exam_id| exam_id.parse().ok()) {
+        Some(exam_id) => exam_id,
+        None => {
+            eprintln!("Usage: terminator --import-ports EXAM_ID");
+            exit(2);
+        },
+    };
+    let mut conn: Connection = open_database(db);
+    let exists: bool = conn.query_row("SELECT COUNT(*) FROM Exams WHERE ExamID = ?1", [exam_id], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .unwrap_or(false);
+    if !exists {
+        eprintln!("There is no exam with the ID {}.", exam_id);
+        exit(1);
     }
-}*/
-
+    match flashcards::import_ports(&mut conn, exam_id) {
+        Ok(added) => println!(
+            "Added {} question(s) to exam {} from the port reference table (version {}).",
+            added, exam_id, flashcards::ports::VERSION,
+        ),
+        Err(e) => {
+            eprintln!("Unable to import the port reference table: {}", e);
+            exit(1);
+        },
+    }
+}
 
 fn main() {
-    println!("{}", LOGO);
-
     // Establish the path to the database for querying whether this User exists
     let mut db: PathBuf = env::current_dir().expect("Couldn't obtain cwd.");
     db.push(ASSETS_DIR);
     db.push(DB_NAME);
 
-    if let Ok(conn) = Connection::open(&db) {
-        println!("Successfully connected to database...");
-        // TODO: Logic for playing the game
-        if let Ok(user) = User::new(&conn) {
-            println!("New User created:\t{:?}", user);
-        } else {
-            println!("Couldn't create new user...");
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).is_some_and(|arg| arg == "--import-ports") {
+        import_ports(&db, args.get(2));
+        return;
+    }
+
+    println!("{}", LOGO);
+
+    let conn: Connection = open_database(&db);
+    println!("Successfully connected to database...");
+    if let Some(user) = start_menu(&conn) {
+        let mut game: Game = Game::new(conn, user);
+        if let Err(e) = game.play() {
+            eprintln!("{}", e);
         }
-    } else {
-        eprintln!("Unable to connect to database...");
     }
 }
\ No newline at end of file
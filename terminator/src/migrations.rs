@@ -0,0 +1,450 @@
+//! Versioned schema migrations for `terminator.db`.
+//!
+//! The schema version lives in SQLite's `PRAGMA user_version`, which is `0` for a database that
+//! has never been migrated. Each entry of `MIGRATIONS` moves the schema forward by one version and
+//! is applied in its own transaction along with the new `user_version`, so a failed migration
+//! leaves the database at the last version that went through. Migrations are never edited once
+//! they've shipped; changes to the schema go in a new entry at the end of the list.
+//!
+//! Databases made before migrations were tracked may have tables in an older shape (e.g., the
+//! first `UserQuestionResponses` had no `Score`). Migration 1 rebuilds any such table with the
+//! columns it should have, keeping its rows, before creating the tables that are missing.
+//!
+//! After the latest migration, the tables are as follows:
+//!
+//! - Users (**Username**: Text, Password: Text)
+//! - Exams (**ExamID**: Int, Title: Text, Description: Text, UUID: Text)
+//! - ExamCreation (_**ExamID**_: Int, _**CreatorUsername**_: Text, DateCreated: Text)
+//! - Questions (**QuestionID**: Int, QuestionText: Text, Options: Text, CorrectAnswer: Text, Explanation: Text, *ExamID*: Int, QuestionType: Text, UUID: Text)
+//! - QuestionOptions (**OptionID**: Int, *QuestionID*: Int, Position: Int, OptionText: Text, IsCorrect: Int, UUID: Text)
+//! - MatchingPairs (**PairID**: Int, *QuestionID*: Int, Position: Int, Prompt: Text, Match: Text, UUID: Text)
+//! - OrderingQuestions (_**QuestionID**_: Int, ScoringRule: Text)
+//! - OrderingItems (**ItemID**: Int, *QuestionID*: Int, Position: Int, ItemText: Text)
+//! - Domains (**DomainID**: Int, *ExamID*: Int, Code: Text, Name: Text, Weight: Int)
+//! - Objectives (**ObjectiveID**: Int, *DomainID*: Int, Code: Text, Title: Text)
+//! - QuestionObjectives (_**QuestionID**_: Int, _**ObjectiveID**_: Int)
+//! - QuestionTags (_**QuestionID**_: Int, **Tag**: Text)
+//! - QuestionReferences (_**QuestionID**_: Int, **Position**: Int, Reference: Text)
+//! - CommandQuestions (_**QuestionID**_: Int, InitialState: Text, ExpectedState: Text)
+//! - CommandFlagRules (**RuleID**: Int, *QuestionID*: Int, Program: Text, Flag: Text, Rule: Text)
+//! - GeneratedQuestions (**GeneratedID**: Int, Generator: Text, Parameters: Text, Difficulty: Text, DateCreated: Text)
+//! - ExamAttempts (**AttemptID**: Int, *Username*: Text, *ExamID*: Int, Mode: Text, StartTime: Text, EndTime: Text, NumQuestions: Int, Score: Real, ScaledScore: Int, PassingScore: Int, Passed: Int)
+//! - UserQuestionResponses (**ResponseID**: Int, *Username*: Text, *QuestionID*: Int, *GeneratedID*: Int, *AttemptID*: Int, IsCorrect: Int, Score: Real, ResponseText: Text, Timestamp: Text)
+//! - UserResponseOptions (_**ResponseID**_: Int, _**OptionID**_: Int)
+//! - UserResponsePairs (_**ResponseID**_: Int, _**PairID**_: Int, *ChosenPairID*: Int, IsCorrect: Int)
+//! - Labs (**LabID**: Int, *ExamID*: Int, Title: Text, Description: Text, InitialState: Text)
+//! - LabTasks (**TaskID**: Int, *LabID*: Int, StepNumber: Int, Instructions: Text, GoalCheck: Text, Hint: Text)
+//! - UserLabProgress (_**Username**_: Text, _**LabID**_: Int, StepsCompleted: Int, State: Text, Completed: Int, Timestamp: Text)
+//! - DrillStreaks (**StreakID**: Int, *Username*: Text, Generator: Text, Difficulty: Text, Length: Int, Seconds: Real, Timestamp: Text)
+//! - FlashcardHistory (**HistoryID**: Int, *Username*: Text, Card: Text, Direction: Text, IsCorrect: Int, DatasetVersion: Int, Timestamp: Text)
+//! - ExamBlueprints (_**ExamID**_: Int, QuestionCount: Int, TimeLimitMinutes: Int, PassingScore: Int)
+//! - AttemptDomainScores (_**AttemptID**_: Int, _**DomainID**_: Int, Score: Real, NumQuestions: Int)
+//! - ReviewSchedule (_**Username**_: Text, _**QuestionID**_: Int, Ease: Real, IntervalDays: Int, Repetitions: Int, DueDate: Text, *LastResponseID*: Int)
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use rusqlite::{params, Connection};
+
+/// A single forward step of the schema.
+#[derive(Debug)]
+pub struct Migration {
+    /// The `user_version` the database is at once this migration has been applied.
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+    /// Changes that SQL alone can't make, which are run before `sql` in the same transaction.
+    pub upgrade: Option<fn(&Connection) -> rusqlite::Result<()>>,
+}
+
+/// Every migration, in the order they're applied. The version of each must be one more than the
+/// version of the one before it.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        sql: include_str!("../migrations/001_initial_schema.sql"),
+        upgrade: Some(upgrade_untracked),
+    },
+    Migration {
+        version: 2,
+        description: "exam and question UUIDs, question tags and references",
+        sql: include_str!("../migrations/002_question_packs.sql"),
+        upgrade: None,
+    },
+    Migration {
+        version: 3,
+        description: "option and pair UUIDs",
+        sql: include_str!("../migrations/003_option_ids.sql"),
+        upgrade: None,
+    },
+];
+
+/// The schema version this binary was built for.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The database was migrated by a newer version of terminator, so this one can't safely use
+    /// it.
+    TooNew { found: u32, supported: u32 },
+    /// A migration failed and was rolled back.
+    Failed { version: u32, source: rusqlite::Error },
+    Sql(rusqlite::Error),
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::TooNew { found, supported } => write!(
+                f,
+                "MigrationError - the database is at schema version {}, but this version of terminator \
+                only supports up to version {}. Please update terminator.",
+                found, supported,
+            ),
+            MigrationError::Failed { version, source } => write!(
+                f,
+                "MigrationError - migration {} failed and was rolled back: {}", version, source,
+            ),
+            MigrationError::Sql(e) => write!(f, "MigrationError - {}", e),
+        }
+    }
+}
+
+impl Error for MigrationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MigrationError::TooNew { .. } => None,
+            MigrationError::Failed { source, .. } => Some(source),
+            MigrationError::Sql(e) => Some(e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sql(e)
+    }
+}
+
+/// Reads the schema version of the database from `PRAGMA user_version`.
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Returns the migrations that haven't been applied to the database yet, or a
+/// `MigrationError::TooNew` if the database is newer than this binary.
+pub fn pending(conn: &Connection) -> Result<Vec<&'static Migration>, MigrationError> {
+    pending_in(conn, MIGRATIONS)
+}
+
+fn pending_in<'a>(conn: &Connection, migrations: &'a [Migration]) -> Result<Vec<&'a Migration>, MigrationError> {
+    let found: u32 = schema_version(conn)?;
+    let supported: u32 = migrations.last().map_or(0, |migration| migration.version);
+    if found > supported {
+        return Err(MigrationError::TooNew { found, supported });
+    }
+    Ok(migrations.iter().filter(|migration| migration.version > found).collect())
+}
+
+/// Brings the database up to `SCHEMA_VERSION`, applying each pending migration in its own
+/// transaction. Returns the migrations that were applied, which is empty if the database was
+/// already up to date.
+pub fn migrate(conn: &mut Connection) -> Result<Vec<&'static Migration>, MigrationError> {
+    migrate_with(conn, MIGRATIONS)
+}
+
+fn migrate_with<'a>(conn: &mut Connection, migrations: &'a [Migration]) -> Result<Vec<&'a Migration>, MigrationError> {
+    let pending: Vec<&'a Migration> = pending_in(conn, migrations)?;
+    // Rebuilding a table drops the old one, which mustn't take the rows that refer to it along.
+    // This has no effect inside a transaction, so it's turned off around them.
+    let foreign_keys: bool = conn.pragma_query_value(None, "foreign_keys", |row| row.get(0))?;
+    conn.pragma_update(None, "foreign_keys", false)?;
+    let applied = apply(conn, &pending);
+    conn.pragma_update(None, "foreign_keys", foreign_keys)?;
+    applied?;
+    Ok(pending)
+}
+
+fn apply(conn: &mut Connection, pending: &[&Migration]) -> Result<(), MigrationError> {
+    for migration in pending {
+        let failed = |source: rusqlite::Error| MigrationError::Failed { version: migration.version, source };
+        let tx = conn.transaction().map_err(failed)?;
+        if let Some(upgrade) = migration.upgrade {
+            upgrade(&tx).map_err(failed)?;
+        }
+        tx.execute_batch(migration.sql).map_err(failed)?;
+        // PRAGMA doesn't take parameters, but the version is a number we wrote ourselves
+        tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version)).map_err(failed)?;
+        tx.commit().map_err(failed)?;
+    }
+    Ok(())
+}
+
+/// A column of a table, as described by `PRAGMA table_info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Column {
+    name: String,
+    kind: String,
+    not_null: bool,
+    default: Option<String>,
+    primary_key: bool,
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if self.not_null {
+            write!(f, " NOT NULL")?;
+        }
+        if let Some(default) = &self.default {
+            write!(f, " DEFAULT {}", default)?;
+        }
+        if self.primary_key {
+            write!(f, " PRIMARY KEY")?;
+        }
+        Ok(())
+    }
+}
+
+/// The columns of `table`, which is empty if there's no such table.
+fn columns(conn: &Connection, table: &str) -> rusqlite::Result<Vec<Column>> {
+    let mut stmt = conn.prepare("SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1)")?;
+    let columns = stmt.query_map([table], |row| {
+        Ok(Column {
+            name: row.get(0)?,
+            kind: row.get(1)?,
+            not_null: row.get(2)?,
+            default: row.get(3)?,
+            primary_key: row.get::<_, i64>(4)? > 0,
+        })
+    })?;
+    columns.collect()
+}
+
+/// The names of the schema's objects of the given `kind` (`table`, `index` or `trigger`) along
+/// with the SQL that made them, leaving out those SQLite makes itself.
+fn objects(conn: &Connection, kind: &str) -> rusqlite::Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, sql FROM sqlite_master WHERE type = ?1 AND name NOT LIKE 'sqlite_%' ORDER BY name"
+    )?;
+    let objects = stmt.query_map(params![kind], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    objects.collect()
+}
+
+/// Rebuilds each table made by a version of create_database from before migrations were tracked
+/// whose columns differ from those migration 1 gives it. The new table is made from migration 1's
+/// own SQL and the rows are copied across, column by column. Columns were only ever added in
+/// those versions, so every old column has somewhere to go.
+fn upgrade_untracked(conn: &Connection) -> rusqlite::Result<()> {
+    let expected: Connection = Connection::open_in_memory()?;
+    expected.execute_batch(MIGRATIONS[0].sql)?;
+    for (table, sql) in objects(&expected, "table")? {
+        let found: Vec<Column> = columns(conn, &table)?;
+        let wanted: Vec<Column> = columns(&expected, &table)?;
+        if found.is_empty() || found == wanted {
+            continue;
+        }
+        let rebuilt: String = format!("{}_new", table);
+        conn.execute_batch(&sql.replacen(&format!("CREATE TABLE {} (", table), &format!("CREATE TABLE {} (", rebuilt), 1))?;
+        let common: Vec<String> = found.iter()
+            .filter(|column| wanted.iter().any(|other| other.name == column.name))
+            .map(|column| format!("\"{}\"", column.name))
+            .collect();
+        // Table and column names can't be bound as parameters, but these come from the schema itself
+        conn.execute_batch(&format!(
+            "INSERT INTO \"{rebuilt}\" ({columns}) SELECT {columns} FROM \"{table}\";\n\
+            DROP TABLE \"{table}\";\n\
+            ALTER TABLE \"{rebuilt}\" RENAME TO \"{table}\";",
+            rebuilt = rebuilt, table = table, columns = common.join(", "),
+        ))?;
+        // Responses from before partial credit were either right or wrong
+        if table == "UserQuestionResponses" && !found.iter().any(|column| column.name == "Score") {
+            conn.execute("UPDATE UserQuestionResponses SET Score = CASE WHEN IsCorrect THEN 1 ELSE 0 END", [])?;
+        }
+    }
+    Ok(())
+}
+
+/// Compares the database's tables, columns, indexes and triggers with those of a new database at
+/// `SCHEMA_VERSION`, and describes each difference. Returns nothing if the schema is as expected.
+pub fn schema_differences(conn: &Connection) -> Result<Vec<String>, MigrationError> {
+    let mut expected: Connection = Connection::open_in_memory()?;
+    migrate(&mut expected)?;
+    let mut differences: Vec<String> = Vec::new();
+    for (table, _) in objects(&expected, "table")? {
+        let found: Vec<Column> = columns(conn, &table)?;
+        if found.is_empty() {
+            differences.push(format!("table {} is missing", table));
+            continue;
+        }
+        let wanted: Vec<Column> = columns(&expected, &table)?;
+        for column in &wanted {
+            match found.iter().find(|other| other.name == column.name) {
+                None => differences.push(format!("column {}.{} is missing", table, column.name)),
+                Some(other) if other != column => differences.push(format!(
+                    "column {}.{} is {} but should be {}", table, column.name, other, column,
+                )),
+                Some(_) => {},
+            }
+        }
+        for column in found.iter().filter(|column| !wanted.iter().any(|other| other.name == column.name)) {
+            differences.push(format!("column {}.{} isn't part of the schema", table, column.name));
+        }
+    }
+    for kind in ["index", "trigger"] {
+        let found: Vec<(String, String)> = objects(conn, kind)?;
+        for (name, _) in objects(&expected, kind)? {
+            if !found.iter().any(|(other, _)| *other == name) {
+                differences.push(format!("{} {} is missing", kind, name));
+            }
+        }
+    }
+    Ok(differences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The tables as the first version of create_database made them.
+    const UNTRACKED_SCHEMA: &str = "
+        CREATE TABLE Users (Username TEXT NOT NULL UNIQUE, Password TEXT NOT NULL);
+        CREATE TABLE Exams (ExamID INTEGER PRIMARY KEY AUTOINCREMENT, Title TEXT NOT NULL, Description TEXT);
+        CREATE TABLE ExamCreation (
+            ExamID INTEGER NOT NULL, CreatorUsername TEXT NOT NULL, DateCreated TEXT NOT NULL,
+            PRIMARY KEY (ExamID, CreatorUsername),
+            FOREIGN KEY (ExamID) REFERENCES Exams(ExamID),
+            FOREIGN KEY (CreatorUsername) REFERENCES Users(Username)
+        );
+        CREATE TABLE Questions (
+            QuestionID INTEGER PRIMARY KEY AUTOINCREMENT, ExamID INTEGER NOT NULL, QuestionText TEXT NOT NULL,
+            Options TEXT NOT NULL, CorrectAnswer TEXT NOT NULL, Explanation TEXT NOT NULL,
+            FOREIGN KEY (ExamID) REFERENCES Exams(ExamID)
+        );
+        CREATE TABLE UserQuestionResponses (
+            ResponseID INTEGER PRIMARY KEY AUTOINCREMENT, Username TEXT NOT NULL, QuestionID INTEGER NOT NULL,
+            IsCorrect INTEGER DEFAULT 0, Timestamp TEXT,
+            FOREIGN KEY (Username) REFERENCES Users (Username),
+            FOREIGN KEY (QuestionID) REFERENCES Questions (QuestionID)
+        );
+        INSERT INTO Users VALUES ('jake', 'hash');
+        INSERT INTO Exams (Title, Description) VALUES ('EXAM1', 'Test Exam...');
+        INSERT INTO Questions (ExamID, QuestionText, Options, CorrectAnswer, Explanation)
+            VALUES (1, 'Test question prompt:', 'A.) 1\nB.) 2', 'B.) 2', 'Because.');
+        INSERT INTO UserQuestionResponses (Username, QuestionID, IsCorrect, Timestamp) VALUES
+            ('jake', 1, 1, '2023-11-03T00:00:00+00:00'),
+            ('jake', 1, 0, '2023-11-04T00:00:00+00:00');
+    ";
+
+    /// Two migrations, the second of which fails part way through.
+    const FAILING: &[Migration] = &[
+        Migration { version: 1, description: "first", sql: "CREATE TABLE First (A INTEGER);", upgrade: None },
+        Migration {
+            version: 2,
+            description: "second",
+            sql: "CREATE TABLE Second (B INTEGER); INSERT INTO Missing VALUES (1);",
+            upgrade: None,
+        },
+    ];
+
+    fn table_exists(conn: &Connection, table: &str) -> bool {
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1", [table], |row| row.get(0))
+            .map(|count: i64| count > 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn migrates_a_new_database_to_the_latest_schema() {
+        let mut conn: Connection = Connection::open_in_memory().unwrap();
+        let applied = migrate(&mut conn).unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert!(schema_differences(&conn).unwrap().is_empty());
+        assert!(migrate(&mut conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn refuses_a_database_from_a_newer_version() {
+        let mut conn: Connection = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION + 1)).unwrap();
+        match migrate(&mut conn) {
+            Err(MigrationError::TooNew { found, supported }) => {
+                assert_eq!(found, SCHEMA_VERSION + 1);
+                assert_eq!(supported, SCHEMA_VERSION);
+            },
+            other => panic!("expected TooNew, got {:?}", other),
+        }
+        assert!(!table_exists(&conn, "Users"));
+    }
+
+    #[test]
+    fn rolls_back_a_failed_migration() {
+        let mut conn: Connection = Connection::open_in_memory().unwrap();
+        match migrate_with(&mut conn, FAILING) {
+            Err(MigrationError::Failed { version, .. }) => assert_eq!(version, 2),
+            other => panic!("expected Failed, got {:?}", other),
+        }
+        // The first migration went through, and none of the second did
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        assert!(table_exists(&conn, "First"));
+        assert!(!table_exists(&conn, "Second"));
+    }
+
+    #[test]
+    fn restores_foreign_keys_after_migrating() {
+        let mut conn: Connection = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", true).unwrap();
+        assert!(migrate_with(&mut conn, FAILING).is_err());
+        let foreign_keys: bool = conn.pragma_query_value(None, "foreign_keys", |row| row.get(0)).unwrap();
+        assert!(foreign_keys);
+    }
+
+    #[test]
+    fn upgrades_tables_from_before_migrations_were_tracked() {
+        let mut conn: Connection = Connection::open_in_memory().unwrap();
+        conn.execute_batch(UNTRACKED_SCHEMA).unwrap();
+        migrate(&mut conn).unwrap();
+        assert!(schema_differences(&conn).unwrap().is_empty());
+
+        let question_type: String = conn.query_row("SELECT QuestionType FROM Questions", [], |row| row.get(0)).unwrap();
+        assert_eq!(question_type, "MultipleChoice");
+        let mut stmt = conn.prepare(
+            "SELECT QuestionID, IsCorrect, Score, Timestamp FROM UserQuestionResponses ORDER BY ResponseID"
+        ).unwrap();
+        let responses: Vec<(i64, bool, f64, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(responses, vec![
+            (1, true, 1.0, String::from("2023-11-03T00:00:00+00:00")),
+            (1, false, 0.0, String::from("2023-11-04T00:00:00+00:00")),
+        ]);
+        // New responses can be recorded for generated questions, which have no QuestionID
+        conn.execute_batch(
+            "INSERT INTO GeneratedQuestions (Generator, Parameters) VALUES ('subnet', 'kind=hosts;prefix=24');
+            INSERT INTO UserQuestionResponses (Username, GeneratedID, IsCorrect, Score) VALUES ('jake', 1, 1, 1);"
+        ).unwrap();
+        let violations: i64 = conn.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0)).unwrap();
+        assert_eq!(violations, 0);
+    }
+
+    #[test]
+    fn finds_columns_that_differ_from_the_schema() {
+        let mut conn: Connection = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        conn.execute_batch(
+            "ALTER TABLE UserQuestionResponses DROP COLUMN ResponseText;
+            ALTER TABLE Users ADD COLUMN Email TEXT;
+            DROP TRIGGER QuestionsDefaultUUID;
+            DROP TABLE QuestionTags;"
+        ).unwrap();
+        let differences: Vec<String> = schema_differences(&conn).unwrap();
+        assert_eq!(differences, vec![
+            "table QuestionTags is missing",
+            "column UserQuestionResponses.ResponseText is missing",
+            "column Users.Email isn't part of the schema",
+            "trigger QuestionsDefaultUUID is missing",
+        ]);
+    }
+}
@@ -0,0 +1,688 @@
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use rand::rngs::ThreadRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::attempt;
+use crate::catalog::Domain;
+use crate::game::{
+    chosen_option, count_word, credit, grade_command, option_label, order_text, parse_order, parse_pairings,
+    parse_selections, selection_credit,
+    AnswerOption, Exam, MatchingPair, PairAnswer, Question, Selections, Simulator,
+};
+use crate::term_io::read_line;
+use terminator::question::QuestionType;
+
+/// The lowest and highest scores on CompTIA's scaled scoring.
+const MIN_SCALED: u32 = 100;
+const MAX_SCALED: u32 = 900;
+
+/// A single row from the `ExamBlueprints` table: how a mock exam is put together.
+#[derive(Debug, Clone, Copy)]
+pub struct Blueprint {
+    pub question_count: usize,
+    pub time_limit_minutes: u32,
+    /// The score needed to pass, from 100 to 900.
+    pub passing_score: u32,
+}
+
+impl Default for Blueprint {
+    /// The limits of most CompTIA exams: up to 90 questions in 90 minutes. The passing score
+    /// differs by exam; 720 is Network+'s.
+    fn default() -> Self {
+        Blueprint { question_count: 90, time_limit_minutes: 90, passing_score: 720 }
+    }
+}
+
+impl Blueprint {
+    /// Queries the database for the blueprint of the exam with the given `exam_id`, or the
+    /// default blueprint if it doesn't have one.
+    pub fn get_for_exam(conn: &Connection, exam_id: i32) -> rusqlite::Result<Blueprint> {
+        let blueprint: Option<Blueprint> = conn.query_row(
+            "SELECT QuestionCount, TimeLimitMinutes, PassingScore FROM ExamBlueprints WHERE ExamID = ?1",
+            [exam_id],
+            |row| Ok(Blueprint { question_count: row.get(0)?, time_limit_minutes: row.get(1)?, passing_score: row.get(2)? }),
+        ).optional()?;
+        Ok(blueprint.unwrap_or_default())
+    }
+}
+
+/// What the user has answered for one item so far. Nothing is graded until the exam is submitted.
+#[derive(Debug, Clone)]
+enum Answer {
+    /// The index of the chosen option in `Question::options`.
+    Choice(usize),
+    /// The indices of the chosen options, in the order they were shown.
+    Choices(Vec<usize>),
+    /// Each prompt's index paired with the index of the match it was given, as shown.
+    Pairings(Vec<(usize, usize)>),
+    /// The indices of `Question::items` in the order the user put them.
+    Order(Vec<usize>),
+    /// A command typed for a `Command`, `Cmd` or `PowerShell` question.
+    Line(String),
+    /// The simulator was used; its state is kept in the item's `Simulator`.
+    Simulated,
+}
+
+/// One question of a mock exam, as the user sees it.
+#[derive(Debug)]
+struct Item<'a> {
+    question: &'a Question,
+    /// The order the options, matches or ordering items are shown in, which stays the same when
+    /// the user comes back to the question.
+    shown: Vec<usize>,
+    /// The simulator of a `Shell` or `Ios` question, kept between visits so that the user can come
+    /// back to it.
+    sim: Option<Simulator>,
+    /// Every command entered in the simulator, across visits.
+    commands: Vec<String>,
+    answer: Option<Answer>,
+    flagged: bool,
+}
+
+impl<'a> Item<'a> {
+    /// Sets up the `question` as an exam item, or returns why it can't be asked.
+    fn new(question: &'a Question, rng: &mut ThreadRng) -> Result<Item<'a>, String> {
+        let count: usize = match question.question_type {
+            QuestionType::MultipleChoice if question.options.is_empty() => {
+                return Err(String::from("it doesn't have any options yet"));
+            },
+            QuestionType::MultipleResponse if question.options.iter().all(|option| !option.is_correct) => {
+                return Err(String::from("it doesn't have any correct options yet"));
+            },
+            QuestionType::MultipleChoice | QuestionType::MultipleResponse => question.options.len(),
+            QuestionType::Matching if question.pairs.iter().all(|pair| pair.prompt.is_none()) => {
+                return Err(String::from("it doesn't have any pairs yet"));
+            },
+            QuestionType::Matching => question.pairs.len(),
+            QuestionType::Ordering if question.items.len() < 2 => {
+                return Err(String::from("it doesn't have enough items yet"));
+            },
+            QuestionType::Ordering => question.items.len(),
+            _ => 0,
+        };
+        let mut shown: Vec<usize> = (0..count).collect();
+        shown.shuffle(rng);
+
+        let sim: Option<Simulator> = match question.question_type {
+            QuestionType::Shell | QuestionType::Ios => Some(Simulator::new(question)?),
+            _ => None,
+        };
+        Ok(Item { question, shown, sim, commands: Vec::new(), answer: None, flagged: false })
+    }
+
+    fn options(&self) -> Vec<&'a AnswerOption> {
+        self.shown.iter().map(|i| &self.question.options[*i]).collect()
+    }
+
+    fn prompts(&self) -> Vec<&'a MatchingPair> {
+        self.question.pairs.iter().filter(|pair| pair.prompt.is_some()).collect()
+    }
+
+    fn matches(&self) -> Vec<&'a MatchingPair> {
+        self.shown.iter().map(|i| &self.question.pairs[*i]).collect()
+    }
+
+    /// Prints the question along with its options, matches or items, and the current answer.
+    fn show(&self) {
+        println!("{}", self.question.question_text);
+        match self.question.question_type {
+            QuestionType::MultipleChoice | QuestionType::MultipleResponse => {
+                if self.question.question_type == QuestionType::MultipleResponse
+                    && !self.question.question_text.to_ascii_lowercase().contains("select") {
+                    let num_correct: usize = self.question.options.iter().filter(|option| option.is_correct).count();
+                    println!("(Select {})", count_word(num_correct));
+                }
+                for (i, option) in self.options().iter().enumerate() {
+                    println!("  {}.) {}", option_label(i), option.text);
+                }
+            },
+            QuestionType::Matching => {
+                let prompts: Vec<&MatchingPair> = self.prompts();
+                let matches: Vec<&MatchingPair> = self.matches();
+                for (i, pair) in prompts.iter().enumerate() {
+                    println!("  {:>2}. {}", i + 1, pair.prompt.as_deref().unwrap_or(""));
+                }
+                for (i, pair) in matches.iter().enumerate() {
+                    println!("   {}. {}", option_label(i).to_lowercase(), pair.match_text);
+                }
+            },
+            QuestionType::Ordering => {
+                for (i, item) in self.shown.iter().enumerate() {
+                    println!("  {}. {}", option_label(i).to_lowercase(), self.question.items[*item]);
+                }
+            },
+            QuestionType::Shell | QuestionType::Ios => {
+                println!("(Enter 'open' to work in the simulator; your changes are kept if you come back.)");
+            },
+            QuestionType::Command | QuestionType::Cmd | QuestionType::PowerShell => {
+                println!("(Enter the command as your answer.)");
+            },
+        }
+        if let Some(answer) = self.describe() {
+            println!("Your answer: {}", answer);
+        }
+    }
+
+    /// The current answer, written the way the user would enter it.
+    fn describe(&self) -> Option<String> {
+        let letters = |indices: &[usize], lower: bool| -> String {
+            let labels: Vec<String> = indices.iter()
+                .map(|i| if lower { option_label(*i).to_lowercase() } else { option_label(*i) })
+                .collect();
+            labels.join(" ")
+        };
+        Some(match self.answer.as_ref()? {
+            Answer::Choice(option) => {
+                option_label(self.shown.iter().position(|i| i == option).expect("The chosen option is shown"))
+            },
+            Answer::Choices(selected) => letters(selected, false),
+            Answer::Pairings(pairings) => {
+                let pairings: Vec<String> = pairings.iter()
+                    .map(|(prompt, answer)| format!("{}-{}", prompt + 1, option_label(*answer).to_lowercase()))
+                    .collect();
+                pairings.join(" ")
+            },
+            Answer::Order(order) => {
+                let shown: Vec<usize> = order.iter()
+                    .map(|item| self.shown.iter().position(|i| i == item).expect("Every item is shown"))
+                    .collect();
+                letters(&shown, true)
+            },
+            Answer::Line(line) => line.clone(),
+            Answer::Simulated => String::from("(worked in the simulator)"),
+        })
+    }
+
+    /// Reads the `entry` as an answer to this item, or returns why it isn't one. Entering `open`
+    /// on a simulator question runs the simulator.
+    fn answer(&mut self, entry: &str) -> Result<(), String> {
+        self.answer = Some(match self.question.question_type {
+            QuestionType::MultipleChoice => {
+                let option: &AnswerOption = chosen_option(&self.options(), entry)
+                    .ok_or_else(|| String::from("Please enter the letter of one of the options."))?;
+                let index: usize = self.question.options.iter()
+                    .position(|other| other.option_id == option.option_id)
+                    .expect("The chosen option belongs to the question");
+                Answer::Choice(index)
+            },
+            QuestionType::MultipleResponse => Answer::Choices(parse_selections(&self.options(), entry)?),
+            QuestionType::Matching => {
+                Answer::Pairings(parse_pairings(entry, self.prompts().len(), self.question.pairs.len())?)
+            },
+            QuestionType::Ordering => {
+                let order: Vec<usize> = parse_order(entry, self.shown.len())?;
+                Answer::Order(order.into_iter().map(|i| self.shown[i]).collect())
+            },
+            QuestionType::Command | QuestionType::Cmd | QuestionType::PowerShell => Answer::Line(String::from(entry)),
+            QuestionType::Shell | QuestionType::Ios => {
+                if !entry.eq_ignore_ascii_case("open") {
+                    return Err(String::from("Enter 'open' to work in the simulator."));
+                }
+                if let Some(sim) = self.sim.as_mut() {
+                    self.commands.extend(sim.interact());
+                }
+                Answer::Simulated
+            },
+        });
+        Ok(())
+    }
+
+    /// Grades the item's answer. Returns the credit and the options or pairs chosen, or `None` if
+    /// it wasn't answered.
+    fn grade(&self) -> Option<(f64, Selections)> {
+        let question: &Question = self.question;
+        let mut selections: Selections = Selections::default();
+        let score: f64 = match self.answer.as_ref()? {
+            Answer::Choice(option) => {
+                selections.options.push(question.options[*option].option_id);
+                credit(question.options[*option].is_correct)
+            },
+            Answer::Choices(selected) => {
+                let shown: Vec<&AnswerOption> = self.options();
+                selections.options = selected.iter().map(|i| shown[*i].option_id).collect();
+                selection_credit(&shown, selected)
+            },
+            Answer::Pairings(pairings) => {
+                let prompts: Vec<&MatchingPair> = self.prompts();
+                let matches: Vec<&MatchingPair> = self.matches();
+                for (i, pair) in prompts.iter().enumerate() {
+                    let chosen: Option<&MatchingPair> = pairings.iter()
+                        .find(|(prompt, _)| *prompt == i)
+                        .map(|(_, answer)| matches[*answer]);
+                    selections.pairs.push(PairAnswer {
+                        pair_id: pair.pair_id,
+                        chosen_pair_id: chosen.map(|chosen| chosen.pair_id),
+                        is_correct: chosen.is_some_and(|chosen| pair.is_matched_by(chosen)),
+                    });
+                }
+                let num_correct: usize = selections.pairs.iter().filter(|pair| pair.is_correct).count();
+                num_correct as f64 / prompts.len() as f64
+            },
+            Answer::Order(order) => {
+                selections.text = Some(order_text(&question.items, order));
+                question.scoring_rule.score(order)
+            },
+            Answer::Line(line) => {
+                selections.text = Some(line.clone());
+                credit(grade_command(question, line).is_ok())
+            },
+            Answer::Simulated => {
+                if !self.commands.is_empty() {
+                    selections.text = Some(self.commands.join("; "));
+                }
+                credit(self.sim.as_ref()?.problems().is_empty())
+            },
+        };
+        Some((score, selections))
+    }
+}
+
+/// The index of the domain that the `question` counts toward: that of its first objective that's
+/// in the catalog.
+fn domain_index(question: &Question, domains: &[Domain]) -> Option<usize> {
+    question.objectives.iter().find_map(|code| domains.iter().position(|domain| domain.contains(code)))
+}
+
+/// Picks `count` of the `eligible` questions of the `exam` so that each domain gets its share by
+/// weight. Shares are rounded down, with the leftover questions going to the domains with the
+/// largest remainders. When a domain doesn't have enough questions (or the exam has no catalog),
+/// the rest are filled from any other question. Returns the indices in a random order.
+fn build(exam: &Exam, domains: &[Domain], eligible: &[usize], count: usize, rng: &mut impl Rng) -> Vec<usize> {
+    let count: usize = count.min(eligible.len());
+    let mut pools: Vec<Vec<usize>> = vec![Vec::new(); domains.len()];
+    for index in eligible {
+        if let Some(domain) = domain_index(&exam.questions[*index], domains) {
+            pools[domain].push(*index);
+        }
+    }
+
+    let total_weight: u32 = domains.iter().map(|domain| domain.weight).sum();
+    let mut quotas: Vec<usize> = vec![0; domains.len()];
+    if total_weight > 0 {
+        let exact: Vec<f64> = domains.iter()
+            .map(|domain| count as f64 * domain.weight as f64 / total_weight as f64)
+            .collect();
+        for (i, share) in exact.iter().enumerate() {
+            quotas[i] = (share.floor() as usize).min(pools[i].len());
+        }
+        let mut by_remainder: Vec<usize> = (0..domains.len()).collect();
+        by_remainder.sort_by(|a, b| exact[*b].fract().total_cmp(&exact[*a].fract()));
+        for i in by_remainder {
+            if quotas.iter().sum::<usize>() >= count {
+                break;
+            }
+            if quotas[i] < pools[i].len() {
+                quotas[i] += 1;
+            }
+        }
+    }
+
+    let mut picked: Vec<usize> = Vec::new();
+    for (pool, quota) in pools.iter_mut().zip(&quotas) {
+        pool.shuffle(rng);
+        picked.extend(&pool[..*quota]);
+    }
+    let mut rest: Vec<usize> = eligible.iter().copied().filter(|index| !picked.contains(index)).collect();
+    rest.shuffle(rng);
+    picked.extend(rest.into_iter().take(count - picked.len()));
+    picked.shuffle(rng);
+    picked
+}
+
+/// How the user did on one domain of a submitted mock exam.
+#[derive(Debug)]
+pub struct DomainResult {
+    /// `None` for the questions that aren't in any of the exam's domains.
+    pub domain_id: Option<i32>,
+    pub label: String,
+    pub score: f64,
+    pub num_questions: usize,
+}
+
+/// A submitted mock exam: what was asked, the graded answers and the scores.
+#[derive(Debug)]
+pub struct MockResult {
+    pub start: DateTime<Utc>,
+    pub blueprint: Blueprint,
+    /// The index of each question asked, with its credit and selections if it was answered.
+    pub graded: Vec<(usize, Option<(f64, Selections)>)>,
+    pub domains: Vec<DomainResult>,
+    pub raw_score: f64,
+    pub scaled_score: u32,
+    pub passed: bool,
+}
+
+impl MockResult {
+    /// Finishes the `ExamAttempts` row with the given `attempt_id` with this result, and adds an
+    /// `AttemptDomainScores` row for each of the exam's domains that had questions.
+    pub fn save(&self, conn: &Connection, attempt_id: i64) -> rusqlite::Result<()> {
+        attempt::finish(
+            conn,
+            attempt_id,
+            self.graded.len(),
+            self.raw_score,
+            Some((self.scaled_score, self.blueprint.passing_score)),
+        )?;
+        for domain in &self.domains {
+            if let Some(domain_id) = domain.domain_id {
+                conn.execute(
+                    "INSERT INTO AttemptDomainScores (AttemptID, DomainID, Score, NumQuestions) VALUES (?1, ?2, ?3, ?4)",
+                    params![attempt_id, domain_id, domain.score, domain.num_questions],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the scaled score, whether it passes, the breakdown by domain and every question
+    /// that didn't earn full credit along with its explanation.
+    pub fn report(&self, exam: &Exam) {
+        println!("\n=== Results ===");
+        println!(
+            "Your score: {} (passing is {}) - {}",
+            self.scaled_score,
+            self.blueprint.passing_score,
+            if self.passed { "PASS" } else { "FAIL" },
+        );
+        println!("You earned {:.2} of {} points.", self.raw_score, self.graded.len());
+        println!("\nBy domain:");
+        for domain in &self.domains {
+            println!(
+                "  {} - {:.2} of {} ({:.0}%)",
+                domain.label,
+                domain.score,
+                domain.num_questions,
+                domain.score / domain.num_questions as f64 * 100.0,
+            );
+        }
+
+        let missed: Vec<(usize, &usize, Option<f64>)> = self.graded.iter()
+            .enumerate()
+            .map(|(i, (index, graded))| (i, index, graded.as_ref().map(|(score, _)| *score)))
+            .filter(|(_, _, score)| score.is_none_or(|score| score < 1.0))
+            .collect();
+        if !missed.is_empty() {
+            println!("\nQuestions to go over:");
+        }
+        for (i, index, score) in missed {
+            let question: &Question = &exam.questions[*index];
+            let verdict: String = match score {
+                None => String::from("not answered"),
+                Some(score) if score > 0.0 => format!("partly correct ({:.0}% credit)", score * 100.0),
+                Some(_) => String::from("incorrect"),
+            };
+            println!("  {}. {} - {}", i + 1, question.question_text, verdict);
+            println!("     {}", question.explanation);
+        }
+    }
+}
+
+/// Formats a duration as minutes and seconds, e.g., `12:05`.
+fn clock(duration: Duration) -> String {
+    format!("{}:{:02}", duration.as_secs() / 60, duration.as_secs() % 60)
+}
+
+/// Shows every item with whether it's been answered or flagged, so the user can see what's left
+/// before submitting.
+fn review_screen(items: &[Item]) {
+    println!("\n=== Review ===");
+    for (i, item) in items.iter().enumerate() {
+        let status: &str = if item.answer.is_some() { "answered  " } else { "unanswered" };
+        let flag: &str = if item.flagged { " [flagged]" } else { "" };
+        let text: String = if item.question.question_text.chars().count() > 50 {
+            format!("{}...", item.question.question_text.chars().take(47).collect::<String>())
+        } else {
+            item.question.question_text.clone()
+        };
+        println!("  {:>2}. {}{} {}", i + 1, status, flag, text);
+    }
+    let unanswered: usize = items.iter().filter(|item| item.answer.is_none()).count();
+    let flagged: usize = items.iter().filter(|item| item.flagged).count();
+    println!("{} unanswered, {} flagged.", unanswered, flagged);
+}
+
+/// Sits a mock exam built from the `exam`'s questions using the `blueprint`. The user can move
+/// between questions, flag them and change their answers until they submit or the time runs
+/// out; since input can't be interrupted, the time is checked after each entry, and an entry
+/// made after the time is up is discarded. Returns the graded result, or `None` if the user
+/// quit before starting or abandoned the exam.
+pub fn sit(exam: &Exam, domains: &[Domain], blueprint: &Blueprint) -> Option<MockResult> {
+    let mut rng: ThreadRng = thread_rng();
+    let mut eligible: Vec<usize> = Vec::new();
+    for (i, question) in exam.questions.iter().enumerate() {
+        match Item::new(question, &mut rng) {
+            Ok(_) => eligible.push(i),
+            Err(e) => println!("Leaving out question {}: {}.", question.question_id, e),
+        }
+    }
+    if eligible.is_empty() {
+        println!("{} doesn't have any questions that can be asked yet...", exam.exam_title);
+        return None;
+    }
+    let picked: Vec<usize> = build(exam, domains, &eligible, blueprint.question_count, &mut rng);
+    let mut items: Vec<Item> = picked.iter()
+        .map(|index| Item::new(&exam.questions[*index], &mut rng).expect("Eligible questions can be set up"))
+        .collect();
+
+    let limit: Duration = Duration::from_secs(blueprint.time_limit_minutes as u64 * 60);
+    println!("\n=== {} - Mock exam ===", exam.exam_title);
+    println!(
+        "{} questions, {} minutes. Passing is {} on a scale of {}-{}.",
+        items.len(), blueprint.time_limit_minutes, blueprint.passing_score, MIN_SCALED, MAX_SCALED,
+    );
+    println!("Answers aren't graded until you submit. At any question you can enter:");
+    println!("  (blank) next question   back   flag   goto N   review   submit   quit");
+    if !read_line("Start the exam? (y/n): ").eq_ignore_ascii_case("y") {
+        return None;
+    }
+
+    let start: DateTime<Utc> = Utc::now();
+    let timer: Instant = Instant::now();
+    let mut current: usize = 0;
+    loop {
+        let item: &mut Item = &mut items[current];
+        let remaining: Duration = limit.saturating_sub(timer.elapsed());
+        println!(
+            "\nQuestion {} of {}{}   (time left {})",
+            current + 1, picked.len(), if item.flagged { " [flagged]" } else { "" }, clock(remaining),
+        );
+        item.show();
+        let entry: String = read_line("> ");
+        if timer.elapsed() >= limit {
+            println!("Time's up! Your exam is being submitted; that last entry wasn't saved.");
+            break;
+        }
+
+        let command: Vec<&str> = entry.split_whitespace().collect();
+        match command.as_slice() {
+            [] => current = (current + 1) % items.len(),
+            ["back"] => current = current.checked_sub(1).unwrap_or(items.len() - 1),
+            ["flag"] => {
+                item.flagged = !item.flagged;
+                println!("{}", if item.flagged { "Flagged for review." } else { "Flag removed." });
+            },
+            ["goto", number] => match number.parse::<usize>() {
+                Ok(number) if number >= 1 && number <= items.len() => current = number - 1,
+                _ => println!("There's no question {}.", number),
+            },
+            ["review"] => {
+                review_screen(&items);
+                let choice: String = read_line("Enter a question number to go to it, or press Enter to continue: ");
+                match choice.parse::<usize>() {
+                    Ok(number) if number >= 1 && number <= items.len() => current = number - 1,
+                    _ if choice.is_empty() => {},
+                    _ => println!("There's no question {}.", choice),
+                }
+            },
+            ["submit"] => {
+                review_screen(&items);
+                if read_line("Submit your exam for grading? (y/n): ").eq_ignore_ascii_case("y") {
+                    break;
+                }
+            },
+            ["quit"] => {
+                if read_line("Abandon this exam? Nothing will be saved. (y/n): ").eq_ignore_ascii_case("y") {
+                    return None;
+                }
+            },
+            _ => match item.answer(&entry) {
+                Ok(()) => current = (current + 1) % items.len(),
+                Err(e) => println!("{}", e),
+            },
+        }
+    }
+
+    let graded: Vec<(usize, Option<(f64, Selections)>)> = picked.iter()
+        .zip(&items)
+        .map(|(index, item)| (*index, item.grade()))
+        .collect();
+    let mut results: Vec<DomainResult> = Vec::new();
+    let mut other: DomainResult = DomainResult {
+        domain_id: None, label: String::from("Not in a domain"), score: 0.0, num_questions: 0,
+    };
+    for (i, domain) in domains.iter().enumerate() {
+        let in_domain: Vec<f64> = graded.iter()
+            .filter(|(index, _)| domain_index(&exam.questions[*index], domains) == Some(i))
+            .map(|(_, graded)| graded.as_ref().map_or(0.0, |(score, _)| *score))
+            .collect();
+        if !in_domain.is_empty() {
+            results.push(DomainResult {
+                domain_id: Some(domain.domain_id),
+                label: format!("{} {}", domain.code, domain.name),
+                score: in_domain.iter().sum(),
+                num_questions: in_domain.len(),
+            });
+        }
+    }
+    for (index, graded) in &graded {
+        if domain_index(&exam.questions[*index], domains).is_none() {
+            other.score += graded.as_ref().map_or(0.0, |(score, _)| *score);
+            other.num_questions += 1;
+        }
+    }
+    if other.num_questions > 0 {
+        results.push(other);
+    }
+
+    let raw_score: f64 = graded.iter()
+        .filter_map(|(_, graded)| graded.as_ref().map(|(score, _)| *score))
+        .fold(0.0, |total, score| total + score);
+    // The real scale isn't published, so the score is mapped onto it linearly
+    let scaled_score: u32 = MIN_SCALED + (raw_score / graded.len() as f64 * (MAX_SCALED - MIN_SCALED) as f64).round() as u32;
+    Some(MockResult {
+        start,
+        blueprint: *blueprint,
+        graded,
+        domains: results,
+        raw_score,
+        scaled_score,
+        passed: scaled_score >= blueprint.passing_score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::catalog::Objective;
+    use terminator::question::ScoringRule;
+
+    /// An exam with `per_domain[i]` questions on objective `i+1.1`, then `untagged` questions
+    /// that aren't on any objective.
+    fn exam(per_domain: &[usize], untagged: usize) -> Exam {
+        let mut questions: Vec<Question> = Vec::new();
+        for (i, num_questions) in per_domain.iter().enumerate() {
+            for _ in 0..*num_questions {
+                questions.push(question(questions.len() as i32, vec![format!("{}.1", i + 1)]));
+            }
+        }
+        for _ in 0..untagged {
+            questions.push(question(questions.len() as i32, Vec::new()));
+        }
+        Exam { exam_id: 1, exam_title: String::from("Network+"), exam_desc: None, questions }
+    }
+
+    fn question(question_id: i32, objectives: Vec<String>) -> Question {
+        Question {
+            question_id,
+            question_text: String::new(),
+            answer: String::new(),
+            explanation: String::new(),
+            question_type: QuestionType::MultipleChoice,
+            initial_state: None,
+            expected_state: None,
+            flag_rules: Vec::new(),
+            options: Vec::new(),
+            pairs: Vec::new(),
+            items: Vec::new(),
+            scoring_rule: ScoringRule::default(),
+            objectives,
+        }
+    }
+
+    fn domains(weights: &[u32]) -> Vec<Domain> {
+        weights.iter().enumerate()
+            .map(|(i, weight)| Domain {
+                domain_id: i as i32 + 1,
+                code: format!("{}.0", i + 1),
+                name: format!("Domain {}", i + 1),
+                weight: *weight,
+                objectives: vec![Objective { code: format!("{}.1", i + 1), title: String::new() }],
+            })
+            .collect()
+    }
+
+    /// Builds a mock exam of `count` questions and checks that none are repeated or ineligible.
+    /// Returns how many went to each domain, with the untagged ones last.
+    fn build_counts(exam: &Exam, domains: &[Domain], count: usize) -> Vec<usize> {
+        let eligible: Vec<usize> = (0..exam.questions.len()).collect();
+        let picked: Vec<usize> = build(exam, domains, &eligible, count, &mut StdRng::seed_from_u64(7));
+        let mut unique: Vec<usize> = picked.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), picked.len(), "{:?}", picked);
+        let mut counts: Vec<usize> = vec![0; domains.len() + 1];
+        for index in picked {
+            counts[domain_index(&exam.questions[index], domains).unwrap_or(domains.len())] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn gives_leftovers_to_the_largest_remainders() {
+        // 7 questions at 50/30/20 are 3.5, 2.1 and 1.4 questions; the half goes to the first domain.
+        let exam: Exam = exam(&[10, 10, 10], 0);
+        assert_eq!(build_counts(&exam, &domains(&[50, 30, 20]), 7), vec![4, 2, 1, 0]);
+        assert_eq!(build_counts(&exam, &domains(&[50, 30, 20]), 10), vec![5, 3, 2, 0]);
+        assert_eq!(build_counts(&exam, &domains(&[34, 33, 33]), 4), vec![2, 1, 1, 0]);
+    }
+
+    #[test]
+    fn fills_a_short_domain_from_the_rest() {
+        let exam: Exam = exam(&[1, 10], 5);
+        let counts: Vec<usize> = build_counts(&exam, &domains(&[80, 20]), 10);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts.iter().sum::<usize>(), 10);
+        assert!(counts[1] >= 2, "{:?}", counts);
+    }
+
+    #[test]
+    fn picks_from_every_question_without_a_catalog() {
+        assert_eq!(build_counts(&exam(&[], 12), &[], 5), vec![5]);
+        assert_eq!(build_counts(&exam(&[4], 0), &domains(&[0]), 3), vec![3, 0]);
+    }
+
+    #[test]
+    fn asks_every_eligible_question_when_there_are_too_few() {
+        let exam: Exam = exam(&[2, 1], 1);
+        assert_eq!(build_counts(&exam, &domains(&[50, 50]), 90), vec![2, 1, 1]);
+
+        let eligible: Vec<usize> = vec![0, 3];
+        let mut picked: Vec<usize> = build(&exam, &domains(&[50, 50]), &eligible, 90, &mut StdRng::seed_from_u64(7));
+        picked.sort();
+        assert_eq!(picked, eligible);
+    }
+}
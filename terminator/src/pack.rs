@@ -0,0 +1,868 @@
+//! Question packs: an exam, its catalog and its questions in a single TOML file that authors can
+//! edit by hand, instead of writing SQL.
+//!
+//! Every exam and question has a stable `id` (a UUID), so importing a pack again updates what it
+//! imported the first time rather than adding copies, and a learner's history stays attached to
+//! the questions it was recorded against. Options and pairs may have an `id` too, which exported
+//! packs always include; without one, they're matched by their content. Exporting an exam and
+//! importing the file into an empty database gives the same exam back.
+//!
+//! ```toml
+//! [exam]
+//! id = "6f1d3c1e-8c1a-4c5e-9a57-1b2f0f3d9e10"
+//! title = "Network+"
+//! description = "CompTIA Network+ (N10-008)"
+//!
+//! [[exam.creators]]
+//! username = "Jake"
+//! date = "03NOV23"
+//!
+//! [blueprint]
+//! question_count = 90
+//! time_limit_minutes = 90
+//! passing_score = 720
+//!
+//! [[domains]]
+//! code = "1.0"
+//! name = "Networking Fundamentals"
+//! weight = 24
+//!
+//! [[domains.objectives]]
+//! code = "1.5"
+//! title = "Explain common ports and protocols, their application, and encrypted alternatives"
+//!
+//! [[questions]]
+//! id = "0b6c9f5e-2f7e-4d0a-8d7c-5e8a3b1c2d4f"
+//! type = "MultipleChoice"
+//! text = "Which port does HTTPS use by default?"
+//! answer = "443"
+//! explanation = "HTTPS is HTTP over TLS on TCP port 443."
+//! objectives = ["1.5"]
+//! tags = ["ports"]
+//! references = ["RFC 2818"]
+//! options = [
+//!     { text = "80" },
+//!     { text = "443", correct = true },
+//! ]
+//! ```
+//!
+//! Besides `options`, a question can have `pairs` (`Matching`), `items` and `scoring_rule`
+//! (`Ordering`), `initial_state` and `expected_state` (`Shell` and `Ios`) and `flag_rules`
+//! (`Command`), which map onto the tables of the same purpose.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::Path;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::question::{QuestionType, RuleKind, ScoringRule};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pack {
+    pub exam: Exam,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blueprint: Option<Blueprint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domains: Vec<Domain>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub questions: Vec<Question>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Exam {
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Who wrote the exam, from `ExamCreation`. Creators that aren't users of the database the
+    /// pack is imported into are skipped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub creators: Vec<Creator>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Creator {
+    pub username: String,
+    pub date: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Blueprint {
+    pub question_count: u32,
+    pub time_limit_minutes: u32,
+    pub passing_score: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Domain {
+    pub code: String,
+    pub name: String,
+    /// The domain's share of the exam, in percent.
+    pub weight: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objectives: Vec<Objective>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Objective {
+    pub code: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Question {
+    pub id: String,
+    #[serde(rename = "type", default)]
+    pub kind: QuestionType,
+    pub text: String,
+    /// The `CorrectAnswer`. Command, Cmd and PowerShell questions accept any one of its lines.
+    /// Multiple response questions are graded by their options' `correct` flags, so for them it's
+    /// only shown to the learner.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub answer: String,
+    #[serde(default)]
+    pub explanation: String,
+    /// The codes of the objectives the question covers, e.g., `1.5`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objectives: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+    /// The old newline-joined `Questions.Options` column, kept so that exporting doesn't lose it.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub legacy_options: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<AnswerOption>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pairs: Vec<Pair>,
+    /// The items of an ordering question, in the correct order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring_rule: Option<ScoringRule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_state: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flag_rules: Vec<FlagRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnswerOption {
+    /// The option's UUID, which keeps the responses that chose it attached when its text is
+    /// edited. Options without one are matched by their text, or added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub correct: bool,
+}
+
+/// A prompt of a matching question and its match. A pair without a prompt only adds an extra
+/// item to the list of matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pair {
+    /// The pair's UUID, as for an option. Pairs without one are matched by their prompt and
+    /// match, or added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(rename = "match")]
+    pub matches: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlagRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+    pub flag: String,
+    pub rule: RuleKind,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Debug)]
+pub enum PackError {
+    Io(std::io::Error),
+    /// The pack isn't valid TOML or doesn't follow the format. The message includes the line.
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    Sql(rusqlite::Error),
+    /// The pack parsed, but can't be imported as it is, e.g., a question has no UUID.
+    Invalid(String),
+}
+
+impl Display for PackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackError::Io(e) => write!(f, "PackError - {}", e),
+            PackError::Parse(e) => write!(f, "PackError - the pack couldn't be read: {}", e),
+            PackError::Serialize(e) => write!(f, "PackError - the pack couldn't be written: {}", e),
+            PackError::Sql(e) => write!(f, "PackError - {}", e),
+            PackError::Invalid(message) => write!(f, "PackError - {}", message),
+        }
+    }
+}
+
+impl Error for PackError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PackError::Io(e) => Some(e),
+            PackError::Parse(e) => Some(e),
+            PackError::Serialize(e) => Some(e),
+            PackError::Sql(e) => Some(e),
+            PackError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for PackError {
+    fn from(e: rusqlite::Error) -> Self {
+        PackError::Sql(e)
+    }
+}
+
+impl Pack {
+    /// Parses a pack from the text of a TOML file.
+    pub fn from_toml(text: &str) -> Result<Pack, PackError> {
+        toml::from_str(text).map_err(PackError::Parse)
+    }
+
+    /// Reads and parses the pack at `path`.
+    pub fn read(path: &Path) -> Result<Pack, PackError> {
+        let text: String = read_to_string(path).map_err(PackError::Io)?;
+        Self::from_toml(&text)
+    }
+
+    pub fn to_toml(&self) -> Result<String, PackError> {
+        toml::to_string_pretty(self).map_err(PackError::Serialize)
+    }
+}
+
+/// What an import changed.
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub exam_id: i64,
+    pub exam_created: bool,
+    pub questions_added: usize,
+    pub questions_updated: usize,
+    /// Creators that were left out of `ExamCreation` because they aren't users of the database.
+    pub skipped_creators: Vec<String>,
+}
+
+/// Checks that `id` is a UUID and returns it in the form it's stored in, e.g., lowercase.
+fn normalize_id(id: &str, what: &str) -> Result<String, PackError> {
+    match Uuid::parse_str(id.trim()) {
+        Ok(uuid) => Ok(uuid.hyphenated().to_string()),
+        Err(_) if id.trim().is_empty() => Err(PackError::Invalid(format!(
+            "{} has no id; give it a UUID (e.g., from uuidgen) so it can be imported again later", what,
+        ))),
+        Err(e) => Err(PackError::Invalid(format!("{} has an id that isn't a UUID ({}): {}", what, id, e))),
+    }
+}
+
+/// Imports `pack` into the database, adding the exam and any questions it doesn't have yet and
+/// updating the ones it does, matched by their UUIDs. Domains and objectives are matched by their
+/// codes. Nothing is removed, except the child rows (e.g., options) of an updated question that
+/// the pack no longer lists and no response refers to.
+///
+/// Run it inside a transaction: it stops at the first problem, which may be part way through.
+pub fn import(conn: &Connection, pack: &Pack) -> Result<ImportSummary, PackError> {
+    let mut summary: ImportSummary = ImportSummary::default();
+    let exam_uuid: String = normalize_id(&pack.exam.id, "The exam")?;
+    let mut question_uuids: Vec<String> = Vec::with_capacity(pack.questions.len());
+    for (i, question) in pack.questions.iter().enumerate() {
+        let uuid: String = normalize_id(&question.id, &format!("Question {}", i + 1))?;
+        if question_uuids.contains(&uuid) {
+            return Err(PackError::Invalid(format!("Question {} has the same id as an earlier question ({})", i + 1, uuid)));
+        }
+        question_uuids.push(uuid);
+    }
+
+    let existing: Option<i64> = conn.query_row(
+        "SELECT ExamID FROM Exams WHERE UUID = ?1", [&exam_uuid], |row| row.get(0),
+    ).optional()?;
+    summary.exam_id = match existing {
+        Some(exam_id) => {
+            conn.execute(
+                "UPDATE Exams SET Title = ?2, Description = ?3 WHERE ExamID = ?1",
+                params![exam_id, pack.exam.title, pack.exam.description],
+            )?;
+            exam_id
+        },
+        None => {
+            conn.execute(
+                "INSERT INTO Exams (Title, Description, UUID) VALUES (?1, ?2, ?3)",
+                params![pack.exam.title, pack.exam.description, exam_uuid],
+            )?;
+            summary.exam_created = true;
+            conn.last_insert_rowid()
+        },
+    };
+    let exam_id: i64 = summary.exam_id;
+
+    for creator in &pack.exam.creators {
+        let is_user: bool = conn.query_row(
+            "SELECT COUNT(*) FROM Users WHERE Username = ?1", [&creator.username], |row| row.get::<_, i64>(0),
+        )? > 0;
+        if is_user {
+            conn.execute(
+                "INSERT OR REPLACE INTO ExamCreation (ExamID, CreatorUsername, DateCreated) VALUES (?1, ?2, ?3)",
+                params![exam_id, creator.username, creator.date],
+            )?;
+        } else {
+            summary.skipped_creators.push(creator.username.clone());
+        }
+    }
+
+    match &pack.blueprint {
+        Some(blueprint) => conn.execute(
+            "INSERT OR REPLACE INTO ExamBlueprints (ExamID, QuestionCount, TimeLimitMinutes, PassingScore) VALUES (?1, ?2, ?3, ?4)",
+            params![exam_id, blueprint.question_count, blueprint.time_limit_minutes, blueprint.passing_score],
+        )?,
+        None => conn.execute("DELETE FROM ExamBlueprints WHERE ExamID = ?1", [exam_id])?,
+    };
+
+    for domain in &pack.domains {
+        let domain_id: i64 = conn.query_row(
+            "INSERT INTO Domains (ExamID, Code, Name, Weight) VALUES (?1, ?2, ?3, ?4) \
+            ON CONFLICT (ExamID, Code) DO UPDATE SET Name = excluded.Name, Weight = excluded.Weight \
+            RETURNING DomainID",
+            params![exam_id, domain.code, domain.name, domain.weight],
+            |row| row.get(0),
+        )?;
+        for objective in &domain.objectives {
+            conn.execute(
+                "INSERT INTO Objectives (DomainID, Code, Title) VALUES (?1, ?2, ?3) \
+                ON CONFLICT (DomainID, Code) DO UPDATE SET Title = excluded.Title",
+                params![domain_id, objective.code, objective.title],
+            )?;
+        }
+    }
+
+    for (question, uuid) in pack.questions.iter().zip(&question_uuids) {
+        let existing: Option<i64> = conn.query_row(
+            "SELECT QuestionID FROM Questions WHERE UUID = ?1", [uuid], |row| row.get(0),
+        ).optional()?;
+        let question_id: i64 = match existing {
+            Some(question_id) => {
+                conn.execute(
+                    "UPDATE Questions SET ExamID = ?2, QuestionText = ?3, Options = ?4, CorrectAnswer = ?5, \
+                    Explanation = ?6, QuestionType = ?7 WHERE QuestionID = ?1",
+                    params![
+                        question_id, exam_id, question.text, question.legacy_options, question.answer,
+                        question.explanation, question.kind.as_str(),
+                    ],
+                )?;
+                summary.questions_updated += 1;
+                question_id
+            },
+            None => {
+                conn.execute(
+                    "INSERT INTO Questions (ExamID, QuestionText, Options, CorrectAnswer, Explanation, QuestionType, UUID) \
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        exam_id, question.text, question.legacy_options, question.answer,
+                        question.explanation, question.kind.as_str(), uuid,
+                    ],
+                )?;
+                summary.questions_added += 1;
+                conn.last_insert_rowid()
+            },
+        };
+        import_question_details(conn, exam_id, question_id, question)?;
+    }
+    Ok(summary)
+}
+
+/// Works out which of a question's existing rows (`QuestionOptions` or `MatchingPairs`) each of
+/// the pack's entries updates. `existing` has the `rowid`, UUID and content of each row, and
+/// `wanted` the normalized id and content of each entry. An entry with an id takes the row with
+/// that UUID, and one without takes a row with the same content that no other entry took. Entries
+/// left with `None` get a new row.
+fn match_rows(existing: &[(i64, Option<String>, String)], wanted: &[(Option<String>, String)]) -> Vec<Option<i64>> {
+    let mut taken: Vec<bool> = vec![false; existing.len()];
+    let mut matched: Vec<Option<i64>> = vec![None; wanted.len()];
+    for (entry, (id, _)) in wanted.iter().enumerate() {
+        let found: Option<usize> = id.as_ref().and_then(|id| existing.iter().position(|(_, uuid, _)| uuid.as_ref() == Some(id)));
+        if let Some(row) = found {
+            taken[row] = true;
+            matched[entry] = Some(existing[row].0);
+        }
+    }
+    for (entry, (id, content)) in wanted.iter().enumerate() {
+        if id.is_some() {
+            continue;
+        }
+        let found: Option<usize> = (0..existing.len()).find(|row| !taken[*row] && existing[*row].2 == *content);
+        if let Some(row) = found {
+            taken[row] = true;
+            matched[entry] = Some(existing[row].0);
+        }
+    }
+    matched
+}
+
+/// Checks the ids of a question's options or pairs, and returns them normalized.
+fn entry_ids<'a>(question: &Question, what: &str, ids: impl Iterator<Item = Option<&'a String>>) -> Result<Vec<Option<String>>, PackError> {
+    let mut normalized: Vec<Option<String>> = Vec::new();
+    for (i, id) in ids.enumerate() {
+        let id: Option<String> = id.map(|id| normalize_id(id, &format!("The {} {} of question {}", what, i + 1, question.id)))
+            .transpose()?;
+        if id.is_some() && normalized.contains(&id) {
+            return Err(PackError::Invalid(format!(
+                "The {} {} of question {} has the same id as an earlier one", what, i + 1, question.id,
+            )));
+        }
+        normalized.push(id);
+    }
+    Ok(normalized)
+}
+
+/// Writes the options, pairs, items and the rest of `question` for the question with the given
+/// `question_id`, replacing what it had before. Options and pairs keep their rows, matched by id
+/// or content (see `match_rows`), so that the responses that chose them still point at them; one
+/// the pack no longer lists is removed, unless a response chose it, which stops the import.
+fn import_question_details(conn: &Connection, exam_id: i64, question_id: i64, question: &Question) -> Result<(), PackError> {
+    // Positions are unique, so the old ones are moved out of the way while the rows are reordered
+    conn.execute("UPDATE QuestionOptions SET Position = -Position WHERE QuestionID = ?1", [question_id])?;
+    conn.execute("UPDATE MatchingPairs SET Position = -Position WHERE QuestionID = ?1", [question_id])?;
+
+    let mut stmt = conn.prepare_cached("SELECT OptionID, UUID, OptionText FROM QuestionOptions WHERE QuestionID = ?1")?;
+    let existing: Vec<(i64, Option<String>, String)> = stmt.query_map([question_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let ids: Vec<Option<String>> = entry_ids(question, "option", question.options.iter().map(|option| option.id.as_ref()))?;
+    let wanted: Vec<(Option<String>, String)> = ids.into_iter()
+        .zip(&question.options)
+        .map(|(id, option)| (id, option.text.clone()))
+        .collect();
+    let matched: Vec<Option<i64>> = match_rows(&existing, &wanted);
+    for (option_id, _, text) in existing.iter().filter(|(option_id, _, _)| !matched.contains(&Some(*option_id))) {
+        let responses: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM UserResponseOptions WHERE OptionID = ?1", [option_id], |row| row.get(0),
+        )?;
+        if responses > 0 {
+            return Err(PackError::Invalid(format!(
+                "Question {} no longer has the option '{}', which {} response(s) chose. Keep the option, \
+                or give it the id it was exported with if its text was edited", question.id, text, responses,
+            )));
+        }
+        conn.execute("DELETE FROM QuestionOptions WHERE OptionID = ?1", [option_id])?;
+    }
+    for (i, ((uuid, _), (option, option_id))) in wanted.iter().zip(question.options.iter().zip(&matched)).enumerate() {
+        match option_id {
+            Some(option_id) => conn.execute(
+                "UPDATE QuestionOptions SET Position = ?2, OptionText = ?3, IsCorrect = ?4 WHERE OptionID = ?1",
+                params![option_id, i + 1, option.text, option.correct],
+            )?,
+            None => {
+                let elsewhere: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM QuestionOptions WHERE UUID = ?1", [uuid], |row| row.get(0),
+                )?;
+                if elsewhere > 0 {
+                    return Err(PackError::Invalid(format!(
+                        "The option '{}' of question {} has the id of another question's option", option.text, question.id,
+                    )));
+                }
+                conn.execute(
+                    "INSERT INTO QuestionOptions (QuestionID, Position, OptionText, IsCorrect, UUID) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![question_id, i + 1, option.text, option.correct, uuid],
+                )?
+            },
+        };
+    }
+
+    // A pair's content is its prompt and match together
+    let content = |prompt: Option<&str>, matches: &str| format!("{}\n{}", prompt.unwrap_or("\0"), matches);
+    let mut stmt = conn.prepare_cached("SELECT PairID, UUID, Prompt, Match FROM MatchingPairs WHERE QuestionID = ?1")?;
+    let existing: Vec<(i64, Option<String>, String)> = stmt.query_map([question_id], |row| {
+        Ok((row.get(0)?, row.get(1)?, content(row.get::<_, Option<String>>(2)?.as_deref(), &row.get::<_, String>(3)?)))
+    })?.collect::<rusqlite::Result<_>>()?;
+    let ids: Vec<Option<String>> = entry_ids(question, "pair", question.pairs.iter().map(|pair| pair.id.as_ref()))?;
+    let wanted: Vec<(Option<String>, String)> = ids.into_iter()
+        .zip(&question.pairs)
+        .map(|(id, pair)| (id, content(pair.prompt.as_deref(), &pair.matches)))
+        .collect();
+    let matched: Vec<Option<i64>> = match_rows(&existing, &wanted);
+    for (pair_id, _, _) in existing.iter().filter(|(pair_id, _, _)| !matched.contains(&Some(*pair_id))) {
+        let responses: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM UserResponsePairs WHERE PairID = ?1 OR ChosenPairID = ?1", [pair_id], |row| row.get(0),
+        )?;
+        if responses > 0 {
+            let matches: String = conn.query_row("SELECT Match FROM MatchingPairs WHERE PairID = ?1", [pair_id], |row| row.get(0))?;
+            return Err(PackError::Invalid(format!(
+                "Question {} no longer has the pair matching '{}', which {} response(s) used. Keep the pair, \
+                or give it the id it was exported with if it was edited", question.id, matches, responses,
+            )));
+        }
+        conn.execute("DELETE FROM MatchingPairs WHERE PairID = ?1", [pair_id])?;
+    }
+    for (i, ((uuid, _), (pair, pair_id))) in wanted.iter().zip(question.pairs.iter().zip(&matched)).enumerate() {
+        match pair_id {
+            Some(pair_id) => conn.execute(
+                "UPDATE MatchingPairs SET Position = ?2, Prompt = ?3, Match = ?4 WHERE PairID = ?1",
+                params![pair_id, i + 1, pair.prompt, pair.matches],
+            )?,
+            None => {
+                let elsewhere: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM MatchingPairs WHERE UUID = ?1", [uuid], |row| row.get(0),
+                )?;
+                if elsewhere > 0 {
+                    return Err(PackError::Invalid(format!(
+                        "The pair matching '{}' of question {} has the id of another question's pair", pair.matches, question.id,
+                    )));
+                }
+                conn.execute(
+                    "INSERT INTO MatchingPairs (QuestionID, Position, Prompt, Match, UUID) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![question_id, i + 1, pair.prompt, pair.matches, uuid],
+                )?
+            },
+        };
+    }
+
+    conn.execute("DELETE FROM OrderingItems WHERE QuestionID = ?1", [question_id])?;
+    for (i, item) in question.items.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO OrderingItems (QuestionID, Position, ItemText) VALUES (?1, ?2, ?3)",
+            params![question_id, i + 1, item],
+        )?;
+    }
+    match question.scoring_rule {
+        Some(rule) => conn.execute(
+            "INSERT OR REPLACE INTO OrderingQuestions (QuestionID, ScoringRule) VALUES (?1, ?2)",
+            params![question_id, rule.as_str()],
+        )?,
+        None => conn.execute("DELETE FROM OrderingQuestions WHERE QuestionID = ?1", [question_id])?,
+    };
+
+    match (&question.initial_state, &question.expected_state) {
+        (Some(initial_state), Some(expected_state)) => conn.execute(
+            "INSERT OR REPLACE INTO CommandQuestions (QuestionID, InitialState, ExpectedState) VALUES (?1, ?2, ?3)",
+            params![question_id, initial_state, expected_state],
+        )?,
+        (None, None) => conn.execute("DELETE FROM CommandQuestions WHERE QuestionID = ?1", [question_id])?,
+        _ => return Err(PackError::Invalid(format!(
+            "Question {} needs both an initial_state and an expected_state, or neither", question.id,
+        ))),
+    };
+
+    conn.execute("DELETE FROM CommandFlagRules WHERE QuestionID = ?1", [question_id])?;
+    for flag_rule in &question.flag_rules {
+        conn.execute(
+            "INSERT INTO CommandFlagRules (QuestionID, Program, Flag, Rule) VALUES (?1, ?2, ?3, ?4)",
+            params![question_id, flag_rule.program, flag_rule.flag, flag_rule.rule.as_str()],
+        )?;
+    }
+
+    conn.execute("DELETE FROM QuestionObjectives WHERE QuestionID = ?1", [question_id])?;
+    for code in &question.objectives {
+        let objective_id: Option<i64> = conn.query_row(
+            "SELECT o.ObjectiveID FROM Objectives o JOIN Domains d ON d.DomainID = o.DomainID \
+            WHERE d.ExamID = ?1 AND o.Code = ?2",
+            params![exam_id, code],
+            |row| row.get(0),
+        ).optional()?;
+        match objective_id {
+            Some(objective_id) => conn.execute(
+                "INSERT OR IGNORE INTO QuestionObjectives (QuestionID, ObjectiveID) VALUES (?1, ?2)",
+                params![question_id, objective_id],
+            )?,
+            None => return Err(PackError::Invalid(format!(
+                "Question {} covers objective {}, which isn't in the exam's catalog", question.id, code,
+            ))),
+        };
+    }
+
+    conn.execute("DELETE FROM QuestionTags WHERE QuestionID = ?1", [question_id])?;
+    for tag in &question.tags {
+        conn.execute("INSERT OR IGNORE INTO QuestionTags (QuestionID, Tag) VALUES (?1, ?2)", params![question_id, tag])?;
+    }
+
+    conn.execute("DELETE FROM QuestionReferences WHERE QuestionID = ?1", [question_id])?;
+    for (i, reference) in question.references.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO QuestionReferences (QuestionID, Position, Reference) VALUES (?1, ?2, ?3)",
+            params![question_id, i + 1, reference],
+        )?;
+    }
+    Ok(())
+}
+
+/// Finds an exam by its `ExamID` or its UUID.
+pub fn find_exam(conn: &Connection, key: &str) -> rusqlite::Result<Option<i64>> {
+    if let Ok(exam_id) = key.trim().parse::<i64>() {
+        return conn.query_row("SELECT ExamID FROM Exams WHERE ExamID = ?1", [exam_id], |row| row.get(0)).optional();
+    }
+    conn.query_row(
+        "SELECT ExamID FROM Exams WHERE UUID = lower(?1)", [key.trim()], |row| row.get(0),
+    ).optional()
+}
+
+/// Runs `sql` with the single parameter `id` and collects the first column of each row.
+fn strings(conn: &Connection, sql: &str, id: i64) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let rows = stmt.query_map([id], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// Builds a pack of the exam with the given `exam_id`, with everything the pack format covers.
+pub fn export(conn: &Connection, exam_id: i64) -> Result<Pack, PackError> {
+    let mut exam: Exam = conn.query_row(
+        "SELECT UUID, Title, Description FROM Exams WHERE ExamID = ?1",
+        [exam_id],
+        |row| Ok(Exam { id: row.get(0)?, title: row.get(1)?, description: row.get(2)?, creators: Vec::new() }),
+    )?;
+    let mut stmt = conn.prepare(
+        "SELECT CreatorUsername, DateCreated FROM ExamCreation WHERE ExamID = ?1 ORDER BY CreatorUsername"
+    )?;
+    let creators = stmt.query_map([exam_id], |row| Ok(Creator { username: row.get(0)?, date: row.get(1)? }))?;
+    exam.creators = creators.collect::<rusqlite::Result<Vec<Creator>>>()?;
+
+    let blueprint: Option<Blueprint> = conn.query_row(
+        "SELECT QuestionCount, TimeLimitMinutes, PassingScore FROM ExamBlueprints WHERE ExamID = ?1",
+        [exam_id],
+        |row| Ok(Blueprint { question_count: row.get(0)?, time_limit_minutes: row.get(1)?, passing_score: row.get(2)? }),
+    ).optional()?;
+
+    let mut domains_stmt = conn.prepare(
+        "SELECT DomainID, Code, Name, Weight FROM Domains WHERE ExamID = ?1 ORDER BY DomainID"
+    )?;
+    let mut objectives_stmt = conn.prepare(
+        "SELECT Code, Title FROM Objectives WHERE DomainID = ?1 ORDER BY ObjectiveID"
+    )?;
+    let rows = domains_stmt.query_map([exam_id], |row| {
+        Ok((row.get::<_, i64>(0)?, Domain { code: row.get(1)?, name: row.get(2)?, weight: row.get(3)?, objectives: Vec::new() }))
+    })?;
+    let mut domains: Vec<Domain> = Vec::new();
+    for row in rows {
+        let (domain_id, mut domain) = row?;
+        let objectives = objectives_stmt.query_map([domain_id], |row| Ok(Objective { code: row.get(0)?, title: row.get(1)? }))?;
+        domain.objectives = objectives.collect::<rusqlite::Result<Vec<Objective>>>()?;
+        domains.push(domain);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT q.QuestionID, q.UUID, q.QuestionType, q.QuestionText, q.CorrectAnswer, q.Explanation, q.Options, \
+        o.ScoringRule, c.InitialState, c.ExpectedState FROM Questions q \
+        LEFT JOIN OrderingQuestions o ON o.QuestionID = q.QuestionID \
+        LEFT JOIN CommandQuestions c ON c.QuestionID = q.QuestionID \
+        WHERE q.ExamID = ?1 ORDER BY q.QuestionID"
+    )?;
+    let rows = stmt.query_map([exam_id], |row| {
+        Ok((row.get::<_, i64>(0)?, Question {
+            id: row.get(1)?,
+            kind: row.get(2)?,
+            text: row.get(3)?,
+            answer: row.get(4)?,
+            explanation: row.get(5)?,
+            legacy_options: row.get(6)?,
+            scoring_rule: row.get(7)?,
+            initial_state: row.get(8)?,
+            expected_state: row.get(9)?,
+            objectives: Vec::new(),
+            tags: Vec::new(),
+            references: Vec::new(),
+            options: Vec::new(),
+            pairs: Vec::new(),
+            items: Vec::new(),
+            flag_rules: Vec::new(),
+        }))
+    })?.collect::<rusqlite::Result<Vec<(i64, Question)>>>()?;
+
+    let mut options_stmt = conn.prepare(
+        "SELECT UUID, OptionText, IsCorrect FROM QuestionOptions WHERE QuestionID = ?1 ORDER BY Position"
+    )?;
+    let mut pairs_stmt = conn.prepare(
+        "SELECT UUID, Prompt, Match FROM MatchingPairs WHERE QuestionID = ?1 ORDER BY Position"
+    )?;
+    let mut rules_stmt = conn.prepare(
+        "SELECT Program, Flag, Rule FROM CommandFlagRules WHERE QuestionID = ?1 ORDER BY RuleID"
+    )?;
+    let mut questions: Vec<Question> = Vec::with_capacity(rows.len());
+    for (question_id, mut question) in rows {
+        question.objectives = strings(
+            conn,
+            "SELECT o.Code FROM QuestionObjectives q JOIN Objectives o ON o.ObjectiveID = q.ObjectiveID \
+            WHERE q.QuestionID = ?1 ORDER BY o.ObjectiveID",
+            question_id,
+        )?;
+        question.tags = strings(conn, "SELECT Tag FROM QuestionTags WHERE QuestionID = ?1 ORDER BY Tag", question_id)?;
+        question.references = strings(
+            conn, "SELECT Reference FROM QuestionReferences WHERE QuestionID = ?1 ORDER BY Position", question_id,
+        )?;
+        question.items = strings(
+            conn, "SELECT ItemText FROM OrderingItems WHERE QuestionID = ?1 ORDER BY Position", question_id,
+        )?;
+        let options = options_stmt.query_map([question_id], |row| {
+            Ok(AnswerOption { id: row.get(0)?, text: row.get(1)?, correct: row.get(2)? })
+        })?;
+        question.options = options.collect::<rusqlite::Result<Vec<AnswerOption>>>()?;
+        let pairs = pairs_stmt.query_map([question_id], |row| {
+            Ok(Pair { id: row.get(0)?, prompt: row.get(1)?, matches: row.get(2)? })
+        })?;
+        question.pairs = pairs.collect::<rusqlite::Result<Vec<Pair>>>()?;
+        let rules = rules_stmt.query_map([question_id], |row| Ok(FlagRule { program: row.get(0)?, flag: row.get(1)?, rule: row.get(2)? }))?;
+        question.flag_rules = rules.collect::<rusqlite::Result<Vec<FlagRule>>>()?;
+        questions.push(question);
+    }
+
+    Ok(Pack { exam, blueprint, domains, questions })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::migrations::migrate;
+    use super::*;
+
+    const PACK: &str = r#"
+        [exam]
+        id = "6f1d3c1e-8c1a-4c5e-9a57-1b2f0f3d9e10"
+        title = "Network+"
+
+        [[questions]]
+        id = "0b6c9f5e-2f7e-4d0a-8d7c-5e8a3b1c2d4f"
+        text = "Which port does HTTPS use by default?"
+        explanation = "HTTPS is HTTP over TLS on TCP port 443."
+        options = [
+            { text = "80" },
+            { text = "443", correct = true },
+            { text = "8443" },
+        ]
+    "#;
+
+    fn database() -> Connection {
+        let mut conn: Connection = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        conn.pragma_update(None, "foreign_keys", true).unwrap();
+        conn.execute("INSERT INTO Users (Username, Password) VALUES ('jake', 'hash')", []).unwrap();
+        conn
+    }
+
+    /// The `OptionID` of each option, by its text.
+    fn option_ids(conn: &Connection) -> Vec<(String, i64)> {
+        let mut stmt = conn.prepare("SELECT OptionText, OptionID FROM QuestionOptions ORDER BY Position").unwrap();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap()
+    }
+
+    /// Records a response from `jake` that chose the option with the given text.
+    fn choose(conn: &Connection, text: &str) {
+        conn.execute(
+            "INSERT INTO UserQuestionResponses (Username, QuestionID, IsCorrect, Score) SELECT 'jake', QuestionID, 0, 0 \
+            FROM Questions",
+            [],
+        ).unwrap();
+        conn.execute(
+            "INSERT INTO UserResponseOptions (ResponseID, OptionID) SELECT ?1, OptionID FROM QuestionOptions WHERE OptionText = ?2",
+            params![conn.last_insert_rowid(), text],
+        ).unwrap();
+    }
+
+    #[test]
+    fn exporting_and_importing_gives_the_same_pack() {
+        let conn: Connection = database();
+        let summary: ImportSummary = import(&conn, &Pack::from_toml(PACK).unwrap()).unwrap();
+        let exported: Pack = export(&conn, summary.exam_id).unwrap();
+        assert!(exported.questions[0].options.iter().all(|option| option.id.is_some()));
+
+        let other: Connection = database();
+        let summary: ImportSummary = import(&other, &exported).unwrap();
+        assert_eq!(export(&other, summary.exam_id).unwrap(), exported);
+    }
+
+    #[test]
+    fn reordering_options_keeps_their_rows() {
+        let conn: Connection = database();
+        import(&conn, &Pack::from_toml(PACK).unwrap()).unwrap();
+        let before: Vec<(String, i64)> = option_ids(&conn);
+        choose(&conn, "80");
+
+        let mut pack: Pack = Pack::from_toml(PACK).unwrap();
+        pack.questions[0].options.reverse();
+        import(&conn, &pack).unwrap();
+        let mut after: Vec<(String, i64)> = option_ids(&conn);
+        assert_eq!(after.iter().map(|(text, _)| text.as_str()).collect::<Vec<&str>>(), vec!["8443", "443", "80"]);
+        after.reverse();
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn editing_an_option_by_its_id_keeps_its_row() {
+        let conn: Connection = database();
+        let summary: ImportSummary = import(&conn, &Pack::from_toml(PACK).unwrap()).unwrap();
+        choose(&conn, "80");
+        let mut pack: Pack = export(&conn, summary.exam_id).unwrap();
+        let before: Vec<(String, i64)> = option_ids(&conn);
+
+        pack.questions[0].options[0].text = String::from("80 (HTTP)");
+        import(&conn, &pack).unwrap();
+        assert_eq!(option_ids(&conn)[0], (String::from("80 (HTTP)"), before[0].1));
+    }
+
+    #[test]
+    fn refuses_to_remove_an_option_that_was_chosen() {
+        let conn: Connection = database();
+        import(&conn, &Pack::from_toml(PACK).unwrap()).unwrap();
+        choose(&conn, "80");
+
+        let mut pack: Pack = Pack::from_toml(PACK).unwrap();
+        pack.questions[0].options.remove(0);
+        assert!(matches!(import(&conn, &pack), Err(PackError::Invalid(_))));
+
+        // An option no response chose can go
+        let mut pack: Pack = Pack::from_toml(PACK).unwrap();
+        pack.questions[0].options.remove(2);
+        import(&conn, &pack).unwrap();
+        assert_eq!(option_ids(&conn).len(), 2);
+    }
+
+    #[test]
+    fn reordering_pairs_keeps_their_rows() {
+        let conn: Connection = database();
+        let mut pack: Pack = Pack::from_toml(PACK).unwrap();
+        pack.questions[0].kind = QuestionType::Matching;
+        pack.questions[0].options.clear();
+        pack.questions[0].pairs = vec![
+            Pair { id: None, prompt: Some(String::from("22")), matches: String::from("SSH") },
+            Pair { id: None, prompt: Some(String::from("443")), matches: String::from("HTTPS") },
+            Pair { id: None, prompt: None, matches: String::from("Telnet") },
+        ];
+        import(&conn, &pack).unwrap();
+        let pair_ids = |conn: &Connection| -> Vec<(String, i64)> {
+            let mut stmt = conn.prepare("SELECT Match, PairID FROM MatchingPairs ORDER BY Match").unwrap();
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+            rows.collect::<rusqlite::Result<_>>().unwrap()
+        };
+        let before: Vec<(String, i64)> = pair_ids(&conn);
+        conn.execute_batch(
+            "INSERT INTO UserQuestionResponses (Username, QuestionID, IsCorrect, Score) SELECT 'jake', QuestionID, 0, 0 FROM Questions;
+            INSERT INTO UserResponsePairs (ResponseID, PairID, ChosenPairID, IsCorrect)
+                SELECT 1, p.PairID, c.PairID, 0 FROM MatchingPairs p, MatchingPairs c WHERE p.Match = 'SSH' AND c.Match = 'Telnet';"
+        ).unwrap();
+
+        pack.questions[0].pairs.reverse();
+        import(&conn, &pack).unwrap();
+        assert_eq!(pair_ids(&conn), before);
+
+        // Telnet was chosen as a match, so it can't be removed
+        pack.questions[0].pairs.remove(0);
+        assert!(matches!(import(&conn, &pack), Err(PackError::Invalid(_))));
+    }
+}
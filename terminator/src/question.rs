@@ -0,0 +1,206 @@
+//! The kinds of question and the rules they're graded by, as stored in the database and written
+//! in question packs.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use serde::{Deserialize, Serialize};
+
+/// How a question is presented and graded, as stored in `Questions.QuestionType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum QuestionType {
+    /// The user picks one of the question's `QuestionOptions`, which are shown in a random order.
+    /// Any option marked `IsCorrect` is accepted.
+    #[default]
+    MultipleChoice,
+    /// As with `MultipleChoice`, but the user selects every option marked `IsCorrect` (e.g.,
+    /// "Select TWO"). Partial credit is given for a partly correct selection.
+    MultipleResponse,
+    /// The user pairs each prompt in one list with an item in another (e.g., ports to protocols),
+    /// as given by the question's `MatchingPairs`. Each pair earns an equal share of the credit.
+    Matching,
+    /// The user puts the question's `OrderingItems` (e.g., the steps of a procedure) in order, and
+    /// is graded by the `ScoringRule` in `OrderingQuestions`.
+    Ordering,
+    /// The user enters commands in a simulated shell, and the resulting system is compared
+    /// against the `ExpectedState` in `CommandQuestions`.
+    Shell,
+    /// The user enters a single command line, which is compared against the `CorrectAnswer` by
+    /// its program, flags and arguments, following any `CommandFlagRules` for the question.
+    Command,
+    /// The user configures a simulated Cisco IOS device, and its running-config is compared
+    /// against the `ExpectedState` in `CommandQuestions`.
+    Ios,
+    /// The user enters a single command at a simulated Windows Command Prompt, which is run for
+    /// its canned output and compared against the `CorrectAnswer` switch by switch.
+    Cmd,
+    /// As with `Cmd`, but at a simulated PowerShell prompt, where cmdlet aliases and abbreviated
+    /// parameters are understood.
+    PowerShell,
+}
+
+impl QuestionType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QuestionType::MultipleChoice => "MultipleChoice",
+            QuestionType::MultipleResponse => "MultipleResponse",
+            QuestionType::Matching => "Matching",
+            QuestionType::Ordering => "Ordering",
+            QuestionType::Shell => "Shell",
+            QuestionType::Command => "Command",
+            QuestionType::Ios => "Ios",
+            QuestionType::Cmd => "Cmd",
+            QuestionType::PowerShell => "PowerShell",
+        }
+    }
+}
+
+impl FromSql for QuestionType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "MultipleChoice" => Ok(QuestionType::MultipleChoice),
+            "MultipleResponse" => Ok(QuestionType::MultipleResponse),
+            "Matching" => Ok(QuestionType::Matching),
+            "Ordering" => Ok(QuestionType::Ordering),
+            "Shell" => Ok(QuestionType::Shell),
+            "Command" => Ok(QuestionType::Command),
+            "Ios" => Ok(QuestionType::Ios),
+            "Cmd" => Ok(QuestionType::Cmd),
+            "PowerShell" => Ok(QuestionType::PowerShell),
+            other => Err(FromSqlError::Other(format!("Unknown question type: {}", other).into())),
+        }
+    }
+}
+
+/// How an ordering question is graded, as stored in `OrderingQuestions.ScoringRule`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScoringRule {
+    /// Only the correct order earns credit.
+    #[default]
+    Exact,
+    /// Partial credit based on the Kendall tau distance: the share of pairs of items that were put
+    /// in the right order relative to each other.
+    Kendall,
+}
+
+impl FromSql for ScoringRule {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "Exact" => Ok(ScoringRule::Exact),
+            "Kendall" => Ok(ScoringRule::Kendall),
+            other => Err(FromSqlError::Other(format!("Unknown scoring rule: {}", other).into())),
+        }
+    }
+}
+
+impl ScoringRule {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScoringRule::Exact => "Exact",
+            ScoringRule::Kendall => "Kendall",
+        }
+    }
+
+    /// The credit for putting the items in the given `order`, where each entry is an item's
+    /// index in the correct order.
+    pub fn score(&self, order: &[usize]) -> f64 {
+        let n: usize = order.len();
+        let discordant: usize = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .filter(|(i, j)| order[*i] > order[*j])
+            .count();
+        match self {
+            _ if discordant == 0 => 1.0,
+            ScoringRule::Exact => 0.0,
+            ScoringRule::Kendall => 1.0 - discordant as f64 / (n * (n - 1) / 2) as f64,
+        }
+    }
+}
+
+/// How a flag is treated when grading a command answer, as stored in `CommandFlagRules.Rule`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleKind {
+    /// The flag must be given, even if the `CorrectAnswer` doesn't include it.
+    Required,
+    /// The flag may be given or left out, even if the `CorrectAnswer` includes it.
+    Optional,
+    /// The flag must not be given.
+    Forbidden,
+}
+
+impl RuleKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RuleKind::Required => "Required",
+            RuleKind::Optional => "Optional",
+            RuleKind::Forbidden => "Forbidden",
+        }
+    }
+}
+
+impl FromSql for RuleKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "Required" => Ok(RuleKind::Required),
+            "Optional" => Ok(RuleKind::Optional),
+            "Forbidden" => Ok(RuleKind::Forbidden),
+            other => Err(FromSqlError::Other(format!("Unknown flag rule: {}", other).into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+    use super::*;
+
+    #[test]
+    fn kendall_gives_full_credit_for_the_correct_order() {
+        assert_eq!(ScoringRule::Kendall.score(&[0, 1, 2, 3]), 1.0);
+        assert_eq!(ScoringRule::Exact.score(&[0, 1, 2, 3]), 1.0);
+    }
+
+    #[test]
+    fn kendall_gives_no_credit_for_the_reverse_order() {
+        assert_eq!(ScoringRule::Kendall.score(&[3, 2, 1, 0]), 0.0);
+        assert_eq!(ScoringRule::Kendall.score(&[1, 0]), 0.0);
+    }
+
+    #[test]
+    fn kendall_counts_pairs_in_the_wrong_order() {
+        // One of the six pairs is swapped
+        assert!((ScoringRule::Kendall.score(&[1, 0, 2, 3]) - 5.0 / 6.0).abs() < 1e-9);
+        // Moving the last item to the front puts three of the six pairs out of order
+        assert!((ScoringRule::Kendall.score(&[3, 0, 1, 2]) - 0.5).abs() < 1e-9);
+        assert_eq!(ScoringRule::Exact.score(&[1, 0, 2, 3]), 0.0);
+    }
+
+    #[test]
+    fn kendall_handles_too_few_items_to_compare() {
+        assert_eq!(ScoringRule::Kendall.score(&[]), 1.0);
+        assert_eq!(ScoringRule::Kendall.score(&[0]), 1.0);
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let conn: Connection = Connection::open_in_memory().unwrap();
+        for kind in [QuestionType::MultipleChoice, QuestionType::Ordering, QuestionType::PowerShell] {
+            assert_eq!(conn.query_row("SELECT ?1", [kind.as_str()], |row| row.get::<_, QuestionType>(0)).unwrap(), kind);
+        }
+        for rule in [ScoringRule::Exact, ScoringRule::Kendall] {
+            assert_eq!(conn.query_row("SELECT ?1", [rule.as_str()], |row| row.get::<_, ScoringRule>(0)).unwrap(), rule);
+        }
+        for kind in [RuleKind::Required, RuleKind::Optional, RuleKind::Forbidden] {
+            assert_eq!(conn.query_row("SELECT ?1", [kind.as_str()], |row| row.get::<_, RuleKind>(0)).unwrap(), kind);
+        }
+    }
+
+    #[test]
+    fn names_unknown_values() {
+        let conn: Connection = Connection::open_in_memory().unwrap();
+        let error = conn.query_row("SELECT 'Essay'", [], |row| row.get::<_, QuestionType>(0)).unwrap_err();
+        assert!(error.to_string().contains("Unknown question type: Essay"));
+        let error = conn.query_row("SELECT 'Sometimes'", [], |row| row.get::<_, RuleKind>(0)).unwrap_err();
+        assert!(error.to_string().contains("Unknown flag rule: Sometimes"));
+        let error = conn.query_row("SELECT 'Spearman'", [], |row| row.get::<_, ScoringRule>(0)).unwrap_err();
+        assert!(error.to_string().contains("Unknown scoring rule: Spearman"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+
+/// The ease a question starts with, and the lowest it can fall to, as in SM-2.
+const STARTING_EASE: f64 = 2.5;
+const MINIMUM_EASE: f64 = 1.3;
+
+/// A single row from the `ReviewSchedule` table: when the user should next see a question, worked
+/// out with the SM-2 algorithm from their answers to it so far. `last_response_id` is the newest
+/// `UserQuestionResponses` row that has been counted.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub question_id: i32,
+    pub ease: f64,
+    pub interval_days: i64,
+    pub repetitions: i64,
+    pub due: DateTime<Utc>,
+    pub last_response_id: i64,
+}
+
+impl Schedule {
+    /// The schedule for a question that hasn't been answered yet.
+    fn new(question_id: i32, now: DateTime<Utc>) -> Self {
+        Schedule { question_id, ease: STARTING_EASE, interval_days: 0, repetitions: 0, due: now, last_response_id: 0 }
+    }
+
+    /// Updates the schedule for an answer worth `score` given at `answered`. SM-2 rates recall
+    /// from 0 to 5; full credit counts as 4 (correct), partial credit as 3 (correct with
+    /// difficulty) or 2, and no credit as 1. A rating below 3 starts the question over.
+    pub fn review(&mut self, score: f64, answered: DateTime<Utc>) {
+        let quality: f64 = if score >= 1.0 {
+            4.0
+        } else if score >= 0.5 {
+            3.0
+        } else if score > 0.0 {
+            2.0
+        } else {
+            1.0
+        };
+        if quality >= 3.0 {
+            self.interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval_days as f64 * self.ease).round() as i64,
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval_days = 1;
+        }
+        self.ease = (self.ease + 0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02)).max(MINIMUM_EASE);
+        self.due = answered + Duration::days(self.interval_days);
+    }
+}
+
+/// Parses a timestamp written by `to_rfc3339`, falling back to `now` for rows that don't have one.
+pub fn parse_timestamp(timestamp: Option<String>, now: DateTime<Utc>) -> DateTime<Utc> {
+    timestamp.and_then(|timestamp| DateTime::parse_from_rfc3339(&timestamp).ok())
+        .map_or(now, |timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Brings the user's `ReviewSchedule` up to date with their `UserQuestionResponses`. Only the
+/// responses newer than a schedule's `last_response_id` are counted, so answers given anywhere
+/// (an exam, a review session or another tool writing to the database) are picked up exactly once.
+pub fn sync(conn: &Connection, username: &str) -> rusqlite::Result<()> {
+    let now: DateTime<Utc> = Utc::now();
+    let mut schedules: HashMap<i32, Schedule> = load(conn, username)?
+        .into_iter()
+        .map(|schedule| (schedule.question_id, schedule))
+        .collect();
+
+    let responses: Vec<(i64, i32, f64, Option<String>)> = {
+        let mut stmt = conn.prepare(
+            "SELECT ResponseID, QuestionID, Score, Timestamp FROM UserQuestionResponses \
+            WHERE Username = ?1 AND QuestionID IS NOT NULL ORDER BY ResponseID"
+        )?;
+        let rows = stmt.query_map([username], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<rusqlite::Result<Vec<(i64, i32, f64, Option<String>)>>>()?
+    };
+    let mut changed: Vec<i32> = Vec::new();
+    for (response_id, question_id, score, timestamp) in responses {
+        let schedule: &mut Schedule = schedules.entry(question_id).or_insert_with(|| Schedule::new(question_id, now));
+        if response_id <= schedule.last_response_id {
+            continue;
+        }
+        schedule.review(score, parse_timestamp(timestamp, now));
+        schedule.last_response_id = response_id;
+        if !changed.contains(&question_id) {
+            changed.push(question_id);
+        }
+    }
+
+    for question_id in changed {
+        let schedule: &Schedule = &schedules[&question_id];
+        conn.execute(
+            "INSERT OR REPLACE INTO ReviewSchedule \
+            (Username, QuestionID, Ease, IntervalDays, Repetitions, DueDate, LastResponseID) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                username,
+                schedule.question_id,
+                schedule.ease,
+                schedule.interval_days,
+                schedule.repetitions,
+                schedule.due.to_rfc3339(),
+                schedule.last_response_id,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Queries the `ReviewSchedule` table for every question scheduled for the user, soonest first.
+pub fn load(conn: &Connection, username: &str) -> rusqlite::Result<Vec<Schedule>> {
+    let now: DateTime<Utc> = Utc::now();
+    let mut stmt = conn.prepare(
+        "SELECT QuestionID, Ease, IntervalDays, Repetitions, DueDate, LastResponseID FROM ReviewSchedule \
+        WHERE Username = ?1 ORDER BY DueDate, QuestionID"
+    )?;
+    let schedules = stmt.query_map([username], |row| {
+        Ok(Schedule {
+            question_id: row.get(0)?,
+            ease: row.get(1)?,
+            interval_days: row.get(2)?,
+            repetitions: row.get(3)?,
+            due: parse_timestamp(row.get(4)?, now),
+            last_response_id: row.get(5)?,
+        })
+    })?;
+    schedules.collect()
+}
+
+/// The questions that are due for review, most overdue first.
+pub fn due(conn: &Connection, username: &str) -> rusqlite::Result<Vec<Schedule>> {
+    let now: DateTime<Utc> = Utc::now();
+    let mut schedules: Vec<Schedule> = load(conn, username)?;
+    schedules.retain(|schedule| schedule.due <= now);
+    Ok(schedules)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use super::{Schedule, MINIMUM_EASE, STARTING_EASE};
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn correct_answers_follow_the_sm2_intervals() {
+        let mut schedule: Schedule = Schedule::new(1, start());
+        let intervals: Vec<i64> = (0..5)
+            .map(|_| {
+                schedule.review(1.0, start());
+                schedule.interval_days
+            })
+            .collect();
+        // Full credit leaves the ease at 2.5, so each interval after the second is 2.5 times the last
+        assert_eq!(intervals, vec![1, 6, 15, 38, 95]);
+        assert_eq!(schedule.ease, STARTING_EASE);
+        assert_eq!(schedule.repetitions, 5);
+        assert_eq!(schedule.due, start() + Duration::days(95));
+    }
+
+    #[test]
+    fn partial_credit_lowers_the_ease() {
+        let mut schedule: Schedule = Schedule::new(1, start());
+        schedule.review(0.5, start());
+        assert_eq!(schedule.interval_days, 1);
+        assert_eq!(schedule.repetitions, 1);
+        assert!((schedule.ease - 2.36).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_wrong_answer_starts_the_question_over() {
+        let mut schedule: Schedule = Schedule::new(1, start());
+        for _ in 0..3 {
+            schedule.review(1.0, start());
+        }
+        schedule.review(0.0, start());
+        assert_eq!(schedule.repetitions, 0);
+        assert_eq!(schedule.interval_days, 1);
+        assert!((schedule.ease - 1.96).abs() < 1e-9);
+        schedule.review(1.0, start());
+        assert_eq!(schedule.interval_days, 1);
+        schedule.review(1.0, start());
+        assert_eq!(schedule.interval_days, 6);
+    }
+
+    #[test]
+    fn the_ease_never_falls_below_the_minimum() {
+        let mut schedule: Schedule = Schedule::new(1, start());
+        for _ in 0..10 {
+            schedule.review(0.0, start());
+        }
+        assert_eq!(schedule.ease, MINIMUM_EASE);
+    }
+}
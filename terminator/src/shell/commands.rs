@@ -0,0 +1,1425 @@
+use regex::{Regex, RegexBuilder};
+use crate::shell::{display_join, state, Output, Process, Service, Shell};
+use crate::shell::options::{parse_args, ParsedArgs};
+use crate::shell::vfs::{apply_mode_spec, file_name, glob_match, join, parent, Node, NodeKind, Vfs};
+
+/// The commands that the simulated shell understands, shown by `help`.
+pub const SUPPORTED: &[&str] = &[
+    "cat", "cd", "chgrp", "chmod", "chown", "clear", "cp", "curl", "echo", "find", "grep", "head",
+    "help", "hostname", "id", "kill", "ls", "mkdir", "mv", "ps", "pwd", "rm", "rmdir", "service", "sort",
+    "sudo", "systemctl", "tail", "tar", "touch", "umask", "uniq", "wc", "whoami",
+];
+
+/// The first line of every archive created by the simulated `tar`.
+const TAR_HEADER: &str = "# terminator tar archive";
+
+/// Runs the command given by `args` (where `args[0]` is the program) in the `shell`. `stdin` is
+/// the output of the previous command in a pipeline or the content of a `<` redirection.
+pub fn run(shell: &mut Shell, args: &[String], stdin: Option<&str>) -> Output {
+    let program: &str = match args.first() {
+        Some(program) => program,
+        None => return Output::default(),
+    };
+    let rest: &[String] = &args[1..];
+    match program {
+        "cat" => cat(shell, rest, stdin),
+        "cd" => cd(shell, rest),
+        "chgrp" => chown(shell, rest, true),
+        "chmod" => chmod(shell, rest),
+        "chown" => chown(shell, rest, false),
+        "clear" => Output::ok(String::new()),
+        "cp" => cp(shell, rest, false),
+        "curl" => curl(shell, rest),
+        "echo" => echo(rest),
+        "find" => find(shell, rest),
+        "grep" => grep(shell, rest, stdin),
+        "head" => head_tail(shell, program, rest, stdin),
+        "help" => Output::ok(format!("Supported commands:\n  {}\n", SUPPORTED.join(" "))),
+        "hostname" => Output::ok(format!("{}\n", shell.hostname)),
+        "id" => id(shell),
+        "kill" => kill(shell, rest),
+        "ls" => ls(shell, rest),
+        "mkdir" => mkdir(shell, rest),
+        "mv" => cp(shell, rest, true),
+        "ps" => ps(shell, rest),
+        "pwd" => Output::ok(format!("{}\n", shell.cwd)),
+        "rm" => rm(shell, rest),
+        "rmdir" => rmdir(shell, rest),
+        "service" => service(shell, rest),
+        "sort" => sort(shell, rest, stdin),
+        "sudo" => sudo(shell, rest, stdin),
+        "systemctl" => systemctl(shell, rest),
+        "tail" => head_tail(shell, program, rest, stdin),
+        "tar" => tar(shell, rest),
+        "touch" => touch(shell, rest),
+        "umask" => umask(shell, rest),
+        "uniq" => uniq(shell, rest, stdin),
+        "wc" => wc(shell, rest, stdin),
+        "whoami" => Output::ok(format!("{}\n", shell.user)),
+        _ => Output {
+            stdout: String::new(),
+            stderr: format!("{}: command not found\n", program),
+            status: 127,
+        },
+    }
+}
+
+/// Parses the arguments for `program`, turning a parse failure into the error `Output`.
+fn options(program: &str, args: &[String]) -> Result<ParsedArgs, Output> {
+    parse_args(program, args).map_err(|e| Output {
+        stdout: String::new(),
+        stderr: format!("{}: {}\n", program, e),
+        status: 2,
+    })
+}
+
+/// Collects the lines of `stderr` and the `stdout` into an `Output`, failing if there were errors.
+fn finish(stdout: String, stderr: String) -> Output {
+    let status: i32 = if stderr.is_empty() { 0 } else { 1 };
+    Output { stdout, stderr, status }
+}
+
+/// Reads the files named by `operands`, or `stdin` if there aren't any. Each entry is the name to
+/// show for the input and its content; files that can't be read are reported in `stderr`.
+fn inputs(shell: &Shell, program: &str, operands: &[String], stdin: Option<&str>, stderr: &mut String)
+    -> Vec<(String, String)> {
+    if operands.is_empty() || operands == ["-"] {
+        return vec![(String::from("(standard input)"), stdin.unwrap_or("").to_string())];
+    }
+    let mut result: Vec<(String, String)> = Vec::new();
+    for operand in operands {
+        match shell.vfs.read_file(&shell.resolve(operand)) {
+            Ok(content) => result.push((operand.clone(), content.to_string())),
+            Err(e) => stderr.push_str(&format!("{}: {}: {}\n", program, operand, e)),
+        }
+    }
+    result
+}
+
+fn cat(shell: &Shell, args: &[String], stdin: Option<&str>) -> Output {
+    let opts: ParsedArgs = match options("cat", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let mut stderr: String = String::new();
+    let mut stdout: String = String::new();
+    for (_, content) in inputs(shell, "cat", &opts.operands, stdin, &mut stderr) {
+        stdout.push_str(&content);
+    }
+    if opts.has("-n") {
+        stdout = stdout.lines().enumerate().map(|(i, line)| format!("{:>6}\t{}\n", i + 1, line)).collect();
+    }
+    finish(stdout, stderr)
+}
+
+fn cd(shell: &mut Shell, args: &[String]) -> Output {
+    let target: String = match args.iter().find(|arg| !arg.starts_with('-')) {
+        Some(arg) => arg.clone(),
+        None => shell.home(),
+    };
+    let path: String = shell.resolve(&target);
+    match shell.vfs.get(&path) {
+        Some(node) if node.is_dir() => {
+            shell.cwd = path;
+            Output::default()
+        },
+        Some(_) => Output::err(format!("bash: cd: {}: Not a directory\n", target)),
+        None => Output::err(format!("bash: cd: {}: No such file or directory\n", target)),
+    }
+}
+
+fn echo(args: &[String]) -> Output {
+    let no_newline: bool = args.first().is_some_and(|arg| arg == "-n");
+    let words: &[String] = if no_newline { &args[1..] } else { args };
+    let mut stdout: String = words.join(" ");
+    if !no_newline {
+        stdout.push('\n');
+    }
+    Output::ok(stdout)
+}
+
+fn id(shell: &Shell) -> Output {
+    let uid: u32 = if shell.user == "root" { 0 } else { 1000 };
+    Output::ok(format!("uid={0}({1}) gid={0}({1}) groups={0}({1})\n", uid, shell.user))
+}
+
+/// Formats a single `ls -l` line for `node`.
+fn long_entry(shell: &Shell, name: &str, path: &str, node: &Node, human: bool) -> String {
+    let links: usize = if node.is_dir() {
+        2 + shell.vfs.children(path).iter().filter(|(_, child)| child.is_dir()).count()
+    } else {
+        1
+    };
+    let size: String = if human && node.size() >= 1024 {
+        format!("{:.1}K", node.size() as f64 / 1024.0)
+    } else {
+        node.size().to_string()
+    };
+    format!("{} {} {} {} {:>5} Jan  1 00:00 {}", node.mode_string(), links, node.owner, node.group, size, name)
+}
+
+fn ls(shell: &Shell, args: &[String]) -> Output {
+    let opts: ParsedArgs = match options("ls", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let long: bool = opts.has("-l");
+    let human: bool = opts.has("-h");
+    let operands: Vec<String> = if opts.operands.is_empty() { vec![String::from(".")] } else { opts.operands.clone() };
+    let mut stdout: String = String::new();
+    let mut stderr: String = String::new();
+
+    let format_entries = |entries: Vec<(String, String, &Node)>| -> String {
+        let mut entries = entries;
+        if opts.has("-S") {
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.2.size()));
+        }
+        if opts.has("-r") {
+            entries.reverse();
+        }
+        if long {
+            entries.iter()
+                .map(|(name, path, node)| long_entry(shell, name, path, node, human) + "\n")
+                .collect()
+        } else if entries.is_empty() {
+            String::new()
+        } else if opts.has("-1") {
+            entries.iter().map(|(name, _, _)| format!("{}\n", name)).collect()
+        } else {
+            entries.iter().map(|(name, _, _)| name.as_str()).collect::<Vec<&str>>().join("  ") + "\n"
+        }
+    };
+
+    let mut files: Vec<(String, String, &Node)> = Vec::new();
+    let mut dirs: Vec<(String, String)> = Vec::new();
+    for operand in &operands {
+        let path: String = shell.resolve(operand);
+        match shell.vfs.get(&path) {
+            Some(node) if node.is_dir() && !opts.has("-d") => dirs.push((operand.clone(), path)),
+            Some(node) => files.push((operand.clone(), path, node)),
+            None => stderr.push_str(&format!("ls: cannot access '{}': No such file or directory\n", operand)),
+        }
+    }
+    let show_headers: bool = operands.len() > 1 || opts.has("-R");
+    stdout.push_str(&format_entries(files));
+
+    let mut queue: Vec<(String, String)> = dirs;
+    queue.reverse();
+    let mut first: bool = stdout.is_empty();
+    while let Some((display, path)) = queue.pop() {
+        let mut entries: Vec<(String, String, &Node)> = Vec::new();
+        if opts.has("-a") {
+            entries.push((String::from("."), path.clone(), shell.vfs.get(&path).expect("Listed dir should exist")));
+            let parent_path: String = parent(&path);
+            entries.push((String::from(".."), parent_path.clone(), shell.vfs.get(&parent_path).expect("Parent should exist")));
+        }
+        let mut subdirs: Vec<(String, String)> = Vec::new();
+        for (name, node) in shell.vfs.children(&path) {
+            if name.starts_with('.') && !opts.has("-a") && !opts.has("-A") {
+                continue;
+            }
+            let child: String = join(&path, &name);
+            if node.is_dir() && opts.has("-R") {
+                subdirs.push((display_join(&display, &name), child.clone()));
+            }
+            entries.push((name, child, node));
+        }
+        if show_headers {
+            if !first {
+                stdout.push('\n');
+            }
+            stdout.push_str(&format!("{}:\n", display));
+        }
+        if long {
+            let blocks: usize = entries.iter().map(|(_, _, node)| node.size().div_ceil(1024) * 4).sum();
+            stdout.push_str(&format!("total {}\n", blocks));
+        }
+        stdout.push_str(&format_entries(entries));
+        first = false;
+        subdirs.reverse();
+        queue.extend(subdirs);
+    }
+    finish(stdout, stderr)
+}
+
+fn mkdir(shell: &mut Shell, args: &[String]) -> Output {
+    let opts: ParsedArgs = match options("mkdir", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let mode: u32 = match opts.value("-m") {
+        Some(spec) => match apply_mode_spec(spec, 0o777, true, 0) {
+            Ok(mode) => mode,
+            Err(e) => return Output::err(format!("mkdir: {}\n", e)),
+        },
+        None => 0o777 & !shell.umask,
+    };
+    if opts.operands.is_empty() {
+        return Output::err(String::from("mkdir: missing operand\n"));
+    }
+    let mut stdout: String = String::new();
+    let mut stderr: String = String::new();
+    for operand in &opts.operands {
+        let path: String = shell.resolve(operand);
+        let node: Node = Node { mode, ..shell.new_node(NodeKind::Dir, 0o7777) };
+        if opts.has("-p") {
+            let mut missing: Vec<String> = Vec::new();
+            let mut current: String = path.clone();
+            while !shell.vfs.exists(&current) {
+                missing.push(current.clone());
+                current = parent(&current);
+            }
+            if !shell.vfs.is_dir(&current) {
+                stderr.push_str(&format!("mkdir: cannot create directory '{}': Not a directory\n", operand));
+                continue;
+            }
+            for dir in missing.into_iter().rev() {
+                if opts.has("-v") {
+                    stdout.push_str(&format!("mkdir: created directory '{}'\n", dir));
+                }
+                shell.vfs.insert(&dir, node.clone());
+            }
+        } else {
+            match shell.vfs.create_dir(&path, node) {
+                Ok(()) if opts.has("-v") => stdout.push_str(&format!("mkdir: created directory '{}'\n", operand)),
+                Ok(()) => {},
+                Err(e) => stderr.push_str(&format!("mkdir: cannot create directory '{}': {}\n", operand, e)),
+            }
+        }
+    }
+    finish(stdout, stderr)
+}
+
+fn rmdir(shell: &mut Shell, args: &[String]) -> Output {
+    let opts: ParsedArgs = match options("rmdir", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let mut stderr: String = String::new();
+    for operand in &opts.operands {
+        let path: String = shell.resolve(operand);
+        match shell.vfs.get(&path) {
+            Some(node) if !node.is_dir() => {
+                stderr.push_str(&format!("rmdir: failed to remove '{}': Not a directory\n", operand));
+            },
+            Some(_) if !shell.vfs.children(&path).is_empty() => {
+                stderr.push_str(&format!("rmdir: failed to remove '{}': Directory not empty\n", operand));
+            },
+            Some(_) => shell.vfs.remove(&path),
+            None => stderr.push_str(&format!("rmdir: failed to remove '{}': No such file or directory\n", operand)),
+        }
+    }
+    finish(String::new(), stderr)
+}
+
+fn touch(shell: &mut Shell, args: &[String]) -> Output {
+    let opts: ParsedArgs = match options("touch", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    if opts.operands.is_empty() {
+        return Output::err(String::from("touch: missing file operand\n"));
+    }
+    let mut stderr: String = String::new();
+    for operand in &opts.operands {
+        let path: String = shell.resolve(operand);
+        if shell.vfs.exists(&path) || opts.has("-c") {
+            continue;
+        }
+        let template: Node = shell.new_node(NodeKind::File(String::new()), 0o666);
+        if let Err(e) = shell.vfs.write_file(&path, "", false, template) {
+            stderr.push_str(&format!("touch: cannot touch '{}': {}\n", operand, e));
+        }
+    }
+    finish(String::new(), stderr)
+}
+
+fn rm(shell: &mut Shell, args: &[String]) -> Output {
+    let opts: ParsedArgs = match options("rm", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let recursive: bool = opts.has("-r") || opts.has("-R");
+    let force: bool = opts.has("-f");
+    if opts.operands.is_empty() && !force {
+        return Output::err(String::from("rm: missing operand\n"));
+    }
+    let mut stdout: String = String::new();
+    let mut stderr: String = String::new();
+    for operand in &opts.operands {
+        let path: String = shell.resolve(operand);
+        match shell.vfs.get(&path) {
+            None if force => {},
+            None => stderr.push_str(&format!("rm: cannot remove '{}': No such file or directory\n", operand)),
+            Some(_) if path == "/" => stderr.push_str("rm: it is dangerous to operate recursively on '/'\n"),
+            Some(node) if node.is_dir() && !recursive => {
+                stderr.push_str(&format!("rm: cannot remove '{}': Is a directory\n", operand));
+            },
+            Some(_) => {
+                if opts.has("-v") {
+                    stdout.push_str(&format!("removed '{}'\n", operand));
+                }
+                shell.vfs.remove(&path);
+            },
+        }
+    }
+    finish(stdout, stderr)
+}
+
+/// Implements `cp` and, when `move_nodes` is set, `mv`.
+fn cp(shell: &mut Shell, args: &[String], move_nodes: bool) -> Output {
+    let program: &str = if move_nodes { "mv" } else { "cp" };
+    let opts: ParsedArgs = match options(program, args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    if opts.operands.len() < 2 {
+        return Output::err(format!("{}: missing destination file operand\n", program));
+    }
+    let (sources, dest) = opts.operands.split_at(opts.operands.len() - 1);
+    let dest_path: String = shell.resolve(&dest[0]);
+    let dest_is_dir: bool = shell.vfs.is_dir(&dest_path);
+    if sources.len() > 1 && !dest_is_dir {
+        return Output::err(format!("{}: target '{}' is not a directory\n", program, dest[0]));
+    }
+    let recursive: bool = move_nodes || opts.has("-r") || opts.has("-R") || opts.has("-a");
+    let preserve: bool = move_nodes || opts.has("-p") || opts.has("-a");
+
+    let mut stdout: String = String::new();
+    let mut stderr: String = String::new();
+    for source in sources {
+        let source_path: String = shell.resolve(source);
+        let target: String = if dest_is_dir {
+            join(&dest_path, file_name(&source_path))
+        } else {
+            dest_path.clone()
+        };
+        match shell.vfs.get(&source_path) {
+            None => {
+                stderr.push_str(&format!("{}: cannot stat '{}': No such file or directory\n", program, source));
+                continue;
+            },
+            Some(node) if node.is_dir() && !recursive => {
+                stderr.push_str(&format!("cp: -r not specified; omitting directory '{}'\n", source));
+                continue;
+            },
+            Some(_) if target == source_path || target.starts_with(&format!("{}/", source_path)) => {
+                stderr.push_str(&format!("{}: cannot {} '{}' into itself\n", program, program, source));
+                continue;
+            },
+            Some(_) => {},
+        }
+        if !shell.vfs.is_dir(&parent(&target)) {
+            stderr.push_str(&format!("{}: cannot create '{}': No such file or directory\n", program, dest[0]));
+            continue;
+        }
+
+        let copies: Vec<(String, Node)> = shell.vfs.descendants(&source_path)
+            .into_iter()
+            .map(|path| {
+                let mut node: Node = shell.vfs.get(&path).expect("Descendant should exist").clone();
+                if !preserve {
+                    node.owner = shell.user.clone();
+                    node.group = shell.user.clone();
+                }
+                (format!("{}{}", target, &path[source_path.len()..]), node)
+            })
+            .collect();
+        if move_nodes {
+            shell.vfs.remove(&source_path);
+        }
+        shell.vfs.remove(&target);
+        for (path, node) in copies {
+            shell.vfs.insert(&path, node);
+        }
+        if opts.has("-v") {
+            stdout.push_str(&format!("'{}' -> '{}'\n", source, target));
+        }
+    }
+    finish(stdout, stderr)
+}
+
+/// Checks that the current user may change the attributes of `node`: `root` may change anything,
+/// and other users may only change what they own.
+fn may_modify(shell: &Shell, node: &Node) -> bool {
+    shell.user == "root" || node.owner == shell.user
+}
+
+fn chmod(shell: &mut Shell, args: &[String]) -> Output {
+    let mut recursive: bool = false;
+    let mut verbose: bool = false;
+    let mut mode_spec: Option<&str> = None;
+    let mut files: Vec<&String> = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-R" | "--recursive" => recursive = true,
+            "-v" | "--verbose" | "-c" | "--changes" => verbose = true,
+            "-f" | "--silent" | "--quiet" => {},
+            _ if mode_spec.is_none() => mode_spec = Some(arg),
+            _ => files.push(arg),
+        }
+    }
+    let mode_spec: &str = match mode_spec {
+        Some(spec) => spec,
+        None => return Output::err(String::from("chmod: missing operand\n")),
+    };
+    if files.is_empty() {
+        return Output::err(format!("chmod: missing operand after '{}'\n", mode_spec));
+    }
+
+    let mut stdout: String = String::new();
+    let mut stderr: String = String::new();
+    for file in files {
+        let path: String = shell.resolve(file);
+        if !shell.vfs.exists(&path) {
+            stderr.push_str(&format!("chmod: cannot access '{}': No such file or directory\n", file));
+            continue;
+        }
+        let targets: Vec<String> = if recursive { shell.vfs.descendants(&path) } else { vec![path] };
+        for target in targets {
+            let umask: u32 = shell.umask;
+            let allowed: bool = may_modify(shell, shell.vfs.get(&target).expect("Target should exist"));
+            let node: &mut Node = shell.vfs.get_mut(&target).expect("Target should exist");
+            if !allowed {
+                stderr.push_str(&format!("chmod: changing permissions of '{}': Operation not permitted\n", file));
+                continue;
+            }
+            match apply_mode_spec(mode_spec, node.mode, node.is_dir(), umask) {
+                Ok(mode) => {
+                    if verbose && mode != node.mode {
+                        stdout.push_str(&format!("mode of '{}' changed from {:04o} to {:04o}\n", target, node.mode, mode));
+                    }
+                    node.mode = mode;
+                },
+                Err(e) => return Output::err(format!("chmod: {}\n", e)),
+            }
+        }
+    }
+    finish(stdout, stderr)
+}
+
+/// Implements `chown` and, when `group_only` is set, `chgrp`.
+fn chown(shell: &mut Shell, args: &[String], group_only: bool) -> Output {
+    let program: &str = if group_only { "chgrp" } else { "chown" };
+    let opts: ParsedArgs = match options(program, args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    if opts.operands.len() < 2 {
+        return Output::err(format!("{}: missing operand\n", program));
+    }
+    let spec: &str = &opts.operands[0];
+    let (owner, group): (Option<String>, Option<String>) = if group_only {
+        (None, Some(spec.to_string()))
+    } else {
+        match spec.split_once(['.', ':']) {
+            Some((owner, "")) => (Some(owner.to_string()), Some(owner.to_string())),
+            Some(("", group)) => (None, Some(group.to_string())),
+            Some((owner, group)) => (Some(owner.to_string()), Some(group.to_string())),
+            None => (Some(spec.to_string()), None),
+        }
+    };
+
+    let mut stderr: String = String::new();
+    for file in &opts.operands[1..] {
+        let path: String = shell.resolve(file);
+        if !shell.vfs.exists(&path) {
+            stderr.push_str(&format!("{}: cannot access '{}': No such file or directory\n", program, file));
+            continue;
+        }
+        if shell.user != "root" {
+            stderr.push_str(&format!("{}: changing ownership of '{}': Operation not permitted\n", program, file));
+            continue;
+        }
+        let targets: Vec<String> = if opts.has("-R") { shell.vfs.descendants(&path) } else { vec![path] };
+        for target in targets {
+            let node: &mut Node = shell.vfs.get_mut(&target).expect("Target should exist");
+            if let Some(owner) = &owner {
+                node.owner = owner.clone();
+            }
+            if let Some(group) = &group {
+                node.group = group.clone();
+            }
+        }
+    }
+    finish(String::new(), stderr)
+}
+
+fn grep(shell: &Shell, args: &[String], stdin: Option<&str>) -> Output {
+    let opts: ParsedArgs = match options("grep", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let mut operands: Vec<String> = opts.operands.clone();
+    let pattern: String = match opts.value("-e") {
+        Some(pattern) => pattern.to_string(),
+        None if !operands.is_empty() => operands.remove(0),
+        None => return Output { stdout: String::new(), stderr: String::from("Usage: grep [OPTION]... PATTERNS [FILE]...\n"), status: 2 },
+    };
+    let mut expression: String = if opts.has("-F") { regex::escape(&pattern) } else { basic_to_extended(&pattern, opts.has("-E")) };
+    if opts.has("-w") {
+        expression = format!(r"\b(?:{})\b", expression);
+    }
+    if opts.has("-x") {
+        expression = format!("^(?:{})$", expression);
+    }
+    let regex: Regex = match RegexBuilder::new(&expression).case_insensitive(opts.has("-i")).build() {
+        Ok(regex) => regex,
+        Err(_) => return Output { stdout: String::new(), stderr: format!("grep: invalid regular expression '{}'\n", pattern), status: 2 },
+    };
+
+    let recursive: bool = opts.has("-r") || opts.has("-R");
+    let mut stderr: String = String::new();
+    let sources: Vec<(String, String)> = if recursive {
+        let roots: Vec<String> = if operands.is_empty() { vec![String::from(".")] } else { operands.clone() };
+        let mut files: Vec<(String, String)> = Vec::new();
+        for root in roots {
+            let path: String = shell.resolve(&root);
+            if !shell.vfs.exists(&path) {
+                stderr.push_str(&format!("grep: {}: No such file or directory\n", root));
+                continue;
+            }
+            for descendant in shell.vfs.descendants(&path) {
+                if let Ok(content) = shell.vfs.read_file(&descendant) {
+                    let relative: &str = descendant.strip_prefix(&path).unwrap_or(&descendant).trim_start_matches('/');
+                    let display: String = if relative.is_empty() {
+                        root.clone()
+                    } else {
+                        display_join(&root, relative)
+                    };
+                    files.push((display, content.to_string()));
+                }
+            }
+        }
+        files
+    } else {
+        inputs(shell, "grep", &operands, stdin, &mut stderr)
+    };
+
+    let show_names: bool = !opts.has("-h") && (opts.has("-H") || recursive || sources.len() > 1);
+    let mut stdout: String = String::new();
+    let mut matched: bool = false;
+    for (name, content) in &sources {
+        let mut count: usize = 0;
+        for (i, line) in content.lines().enumerate() {
+            if regex.is_match(line) == opts.has("-v") {
+                continue;
+            }
+            count += 1;
+            if opts.has("-c") || opts.has("-l") || opts.has("-q") {
+                continue;
+            }
+            let prefix: String = match (show_names, opts.has("-n")) {
+                (true, true) => format!("{}:{}:", name, i + 1),
+                (true, false) => format!("{}:", name),
+                (false, true) => format!("{}:", i + 1),
+                (false, false) => String::new(),
+            };
+            if opts.has("-o") && !opts.has("-v") {
+                for found in regex.find_iter(line) {
+                    stdout.push_str(&format!("{}{}\n", prefix, found.as_str()));
+                }
+            } else {
+                stdout.push_str(&format!("{}{}\n", prefix, line));
+            }
+        }
+        matched |= count > 0;
+        if opts.has("-c") {
+            if show_names {
+                stdout.push_str(&format!("{}:{}\n", name, count));
+            } else {
+                stdout.push_str(&format!("{}\n", count));
+            }
+        } else if opts.has("-l") && count > 0 {
+            stdout.push_str(&format!("{}\n", name));
+        }
+    }
+    if opts.has("-q") {
+        stdout.clear();
+    }
+    if opts.has("-s") {
+        stderr.clear();
+    }
+    let status: i32 = if !stderr.is_empty() && !matched { 2 } else if matched { 0 } else { 1 };
+    Output { stdout, stderr, status }
+}
+
+/// Converts a basic regular expression (where `\|`, `\+`, `\?`, `\(` and `\)` are operators) to
+/// the extended syntax that the `regex` crate uses. Extended expressions are returned as given.
+fn basic_to_extended(pattern: &str, extended: bool) -> String {
+    if extended {
+        return pattern.to_string();
+    }
+    let mut result: String = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(op @ ('|' | '+' | '?' | '(' | ')' | '{' | '}')) => result.push(op),
+                Some(other) => {
+                    result.push('\\');
+                    result.push(other);
+                },
+                None => result.push_str("\\\\"),
+            },
+            '|' | '+' | '?' | '(' | ')' | '{' | '}' => {
+                result.push('\\');
+                result.push(c);
+            },
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// A single test in a `find` expression.
+enum FindTest {
+    Name(String, bool),
+    Type(char),
+    Perm(u32, char),
+    User(String),
+    Group(String),
+    Empty,
+}
+
+fn find(shell: &Shell, args: &[String]) -> Output {
+    let mut roots: Vec<String> = Vec::new();
+    let mut tests: Vec<(FindTest, bool)> = Vec::new();
+    let mut max_depth: Option<usize> = None;
+    let mut min_depth: usize = 0;
+    let mut negate: bool = false;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if tests.is_empty() && !negate && !arg.starts_with('-') && arg != "!" {
+            roots.push(arg.clone());
+            continue;
+        }
+        let mut value = |name: &str| match iter.next() {
+            Some(value) => Ok(value.clone()),
+            None => Err(Output::err(format!("find: missing argument to `{}'\n", name))),
+        };
+        let test: FindTest = match arg.as_str() {
+            "!" | "-not" => {
+                negate = !negate;
+                continue;
+            },
+            "-print" | "-a" | "-and" => continue,
+            "-name" | "-iname" => match value(arg) {
+                Ok(pattern) => FindTest::Name(pattern, arg == "-iname"),
+                Err(output) => return output,
+            },
+            "-type" => match value(arg) {
+                Ok(kind) if kind == "f" || kind == "d" => FindTest::Type(kind.chars().next().expect("Type should be one char")),
+                Ok(kind) => return Output::err(format!("find: Unknown argument to -type: {}\n", kind)),
+                Err(output) => return output,
+            },
+            "-perm" => match value(arg) {
+                Ok(perm) => {
+                    let (prefix, digits) = match perm.chars().next() {
+                        Some(c @ ('-' | '/')) => (c, &perm[1..]),
+                        _ => ('=', perm.as_str()),
+                    };
+                    match apply_mode_spec(digits, 0, false, 0) {
+                        Ok(mode) => FindTest::Perm(mode, prefix),
+                        Err(_) => return Output::err(format!("find: invalid mode '{}'\n", perm)),
+                    }
+                },
+                Err(output) => return output,
+            },
+            "-user" => match value(arg) {
+                Ok(user) => FindTest::User(user),
+                Err(output) => return output,
+            },
+            "-group" => match value(arg) {
+                Ok(group) => FindTest::Group(group),
+                Err(output) => return output,
+            },
+            "-empty" => FindTest::Empty,
+            "-maxdepth" | "-mindepth" => match value(arg).map(|depth| depth.parse::<usize>()) {
+                Ok(Ok(depth)) => {
+                    if arg == "-maxdepth" {
+                        max_depth = Some(depth);
+                    } else {
+                        min_depth = depth;
+                    }
+                    continue;
+                },
+                _ => return Output::err(format!("find: Expected a positive decimal integer argument to {}\n", arg)),
+            },
+            _ => return Output::err(format!("find: unknown predicate `{}'\n", arg)),
+        };
+        tests.push((test, negate));
+        negate = false;
+    }
+    if roots.is_empty() {
+        roots.push(String::from("."));
+    }
+
+    let mut stdout: String = String::new();
+    let mut stderr: String = String::new();
+    for root in roots {
+        let root_path: String = shell.resolve(&root);
+        if !shell.vfs.exists(&root_path) {
+            stderr.push_str(&format!("find: '{}': No such file or directory\n", root));
+            continue;
+        }
+        for path in shell.vfs.descendants(&root_path) {
+            let relative: &str = if path == root_path { "" } else { path[root_path.len()..].trim_start_matches('/') };
+            let depth: usize = if relative.is_empty() { 0 } else { relative.matches('/').count() + 1 };
+            if max_depth.is_some_and(|max| depth > max) || depth < min_depth {
+                continue;
+            }
+            let node: &Node = shell.vfs.get(&path).expect("Descendant should exist");
+            let name: &str = if relative.is_empty() { root.as_str() } else { file_name(&path) };
+            let all_pass: bool = tests.iter().all(|(test, negated)| {
+                let passes: bool = match test {
+                    FindTest::Name(pattern, false) => glob_match(pattern, name),
+                    FindTest::Name(pattern, true) => glob_match(&pattern.to_lowercase(), &name.to_lowercase()),
+                    FindTest::Type(kind) => (*kind == 'd') == node.is_dir(),
+                    FindTest::Perm(mode, '-') => node.mode & mode == *mode,
+                    FindTest::Perm(mode, '/') => *mode == 0 || node.mode & mode != 0,
+                    FindTest::Perm(mode, _) => node.mode & 0o7777 == *mode,
+                    FindTest::User(user) => node.owner == *user,
+                    FindTest::Group(group) => node.group == *group,
+                    FindTest::Empty => match &node.kind {
+                        NodeKind::File(content) => content.is_empty(),
+                        NodeKind::Dir => shell.vfs.children(&path).is_empty(),
+                    },
+                };
+                passes != *negated
+            });
+            if all_pass {
+                if relative.is_empty() {
+                    stdout.push_str(&format!("{}\n", root));
+                } else {
+                    stdout.push_str(&format!("{}\n", display_join(&root, relative)));
+                }
+            }
+        }
+    }
+    finish(stdout, stderr)
+}
+
+/// Implements both `head` and `tail`.
+fn head_tail(shell: &Shell, program: &str, args: &[String], stdin: Option<&str>) -> Output {
+    let args: Vec<String> = args.iter()
+        .map(|arg| match arg.strip_prefix('-') {
+            Some(num) if !num.is_empty() && num.chars().all(|c| c.is_ascii_digit()) => format!("-n{}", num),
+            _ => arg.clone(),
+        })
+        .collect();
+    let opts: ParsedArgs = match options(program, &args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let count: usize = match opts.value("-n").unwrap_or("10").trim_start_matches('+').parse() {
+        Ok(count) => count,
+        Err(_) => return Output::err(format!("{}: invalid number of lines\n", program)),
+    };
+    let mut stderr: String = String::new();
+    let sources: Vec<(String, String)> = inputs(shell, program, &opts.operands, stdin, &mut stderr);
+    let mut stdout: String = String::new();
+    for (i, (name, content)) in sources.iter().enumerate() {
+        if sources.len() > 1 {
+            stdout.push_str(&format!("{}==> {} <==\n", if i > 0 { "\n" } else { "" }, name));
+        }
+        let lines: Vec<&str> = content.lines().collect();
+        let selected: &[&str] = if program == "head" {
+            &lines[..count.min(lines.len())]
+        } else {
+            &lines[lines.len().saturating_sub(count)..]
+        };
+        for line in selected {
+            stdout.push_str(line);
+            stdout.push('\n');
+        }
+    }
+    finish(stdout, stderr)
+}
+
+fn wc(shell: &Shell, args: &[String], stdin: Option<&str>) -> Output {
+    let opts: ParsedArgs = match options("wc", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let all: bool = !opts.has("-l") && !opts.has("-w") && !opts.has("-c") && !opts.has("-m");
+    let mut stderr: String = String::new();
+    let sources: Vec<(String, String)> = inputs(shell, "wc", &opts.operands, stdin, &mut stderr);
+    let mut stdout: String = String::new();
+    for (name, content) in &sources {
+        let mut counts: Vec<String> = Vec::new();
+        if all || opts.has("-l") {
+            counts.push(content.matches('\n').count().to_string());
+        }
+        if all || opts.has("-w") {
+            counts.push(content.split_whitespace().count().to_string());
+        }
+        if all || opts.has("-c") || opts.has("-m") {
+            counts.push(content.len().to_string());
+        }
+        if !opts.operands.is_empty() {
+            counts.push(name.clone());
+        }
+        stdout.push_str(&counts.join(" "));
+        stdout.push('\n');
+    }
+    finish(stdout, stderr)
+}
+
+fn sort(shell: &Shell, args: &[String], stdin: Option<&str>) -> Output {
+    let opts: ParsedArgs = match options("sort", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let mut stderr: String = String::new();
+    let content: String = inputs(shell, "sort", &opts.operands, stdin, &mut stderr)
+        .into_iter()
+        .map(|(_, content)| content)
+        .collect();
+    let mut lines: Vec<&str> = content.lines().collect();
+    if opts.has("-n") {
+        let number = |line: &str| line.split_whitespace().next().and_then(|n| n.parse::<f64>().ok()).unwrap_or(0.0);
+        lines.sort_by(|a, b| number(a).partial_cmp(&number(b)).unwrap_or(std::cmp::Ordering::Equal));
+    } else if opts.has("-f") {
+        lines.sort_by_key(|line| line.to_lowercase());
+    } else {
+        lines.sort();
+    }
+    if opts.has("-u") {
+        lines.dedup();
+    }
+    if opts.has("-r") {
+        lines.reverse();
+    }
+    finish(lines.iter().map(|line| format!("{}\n", line)).collect(), stderr)
+}
+
+fn uniq(shell: &Shell, args: &[String], stdin: Option<&str>) -> Output {
+    let opts: ParsedArgs = match options("uniq", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let mut stderr: String = String::new();
+    let content: String = inputs(shell, "uniq", &opts.operands, stdin, &mut stderr)
+        .into_iter()
+        .map(|(_, content)| content)
+        .collect();
+    let mut groups: Vec<(usize, &str)> = Vec::new();
+    for line in content.lines() {
+        match groups.last_mut() {
+            Some((count, last)) if *last == line => *count += 1,
+            _ => groups.push((1, line)),
+        }
+    }
+    let stdout: String = groups.iter()
+        .filter(|(count, _)| (!opts.has("-d") || *count > 1) && (!opts.has("-u") || *count == 1))
+        .map(|(count, line)| if opts.has("-c") { format!("{:>7} {}\n", count, line) } else { format!("{}\n", line) })
+        .collect();
+    finish(stdout, stderr)
+}
+
+fn umask(shell: &mut Shell, args: &[String]) -> Output {
+    match args.iter().find(|arg| !arg.starts_with('-')) {
+        Some(mask) => match u32::from_str_radix(mask, 8) {
+            Ok(mask) if mask <= 0o777 => {
+                shell.umask = mask;
+                Output::default()
+            },
+            _ => Output::err(format!("bash: umask: {}: octal number out of range\n", mask)),
+        },
+        None if args.iter().any(|arg| arg == "-S") => {
+            let allowed: u32 = !shell.umask & 0o777;
+            let part = |shift: u32| {
+                let bits: u32 = (allowed >> shift) & 0o7;
+                format!("{}{}{}",
+                    if bits & 4 != 0 { "r" } else { "" },
+                    if bits & 2 != 0 { "w" } else { "" },
+                    if bits & 1 != 0 { "x" } else { "" })
+            };
+            Output::ok(format!("u={},g={},o={}\n", part(6), part(3), part(0)))
+        },
+        None => Output::ok(format!("{:04o}\n", shell.umask)),
+    }
+}
+
+fn sudo(shell: &mut Shell, args: &[String], stdin: Option<&str>) -> Output {
+    if args.is_empty() {
+        return Output::err(String::from("usage: sudo command\n"));
+    }
+    let user: String = std::mem::replace(&mut shell.user, String::from("root"));
+    let output: Output = run(shell, args, stdin);
+    shell.user = user;
+    output
+}
+
+fn ps(shell: &Shell, args: &[String]) -> Output {
+    let bsd: bool = args.iter().any(|arg| !arg.starts_with('-') && arg.contains(['a', 'x']));
+    let opts: ParsedArgs = match options("ps", &args.iter().filter(|arg| arg.starts_with('-')).cloned().collect::<Vec<String>>()) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let everyone: bool = bsd || opts.has("-e") || opts.has("-A");
+    let mut processes: Vec<&Process> = shell.processes.iter()
+        .filter(|p| everyone || p.user == shell.user)
+        .filter(|p| opts.value("-u").is_none_or(|user| p.user == user))
+        .filter(|p| opts.value("-C").is_none_or(|name| p.command.split_whitespace().next() == Some(name)))
+        .filter(|p| opts.value("-p").is_none_or(|pid| pid.split(',').any(|pid| pid == p.pid.to_string())))
+        .collect();
+    processes.sort_by_key(|p| p.pid);
+
+    let mut stdout: String = String::new();
+    if bsd {
+        stdout.push_str("USER         PID %CPU %MEM    VSZ   RSS TTY      STAT START   TIME COMMAND\n");
+        for p in processes {
+            stdout.push_str(&format!("{:<10} {:>5}  0.0  0.1  10000  4000 ?        Ss   00:00   0:00 {}\n", p.user, p.pid, p.command));
+        }
+    } else if opts.has("-f") || opts.has("-F") {
+        stdout.push_str("UID          PID    PPID  C STIME TTY          TIME CMD\n");
+        for p in processes {
+            stdout.push_str(&format!("{:<10} {:>5} {:>7}  0 00:00 ?        00:00:00 {}\n", p.user, p.pid, if p.pid == 1 { 0 } else { 1 }, p.command));
+        }
+    } else {
+        stdout.push_str("    PID TTY          TIME CMD\n");
+        for p in processes {
+            let name: &str = p.command.split_whitespace().next().unwrap_or("");
+            stdout.push_str(&format!("{:>7} ?        00:00:00 {}\n", p.pid, file_name(name)));
+        }
+    }
+    Output::ok(stdout)
+}
+
+fn kill(shell: &mut Shell, args: &[String]) -> Output {
+    let mut stderr: String = String::new();
+    let mut skip_next: bool = false;
+    for arg in args {
+        if skip_next {
+            skip_next = false;
+            continue;
+        }
+        if arg == "-s" {
+            skip_next = true;
+            continue;
+        }
+        if arg.starts_with('-') {
+            continue;
+        }
+        let pid: u32 = match arg.parse() {
+            Ok(pid) => pid,
+            Err(_) => {
+                stderr.push_str(&format!("bash: kill: {}: arguments must be process or job IDs\n", arg));
+                continue;
+            },
+        };
+        match shell.processes.iter().position(|p| p.pid == pid) {
+            Some(i) if shell.user != "root" && shell.processes[i].user != shell.user => {
+                stderr.push_str(&format!("bash: kill: ({}) - Operation not permitted\n", pid));
+            },
+            Some(i) => {
+                shell.processes.remove(i);
+            },
+            None => stderr.push_str(&format!("bash: kill: ({}) - No such process\n", pid)),
+        }
+    }
+    finish(String::new(), stderr)
+}
+
+/// The services that answer HTTP requests made with `curl` to the local machine.
+const WEB_SERVERS: &[&str] = &["nginx", "apache2", "httpd"];
+
+/// The directory that the simulated web servers serve files from.
+const WEB_ROOT: &str = "/var/www/html";
+
+/// Finds the service named by `unit`, which may include the `.service` suffix.
+fn find_service(shell: &Shell, unit: &str) -> Option<usize> {
+    let name: &str = unit.strip_suffix(".service").unwrap_or(unit);
+    shell.services.iter().position(|service| service.name == name)
+}
+
+/// Starts or stops the service at index `i`, adding or removing its process.
+fn set_active(shell: &mut Shell, i: usize, active: bool) {
+    let command: String = format!("/usr/sbin/{}", shell.services[i].name);
+    shell.services[i].active = active;
+    shell.processes.retain(|p| p.command != command);
+    if active {
+        let pid: u32 = shell.processes.iter().map(|p| p.pid).max().unwrap_or(1) + 1;
+        shell.processes.push(Process { pid, user: String::from("root"), command });
+    }
+}
+
+fn systemctl(shell: &mut Shell, args: &[String]) -> Output {
+    let opts: ParsedArgs = match options("systemctl", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let (verb, units) = match opts.operands.split_first() {
+        Some((verb, units)) => (verb.as_str(), units),
+        None => ("list-units", &[] as &[String]),
+    };
+    let mut stdout: String = String::new();
+    let mut stderr: String = String::new();
+    match verb {
+        "list-units" => {
+            stdout.push_str("  UNIT                     LOAD   ACTIVE   SUB     DESCRIPTION\n");
+            let mut services: Vec<&Service> = shell.services.iter()
+                .filter(|service| service.active || opts.has("-a"))
+                .collect();
+            services.sort_by(|a, b| a.name.cmp(&b.name));
+            for service in &services {
+                let (active, sub) = if service.active { ("active", "running") } else { ("inactive", "dead") };
+                let unit: String = format!("{}.service", service.name);
+                stdout.push_str(&format!("  {:<24} loaded {:<8} {:<7} {}\n", unit, active, sub, service.name));
+            }
+            stdout.push_str(&format!("\n{} loaded units listed.\n", services.len()));
+            Output::ok(stdout)
+        },
+        "start" | "stop" | "restart" | "reload" | "enable" | "disable" => {
+            if units.is_empty() {
+                return Output::err(String::from("Too few arguments.\n"));
+            }
+            for unit in units {
+                let unit: String = format!("{}.service", unit.trim_end_matches(".service"));
+                if shell.user != "root" {
+                    stderr.push_str(&format!(
+                        "Failed to {} {}: Interactive authentication required.\nSee system logs and \
+                        'systemctl status {}' for details.\n", verb, unit, unit,
+                    ));
+                    continue;
+                }
+                let i: usize = match find_service(shell, &unit) {
+                    Some(i) => i,
+                    None => {
+                        stderr.push_str(&format!("Failed to {} {}: Unit {} not found.\n", verb, unit, unit));
+                        continue;
+                    },
+                };
+                match verb {
+                    "start" => set_active(shell, i, true),
+                    "stop" => set_active(shell, i, false),
+                    "restart" => {
+                        set_active(shell, i, false);
+                        set_active(shell, i, true);
+                    },
+                    "reload" if !shell.services[i].active => {
+                        stderr.push_str(&format!("Job for {} failed because the unit is not active.\n", unit));
+                    },
+                    "enable" => {
+                        if !shell.services[i].enabled {
+                            stdout.push_str(&format!(
+                                "Created symlink /etc/systemd/system/multi-user.target.wants/{0} \u{2192} \
+                                /lib/systemd/system/{0}.\n", unit,
+                            ));
+                        }
+                        shell.services[i].enabled = true;
+                        if opts.has("--now") {
+                            set_active(shell, i, true);
+                        }
+                    },
+                    "disable" => {
+                        if shell.services[i].enabled {
+                            stdout.push_str(&format!("Removed /etc/systemd/system/multi-user.target.wants/{}.\n", unit));
+                        }
+                        shell.services[i].enabled = false;
+                        if opts.has("--now") {
+                            set_active(shell, i, false);
+                        }
+                    },
+                    _ => {},
+                }
+            }
+            finish(stdout, stderr)
+        },
+        "status" => {
+            let mut status: i32 = 0;
+            for unit in units {
+                let unit: String = format!("{}.service", unit.trim_end_matches(".service"));
+                let service: &Service = match find_service(shell, &unit) {
+                    Some(i) => &shell.services[i],
+                    None => {
+                        stderr.push_str(&format!("Unit {} could not be found.\n", unit));
+                        status = 4;
+                        continue;
+                    },
+                };
+                let enabled: &str = if service.enabled { "enabled" } else { "disabled" };
+                stdout.push_str(&format!("\u{25cf} {} - {}\n", unit, service.name));
+                stdout.push_str(&format!(
+                    "     Loaded: loaded (/lib/systemd/system/{}; {}; vendor preset: enabled)\n", unit, enabled,
+                ));
+                let command: String = format!("/usr/sbin/{}", service.name);
+                match shell.processes.iter().find(|p| p.command == command) {
+                    Some(process) if service.active => {
+                        stdout.push_str("     Active: active (running)\n");
+                        stdout.push_str(&format!("   Main PID: {} ({})\n", process.pid, service.name));
+                    },
+                    _ => {
+                        stdout.push_str("     Active: inactive (dead)\n");
+                        status = status.max(3);
+                    },
+                }
+            }
+            Output { stdout, stderr, status }
+        },
+        "is-active" | "is-enabled" => {
+            let mut status: i32 = 0;
+            for unit in units {
+                let service: Option<&Service> = find_service(shell, unit).map(|i| &shell.services[i]);
+                let (state, ok) = match (verb, service) {
+                    ("is-active", Some(service)) if service.active => ("active", true),
+                    ("is-active", _) => ("inactive", false),
+                    (_, Some(service)) if service.enabled => ("enabled", true),
+                    (_, Some(_)) => ("disabled", false),
+                    (_, None) => {
+                        stderr.push_str(&format!(
+                            "Failed to get unit file state for {}.service: No such file or directory\n",
+                            unit.trim_end_matches(".service"),
+                        ));
+                        status = if verb == "is-active" { 3 } else { 1 };
+                        continue;
+                    },
+                };
+                stdout.push_str(&format!("{}\n", state));
+                if !ok {
+                    status = if verb == "is-active" { 3 } else { 1 };
+                }
+            }
+            Output { stdout, stderr, status }
+        },
+        _ => Output::err(format!("Unknown command verb {}.\n", verb)),
+    }
+}
+
+/// The SysV-style `service NAME COMMAND`, which is passed on to `systemctl`.
+fn service(shell: &mut Shell, args: &[String]) -> Output {
+    match args {
+        [name, command] => systemctl(shell, &[command.clone(), name.clone()]),
+        _ => Output::err(String::from(
+            "Usage: service < option > | --status-all | [ service_name [ command | --full-restart ] ]\n",
+        )),
+    }
+}
+
+/// Requests a page from the web server on the local machine, which is served from `WEB_ROOT` by
+/// whichever of the `WEB_SERVERS` is running.
+fn curl(shell: &Shell, args: &[String]) -> Output {
+    let opts: ParsedArgs = match options("curl", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let url: &str = match opts.operands.first() {
+        Some(url) => url,
+        None => return Output {
+            stdout: String::new(),
+            stderr: String::from("curl: try 'curl --help' or 'curl --manual' for more information\n"),
+            status: 2,
+        },
+    };
+    let rest: &str = url.strip_prefix("http://").unwrap_or(url);
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let host: &str = authority.split(':').next().unwrap_or(authority);
+    if !["localhost", "127.0.0.1", shell.hostname.as_str()].contains(&host) {
+        return Output {
+            stdout: String::new(),
+            stderr: format!("curl: (6) Could not resolve host: {}\n", host),
+            status: 6,
+        };
+    }
+    let server: &str = match WEB_SERVERS.iter().find(|name| {
+        shell.services.iter().any(|service| service.name == **name && service.active)
+    }) {
+        Some(server) => server,
+        None => return Output {
+            stdout: String::new(),
+            stderr: format!("curl: (7) Failed to connect to {} port 80 after 0 ms: Connection refused\n", host),
+            status: 7,
+        },
+    };
+
+    let mut file: String = Vfs::normalize(WEB_ROOT, path.trim_start_matches('/'));
+    if shell.vfs.is_dir(&file) {
+        file = join(&file, "index.html");
+    }
+    let (code, body) = match shell.vfs.get(&file).map(|node| (&node.kind, node.mode)) {
+        Some((NodeKind::File(content), mode)) if mode & 0o004 != 0 => (String::from("200 OK"), content.clone()),
+        Some((NodeKind::File(_), _)) => (String::from("403 Forbidden"), String::new()),
+        _ => (String::from("404 Not Found"), String::new()),
+    };
+    let body: String = if body.is_empty() && !code.starts_with("200") {
+        format!(
+            "<html>\n<head><title>{0}</title></head>\n<body>\n<center><h1>{0}</h1></center>\n\
+            <hr><center>{1}</center>\n</body>\n</html>\n", code, server,
+        )
+    } else {
+        body
+    };
+    if opts.has("-I") {
+        Output::ok(format!(
+            "HTTP/1.1 {}\nServer: {}\nContent-Type: text/html\nContent-Length: {}\n\n", code, server, body.len(),
+        ))
+    } else {
+        Output::ok(body)
+    }
+}
+
+fn tar(shell: &mut Shell, args: &[String]) -> Output {
+    let opts: ParsedArgs = match options("tar", args) {
+        Ok(opts) => opts,
+        Err(output) => return output,
+    };
+    let archive: &str = match opts.value("-f") {
+        Some(archive) => archive,
+        None => return Output::err(String::from("tar: Refusing to read archive contents from terminal (missing -f option?)\n")),
+    };
+    let archive_path: String = shell.resolve(archive);
+    let base: String = match opts.value("-C") {
+        Some(dir) => shell.resolve(dir),
+        None => shell.cwd.clone(),
+    };
+    if !shell.vfs.is_dir(&base) {
+        return Output::err(format!("tar: {}: Cannot open: No such file or directory\n", opts.value("-C").unwrap_or(".")));
+    }
+    let verbose: bool = opts.has("-v");
+    let mut stdout: String = String::new();
+    let mut stderr: String = String::new();
+
+    if opts.has("-c") {
+        if opts.operands.is_empty() {
+            return Output::err(String::from("tar: Cowardly refusing to create an empty archive\n"));
+        }
+        let mut lines: Vec<String> = vec![String::from(TAR_HEADER), String::from("cwd /")];
+        for operand in &opts.operands {
+            let path: String = Vfs::normalize(&base, operand);
+            if !shell.vfs.exists(&path) {
+                stderr.push_str(&format!("tar: {}: Cannot stat: No such file or directory\n", operand));
+                continue;
+            }
+            let member: &str = if operand.starts_with('/') {
+                stderr.push_str("tar: Removing leading `/' from member names\n");
+                operand.trim_start_matches('/')
+            } else {
+                operand.as_str()
+            };
+            for descendant in shell.vfs.descendants(&path) {
+                let name: String = format!("{}{}", member.trim_end_matches('/'), &descendant[path.len()..]);
+                let node: &Node = shell.vfs.get(&descendant).expect("Descendant should exist");
+                if verbose {
+                    stdout.push_str(&format!("{}{}\n", name, if node.is_dir() { "/" } else { "" }));
+                }
+                lines.push(state::node_entry(&Vfs::normalize("/", &name), node));
+            }
+        }
+        let template: Node = shell.new_node(NodeKind::File(String::new()), 0o666);
+        if let Err(e) = shell.vfs.write_file(&archive_path, &(lines.join("\n") + "\n"), false, template) {
+            return Output::err(format!("tar: {}: Cannot open: {}\n", archive, e));
+        }
+        return finish(stdout, stderr);
+    }
+
+    if !opts.has("-x") && !opts.has("-t") {
+        return Output::err(String::from("tar: You must specify one of the '-Acdtrux', '--delete' or '--test-label' options\n"));
+    }
+    let contents: Shell = match shell.vfs.read_file(&archive_path) {
+        Ok(content) if content.starts_with(TAR_HEADER) => match state::load(content) {
+            Ok(contents) => contents,
+            Err(_) => return Output::err(String::from("tar: This does not look like a tar archive\n")),
+        },
+        Ok(_) => return Output::err(String::from("tar: This does not look like a tar archive\n")),
+        Err(e) => return Output::err(format!("tar: {}: Cannot open: {}\n", archive, e)),
+    };
+    for path in contents.vfs.paths().filter(|path| path.as_str() != "/") {
+        let node: &Node = contents.vfs.get(path).expect("Archived path should exist");
+        let name: &str = &path[1..];
+        if opts.has("-t") {
+            if verbose {
+                stdout.push_str(&format!("{} {}/{} {:>8} 1970-01-01 00:00 {}\n", node.mode_string(), node.owner, node.group, node.size(), name));
+            } else {
+                stdout.push_str(&format!("{}{}\n", name, if node.is_dir() { "/" } else { "" }));
+            }
+            continue;
+        }
+        if verbose {
+            stdout.push_str(&format!("{}{}\n", name, if node.is_dir() { "/" } else { "" }));
+        }
+        let mut node: Node = node.clone();
+        if shell.user != "root" {
+            node.owner = shell.user.clone();
+            node.group = shell.user.clone();
+        }
+        shell.vfs.insert(&join(&base, name), node);
+    }
+    finish(stdout, stderr)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shell::Shell;
+    use crate::shell::state::{check, parse_expectations};
+
+    const SCRIPT: &str = "user student\nfile /home/student/backup.sh 644 student:student \"tar -czf /tmp/home.tar.gz ~\\n\"";
+
+    /// Runs each of `lines` in a fresh shell loaded from `SCRIPT`, returning the shell.
+    fn run(lines: &[&str]) -> Shell {
+        let mut shell: Shell = Shell::from_state(SCRIPT).unwrap();
+        for line in lines {
+            let output = shell.execute(line);
+            assert_eq!(output.stderr, "", "{}", line);
+        }
+        shell
+    }
+
+    /// The problems found checking `shell` against the `expected` state spec.
+    fn problems(shell: &Shell, expected: &str) -> Vec<String> {
+        check(shell, &parse_expectations(expected).unwrap())
+    }
+
+    #[test]
+    fn octal_and_symbolic_chmod_reach_the_same_state() {
+        let expected: &str = "file /home/student/backup.sh 750 student:student";
+        assert!(problems(&run(&["chmod 750 backup.sh"]), expected).is_empty());
+        assert!(problems(&run(&["chmod u=rwx,g=rx,o= ~/backup.sh"]), expected).is_empty());
+        assert!(problems(&run(&["chmod 0750 /home/student/backup.sh"]), expected).is_empty());
+        assert!(problems(&run(&["chmod u+x,g+x,o-r backup.sh"]), expected).is_empty());
+        assert_eq!(
+            problems(&run(&["chmod 755 backup.sh"]), expected),
+            vec![String::from("/home/student/backup.sh should have mode 750 but has 755")],
+        );
+    }
+
+    #[test]
+    fn relative_modes_change_only_the_bits_they_name() {
+        let shell: Shell = run(&["chmod 664 backup.sh", "chmod +x backup.sh", "chmod g-w backup.sh"]);
+        assert!(problems(&shell, "file /home/student/backup.sh 755").is_empty());
+        let shell: Shell = run(&["chmod a=r,u+w backup.sh"]);
+        assert!(problems(&shell, "file /home/student/backup.sh 644").is_empty());
+    }
+
+    #[test]
+    fn the_umask_limits_modes_without_a_who() {
+        let shell: Shell = run(&["umask 077", "chmod +x backup.sh", "touch new.txt", "mkdir new"]);
+        let expected: &str = "file /home/student/backup.sh 744\nfile /home/student/new.txt 600\ndir /home/student/new 700";
+        assert!(problems(&shell, expected).is_empty());
+        let shell: Shell = run(&["umask 077", "chmod a+x backup.sh"]);
+        assert!(problems(&shell, "file /home/student/backup.sh 755").is_empty());
+    }
+
+    #[test]
+    fn invalid_modes_leave_the_file_alone() {
+        let mut shell: Shell = Shell::from_state(SCRIPT).unwrap();
+        for mode in ["75a", "77777", "u*x", "u"] {
+            assert_ne!(shell.execute(&format!("chmod {} backup.sh", mode)).stderr, "", "{}", mode);
+        }
+        assert!(problems(&shell, "file /home/student/backup.sh 644").is_empty());
+    }
+
+    #[test]
+    fn grep_recursive_from_root_keeps_full_paths() {
+        let mut shell: Shell = Shell::from_state("user student\nfile /etc/motd 644 root:root \"hello\\n\"").unwrap();
+        let output = shell.execute("grep -r hello /");
+        assert_eq!(output.stdout, "/etc/motd:hello\n");
+        let output = shell.execute("grep -r hello /etc");
+        assert_eq!(output.stdout, "/etc/motd:hello\n");
+    }
+}
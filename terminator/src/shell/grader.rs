@@ -0,0 +1,325 @@
+use crate::shell::lexer::{parse, Connector, RedirectKind, SimpleCommand};
+use crate::shell::options::{canonical_flag, parse_args, ParsedArgs};
+use terminator::question::RuleKind;
+
+/// How many of a program's leading operands name a subcommand rather than an argument, e.g.,
+/// `restart` in `systemctl restart sshd` or `addr show` in `ip addr show`.
+const SUBCOMMAND_DEPTHS: &[(&str, usize)] = &[
+    ("systemctl", 1), ("git", 1), ("apt", 1), ("apt-get", 1), ("dnf", 1), ("yum", 1), ("docker", 1),
+    ("hostnamectl", 1), ("timedatectl", 1), ("ufw", 1), ("ip", 2), ("nmcli", 2),
+];
+
+/// Abbreviations that a program accepts for its subcommands, as `(program, abbreviation, full)`.
+const SUBCOMMAND_ALIASES: &[(&str, &str, &str)] = &[
+    ("ip", "a", "address"), ("ip", "addr", "address"), ("ip", "r", "route"), ("ip", "ro", "route"),
+    ("ip", "l", "link"), ("ip", "n", "neigh"), ("ip", "neighbor", "neigh"), ("ip", "neighbour", "neigh"),
+    ("ip", "sh", "show"), ("ip", "s", "show"), ("ip", "ls", "show"), ("ip", "list", "show"),
+    ("ip", "lst", "show"), ("ip", "d", "delete"), ("ip", "del", "delete"),
+    ("nmcli", "c", "connection"), ("nmcli", "con", "connection"), ("nmcli", "d", "device"),
+    ("nmcli", "dev", "device"), ("nmcli", "g", "general"), ("nmcli", "r", "radio"),
+];
+
+/// The subcommand a program runs when it's only given the first of its subcommands, e.g., `ip a`
+/// is the same as `ip address show`.
+const DEFAULT_SUBCOMMANDS: &[(&str, &str)] = &[("ip", "show"), ("nmcli", "show")];
+
+/// A single row from the `CommandFlagRules` table. A rule without a `program` applies to the
+/// first command in the answer; otherwise it applies to every command that runs `program`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagRule {
+    pub program: Option<String>,
+    pub flag: String,
+    pub kind: RuleKind,
+}
+
+/// One command from a command line, normalized so that equivalent ways of writing it compare
+/// equal: clustered, separate and long flags are reduced to their canonical names, and flag
+/// order is ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCommand {
+    /// Whether the command is run through `sudo`.
+    pub sudo: bool,
+    pub program: String,
+    pub subcommands: Vec<String>,
+    /// The canonical name and value of each flag, sorted and without duplicates.
+    pub flags: Vec<(String, Option<String>)>,
+    pub operands: Vec<String>,
+    pub redirects: Vec<(RedirectKind, Option<String>)>,
+}
+
+impl ParsedCommand {
+    /// Normalizes a command from the lexer.
+    fn from_simple(command: &SimpleCommand) -> Result<Self, String> {
+        let mut words: Vec<String> = command.words.iter().map(|word| word.text.clone()).collect();
+        let mut sudo: bool = false;
+        if words.first().is_some_and(|word| word == "sudo") {
+            sudo = true;
+            let mut i: usize = 1;
+            while i < words.len() && words[i].starts_with('-') {
+                let takes_value: bool = matches!(words[i].as_str(), "-u" | "-g" | "--user" | "--group");
+                i += if takes_value { 2 } else { 1 };
+            }
+            words.drain(..i.min(words.len()));
+            if words.is_empty() {
+                return Err(String::from("sudo needs a command to run"));
+            }
+        }
+
+        let program: String = match words[0].rsplit_once('/') {
+            Some((_, name)) if !name.is_empty() => name.to_string(),
+            _ => words[0].clone(),
+        };
+        let ParsedArgs { mut flags, mut operands } = parse_args(&program, &words[1..])?;
+        flags.sort();
+        flags.dedup();
+
+        let depth: usize = SUBCOMMAND_DEPTHS.iter()
+            .find(|(name, _)| *name == program)
+            .map_or(0, |(_, depth)| *depth);
+        let taken: usize = depth.min(operands.len());
+        let mut subcommands: Vec<String> = operands.drain(..taken)
+            .map(|sub| {
+                SUBCOMMAND_ALIASES.iter()
+                    .find(|(name, alias, _)| *name == program && *alias == sub)
+                    .map_or(sub.clone(), |(_, _, full)| full.to_string())
+            })
+            .collect();
+        if subcommands.len() == 1 && depth > 1 {
+            if let Some((_, default)) = DEFAULT_SUBCOMMANDS.iter().find(|(name, _)| *name == program) {
+                subcommands.push(default.to_string());
+            }
+        }
+
+        let redirects: Vec<(RedirectKind, Option<String>)> = command.redirects.iter()
+            .map(|redirect| (redirect.kind, redirect.target.as_ref().map(|word| word.text.clone())))
+            .collect();
+        Ok(ParsedCommand { sudo, program, subcommands, flags, operands, redirects })
+    }
+
+    /// The program and its subcommands, e.g., `systemctl restart`.
+    fn name(&self) -> String {
+        let mut name: String = self.program.clone();
+        for sub in &self.subcommands {
+            name.push(' ');
+            name.push_str(sub);
+        }
+        name
+    }
+}
+
+/// A full command line: each pipeline with the `Connector` that joins it to the previous one.
+pub type ParsedLine = Vec<(Connector, Vec<ParsedCommand>)>;
+
+/// Parses and normalizes a command line for grading.
+pub fn parse_line(line: &str) -> Result<ParsedLine, String> {
+    parse(line)?
+        .iter()
+        .map(|(connector, pipeline)| {
+            let commands = pipeline.commands.iter()
+                .map(ParsedCommand::from_simple)
+                .collect::<Result<Vec<ParsedCommand>, String>>()?;
+            Ok((*connector, commands))
+        })
+        .collect()
+}
+
+/// Compares the flags of one command in the response against the expected command and the
+/// rules that apply to it, adding a description of every difference to `problems`.
+fn compare_flags(expected: &ParsedCommand, actual: &ParsedCommand, rules: &[&FlagRule], problems: &mut Vec<String>) {
+    let rule_for = |flag: &str| rules.iter()
+        .find(|rule| canonical_flag(&expected.program, &rule.flag) == flag)
+        .map(|rule| rule.kind);
+
+    let mut required: Vec<(String, Option<String>)> = expected.flags.iter()
+        .filter(|(flag, _)| !matches!(rule_for(flag), Some(RuleKind::Optional | RuleKind::Forbidden)))
+        .cloned()
+        .collect();
+    for rule in rules.iter().filter(|rule| rule.kind == RuleKind::Required) {
+        let flag: String = canonical_flag(&expected.program, &rule.flag);
+        if !required.iter().any(|(name, _)| *name == flag) {
+            required.push((flag, None));
+        }
+    }
+
+    for (flag, value) in &required {
+        let given: Vec<&Option<String>> = actual.flags.iter()
+            .filter(|(name, _)| name == flag)
+            .map(|(_, value)| value)
+            .collect();
+        match (given.is_empty(), value) {
+            (true, _) => problems.push(format!("{} is missing the {} flag", expected.name(), flag)),
+            (false, Some(value)) if !given.contains(&&Some(value.clone())) => {
+                problems.push(format!("{} has the wrong value for {}", expected.name(), flag));
+            },
+            _ => {},
+        }
+    }
+
+    for (flag, _) in &actual.flags {
+        match rule_for(flag) {
+            Some(RuleKind::Forbidden) => {
+                problems.push(format!("{} shouldn't be given the {} flag", expected.name(), flag));
+            },
+            Some(_) => {},
+            None if !expected.flags.iter().any(|(name, _)| name == flag) => {
+                problems.push(format!("{} doesn't need the {} flag", expected.name(), flag));
+            },
+            None => {},
+        }
+    }
+}
+
+/// Compares a parsed `response` against one `expected` command line, returning a description of
+/// every difference. An empty result means the response is equivalent.
+fn compare(expected: &ParsedLine, response: &ParsedLine, rules: &[FlagRule]) -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+    let expected_commands: Vec<&ParsedCommand> = expected.iter().flat_map(|(_, commands)| commands).collect();
+    let actual_commands: Vec<&ParsedCommand> = response.iter().flat_map(|(_, commands)| commands).collect();
+    let shape = |line: &ParsedLine| line.iter()
+        .map(|(connector, commands)| (*connector, commands.len()))
+        .collect::<Vec<(Connector, usize)>>();
+    if shape(expected) != shape(response) {
+        problems.push(format!(
+            "the answer should be made of {} command(s) joined the same way, but {} were given",
+            expected_commands.len(),
+            actual_commands.len(),
+        ));
+        return problems;
+    }
+
+    for (i, (expected, actual)) in expected_commands.iter().zip(&actual_commands).enumerate() {
+        if expected.name() != actual.name() {
+            problems.push(format!("expected {} but found {}", expected.name(), actual.name()));
+            continue;
+        }
+        if expected.sudo && !actual.sudo {
+            problems.push(format!("{} needs to be run with sudo", expected.name()));
+        } else if !expected.sudo && actual.sudo {
+            problems.push(format!("{} doesn't need sudo", expected.name()));
+        }
+
+        let applicable: Vec<&FlagRule> = rules.iter()
+            .filter(|rule| match &rule.program {
+                Some(program) => *program == expected.program,
+                None => i == 0,
+            })
+            .collect();
+        compare_flags(expected, actual, &applicable, &mut problems);
+
+        if expected.operands != actual.operands {
+            problems.push(format!("the arguments to {} aren't right", expected.name()));
+        }
+        if expected.redirects != actual.redirects {
+            problems.push(format!("the redirections for {} aren't right", expected.name()));
+        }
+    }
+    problems
+}
+
+/// Grades a command line `response` against the `CorrectAnswer` of a command question. Each
+/// line of `answers` is an acceptable answer; the response is correct if it's equivalent to any
+/// of them under the question's `rules`. If it isn't, the problems found when comparing against
+/// the closest answer are returned.
+///
+/// Flags may be clustered, separated, written in long form, or given in any order, so
+/// `ls -la`, `ls -al`, `ls -l -a` and `ls --all -l` are all the same. Quoting is removed before
+/// comparing, so `grep "error" log` matches `grep error log`.
+pub fn grade(answers: &str, response: &str, rules: &[FlagRule]) -> Result<(), Vec<String>> {
+    let response: ParsedLine = match parse_line(response) {
+        Ok(line) if !line.is_empty() => line,
+        Ok(_) => return Err(vec![String::from("no command was entered")]),
+        Err(e) => return Err(vec![e]),
+    };
+
+    let mut closest: Option<Vec<String>> = None;
+    for answer in answers.lines().map(str::trim).filter(|answer| !answer.is_empty()) {
+        let expected: ParsedLine = match parse_line(answer) {
+            Ok(line) => line,
+            Err(e) => return Err(vec![format!("the correct answer can't be parsed ({})", e)]),
+        };
+        let problems: Vec<String> = compare(&expected, &response, rules);
+        if problems.is_empty() {
+            return Ok(());
+        }
+        if closest.as_ref().is_none_or(|closest| problems.len() < closest.len()) {
+            closest = Some(problems);
+        }
+    }
+    Err(closest.unwrap_or_else(|| vec![String::from("the question has no correct answer")]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(program: Option<&str>, flag: &str, kind: RuleKind) -> FlagRule {
+        FlagRule { program: program.map(String::from), flag: String::from(flag), kind }
+    }
+
+    #[test]
+    fn flags_can_be_clustered_split_long_or_reordered() {
+        for response in ["ls -la", "ls -al", "ls -l -a", "ls --all -l", "ls -a -l -a"] {
+            assert_eq!(grade("ls -la", response, &[]), Ok(()), "{}", response);
+        }
+        assert!(grade("ls -la", "ls -l", &[]).is_err());
+        assert!(grade("ls -la", "ls -laR", &[]).is_err());
+    }
+
+    #[test]
+    fn sudo_is_stripped_but_must_match() {
+        assert_eq!(grade("sudo systemctl restart sshd", "sudo -u root systemctl restart sshd", &[]), Ok(()));
+        assert_eq!(grade("sudo chmod 750 f", "sudo /bin/chmod 750 f", &[]), Ok(()));
+        assert_eq!(grade("chmod 750 f", "sudo chmod 750 f", &[]), Err(vec![String::from("chmod doesn't need sudo")]));
+        assert_eq!(grade("sudo reboot", "reboot", &[]), Err(vec![String::from("reboot needs to be run with sudo")]));
+        assert!(grade("sudo ls", "sudo", &[]).is_err());
+    }
+
+    #[test]
+    fn subcommand_aliases_are_expanded() {
+        for response in ["ip a", "ip addr", "ip address show", "ip addr sh"] {
+            assert_eq!(grade("ip address show", response, &[]), Ok(()), "{}", response);
+        }
+        assert_eq!(grade("nmcli connection show", "nmcli c", &[]), Ok(()));
+        assert!(grade("ip address show", "ip route show", &[]).is_err());
+        assert!(grade("systemctl restart sshd", "systemctl stop sshd", &[]).is_err());
+    }
+
+    #[test]
+    fn flag_rules_require_allow_or_forbid_flags() {
+        let rules: Vec<FlagRule> = vec![
+            rule(Some("ls"), "-h", RuleKind::Optional),
+            rule(None, "-R", RuleKind::Forbidden),
+            rule(None, "--all", RuleKind::Required),
+        ];
+        assert_eq!(grade("ls -l", "ls -la", &rules), Ok(()));
+        assert_eq!(grade("ls -l", "ls -lah", &rules), Ok(()));
+        assert_eq!(grade("ls -l", "ls -l", &rules), Err(vec![String::from("ls is missing the -a flag")]));
+        assert_eq!(grade("ls -l", "ls -laR", &rules), Err(vec![String::from("ls shouldn't be given the -R flag")]));
+        assert!(grade("ls -l", "ls -lat", &rules).is_err());
+    }
+
+    #[test]
+    fn quotes_are_removed_before_comparing() {
+        assert_eq!(grade("grep error /var/log/syslog", "grep \"error\" '/var/log/syslog'", &[]), Ok(()));
+        assert_eq!(grade("grep 'a b' f", "grep a\\ b f", &[]), Ok(()));
+        assert!(grade("grep 'a b' f", "grep a b f", &[]).is_err());
+        assert!(grade("grep error f", "grep \"error f", &[]).is_err());
+    }
+
+    #[test]
+    fn pipes_and_redirects_must_have_the_same_shape() {
+        let answer: &str = "ps aux | grep sshd > procs.txt";
+        assert_eq!(grade(answer, "ps aux|grep sshd >procs.txt", &[]), Ok(()));
+        assert!(grade(answer, "ps aux | grep sshd >> procs.txt", &[]).is_err());
+        assert!(grade(answer, "ps aux | grep sshd", &[]).is_err());
+        assert!(grade(answer, "ps aux && grep sshd > procs.txt", &[]).is_err());
+        assert_eq!(grade("make 2>&1 | tee log", "make 2>&1 | tee log", &[]), Ok(()));
+    }
+
+    #[test]
+    fn any_line_of_the_answer_is_accepted() {
+        let answers: &str = "chmod 750 f\nchmod u=rwx,g=rx,o= f";
+        assert_eq!(grade(answers, "chmod u=rwx,g=rx,o= f", &[]), Ok(()));
+        assert_eq!(grade(answers, "", &[]), Err(vec![String::from("no command was entered")]));
+    }
+}
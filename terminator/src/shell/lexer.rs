@@ -0,0 +1,249 @@
+/// A single word from a command line with its quotes removed. `quoted` records whether any part
+/// of the word was quoted or escaped, since those words aren't subject to `~` or glob expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    pub text: String,
+    pub quoted: bool,
+}
+
+/// The kinds of redirection understood by the simulated shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectKind {
+    /// `> file`
+    Out,
+    /// `>> file`
+    Append,
+    /// `< file`
+    In,
+    /// `2> file`
+    ErrOut,
+    /// `2>> file`
+    ErrAppend,
+    /// `2>&1`
+    ErrToOut,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Word(Word),
+    Pipe,
+    And,
+    Or,
+    Semicolon,
+    Redirect(RedirectKind),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub kind: RedirectKind,
+    /// The file being redirected to or from; `None` for `2>&1`.
+    pub target: Option<Word>,
+}
+
+/// A program and its arguments, along with any redirections that apply to it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SimpleCommand {
+    pub words: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+}
+
+/// Commands joined by `|`, where each command's output is the next command's input.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pipeline {
+    pub commands: Vec<SimpleCommand>,
+}
+
+/// How a pipeline is joined to the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connector {
+    /// The first pipeline on the line, or one following `;`.
+    Always,
+    /// Following `&&`; only runs if the previous pipeline succeeded.
+    And,
+    /// Following `||`; only runs if the previous pipeline failed.
+    Or,
+}
+
+/// Splits a command line into tokens, handling single quotes, double quotes, backslash escapes,
+/// pipes, `&&`, `||`, `;`, redirections, and `#` comments.
+pub fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut current: String = String::new();
+    let mut in_word: bool = false;
+    let mut quoted: bool = false;
+    let mut chars = line.chars().peekable();
+
+    fn finish(tokens: &mut Vec<Token>, current: &mut String, in_word: &mut bool, quoted: &mut bool) {
+        if *in_word {
+            tokens.push(Token::Word(Word { text: std::mem::take(current), quoted: *quoted }));
+        }
+        *in_word = false;
+        *quoted = false;
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => finish(&mut tokens, &mut current, &mut in_word, &mut quoted),
+            '#' if !in_word => break,
+            '\'' => {
+                in_word = true;
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err(String::from("unexpected end of line while looking for matching `''")),
+                    }
+                }
+            },
+            '"' => {
+                in_word = true;
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.peek() {
+                            Some(&next) if next == '"' || next == '\\' || next == '$' || next == '`' => {
+                                current.push(next);
+                                chars.next();
+                            },
+                            _ => current.push('\\'),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(String::from("unexpected end of line while looking for matching `\"'")),
+                    }
+                }
+            },
+            '\\' => {
+                in_word = true;
+                quoted = true;
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            },
+            '|' => {
+                finish(&mut tokens, &mut current, &mut in_word, &mut quoted);
+                if chars.peek() == Some(&'|') {
+                    chars.next();
+                    tokens.push(Token::Or);
+                } else {
+                    tokens.push(Token::Pipe);
+                }
+            },
+            '&' => {
+                if chars.peek() == Some(&'&') {
+                    chars.next();
+                    finish(&mut tokens, &mut current, &mut in_word, &mut quoted);
+                    tokens.push(Token::And);
+                } else {
+                    return Err(String::from("background jobs (`&`) aren't supported"));
+                }
+            },
+            ';' => {
+                finish(&mut tokens, &mut current, &mut in_word, &mut quoted);
+                tokens.push(Token::Semicolon);
+            },
+            '<' => {
+                finish(&mut tokens, &mut current, &mut in_word, &mut quoted);
+                tokens.push(Token::Redirect(RedirectKind::In));
+            },
+            '>' => {
+                let is_stderr: bool = in_word && !quoted && current == "2";
+                if is_stderr {
+                    current.clear();
+                    in_word = false;
+                } else {
+                    finish(&mut tokens, &mut current, &mut in_word, &mut quoted);
+                }
+                let append: bool = chars.peek() == Some(&'>');
+                if append {
+                    chars.next();
+                }
+                let kind: RedirectKind = match (is_stderr, append) {
+                    (true, false) if chars.peek() == Some(&'&') => {
+                        chars.next();
+                        match chars.next() {
+                            Some('1') => RedirectKind::ErrToOut,
+                            _ => return Err(String::from("only `2>&1` is supported for duplicating output")),
+                        }
+                    },
+                    (true, false) => RedirectKind::ErrOut,
+                    (true, true) => RedirectKind::ErrAppend,
+                    (false, false) => RedirectKind::Out,
+                    (false, true) => RedirectKind::Append,
+                };
+                tokens.push(Token::Redirect(kind));
+            },
+            _ => {
+                in_word = true;
+                current.push(c);
+            },
+        }
+    }
+    finish(&mut tokens, &mut current, &mut in_word, &mut quoted);
+    Ok(tokens)
+}
+
+/// Parses a command line into its pipelines, each paired with the `Connector` that joins it to
+/// the previous pipeline.
+pub fn parse(line: &str) -> Result<Vec<(Connector, Pipeline)>, String> {
+    let tokens: Vec<Token> = tokenize(line)?;
+    let mut result: Vec<(Connector, Pipeline)> = Vec::new();
+    let mut connector: Connector = Connector::Always;
+    let mut pipeline: Pipeline = Pipeline::default();
+    let mut command: SimpleCommand = SimpleCommand::default();
+    let mut iter = tokens.into_iter().peekable();
+
+    fn syntax_error(token: &str) -> String {
+        format!("syntax error near unexpected token `{}'", token)
+    }
+
+    while let Some(token) = iter.next() {
+        match token {
+            Token::Word(word) => command.words.push(word),
+            Token::Redirect(RedirectKind::ErrToOut) => {
+                command.redirects.push(Redirect { kind: RedirectKind::ErrToOut, target: None });
+            },
+            Token::Redirect(kind) => match iter.next() {
+                Some(Token::Word(word)) => command.redirects.push(Redirect { kind, target: Some(word) }),
+                _ => return Err(syntax_error("newline")),
+            },
+            Token::Pipe => {
+                if command.words.is_empty() {
+                    return Err(syntax_error("|"));
+                }
+                pipeline.commands.push(std::mem::take(&mut command));
+            },
+            Token::And | Token::Or | Token::Semicolon => {
+                let next_connector: Connector = match token {
+                    Token::And => Connector::And,
+                    Token::Or => Connector::Or,
+                    _ => Connector::Always,
+                };
+                if command.words.is_empty() {
+                    if pipeline.commands.is_empty() && next_connector == Connector::Always && iter.peek().is_none() {
+                        continue;
+                    }
+                    return Err(syntax_error(match next_connector {
+                        Connector::And => "&&",
+                        Connector::Or => "||",
+                        Connector::Always => ";",
+                    }));
+                }
+                pipeline.commands.push(std::mem::take(&mut command));
+                result.push((connector, std::mem::take(&mut pipeline)));
+                connector = next_connector;
+            },
+        }
+    }
+
+    if command.words.is_empty() {
+        if !pipeline.commands.is_empty() || connector != Connector::Always {
+            return Err(syntax_error("newline"));
+        }
+    } else {
+        pipeline.commands.push(command);
+        result.push((connector, pipeline));
+    }
+    Ok(result)
+}
@@ -0,0 +1,255 @@
+pub mod commands;
+pub mod grader;
+pub mod lexer;
+pub mod options;
+pub mod state;
+pub mod vfs;
+
+use crate::shell::lexer::{parse, Connector, RedirectKind, SimpleCommand, Word};
+use crate::shell::vfs::{glob_match, join, Node, NodeKind, Vfs};
+use crate::term_io::read_line;
+
+/// A process shown by `ps` in the simulated system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Process {
+    pub pid: u32,
+    pub user: String,
+    pub command: String,
+}
+
+/// A systemd service in the simulated system, managed with `systemctl`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Service {
+    pub name: String,
+    /// Whether the service is running.
+    pub active: bool,
+    /// Whether the service starts at boot.
+    pub enabled: bool,
+}
+
+/// The result of running a command or command line.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: i32,
+}
+
+impl Output {
+    pub fn ok(stdout: String) -> Self {
+        Output { stdout, stderr: String::new(), status: 0 }
+    }
+
+    pub fn err(stderr: String) -> Self {
+        Output { stdout: String::new(), stderr, status: 1 }
+    }
+}
+
+/// A simulated Linux shell with its own in-memory filesystem and process table, used for
+/// questions that are answered by entering commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shell {
+    pub vfs: Vfs,
+    pub processes: Vec<Process>,
+    pub services: Vec<Service>,
+    pub user: String,
+    pub hostname: String,
+    pub cwd: String,
+    pub umask: u32,
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Shell {
+            vfs: Vfs::new(),
+            processes: Vec::new(),
+            services: Vec::new(),
+            user: String::from("root"),
+            hostname: String::from("terminator"),
+            cwd: String::from("/"),
+            umask: 0o022,
+        }
+    }
+}
+
+impl Shell {
+    /// Builds a shell from a state spec; see `state::load` for the format.
+    pub fn from_state(spec: &str) -> Result<Self, String> {
+        state::load(spec)
+    }
+
+    /// The current user's home directory.
+    pub fn home(&self) -> String {
+        if self.user == "root" {
+            String::from("/root")
+        } else {
+            format!("/home/{}", self.user)
+        }
+    }
+
+    /// The prompt shown before each command, such as `student@terminator:~/docs$ `.
+    pub fn prompt(&self) -> String {
+        let home: String = self.home();
+        let cwd: String = if self.cwd == home {
+            String::from("~")
+        } else if let Some(rest) = self.cwd.strip_prefix(&format!("{}/", home)) {
+            format!("~/{}", rest)
+        } else {
+            self.cwd.clone()
+        };
+        let symbol: char = if self.user == "root" { '#' } else { '$' };
+        format!("{}@{}:{}{} ", self.user, self.hostname, cwd, symbol)
+    }
+
+    /// Resolves `path` against the current directory.
+    pub fn resolve(&self, path: &str) -> String {
+        Vfs::normalize(&self.cwd, path)
+    }
+
+    /// A new node owned by the current user, with the given `mode` masked by the umask.
+    pub fn new_node(&self, kind: NodeKind, mode: u32) -> Node {
+        Node { kind, mode: mode & !self.umask, owner: self.user.clone(), group: self.user.clone() }
+    }
+
+    /// Runs a full command line, including any pipes, redirections, `&&`, `||` and `;`.
+    pub fn execute(&mut self, line: &str) -> Output {
+        let pipelines = match parse(line) {
+            Ok(pipelines) => pipelines,
+            Err(e) => return Output { stdout: String::new(), stderr: format!("bash: {}\n", e), status: 2 },
+        };
+
+        let mut result: Output = Output::default();
+        for (connector, pipeline) in pipelines {
+            let should_run: bool = match connector {
+                Connector::Always => true,
+                Connector::And => result.status == 0,
+                Connector::Or => result.status != 0,
+            };
+            if !should_run {
+                continue;
+            }
+
+            let mut input: Option<String> = None;
+            let count: usize = pipeline.commands.len();
+            for (i, command) in pipeline.commands.iter().enumerate() {
+                let output: Output = self.run_simple(command, input.take());
+                result.stderr.push_str(&output.stderr);
+                result.status = output.status;
+                if i + 1 < count {
+                    input = Some(output.stdout);
+                } else {
+                    result.stdout.push_str(&output.stdout);
+                }
+            }
+        }
+        result
+    }
+
+    /// Runs one command from a pipeline, applying its redirections.
+    fn run_simple(&mut self, command: &SimpleCommand, mut input: Option<String>) -> Output {
+        let args: Vec<String> = command.words.iter().flat_map(|word| self.expand(word)).collect();
+
+        for redirect in command.redirects.iter().filter(|r| r.kind == RedirectKind::In) {
+            let typed: &str = &redirect.target.as_ref().expect("`<` should have a target").text;
+            match self.vfs.read_file(&self.resolve(typed)) {
+                Ok(content) => input = Some(content.to_string()),
+                Err(e) => return Output::err(format!("bash: {}: {}\n", typed, e)),
+            }
+        }
+
+        let mut output: Output = commands::run(self, &args, input.as_deref());
+
+        for redirect in &command.redirects {
+            let (content, append) = match redirect.kind {
+                RedirectKind::In => continue,
+                RedirectKind::ErrToOut => {
+                    let stderr: String = std::mem::take(&mut output.stderr);
+                    output.stdout.push_str(&stderr);
+                    continue;
+                },
+                RedirectKind::Out => (std::mem::take(&mut output.stdout), false),
+                RedirectKind::Append => (std::mem::take(&mut output.stdout), true),
+                RedirectKind::ErrOut => (std::mem::take(&mut output.stderr), false),
+                RedirectKind::ErrAppend => (std::mem::take(&mut output.stderr), true),
+            };
+            let typed: &str = &redirect.target.as_ref().expect("Redirect should have a target").text;
+            let target: String = self.resolve(typed);
+            if target == "/dev/null" {
+                continue;
+            }
+            let template: Node = self.new_node(NodeKind::File(String::new()), 0o666);
+            if let Err(e) = self.vfs.write_file(&target, &content, append, template) {
+                output.stderr.push_str(&format!("bash: {}: {}\n", typed, e));
+                output.status = 1;
+            }
+        }
+        output
+    }
+
+    /// Expands a leading `~` and any wildcards in an unquoted `word`. Wildcards that don't match
+    /// anything are left as they are, as `bash` does.
+    fn expand(&self, word: &Word) -> Vec<String> {
+        if word.quoted {
+            return vec![word.text.clone()];
+        }
+        let text: String = if word.text == "~" {
+            self.home()
+        } else if let Some(rest) = word.text.strip_prefix("~/") {
+            format!("{}/{}", self.home(), rest)
+        } else {
+            word.text.clone()
+        };
+        if !text.contains(['*', '?', '[']) {
+            return vec![text];
+        }
+
+        let (dir, pattern) = match text.rfind('/') {
+            Some(i) => (&text[..=i], &text[i + 1..]),
+            None => ("", text.as_str()),
+        };
+        if dir.contains(['*', '?', '[']) {
+            return vec![text];
+        }
+        let mut matches: Vec<String> = self.vfs
+            .children(&self.resolve(if dir.is_empty() { "." } else { dir }))
+            .into_iter()
+            .filter(|(name, _)| (!name.starts_with('.') || pattern.starts_with('.')) && glob_match(pattern, name))
+            .map(|(name, _)| format!("{}{}", dir, name))
+            .collect();
+        if matches.is_empty() {
+            return vec![text];
+        }
+        matches.sort();
+        matches
+    }
+
+    /// Runs an interactive session in the simulated shell until the user enters `submit`.
+    /// Every command that was entered is returned, in order.
+    pub fn interact(&mut self) -> Vec<String> {
+        let mut history: Vec<String> = Vec::new();
+        println!("Enter commands at the prompt. Type 'submit' when you're finished.");
+        loop {
+            let line: String = read_line(&self.prompt());
+            if line.is_empty() {
+                continue;
+            }
+            if line == "submit" {
+                return history;
+            }
+            let output: Output = self.execute(&line);
+            history.push(line);
+            print!("{}", output.stdout);
+            eprint!("{}", output.stderr);
+        }
+    }
+}
+
+/// Joins `name` onto `dir` as it was typed, without resolving it, for messages such as `find`'s
+/// output.
+pub fn display_join(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        join(dir, name)
+    }
+}
@@ -0,0 +1,347 @@
+/// A flag that a program accepts, by its short form (e.g., `-a`), its long form (e.g., `--all`),
+/// or both.
+#[derive(Debug)]
+pub struct FlagDef {
+    pub short: Option<char>,
+    pub long: Option<&'static str>,
+    pub takes_value: bool,
+}
+
+impl FlagDef {
+    /// The name that's used when comparing flags: the short form if there is one, otherwise the
+    /// long form. This makes `--all` and `-a` the same flag for `ls`.
+    pub fn canonical(&self) -> String {
+        match (self.short, self.long) {
+            (Some(short), _) => format!("-{}", short),
+            (None, Some(long)) => format!("--{}", long),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// The flags that a program accepts.
+#[derive(Debug)]
+pub struct ProgramSpec {
+    pub name: &'static str,
+    pub flags: &'static [FlagDef],
+}
+
+const fn flag(short: char, long: &'static str) -> FlagDef {
+    FlagDef { short: Some(short), long: Some(long), takes_value: false }
+}
+
+const fn short(short: char) -> FlagDef {
+    FlagDef { short: Some(short), long: None, takes_value: false }
+}
+
+const fn long(long: &'static str) -> FlagDef {
+    FlagDef { short: None, long: Some(long), takes_value: false }
+}
+
+const fn value(short: char, long: &'static str) -> FlagDef {
+    FlagDef { short: Some(short), long: Some(long), takes_value: true }
+}
+
+const fn short_value(short: char) -> FlagDef {
+    FlagDef { short: Some(short), long: None, takes_value: true }
+}
+
+const fn long_value(long: &'static str) -> FlagDef {
+    FlagDef { short: None, long: Some(long), takes_value: true }
+}
+
+const SPECS: &[ProgramSpec] = &[
+    ProgramSpec { name: "ls", flags: &[
+        flag('a', "all"), flag('A', "almost-all"), short('l'), flag('h', "human-readable"),
+        flag('R', "recursive"), flag('r', "reverse"), short('t'), short('S'), flag('d', "directory"),
+        flag('i', "inode"), short('1'), flag('F', "classify"), long("color"),
+    ] },
+    ProgramSpec { name: "cat", flags: &[
+        flag('n', "number"), flag('A', "show-all"), flag('b', "number-nonblank"),
+    ] },
+    ProgramSpec { name: "cd", flags: &[short('L'), short('P')] },
+    ProgramSpec { name: "pwd", flags: &[short('L'), short('P')] },
+    ProgramSpec { name: "echo", flags: &[short('n'), short('e')] },
+    ProgramSpec { name: "chmod", flags: &[flag('R', "recursive"), flag('v', "verbose"), flag('c', "changes")] },
+    ProgramSpec { name: "chown", flags: &[flag('R', "recursive"), flag('v', "verbose"), flag('c', "changes")] },
+    ProgramSpec { name: "chgrp", flags: &[flag('R', "recursive"), flag('v', "verbose"), flag('c', "changes")] },
+    ProgramSpec { name: "grep", flags: &[
+        flag('i', "ignore-case"), flag('v', "invert-match"), flag('n', "line-number"), flag('c', "count"),
+        flag('l', "files-with-matches"), flag('r', "recursive"), flag('R', "dereference-recursive"),
+        flag('E', "extended-regexp"), flag('F', "fixed-strings"), flag('w', "word-regexp"),
+        flag('x', "line-regexp"), flag('o', "only-matching"), flag('q', "quiet"), flag('s', "no-messages"),
+        flag('H', "with-filename"), flag('h', "no-filename"), value('e', "regexp"), long("color"),
+    ] },
+    ProgramSpec { name: "mkdir", flags: &[flag('p', "parents"), flag('v', "verbose"), value('m', "mode")] },
+    ProgramSpec { name: "rmdir", flags: &[flag('p', "parents"), flag('v', "verbose")] },
+    ProgramSpec { name: "touch", flags: &[short('a'), short('m'), flag('c', "no-create")] },
+    ProgramSpec { name: "rm", flags: &[
+        flag('r', "recursive"), short('R'), flag('f', "force"), short('i'), flag('v', "verbose"),
+        flag('d', "dir"),
+    ] },
+    ProgramSpec { name: "cp", flags: &[
+        flag('r', "recursive"), short('R'), flag('p', "preserve"), flag('a', "archive"), flag('v', "verbose"),
+        flag('f', "force"), flag('i', "interactive"), flag('n', "no-clobber"),
+    ] },
+    ProgramSpec { name: "mv", flags: &[
+        flag('f', "force"), flag('i', "interactive"), flag('n', "no-clobber"), flag('v', "verbose"),
+    ] },
+    ProgramSpec { name: "tar", flags: &[
+        flag('c', "create"), flag('x', "extract"), flag('t', "list"), flag('v', "verbose"), value('f', "file"),
+        flag('z', "gzip"), flag('j', "bzip2"), flag('J', "xz"), value('C', "directory"), flag('r', "append"),
+        flag('p', "preserve-permissions"),
+    ] },
+    ProgramSpec { name: "head", flags: &[value('n', "lines"), value('c', "bytes")] },
+    ProgramSpec { name: "tail", flags: &[value('n', "lines"), value('c', "bytes"), flag('f', "follow")] },
+    ProgramSpec { name: "wc", flags: &[flag('l', "lines"), flag('w', "words"), flag('c', "bytes"), flag('m', "chars")] },
+    ProgramSpec { name: "sort", flags: &[
+        flag('r', "reverse"), flag('n', "numeric-sort"), flag('u', "unique"), value('k', "key"),
+        value('t', "field-separator"), flag('f', "ignore-case"), flag('h', "human-numeric-sort"),
+    ] },
+    ProgramSpec { name: "uniq", flags: &[flag('c', "count"), flag('d', "repeated"), flag('u', "unique")] },
+    ProgramSpec { name: "ps", flags: &[
+        short('e'), short('A'), short('f'), short('F'), short('l'), value('u', "user"), value('p', "pid"),
+        value('C', "command"), long("forest"),
+    ] },
+    ProgramSpec { name: "kill", flags: &[value('s', "signal"), short('9'), short('l')] },
+    ProgramSpec { name: "id", flags: &[flag('u', "user"), flag('g', "group"), flag('n', "name")] },
+    ProgramSpec { name: "umask", flags: &[short('S'), short('p')] },
+    ProgramSpec { name: "systemctl", flags: &[long("now"), flag('l', "full"), long("no-pager"), value('t', "type"), flag('a', "all")] },
+    ProgramSpec { name: "curl", flags: &[
+        value('o', "output"), flag('O', "remote-name"), flag('L', "location"), flag('I', "head"), flag('s', "silent"),
+        value('X', "request"), value('H', "header"), value('d', "data"), flag('k', "insecure"), flag('v', "verbose"),
+        value('u', "user"),
+    ] },
+    // The programs below aren't simulated by the shell, but are known so that command answers
+    // using them can be compared flag by flag.
+    ProgramSpec { name: "sudo", flags: &[value('u', "user"), flag('i', "login"), flag('s', "shell"), value('g', "group")] },
+    ProgramSpec { name: "ln", flags: &[flag('s', "symbolic"), flag('f', "force"), flag('v', "verbose")] },
+    ProgramSpec { name: "df", flags: &[flag('h', "human-readable"), flag('T', "print-type"), flag('i', "inodes")] },
+    ProgramSpec { name: "du", flags: &[
+        flag('h', "human-readable"), flag('s', "summarize"), flag('a', "all"), long_value("max-depth"),
+        flag('c', "total"),
+    ] },
+    ProgramSpec { name: "free", flags: &[flag('h', "human"), flag('m', "mebi"), flag('g', "gibi"), flag('t', "total")] },
+    ProgramSpec { name: "useradd", flags: &[
+        flag('m', "create-home"), value('s', "shell"), value('G', "groups"), value('g', "gid"),
+        value('d', "home-dir"), value('u', "uid"), value('c', "comment"), value('e', "expiredate"),
+        flag('r', "system"),
+    ] },
+    ProgramSpec { name: "usermod", flags: &[
+        flag('a', "append"), value('G', "groups"), value('g', "gid"), flag('L', "lock"), flag('U', "unlock"),
+        value('s', "shell"), value('d', "home"), flag('m', "move-home"), value('l', "login"),
+        value('e', "expiredate"),
+    ] },
+    ProgramSpec { name: "userdel", flags: &[flag('r', "remove"), flag('f', "force")] },
+    ProgramSpec { name: "groupadd", flags: &[value('g', "gid"), flag('r', "system")] },
+    ProgramSpec { name: "passwd", flags: &[flag('l', "lock"), flag('u', "unlock"), flag('e', "expire"), flag('d', "delete"), flag('S', "status")] },
+    ProgramSpec { name: "chage", flags: &[
+        value('M', "maxdays"), value('m', "mindays"), value('W', "warndays"), value('E', "expiredate"),
+        value('I', "inactive"), flag('l', "list"), value('d', "lastday"),
+    ] },
+    ProgramSpec { name: "journalctl", flags: &[
+        value('u', "unit"), flag('f', "follow"), flag('b', "boot"), value('p', "priority"), long_value("since"),
+        long_value("until"), value('n', "lines"), flag('x', "catalog"), flag('e', "pager-end"), flag('k', "dmesg"),
+    ] },
+    ProgramSpec { name: "ssh", flags: &[
+        short_value('p'), short_value('i'), short_value('l'), short_value('L'), short_value('R'), short_value('D'),
+        short('v'), short('X'), short('N'), short('f'), short_value('o'),
+    ] },
+    ProgramSpec { name: "scp", flags: &[short('r'), short_value('P'), short_value('i'), short('p'), short('v'), short('C')] },
+    ProgramSpec { name: "ssh-keygen", flags: &[
+        short_value('t'), short_value('b'), short_value('f'), short_value('C'), short_value('N'), short('l'),
+        short_value('R'),
+    ] },
+    ProgramSpec { name: "rsync", flags: &[
+        flag('a', "archive"), flag('v', "verbose"), flag('z', "compress"), flag('r', "recursive"),
+        short('P'), long("delete"), value('e', "rsh"), flag('n', "dry-run"), flag('h', "human-readable"),
+        long("progress"),
+    ] },
+    ProgramSpec { name: "ss", flags: &[
+        flag('t', "tcp"), flag('u', "udp"), flag('l', "listening"), flag('n', "numeric"), flag('p', "processes"),
+        flag('a', "all"), flag('s', "summary"),
+    ] },
+    ProgramSpec { name: "netstat", flags: &[
+        flag('t', "tcp"), flag('u', "udp"), flag('l', "listening"), flag('n', "numeric"), flag('p', "program"),
+        flag('a', "all"), flag('r', "route"), flag('i', "interfaces"), flag('s', "statistics"),
+    ] },
+    ProgramSpec { name: "ping", flags: &[short_value('c'), short_value('i'), short_value('W'), short_value('s'), short('4'), short('6')] },
+    ProgramSpec { name: "traceroute", flags: &[short('n'), short_value('m'), short('I'), short('T')] },
+    ProgramSpec { name: "dig", flags: &[short_value('t'), short('x'), short_value('p')] },
+    ProgramSpec { name: "wget", flags: &[value('O', "output-document"), flag('q', "quiet"), flag('c', "continue"), value('P', "directory-prefix")] },
+    ProgramSpec { name: "mount", flags: &[value('t', "types"), value('o', "options"), flag('a', "all"), flag('r', "read-only")] },
+    ProgramSpec { name: "umount", flags: &[flag('l', "lazy"), flag('f', "force"), flag('a', "all")] },
+    ProgramSpec { name: "mkfs", flags: &[value('t', "type")] },
+    ProgramSpec { name: "lsblk", flags: &[flag('f', "fs"), flag('a', "all"), flag('p', "paths"), value('o', "output")] },
+    ProgramSpec { name: "fdisk", flags: &[flag('l', "list")] },
+    ProgramSpec { name: "nice", flags: &[value('n', "adjustment")] },
+    ProgramSpec { name: "renice", flags: &[value('n', "priority"), value('p', "pid"), value('u', "user"), value('g', "pgrp")] },
+    ProgramSpec { name: "crontab", flags: &[short('e'), short('l'), short('r'), short_value('u')] },
+    ProgramSpec { name: "sed", flags: &[
+        flag('i', "in-place"), value('e', "expression"), flag('n', "quiet"), flag('E', "regexp-extended"),
+        short('r'),
+    ] },
+    ProgramSpec { name: "awk", flags: &[short_value('F'), short_value('v'), short_value('f')] },
+    ProgramSpec { name: "cut", flags: &[value('d', "delimiter"), value('f', "fields"), value('c', "characters"), value('b', "bytes")] },
+    ProgramSpec { name: "gzip", flags: &[flag('d', "decompress"), flag('k', "keep"), flag('r', "recursive"), flag('v', "verbose"), flag('c', "stdout")] },
+    ProgramSpec { name: "zip", flags: &[short('r'), short('q'), short('e')] },
+    ProgramSpec { name: "unzip", flags: &[short_value('d'), short('l'), short('o'), short('q')] },
+    ProgramSpec { name: "apt", flags: &[flag('y', "yes"), long("purge"), long("no-install-recommends")] },
+    ProgramSpec { name: "apt-get", flags: &[flag('y', "yes"), long("purge"), long("no-install-recommends")] },
+    ProgramSpec { name: "dnf", flags: &[flag('y', "assumeyes"), flag('q', "quiet")] },
+    ProgramSpec { name: "yum", flags: &[flag('y', "assumeyes"), flag('q', "quiet")] },
+    ProgramSpec { name: "rpm", flags: &[
+        flag('i', "install"), flag('v', "verbose"), flag('h', "hash"), flag('q', "query"), flag('a', "all"),
+        flag('e', "erase"), flag('U', "upgrade"), short('l'), short('f'),
+    ] },
+    ProgramSpec { name: "dpkg", flags: &[
+        flag('i', "install"), flag('l', "list"), flag('r', "remove"), flag('P', "purge"), flag('L', "listfiles"),
+        flag('s', "status"), flag('S', "search"),
+    ] },
+    ProgramSpec { name: "iptables", flags: &[
+        value('A', "append"), value('I', "insert"), value('D', "delete"), value('p', "protocol"), value('s', "source"),
+        value('d', "destination"), long_value("dport"), long_value("sport"), value('j', "jump"), flag('L', "list"),
+        flag('n', "numeric"), flag('v', "verbose"), flag('F', "flush"), value('P', "policy"), value('t', "table"),
+        value('i', "in-interface"), value('o', "out-interface"), value('m', "match"), long_value("state"),
+        long_value("ctstate"),
+    ] },
+    ProgramSpec { name: "firewall-cmd", flags: &[
+        long("permanent"), long_value("add-service"), long_value("remove-service"), long_value("add-port"),
+        long_value("remove-port"), long("reload"), long("list-all"), long_value("zone"), long("state"),
+        long_value("set-default-zone"), long("get-default-zone"),
+    ] },
+    ProgramSpec { name: "git", flags: &[
+        short_value('C'), value('m', "message"), flag('a', "all"), flag('b', "branch"), flag('v', "verbose"),
+        flag('f', "force"), flag('u', "set-upstream"), long("hard"), long("soft"), long("oneline"),
+    ] },
+    ProgramSpec { name: "docker", flags: &[
+        flag('d', "detach"), flag('i', "interactive"), flag('t', "tty"), value('p', "publish"), value('v', "volume"),
+        value('e', "env"), long_value("name"), long("rm"), flag('a', "all"), flag('f', "force"),
+    ] },
+    ProgramSpec { name: "nmcli", flags: &[short('t'), short_value('f'), short('p')] },
+    ProgramSpec { name: "hostnamectl", flags: &[long("static"), long("transient"), long("pretty")] },
+    ProgramSpec { name: "xargs", flags: &[short_value('n'), short_value('I'), short('0'), short('r')] },
+    ProgramSpec { name: "dd", flags: &[] },
+    ProgramSpec { name: "lsof", flags: &[short_value('i'), short_value('u'), short_value('p'), short('n'), short('P')] },
+    ProgramSpec { name: "top", flags: &[short_value('u'), short_value('p'), short_value('n'), short('b'), short_value('d')] },
+    ProgramSpec { name: "shutdown", flags: &[flag('r', "reboot"), flag('h', "halt"), flag('P', "poweroff"), short('c')] },
+];
+
+/// Looks up the flags that `program` accepts, if the program is known.
+pub fn spec(program: &str) -> Option<&'static ProgramSpec> {
+    SPECS.iter().find(|spec| spec.name == program)
+}
+
+/// The canonical name (see `FlagDef::canonical`) of a single flag written as `-x` or `--long`
+/// for `program`. Flags that aren't in the program's spec are returned as they were written.
+pub fn canonical_flag(program: &str, written: &str) -> String {
+    let def: Option<&FlagDef> = spec(program).and_then(|spec| {
+        spec.flags.iter().find(|f| match written.strip_prefix("--") {
+            Some(name) => f.long == Some(name),
+            None => {
+                let mut chars = written.chars().skip(1);
+                let c: Option<char> = chars.next();
+                chars.next().is_none() && c.is_some() && f.short == c
+            },
+        })
+    });
+    match def {
+        Some(def) => def.canonical(),
+        None => written.to_string(),
+    }
+}
+
+/// A program's arguments, split into flags (with any values they take) and operands.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParsedArgs {
+    /// The canonical name of each flag (see `FlagDef::canonical`) in the order given.
+    pub flags: Vec<(String, Option<String>)>,
+    pub operands: Vec<String>,
+}
+
+impl ParsedArgs {
+    /// Whether the flag with the canonical name `name` (e.g., `-l`) was given.
+    pub fn has(&self, name: &str) -> bool {
+        self.flags.iter().any(|(flag, _)| flag == name)
+    }
+
+    /// The value of the last occurrence of the flag with the canonical name `name`.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.flags.iter().rev().find(|(flag, _)| flag == name).and_then(|(_, value)| value.as_deref())
+    }
+}
+
+/// Splits `args` (not including the program name) into flags and operands using the flags that
+/// `program` is known to accept. Clustered short flags (`-la`), long flags (`--all`), values given
+/// inline (`-n5`, `--lines=5`) or as the next argument (`-n 5`), and `--` are all understood.
+///
+/// Flags that aren't in the program's spec are kept as they were written. `tar`'s old style of
+/// bundling flags without a leading dash (e.g., `tar czf`) is also accepted.
+pub fn parse_args(program: &str, args: &[String]) -> Result<ParsedArgs, String> {
+    let spec: Option<&ProgramSpec> = spec(program);
+    let mut parsed: ParsedArgs = ParsedArgs::default();
+    let mut iter = args.iter().enumerate().peekable();
+    let mut only_operands: bool = false;
+
+    let find_short = |c: char| spec.and_then(|spec| spec.flags.iter().find(|f| f.short == Some(c)));
+    let find_long = |name: &str| spec.and_then(|spec| spec.flags.iter().find(|f| f.long == Some(name)));
+
+    while let Some((i, arg)) = iter.next() {
+        let arg: String = if i == 0 && program == "tar" && !arg.starts_with('-')
+            && !arg.is_empty() && arg.chars().all(|c| find_short(c).is_some()) {
+            format!("-{}", arg)
+        } else {
+            arg.clone()
+        };
+
+        if only_operands || arg == "-" || !arg.starts_with('-') {
+            parsed.operands.push(arg);
+        } else if arg == "--" {
+            only_operands = true;
+        } else if let Some(name) = arg.strip_prefix("--") {
+            let (name, inline_value) = match name.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (name, None),
+            };
+            match find_long(name) {
+                Some(def) if def.takes_value => {
+                    let value = match inline_value {
+                        Some(value) => value,
+                        None => match iter.next() {
+                            Some((_, value)) => value.clone(),
+                            None => return Err(format!("option '--{}' requires an argument", name)),
+                        },
+                    };
+                    parsed.flags.push((def.canonical(), Some(value)));
+                },
+                Some(def) => parsed.flags.push((def.canonical(), inline_value)),
+                None => parsed.flags.push((format!("--{}", name), inline_value)),
+            }
+        } else {
+            let cluster: Vec<char> = arg[1..].chars().collect();
+            let mut j: usize = 0;
+            while j < cluster.len() {
+                let c: char = cluster[j];
+                match find_short(c) {
+                    Some(def) if def.takes_value => {
+                        let rest: String = cluster[j + 1..].iter().collect();
+                        let value: String = if !rest.is_empty() {
+                            rest
+                        } else {
+                            match iter.next() {
+                                Some((_, value)) => value.clone(),
+                                None => return Err(format!("option requires an argument -- '{}'", c)),
+                            }
+                        };
+                        parsed.flags.push((def.canonical(), Some(value)));
+                        break;
+                    },
+                    Some(def) => parsed.flags.push((def.canonical(), None)),
+                    None => parsed.flags.push((format!("-{}", c), None)),
+                }
+                j += 1;
+            }
+        }
+    }
+    Ok(parsed)
+}
@@ -0,0 +1,371 @@
+use crate::shell::{Process, Service, Shell};
+use crate::shell::lexer::{tokenize, Token};
+use crate::shell::vfs::{symbolic_to_mode, Node, NodeKind, Vfs};
+
+/// Something that must be true of the simulated system for a command question to be answered
+/// correctly. `None` fields aren't checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expectation {
+    Dir { path: String, mode: Option<u32>, owner: Option<String>, group: Option<String> },
+    File { path: String, mode: Option<u32>, owner: Option<String>, group: Option<String>, content: Option<String> },
+    Absent(String),
+    Contains(String, String),
+    Cwd(String),
+    NoProcess(String),
+    Service { name: String, active: Option<bool>, enabled: Option<bool> },
+}
+
+/// Splits a state spec into lines of words, skipping blank lines and comments. Each entry keeps
+/// the line's number for error messages.
+fn spec_lines(spec: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut result: Vec<(usize, Vec<String>)> = Vec::new();
+    for (i, line) in spec.lines().enumerate() {
+        let words: Vec<String> = tokenize(line)
+            .map_err(|e| format!("line {}: {}", i + 1, e))?
+            .into_iter()
+            .map(|token| match token {
+                Token::Word(word) => Ok(word.text),
+                _ => Err(format!("line {}: unexpected operator", i + 1)),
+            })
+            .collect::<Result<Vec<String>, String>>()?;
+        if !words.is_empty() {
+            result.push((i + 1, words));
+        }
+    }
+    Ok(result)
+}
+
+/// Parses a mode given as octal (`750`) or as `ls -l` characters (`rwxr-x---`). `-` means any mode.
+fn parse_mode(mode: &str, line: usize) -> Result<Option<u32>, String> {
+    if mode == "-" {
+        return Ok(None);
+    }
+    if mode.chars().all(|c| c.is_digit(8)) && mode.len() <= 4 {
+        return Ok(Some(u32::from_str_radix(mode, 8).expect("Octal digits should parse")));
+    }
+    match symbolic_to_mode(mode) {
+        Some(mode) => Ok(Some(mode)),
+        None => Err(format!("line {}: invalid mode '{}'", line, mode)),
+    }
+}
+
+/// Parses an `owner:group` (or just `owner`, in which case the group is the same). `-` means
+/// any owner and group.
+fn parse_owner(owner: &str) -> (Option<String>, Option<String>) {
+    match owner.split_once(':') {
+        _ if owner == "-" => (None, None),
+        Some((owner, group)) => (
+            Some(owner.to_string()).filter(|o| !o.is_empty() && o != "-"),
+            Some(group.to_string()).filter(|g| !g.is_empty() && g != "-"),
+        ),
+        None => (Some(owner.to_string()), Some(owner.to_string())),
+    }
+}
+
+/// Parses a service's state, e.g., `active` or `disabled`. `-` means any state.
+fn parse_service_state(state: Option<&str>, on: &str, off: &str, line: usize) -> Result<Option<bool>, String> {
+    match state {
+        None | Some("-") => Ok(None),
+        Some(state) if state == on => Ok(Some(true)),
+        Some(state) if state == off => Ok(Some(false)),
+        Some(state) => Err(format!("line {}: expected '{}' or '{}' but found '{}'", line, on, off, state)),
+    }
+}
+
+/// Expands the `\n`, `\t` and `\\` escapes that file contents use in a state spec.
+fn unescape(content: &str) -> String {
+    let mut result: String = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            },
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Quotes `text` so that `spec_lines` and `unescape` will read it back unchanged.
+fn quote(text: &str) -> String {
+    let mut result: String = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\\' => result.push_str("\\\\\\\\"),
+            '"' | '$' | '`' => {
+                result.push('\\');
+                result.push(c);
+            },
+            _ => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Builds a `Shell` from a state spec. Each line of the spec is one of:
+///
+/// - `user NAME` - the user the shell runs as (defaults to `root`)
+/// - `hostname NAME` - the name shown in the prompt
+/// - `cwd PATH` - the starting directory (defaults to the user's home directory)
+/// - `umask MASK` - the umask in octal (defaults to `022`)
+/// - `dir PATH [MODE [OWNER[:GROUP]]]`
+/// - `file PATH [MODE [OWNER[:GROUP] [CONTENT]]]` - content may use `\n` for line breaks
+/// - `process PID USER COMMAND...`
+/// - `service NAME [active|inactive [enabled|disabled]]` - services are inactive and disabled
+///   unless stated otherwise
+///
+/// Missing parent directories are created automatically, and `#` starts a comment.
+pub fn load(spec: &str) -> Result<Shell, String> {
+    let mut shell: Shell = Shell::default();
+    let mut cwd: Option<String> = None;
+    let mut declared: Vec<String> = Vec::new();
+    for (line, words) in spec_lines(spec)? {
+        let arg = |i: usize| words.get(i).map(|word| word.as_str());
+        match (words[0].as_str(), words.len()) {
+            ("user", 2) => shell.user = words[1].clone(),
+            ("hostname", 2) => shell.hostname = words[1].clone(),
+            ("cwd", 2) => cwd = Some(Vfs::normalize("/", &words[1])),
+            ("umask", 2) => {
+                shell.umask = u32::from_str_radix(&words[1], 8)
+                    .map_err(|_| format!("line {}: invalid umask '{}'", line, words[1]))?;
+            },
+            ("dir", 2..=4) | ("file", 2..=5) => {
+                let path: String = Vfs::normalize("/", &words[1]);
+                declared.push(path.clone());
+                let is_dir: bool = words[0] == "dir";
+                let default_mode: u32 = if is_dir { 0o755 } else { 0o644 };
+                let mode: u32 = parse_mode(arg(2).unwrap_or("-"), line)?.unwrap_or(default_mode);
+                let (owner, group) = parse_owner(arg(3).unwrap_or("-"));
+                let kind: NodeKind = if is_dir {
+                    NodeKind::Dir
+                } else {
+                    NodeKind::File(unescape(arg(4).unwrap_or("")))
+                };
+                shell.vfs.insert(&path, Node {
+                    kind,
+                    mode,
+                    owner: owner.unwrap_or_else(|| String::from("root")),
+                    group: group.unwrap_or_else(|| String::from("root")),
+                });
+            },
+            ("process", n) if n >= 4 => {
+                let pid: u32 = words[1].parse()
+                    .map_err(|_| format!("line {}: invalid PID '{}'", line, words[1]))?;
+                shell.processes.push(Process {
+                    pid,
+                    user: words[2].clone(),
+                    command: words[3..].join(" "),
+                });
+            },
+            ("service", 2..=4) => {
+                shell.services.retain(|service| service.name != words[1]);
+                shell.services.push(Service {
+                    name: words[1].clone(),
+                    active: parse_service_state(arg(2), "active", "inactive", line)?.unwrap_or(false),
+                    enabled: parse_service_state(arg(3), "enabled", "disabled", line)?.unwrap_or(false),
+                });
+            },
+            _ => return Err(format!("line {}: unrecognized state entry '{}'", line, words.join(" "))),
+        }
+    }
+    // Home directories that were only created as the parent of another entry belong to the user
+    let home: String = shell.home();
+    if !declared.contains(&home) {
+        let user: String = shell.user.clone();
+        if let Some(node) = shell.vfs.get_mut(&home) {
+            node.owner = user.clone();
+            node.group = user;
+        }
+    }
+
+    shell.cwd = cwd.unwrap_or(home);
+    if !shell.vfs.is_dir(&shell.cwd) {
+        let home: Node = Node {
+            kind: NodeKind::Dir,
+            mode: 0o755,
+            owner: shell.user.clone(),
+            group: shell.user.clone(),
+        };
+        let cwd: String = shell.cwd.clone();
+        shell.vfs.insert(&cwd, home);
+    }
+    Ok(shell)
+}
+
+/// Parses the expected state for a command question. In addition to the `dir` and `file` entries
+/// that `load` accepts (where `-` means "don't check"), the following may be used:
+///
+/// - `absent PATH` - nothing may exist at `PATH`
+/// - `contains PATH TEXT` - the file at `PATH` must contain `TEXT`
+/// - `cwd PATH` - the shell must be in `PATH`
+/// - `no-process NAME` - no running process's command may contain `NAME`
+/// - `service NAME [active|inactive|- [enabled|disabled|-]]` - the service must exist and be in
+///   the given state
+pub fn parse_expectations(spec: &str) -> Result<Vec<Expectation>, String> {
+    let mut result: Vec<Expectation> = Vec::new();
+    for (line, words) in spec_lines(spec)? {
+        let arg = |i: usize| words.get(i).map(|word| word.as_str());
+        let expectation: Expectation = match (words[0].as_str(), words.len()) {
+            ("dir", 2..=4) => {
+                let (owner, group) = parse_owner(arg(3).unwrap_or("-"));
+                Expectation::Dir {
+                    path: Vfs::normalize("/", &words[1]),
+                    mode: parse_mode(arg(2).unwrap_or("-"), line)?,
+                    owner,
+                    group,
+                }
+            },
+            ("file", 2..=5) => {
+                let (owner, group) = parse_owner(arg(3).unwrap_or("-"));
+                Expectation::File {
+                    path: Vfs::normalize("/", &words[1]),
+                    mode: parse_mode(arg(2).unwrap_or("-"), line)?,
+                    owner,
+                    group,
+                    content: arg(4).map(unescape),
+                }
+            },
+            ("absent", 2) => Expectation::Absent(Vfs::normalize("/", &words[1])),
+            ("contains", 3) => Expectation::Contains(Vfs::normalize("/", &words[1]), unescape(&words[2])),
+            ("cwd", 2) => Expectation::Cwd(Vfs::normalize("/", &words[1])),
+            ("no-process", 2) => Expectation::NoProcess(words[1].clone()),
+            ("service", 2..=4) => Expectation::Service {
+                name: words[1].clone(),
+                active: parse_service_state(arg(2), "active", "inactive", line)?,
+                enabled: parse_service_state(arg(3), "enabled", "disabled", line)?,
+            },
+            _ => return Err(format!("line {}: unrecognized expectation '{}'", line, words.join(" "))),
+        };
+        result.push(expectation);
+    }
+    Ok(result)
+}
+
+/// Checks the `shell` against each of the `expectations`, returning a description of every one
+/// that isn't met. An empty result means the system is in the expected state.
+pub fn check(shell: &Shell, expectations: &[Expectation]) -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+    let check_node = |problems: &mut Vec<String>, path: &str, node: &Node, mode: &Option<u32>,
+                      owner: &Option<String>, group: &Option<String>| {
+        if let Some(mode) = mode {
+            if node.mode != *mode {
+                problems.push(format!("{} should have mode {:o} but has {:o}", path, mode, node.mode));
+            }
+        }
+        if let Some(owner) = owner {
+            if node.owner != *owner {
+                problems.push(format!("{} should be owned by {} but is owned by {}", path, owner, node.owner));
+            }
+        }
+        if let Some(group) = group {
+            if node.group != *group {
+                problems.push(format!("{} should belong to group {} but belongs to {}", path, group, node.group));
+            }
+        }
+    };
+
+    for expectation in expectations {
+        match expectation {
+            Expectation::Dir { path, mode, owner, group } => match shell.vfs.get(path) {
+                Some(node) if node.is_dir() => check_node(&mut problems, path, node, mode, owner, group),
+                Some(_) => problems.push(format!("{} should be a directory, not a file", path)),
+                None => problems.push(format!("{} should be a directory but doesn't exist", path)),
+            },
+            Expectation::File { path, mode, owner, group, content } => match shell.vfs.get(path) {
+                Some(node @ Node { kind: NodeKind::File(actual), .. }) => {
+                    check_node(&mut problems, path, node, mode, owner, group);
+                    if let Some(content) = content {
+                        if actual.trim_end() != content.trim_end() {
+                            problems.push(format!("{} doesn't have the expected content", path));
+                        }
+                    }
+                },
+                Some(_) => problems.push(format!("{} should be a file, not a directory", path)),
+                None => problems.push(format!("{} should be a file but doesn't exist", path)),
+            },
+            Expectation::Absent(path) => {
+                if shell.vfs.exists(path) {
+                    problems.push(format!("{} should not exist", path));
+                }
+            },
+            Expectation::Contains(path, text) => match shell.vfs.read_file(path) {
+                Ok(content) if content.contains(text.as_str()) => {},
+                Ok(_) => problems.push(format!("{} should contain \"{}\"", path, text)),
+                Err(_) => problems.push(format!("{} should be a file containing \"{}\"", path, text)),
+            },
+            Expectation::Cwd(path) => {
+                if shell.cwd != *path {
+                    problems.push(format!("the current directory should be {} but is {}", path, shell.cwd));
+                }
+            },
+            Expectation::NoProcess(name) => {
+                if let Some(process) = shell.processes.iter().find(|p| p.command.contains(name.as_str())) {
+                    problems.push(format!("process {} ({}) should not be running", process.pid, process.command));
+                }
+            },
+            Expectation::Service { name, active, enabled } => match shell.services.iter().find(|s| s.name == *name) {
+                Some(service) => {
+                    if active.is_some_and(|active| active != service.active) {
+                        let state: &str = if service.active { "running" } else { "stopped" };
+                        problems.push(format!("the {} service shouldn't be {}", name, state));
+                    }
+                    if enabled.is_some_and(|enabled| enabled != service.enabled) {
+                        let state: &str = if service.enabled { "enabled" } else { "disabled" };
+                        problems.push(format!("the {} service shouldn't be {}", name, state));
+                    }
+                },
+                None => problems.push(format!("the {} service doesn't exist", name)),
+            },
+        }
+    }
+    problems
+}
+
+/// Writes a single `dir` or `file` entry for the node at `path`, as `load` would read it.
+pub fn node_entry(path: &str, node: &Node) -> String {
+    match &node.kind {
+        NodeKind::Dir => format!("dir {} {:o} {}:{}", quote(path), node.mode, node.owner, node.group),
+        NodeKind::File(content) => format!(
+            "file {} {:o} {}:{} {}", quote(path), node.mode, node.owner, node.group, quote(content)
+        ),
+    }
+}
+
+/// Writes the full state of the `shell` as a spec that `load` will read back into an identical
+/// shell, so that progress through a lab can be saved.
+pub fn serialize(shell: &Shell) -> String {
+    let mut lines: Vec<String> = vec![
+        format!("user {}", quote(&shell.user)),
+        format!("hostname {}", quote(&shell.hostname)),
+        format!("cwd {}", quote(&shell.cwd)),
+        format!("umask {:03o}", shell.umask),
+    ];
+    for path in shell.vfs.paths() {
+        let node: &Node = shell.vfs.get(path).expect("Listed paths should exist");
+        lines.push(node_entry(path, node));
+    }
+    for process in &shell.processes {
+        lines.push(format!("process {} {} {}", process.pid, quote(&process.user), quote(&process.command)));
+    }
+    for service in &shell.services {
+        lines.push(format!(
+            "service {} {} {}",
+            quote(&service.name),
+            if service.active { "active" } else { "inactive" },
+            if service.enabled { "enabled" } else { "disabled" },
+        ));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,421 @@
+use std::collections::BTreeMap;
+
+pub const SETUID: u32 = 0o4000;
+pub const SETGID: u32 = 0o2000;
+pub const STICKY: u32 = 0o1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    File(String),
+    Dir,
+}
+
+/// A file or directory in the simulated filesystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub mode: u32,
+    pub owner: String,
+    pub group: String,
+}
+
+impl Node {
+    pub fn is_dir(&self) -> bool {
+        self.kind == NodeKind::Dir
+    }
+
+    /// The node's size as `ls -l` would show it.
+    pub fn size(&self) -> usize {
+        match &self.kind {
+            NodeKind::File(content) => content.len(),
+            NodeKind::Dir => 4096,
+        }
+    }
+
+    /// The node's type and permissions as they're shown by `ls -l` (e.g., `drwxr-xr-x`).
+    pub fn mode_string(&self) -> String {
+        let type_char: char = if self.is_dir() { 'd' } else { '-' };
+        format!("{}{}", type_char, mode_to_symbolic(self.mode))
+    }
+}
+
+/// Converts the permission bits of `mode` to the nine characters shown by `ls -l` (e.g.,
+/// `rwxr-x---`). The setuid, setgid and sticky bits are shown as `s`/`S` and `t`/`T`.
+pub fn mode_to_symbolic(mode: u32) -> String {
+    let mut result: String = String::with_capacity(9);
+    let specials: [(u32, char, char); 3] = [(SETUID, 's', 'S'), (SETGID, 's', 'S'), (STICKY, 't', 'T')];
+    for (i, (special, with_exec, without_exec)) in specials.iter().enumerate() {
+        let bits: u32 = (mode >> (6 - 3 * i)) & 0o7;
+        result.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        result.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        let exec: bool = bits & 0o1 != 0;
+        result.push(match (mode & special != 0, exec) {
+            (true, true) => *with_exec,
+            (true, false) => *without_exec,
+            (false, true) => 'x',
+            (false, false) => '-',
+        });
+    }
+    result
+}
+
+/// Parses the nine permission characters shown by `ls -l` (with or without the leading type
+/// character) back into a mode.
+pub fn symbolic_to_mode(symbolic: &str) -> Option<u32> {
+    let chars: Vec<char> = symbolic.chars().collect();
+    let chars: &[char] = match chars.len() {
+        10 => &chars[1..],
+        9 => &chars[..],
+        _ => return None,
+    };
+    let mut mode: u32 = 0;
+    let specials: [(u32, char, char); 3] = [(SETUID, 's', 'S'), (SETGID, 's', 'S'), (STICKY, 't', 'T')];
+    for (i, (special, with_exec, without_exec)) in specials.iter().enumerate() {
+        let shift: u32 = 6 - 3 * i as u32;
+        match chars[3 * i] {
+            'r' => mode |= 0o4 << shift,
+            '-' => {},
+            _ => return None,
+        }
+        match chars[3 * i + 1] {
+            'w' => mode |= 0o2 << shift,
+            '-' => {},
+            _ => return None,
+        }
+        match chars[3 * i + 2] {
+            'x' => mode |= 0o1 << shift,
+            '-' => {},
+            c if c == *with_exec => mode |= (0o1 << shift) | special,
+            c if c == *without_exec => mode |= special,
+            _ => return None,
+        }
+    }
+    Some(mode)
+}
+
+/// Applies a `chmod` mode argument to `current`, the node's existing mode. Both octal modes
+/// (`750`, `2755`) and comma-separated symbolic modes (`u=rwx,g=rx,o=`, `a+x`, `g-w`, `+t`) are
+/// supported. `umask` is used for symbolic modes that don't say who they apply to, as `chmod` does.
+pub fn apply_mode_spec(spec: &str, current: u32, is_dir: bool, umask: u32) -> Result<u32, String> {
+    if !spec.is_empty() && spec.chars().all(|c| c.is_digit(8)) {
+        if spec.len() > 4 {
+            return Err(format!("invalid mode: '{}'", spec));
+        }
+        return u32::from_str_radix(spec, 8).map_err(|_| format!("invalid mode: '{}'", spec));
+    }
+
+    let mut mode: u32 = current & 0o7777;
+    for clause in spec.split(',') {
+        let mut chars = clause.chars().peekable();
+        let mut who: u32 = 0;
+        while let Some(&c) = chars.peek() {
+            match c {
+                'u' => who |= 0o4700,
+                'g' => who |= 0o2070,
+                'o' => who |= 0o1007,
+                'a' => who |= 0o7777,
+                _ => break,
+            }
+            chars.next();
+        }
+        let implicit_who: bool = who == 0;
+        if implicit_who {
+            who = 0o7777;
+        }
+
+        let mut has_op: bool = false;
+        while let Some(op) = chars.next() {
+            if op != '+' && op != '-' && op != '=' {
+                return Err(format!("invalid mode: '{}'", spec));
+            }
+            has_op = true;
+            let mut perms: u32 = 0;
+            while let Some(&c) = chars.peek() {
+                match c {
+                    'r' => perms |= 0o444,
+                    'w' => perms |= 0o222,
+                    'x' => perms |= 0o111,
+                    'X' => if is_dir || mode & 0o111 != 0 { perms |= 0o111 },
+                    's' => perms |= SETUID | SETGID,
+                    't' => perms |= STICKY,
+                    'u' | 'g' | 'o' => {
+                        let shift: u32 = match c { 'u' => 6, 'g' => 3, _ => 0 };
+                        let bits: u32 = (mode >> shift) & 0o7;
+                        perms |= bits << 6 | bits << 3 | bits;
+                    },
+                    _ => break,
+                }
+                chars.next();
+            }
+            let mut mask: u32 = who & perms;
+            if implicit_who && op != '-' {
+                mask &= !umask;
+            }
+            match op {
+                '+' => mode |= mask,
+                '-' => mode &= !(who & perms),
+                _ => {
+                    let cleared: u32 = if is_dir { who & !(SETUID | SETGID) } else { who };
+                    mode = (mode & !cleared) | mask;
+                },
+            }
+        }
+        if !has_op {
+            return Err(format!("invalid mode: '{}'", spec));
+        }
+    }
+    Ok(mode)
+}
+
+/// Checks whether `name` matches the shell wildcard `pattern`, which may contain `*`, `?` and
+/// bracketed character classes such as `[abc]`, `[a-z]` or `[!0-9]`.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.first() {
+            None => name.is_empty(),
+            Some('*') => (0..=name.len()).any(|i| matches(&pattern[1..], &name[i..])),
+            Some('?') => !name.is_empty() && matches(&pattern[1..], &name[1..]),
+            Some('[') => {
+                let close = pattern.iter().skip(2).position(|&c| c == ']').map(|i| i + 2);
+                match (close, name.first()) {
+                    (Some(close), Some(&c)) => {
+                        let mut class: &[char] = &pattern[1..close];
+                        let negate: bool = matches!(class.first(), Some('!') | Some('^'));
+                        if negate {
+                            class = &class[1..];
+                        }
+                        let mut found: bool = false;
+                        let mut i: usize = 0;
+                        while i < class.len() {
+                            if i + 2 < class.len() && class[i + 1] == '-' {
+                                found |= class[i] <= c && c <= class[i + 2];
+                                i += 3;
+                            } else {
+                                found |= class[i] == c;
+                                i += 1;
+                            }
+                        }
+                        found != negate && matches(&pattern[close + 1..], &name[1..])
+                    },
+                    (None, Some(&c)) => c == '[' && matches(&pattern[1..], &name[1..]),
+                    (_, None) => false,
+                }
+            },
+            Some(&p) => name.first() == Some(&p) && matches(&pattern[1..], &name[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Returns the parent directory of the absolute, normalized `path`.
+pub fn parent(path: &str) -> String {
+    match path.rfind('/') {
+        Some(0) | None => String::from("/"),
+        Some(i) => path[..i].to_string(),
+    }
+}
+
+/// Returns the last component of the absolute, normalized `path`.
+pub fn file_name(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(i) if path.len() > 1 => &path[i + 1..],
+        _ => path,
+    }
+}
+
+/// Joins `name` onto the directory `dir`.
+pub fn join(dir: &str, name: &str) -> String {
+    if dir == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// An in-memory filesystem keyed by absolute path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vfs {
+    nodes: BTreeMap<String, Node>,
+}
+
+impl Default for Vfs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vfs {
+    /// Creates a filesystem containing only the root directory.
+    pub fn new() -> Self {
+        let mut nodes: BTreeMap<String, Node> = BTreeMap::new();
+        nodes.insert(String::from("/"), Node {
+            kind: NodeKind::Dir,
+            mode: 0o755,
+            owner: String::from("root"),
+            group: String::from("root"),
+        });
+        Vfs { nodes }
+    }
+
+    /// Resolves `path` relative to `cwd`, handling `.`, `..` and repeated slashes. The result is
+    /// always an absolute path.
+    pub fn normalize(cwd: &str, path: &str) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        let full: String = if path.starts_with('/') { path.to_string() } else { format!("{}/{}", cwd, path) };
+        for part in full.split('/') {
+            match part {
+                "" | "." => {},
+                ".." => { parts.pop(); },
+                _ => parts.push(part),
+            }
+        }
+        format!("/{}", parts.join("/"))
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Node> {
+        self.nodes.get(path)
+    }
+
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut Node> {
+        self.nodes.get_mut(path)
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.nodes.contains_key(path)
+    }
+
+    pub fn is_dir(&self, path: &str) -> bool {
+        self.nodes.get(path).is_some_and(|node| node.is_dir())
+    }
+
+    /// Every path in the filesystem, in sorted order.
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        self.nodes.keys()
+    }
+
+    /// The names and nodes directly inside the directory `dir`, sorted by name.
+    pub fn children(&self, dir: &str) -> Vec<(String, &Node)> {
+        let prefix: String = if dir == "/" { String::from("/") } else { format!("{}/", dir) };
+        self.nodes
+            .range(prefix.clone()..)
+            .take_while(|(path, _)| path.starts_with(&prefix))
+            .filter(|(path, _)| path.len() > prefix.len() && !path[prefix.len()..].contains('/'))
+            .map(|(path, node)| (path[prefix.len()..].to_string(), node))
+            .collect()
+    }
+
+    /// The paths of `dir` and everything beneath it, in sorted order.
+    pub fn descendants(&self, dir: &str) -> Vec<String> {
+        let prefix: String = if dir == "/" { String::from("/") } else { format!("{}/", dir) };
+        let mut result: Vec<String> = vec![dir.to_string()];
+        result.extend(
+            self.nodes
+                .range(prefix.clone()..)
+                .take_while(|(path, _)| path.starts_with(&prefix))
+                .filter(|(path, _)| path.as_str() != dir)
+                .map(|(path, _)| path.clone()),
+        );
+        result
+    }
+
+    /// Adds `node` at `path`, creating any missing parent directories (owned by `root` with mode
+    /// `755`). Any existing node at `path` is replaced.
+    pub fn insert(&mut self, path: &str, node: Node) {
+        let mut ancestors: Vec<String> = Vec::new();
+        let mut current: String = parent(path);
+        while !self.nodes.contains_key(&current) {
+            ancestors.push(current.clone());
+            current = parent(&current);
+        }
+        for ancestor in ancestors.into_iter().rev() {
+            self.nodes.insert(ancestor, Node {
+                kind: NodeKind::Dir,
+                mode: 0o755,
+                owner: String::from("root"),
+                group: String::from("root"),
+            });
+        }
+        self.nodes.insert(path.to_string(), node);
+    }
+
+    /// Creates a directory at `path`; its parent must already exist.
+    pub fn create_dir(&mut self, path: &str, node: Node) -> Result<(), String> {
+        if self.exists(path) {
+            return Err(String::from("File exists"));
+        }
+        if !self.is_dir(&parent(path)) {
+            return Err(String::from("No such file or directory"));
+        }
+        self.nodes.insert(path.to_string(), node);
+        Ok(())
+    }
+
+    /// Writes `content` to the file at `path`, creating it from `template` if it doesn't exist.
+    /// When `append` is set the content is added to the end of the file instead of replacing it.
+    pub fn write_file(&mut self, path: &str, content: &str, append: bool, template: Node) -> Result<(), String> {
+        match self.nodes.get_mut(path) {
+            Some(Node { kind: NodeKind::Dir, .. }) => Err(String::from("Is a directory")),
+            Some(Node { kind: NodeKind::File(existing), .. }) => {
+                if append {
+                    existing.push_str(content);
+                } else {
+                    *existing = content.to_string();
+                }
+                Ok(())
+            },
+            None => {
+                if !self.is_dir(&parent(path)) {
+                    return Err(String::from("No such file or directory"));
+                }
+                self.nodes.insert(path.to_string(), Node { kind: NodeKind::File(content.to_string()), ..template });
+                Ok(())
+            },
+        }
+    }
+
+    /// Reads the content of the file at `path`.
+    pub fn read_file(&self, path: &str) -> Result<&str, String> {
+        match self.nodes.get(path) {
+            Some(Node { kind: NodeKind::File(content), .. }) => Ok(content),
+            Some(_) => Err(String::from("Is a directory")),
+            None => Err(String::from("No such file or directory")),
+        }
+    }
+
+    /// Removes the node at `path` along with everything beneath it.
+    pub fn remove(&mut self, path: &str) {
+        for descendant in self.descendants(path) {
+            self.nodes.remove(&descendant);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_octal_and_symbolic_modes() {
+        assert_eq!(apply_mode_spec("750", 0o644, false, 0o022), Ok(0o750));
+        assert_eq!(apply_mode_spec("4755", 0o644, false, 0o022), Ok(0o4755));
+        assert_eq!(apply_mode_spec("u=rwx,g=rx,o=", 0o644, false, 0o022), Ok(0o750));
+        assert_eq!(apply_mode_spec("g-w", 0o664, false, 0o022), Ok(0o644));
+        assert_eq!(apply_mode_spec("+x", 0o644, false, 0o022), Ok(0o755));
+        assert_eq!(apply_mode_spec("+x", 0o644, false, 0o077), Ok(0o744));
+        assert_eq!(apply_mode_spec("a+X", 0o644, false, 0o022), Ok(0o644));
+        assert_eq!(apply_mode_spec("a+X", 0o644, true, 0o022), Ok(0o755));
+        assert_eq!(apply_mode_spec("g=u", 0o640, false, 0o022), Ok(0o660));
+        assert!(apply_mode_spec("u+q", 0o644, false, 0o022).is_err());
+        assert!(apply_mode_spec("12345", 0o644, false, 0o022).is_err());
+    }
+
+    #[test]
+    fn reads_the_modes_ls_shows() {
+        assert_eq!(symbolic_to_mode("rwxr-x---"), Some(0o750));
+        assert_eq!(symbolic_to_mode("-rwsr-xr-x"), Some(0o4755));
+        assert_eq!(symbolic_to_mode("drwxrwxrwT"), Some(0o1776));
+        assert_eq!(symbolic_to_mode("rwxr-x--"), None);
+    }
+}
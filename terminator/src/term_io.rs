@@ -0,0 +1,32 @@
+use std::io::{stdin, stdout, Write};
+use std::process::exit;
+
+/// Prints the `prompt` and reads a single line from stdin, returning it with the surrounding
+/// whitespace trimmed. Unlike the `User` input helper, the entry is not confirmed a second time.
+///
+/// If stdin has been closed (e.g., `Ctrl+D`), the program exits since no more answers can be read.
+pub fn read_line(prompt: &str) -> String {
+    let mut result: String = String::new();
+    print!("{}", prompt);
+    stdout().flush().expect("Unable to flush stdout...");
+    if stdin().read_line(&mut result).expect("Unable to read stdin...") == 0 {
+        println!();
+        exit(0);
+    }
+    String::from(result.trim())
+}
+
+/// Repeatedly prompts until the user enters a number between `1` and `max` (inclusive), or `q`
+/// to back out. Returns the zero-based index of the selection, or `None` if the user quit.
+pub fn read_choice(prompt: &str, max: usize) -> Option<usize> {
+    loop {
+        let entry: String = read_line(prompt);
+        if entry.eq_ignore_ascii_case("q") {
+            return None;
+        }
+        match entry.parse::<usize>() {
+            Ok(num) if num >= 1 && num <= max => return Some(num - 1),
+            _ => println!("Please enter a number between 1 and {}, or 'q' to go back.", max),
+        }
+    }
+}
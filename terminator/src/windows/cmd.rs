@@ -0,0 +1,493 @@
+use crate::windows::{directory, Console, Directory};
+
+/// An address as `netstat -n` shows it, and with the names it shows without `-n`.
+type Address = (&'static str, &'static str);
+
+/// The simulated machine's network connections, as `(protocol, local, foreign, state, PID)`.
+const CONNECTIONS: &[(&str, Address, Address, &str, u32)] = &[
+    ("TCP", ("0.0.0.0:135", "0.0.0.0:epmap"), ("0.0.0.0:0", "DESKTOP-7Q2K9LM:0"), "LISTENING", 1004),
+    ("TCP", ("0.0.0.0:445", "0.0.0.0:microsoft-ds"), ("0.0.0.0:0", "DESKTOP-7Q2K9LM:0"), "LISTENING", 4),
+    ("TCP", ("0.0.0.0:3389", "0.0.0.0:ms-wbt-server"), ("0.0.0.0:0", "DESKTOP-7Q2K9LM:0"), "LISTENING", 1192),
+    ("TCP", ("192.168.1.25:139", "DESKTOP-7Q2K9LM:netbios-ssn"), ("0.0.0.0:0", "DESKTOP-7Q2K9LM:0"), "LISTENING", 4),
+    (
+        "TCP", ("192.168.1.25:50522", "DESKTOP-7Q2K9LM:50522"), ("140.82.113.4:443", "lb-140-82-113-4-iad:https"),
+        "ESTABLISHED", 6120,
+    ),
+    (
+        "TCP", ("192.168.1.25:50531", "DESKTOP-7Q2K9LM:50531"), ("13.107.42.14:443", "13.107.42.14:https"),
+        "ESTABLISHED", 7344,
+    ),
+    (
+        "TCP", ("192.168.1.25:50540", "DESKTOP-7Q2K9LM:50540"), ("192.168.1.10:445", "FILESRV01:microsoft-ds"),
+        "TIME_WAIT", 0,
+    ),
+    ("UDP", ("0.0.0.0:5353", "0.0.0.0:mdns"), ("*:*", "*:*"), "", 2284),
+    ("UDP", ("192.168.1.25:137", "DESKTOP-7Q2K9LM:netbios-ns"), ("*:*", "*:*"), "", 4),
+];
+
+/// The simulated machine's processes, as `(image name, PID, session name, memory in KB)`.
+pub const PROCESSES: &[(&str, u32, &str, u64)] = &[
+    ("System Idle Process", 0, "Services", 8),
+    ("System", 4, "Services", 144),
+    ("smss.exe", 412, "Services", 1_072),
+    ("csrss.exe", 596, "Services", 5_412),
+    ("wininit.exe", 688, "Services", 6_880),
+    ("services.exe", 760, "Services", 10_244),
+    ("lsass.exe", 776, "Services", 18_356),
+    ("svchost.exe", 1004, "Services", 28_720),
+    ("svchost.exe", 1192, "Services", 14_064),
+    ("spoolsv.exe", 2284, "Services", 15_308),
+    ("explorer.exe", 4532, "Console", 96_412),
+    ("notepad.exe", 4812, "Console", 14_876),
+    ("chrome.exe", 6120, "Console", 212_336),
+    ("OneDrive.exe", 7344, "Console", 42_908),
+];
+
+/// The arguments that aren't switches.
+fn operands(args: &[String]) -> Vec<&String> {
+    args.iter().filter(|arg| !(arg.starts_with('/') || arg.starts_with('-') && arg.len() > 1)).collect()
+}
+
+/// Whether `switch` (without its `/` or `-`) was given, ignoring case.
+fn has(args: &[String], switch: &str) -> bool {
+    args.iter().any(|arg| {
+        let lower: String = arg.to_ascii_lowercase();
+        lower.strip_prefix('/').or_else(|| lower.strip_prefix('-')).is_some_and(|name| name == switch)
+    })
+}
+
+/// The value following `switch`, as in `/t 60` or `-n 4`.
+fn value<'a>(args: &'a [String], switch: &str) -> Option<&'a String> {
+    let position: usize = args.iter().position(|arg| {
+        let lower: String = arg.to_ascii_lowercase();
+        lower.strip_prefix('/').or_else(|| lower.strip_prefix('-')).is_some_and(|name| name == switch)
+    })?;
+    args.get(position + 1)
+}
+
+/// Formats `n` with thousands separators, as Windows does.
+pub fn with_commas(n: u64) -> String {
+    let digits: String = n.to_string();
+    let mut result: String = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Runs a `cmd` program, returning its canned output, or `None` if the program isn't known.
+pub fn run(console: &mut Console, name: &str, args: &[String]) -> Option<String> {
+    let name: String = name.to_ascii_lowercase();
+    let output: String = match name.strip_suffix(".exe").unwrap_or(&name) {
+        "ipconfig" => ipconfig(args),
+        "netstat" => netstat(args),
+        "ping" => ping(args),
+        "tracert" => tracert(args),
+        "nslookup" => nslookup(args),
+        "sfc" => sfc(args),
+        "chkdsk" => chkdsk(args),
+        "gpupdate" => gpupdate(args),
+        "gpresult" => gpresult(console, args),
+        "robocopy" => robocopy(args),
+        "tasklist" => tasklist(),
+        "taskkill" => taskkill(args),
+        "shutdown" => shutdown(args),
+        "hostname" => format!("{}\n", console.hostname),
+        "whoami" => format!("{}\\{}\n", console.hostname.to_ascii_lowercase(), console.user),
+        "ver" => String::from("\nMicrosoft Windows [Version 10.0.19045.3693]\n"),
+        "cls" => String::new(),
+        "cd" | "chdir" => cd(console, args),
+        "dir" => dir(console, args),
+        _ => return None,
+    };
+    Some(output)
+}
+
+fn ipconfig(args: &[String]) -> String {
+    if has(args, "flushdns") {
+        return String::from("\nWindows IP Configuration\n\nSuccessfully flushed the DNS Resolver Cache.\n");
+    }
+    if has(args, "registerdns") {
+        return String::from(
+            "\nWindows IP Configuration\n\nRegistration of the DNS resource records for all adapters of this \
+            computer has been initiated. Any errors will be reported in the Event Viewer in 15 minutes.\n"
+        );
+    }
+    if has(args, "displaydns") {
+        return String::from(
+            "\nWindows IP Configuration\n\n    www.comptia.org\n    ----------------------------------------\n    \
+            Record Name . . . . . : www.comptia.org\n    Record Type . . . . . : 1\n    Time To Live  . . . . : 142\n    \
+            Data Length . . . . . : 4\n    Section . . . . . . . : Answer\n    A (Host) Record . . . : 104.18.20.35\n\n"
+        );
+    }
+    if has(args, "release") {
+        return String::from(
+            "\nWindows IP Configuration\n\n\nEthernet adapter Ethernet0:\n\n   Connection-specific DNS Suffix  . : \n   \
+            Link-local IPv6 Address . . . . . : fe80::8d4c:2a1b:3f6e:91d2%12\n   Default Gateway . . . . . . . . . : \n"
+        );
+    }
+
+    let mut output: String = String::from("\nWindows IP Configuration\n\n");
+    let all: bool = has(args, "all");
+    if all {
+        output.push_str(
+            "   Host Name . . . . . . . . . . . . : DESKTOP-7Q2K9LM\n   Primary Dns Suffix  . . . . . . . : \n   \
+            Node Type . . . . . . . . . . . . : Hybrid\n   IP Routing Enabled. . . . . . . . : No\n   \
+            WINS Proxy Enabled. . . . . . . . : No\n   DNS Suffix Search List. . . . . . : home.lan\n"
+        );
+    }
+    output.push_str("\nEthernet adapter Ethernet0:\n\n   Connection-specific DNS Suffix  . : home.lan\n");
+    if all {
+        output.push_str(
+            "   Description . . . . . . . . . . . : Intel(R) Ethernet Connection I219-V\n   \
+            Physical Address. . . . . . . . . : 00-15-5D-4A-3B-2C\n   DHCP Enabled. . . . . . . . . . . : Yes\n   \
+            Autoconfiguration Enabled . . . . : Yes\n"
+        );
+    }
+    output.push_str("   Link-local IPv6 Address . . . . . : fe80::8d4c:2a1b:3f6e:91d2%12\n");
+    if all {
+        output.push_str("   IPv4 Address. . . . . . . . . . . : 192.168.1.25(Preferred)\n");
+    } else {
+        output.push_str("   IPv4 Address. . . . . . . . . . . : 192.168.1.25\n");
+    }
+    output.push_str("   Subnet Mask . . . . . . . . . . . : 255.255.255.0\n");
+    if all {
+        output.push_str(
+            "   Lease Obtained. . . . . . . . . . : Monday, October 12, 2026 8:02:14 AM\n   \
+            Lease Expires . . . . . . . . . . : Tuesday, October 13, 2026 8:02:14 AM\n"
+        );
+    }
+    output.push_str("   Default Gateway . . . . . . . . . : 192.168.1.1\n");
+    if all {
+        output.push_str(
+            "   DHCP Server . . . . . . . . . . . : 192.168.1.1\n   \
+            DNS Servers . . . . . . . . . . . : 192.168.1.1\n                                       8.8.8.8\n   \
+            NetBIOS over Tcpip. . . . . . . . : Enabled\n"
+        );
+    }
+    if has(args, "renew") {
+        output.insert_str(0, "\nWindows IP Configuration\n\nAn IP address has been obtained from 192.168.1.1.\n");
+    }
+    output
+}
+
+fn netstat(args: &[String]) -> String {
+    let mut flags: Vec<char> = Vec::new();
+    for arg in args.iter().filter(|arg| arg.starts_with('-') || arg.starts_with('/')) {
+        flags.extend(arg[1..].to_ascii_lowercase().chars());
+    }
+    if flags.contains(&'b') {
+        return String::from("The requested operation requires elevation.\n");
+    }
+    if flags.contains(&'r') {
+        return String::from(
+            "===========================================================================\nInterface List\n \
+            12...00 15 5d 4a 3b 2c ......Intel(R) Ethernet Connection I219-V\n  1...........................Software \
+            Loopback Interface 1\n===========================================================================\n\n\
+            IPv4 Route Table\n===========================================================================\n\
+            Active Routes:\nNetwork Destination        Netmask          Gateway       Interface  Metric\n          \
+            0.0.0.0          0.0.0.0      192.168.1.1     192.168.1.25     25\n        127.0.0.0        \
+            255.0.0.0         On-link         127.0.0.1    331\n      192.168.1.0    255.255.255.0         \
+            On-link      192.168.1.25    281\n===========================================================================\n"
+        );
+    }
+    let all: bool = flags.contains(&'a');
+    let numeric: bool = flags.contains(&'n');
+    let pids: bool = flags.contains(&'o');
+
+    let mut output: String = String::from("\nActive Connections\n\n  Proto  Local Address          Foreign Address        State");
+    if pids {
+        output.push_str("           PID");
+    }
+    output.push('\n');
+    for (proto, local, foreign, state, pid) in CONNECTIONS {
+        if !all && (*state == "LISTENING" || state.is_empty()) {
+            continue;
+        }
+        let (local, foreign) = if numeric { (local.0, foreign.0) } else { (local.1, foreign.1) };
+        let mut line: String = format!("  {:<7}{:<23}{:<23}{:<16}", proto, local, foreign, state);
+        if pids {
+            line.push_str(&pid.to_string());
+        }
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+fn ping(args: &[String]) -> String {
+    let target: &str = match operands(args).into_iter().find(|arg| Some(*arg) != value(args, "n")) {
+        Some(target) => target,
+        None => return String::from("\nUsage: ping [-t] [-a] [-n count] [-l size] [-w timeout] target_name\n"),
+    };
+    let count: usize = value(args, "n").and_then(|n| n.parse().ok()).unwrap_or(4);
+    let address: &str = if target.parse::<std::net::Ipv4Addr>().is_ok() { target } else { "142.250.72.14" };
+    let header: String = if address == target {
+        format!("\nPinging {} with 32 bytes of data:\n", target)
+    } else {
+        format!("\nPinging {} [{}] with 32 bytes of data:\n", target, address)
+    };
+    let mut output: String = header;
+    for i in 0..count {
+        output.push_str(&format!("Reply from {}: bytes=32 time={}ms TTL=117\n", address, 12 + i % 3));
+    }
+    output.push_str(&format!(
+        "\nPing statistics for {}:\n    Packets: Sent = {}, Received = {}, Lost = 0 (0% loss),\n\
+        Approximate round trip times in milli-seconds:\n    Minimum = 12ms, Maximum = 14ms, Average = 13ms\n",
+        address, count, count,
+    ));
+    output
+}
+
+fn tracert(args: &[String]) -> String {
+    let target: &str = match operands(args).last() {
+        Some(target) => target,
+        None => return String::from("\nUsage: tracert [-d] [-h maximum_hops] [-w timeout] target_name\n"),
+    };
+    format!(
+        "\nTracing route to {} [142.250.72.14]\nover a maximum of 30 hops:\n\n  \
+        1    <1 ms    <1 ms    <1 ms  192.168.1.1\n  2     9 ms     8 ms     9 ms  10.20.0.1\n  \
+        3    11 ms    10 ms    11 ms  72.14.215.85\n  4    12 ms    12 ms    13 ms  142.250.72.14\n\nTrace complete.\n",
+        target,
+    )
+}
+
+fn nslookup(args: &[String]) -> String {
+    let target: &str = match operands(args).first() {
+        Some(target) => target,
+        None => return String::from("Default Server:  router.home.lan\nAddress:  192.168.1.1\n\n> "),
+    };
+    format!(
+        "Server:  router.home.lan\nAddress:  192.168.1.1\n\nNon-authoritative answer:\nName:    {}\n\
+        Addresses:  2607:f8b0:4009:80b::200e\n          142.250.72.14\n\n",
+        target,
+    )
+}
+
+fn sfc(args: &[String]) -> String {
+    if has(args, "scannow") {
+        String::from(
+            "\nBeginning system scan.  This process will take some time.\n\n\
+            Beginning verification phase of system scan.\nVerification 100% complete.\n\n\
+            Windows Resource Protection did not find any integrity violations.\n"
+        )
+    } else if has(args, "verifyonly") {
+        String::from(
+            "\nBeginning verification phase of system scan.\nVerification 100% complete.\n\n\
+            Windows Resource Protection did not find any integrity violations.\n"
+        )
+    } else {
+        String::from(
+            "\nMicrosoft (R) Windows (R) Resource Checker Version 6.0\n\n\
+            Scans the integrity of all protected system files and replaces incorrect versions with\n\
+            correct Microsoft versions.\n\nSFC [/SCANNOW] [/VERIFYONLY] [/SCANFILE=<file>] [/VERIFYFILE=<file>]\n"
+        )
+    }
+}
+
+fn chkdsk(args: &[String]) -> String {
+    if has(args, "f") || has(args, "r") {
+        return String::from(
+            "The type of the file system is NTFS.\nCannot lock current drive.\n\n\
+            Chkdsk cannot run because the volume is in use by another\nprocess.  Would you like to schedule this \
+            volume to be\nchecked the next time the system restarts? (Y/N) Y\n\n\
+            This volume will be checked the next time the system restarts.\n"
+        );
+    }
+    String::from(
+        "The type of the file system is NTFS.\n\nWARNING!  /F parameter not specified.\n\
+        Running CHKDSK in read-only mode.\n\nStage 1: Examining basic file system structure ...\n\
+        Stage 2: Examining file name linkage ...\nStage 3: Examining security descriptors ...\n\n\
+        Windows has scanned the file system and found no problems.\nNo further action is required.\n\n \
+        498797567 KB total disk space.\n 176232448 KB in 412954 files.\n"
+    )
+}
+
+fn gpupdate(args: &[String]) -> String {
+    let mut output: String = String::from("Updating policy...\n\n");
+    let target: Option<String> = value(args, "target").map(|target| target.to_ascii_lowercase());
+    if target.as_deref() != Some("user") {
+        output.push_str("Computer Policy update has completed successfully.\n");
+    }
+    if target.as_deref() != Some("computer") {
+        output.push_str("User Policy update has completed successfully.\n");
+    }
+    output.push('\n');
+    output
+}
+
+fn gpresult(console: &Console, args: &[String]) -> String {
+    if !has(args, "r") && !has(args, "z") && !has(args, "v") {
+        return String::from("ERROR: Invalid Syntax. Type \"GPRESULT /?\" for usage.\n");
+    }
+    format!(
+        "\nMicrosoft (R) Windows (R) Operating System Group Policy Result tool v2.0\n\n\
+        RSOP data for {0}\\{1} on {0} : Logging Mode\n\
+        ------------------------------------------------------------\n\n\
+        OS Configuration:            Member Workstation\nOS version:                  10.0.19045\n\
+        Domain Name:                 CORP\nDomain Type:                 Windows 2008 or later\n\n\
+        USER SETTINGS\n--------------\n    Applied Group Policy Objects\n    -----------------------------\n        \
+        Default Domain Policy\n        Drive Mappings\n",
+        console.hostname, console.user,
+    )
+}
+
+fn robocopy(args: &[String]) -> String {
+    let paths: Vec<&String> = operands(args);
+    if paths.len() < 2 {
+        return String::from(
+            "\n-------------------------------------------------------------------------------\n   \
+            ROBOCOPY     ::     Robust File Copy for Windows\n\
+            -------------------------------------------------------------------------------\n\n\
+            ERROR : Invalid Parameter #1 : \"\"\n\n       Simple Usage :: ROBOCOPY source destination /MIR\n"
+        );
+    }
+    let files: String = if paths.len() > 2 {
+        paths[2..].iter().map(|file| file.as_str()).collect::<Vec<&str>>().join(" ")
+    } else {
+        String::from("*.*")
+    };
+    let mut options: Vec<String> = args.iter()
+        .filter(|arg| arg.starts_with('/'))
+        .map(|arg| arg.to_ascii_uppercase())
+        .collect();
+    options.extend(["/DCOPY:DA", "/COPY:DAT", "/R:1000000", "/W:30"].iter().map(|o| o.to_string()));
+    let mut output: String = format!(
+        "\n-------------------------------------------------------------------------------\n   \
+        ROBOCOPY     ::     Robust File Copy for Windows\n\
+        -------------------------------------------------------------------------------\n\n  \
+        Started : Sunday, October 18, 2026 9:14:03 AM\n   Source : {}\\\n     Dest : {}\\\n\n    Files : {}\n\n  \
+        Options : {}\n\n------------------------------------------------------------------------------\n\n",
+        paths[0], paths[1], files, options.join(" "),
+    );
+    output.push_str(&format!("\t                   3\t{}\\\n", paths[0]));
+    output.push_str("\t    New File  \t\t  48312\tbudget.xlsx\n");
+    output.push_str("\t    New File  \t\t  23877\tresume.docx\n");
+    output.push_str("\t    New File  \t\t 512044\tq3-report.pdf\n\n");
+    output.push_str("------------------------------------------------------------------------------\n\n");
+    output.push_str("               Total    Copied   Skipped  Mismatch    FAILED    Extras\n");
+    output.push_str("    Dirs :         2         1         1         0         0         0\n");
+    output.push_str("   Files :         3         3         0         0         0         0\n");
+    output.push_str("   Bytes :   570.5 k   570.5 k         0         0         0         0\n");
+    output
+}
+
+fn tasklist() -> String {
+    let mut output: String = String::from(
+        "\nImage Name                     PID Session Name        Session#    Mem Usage\n\
+        ========================= ======== ================ =========== ============\n"
+    );
+    for (image, pid, session, memory) in PROCESSES {
+        let session_number: u32 = if *session == "Console" { 1 } else { 0 };
+        output.push_str(&format!(
+            "{:<25} {:>8} {:<16} {:>11} {:>10} K\n", image, pid, session, session_number, with_commas(*memory)
+        ));
+    }
+    output
+}
+
+fn taskkill(args: &[String]) -> String {
+    let force: bool = has(args, "f");
+    let found: Vec<&(&str, u32, &str, u64)> = if let Some(image) = value(args, "im") {
+        PROCESSES.iter().filter(|(name, ..)| name.eq_ignore_ascii_case(image)).collect()
+    } else if let Some(pid) = value(args, "pid") {
+        PROCESSES.iter().filter(|(_, id, ..)| id.to_string() == *pid).collect()
+    } else {
+        return String::from("ERROR: Invalid syntax. Neither /FI nor /PID nor /IM were specified.\n");
+    };
+
+    if found.is_empty() {
+        let target: &String = value(args, "im").or_else(|| value(args, "pid")).expect("A target was given");
+        return format!("ERROR: The process \"{}\" not found.\n", target);
+    }
+    let mut output: String = String::new();
+    for (image, pid, ..) in found {
+        if *pid <= 4 {
+            output.push_str(&format!("ERROR: The process with PID {} could not be terminated.\nReason: Access is denied.\n", pid));
+        } else if force {
+            output.push_str(&format!("SUCCESS: The process \"{}\" with PID {} has been terminated.\n", image, pid));
+        } else {
+            output.push_str(&format!("SUCCESS: Sent termination signal to the process \"{}\" with PID {}.\n", image, pid));
+        }
+    }
+    output
+}
+
+fn shutdown(args: &[String]) -> String {
+    if has(args, "a") {
+        return String::from("Unable to abort the system shutdown because no shutdown was in progress.(1116)\n");
+    }
+    if !has(args, "s") && !has(args, "r") && !has(args, "l") && !has(args, "h") {
+        return String::from(
+            "Usage: shutdown [/i | /l | /s | /sg | /r | /g | /a | /p | /h | /e | /o] [/hybrid] [/soft] [/fw] [/f]\n    \
+            [/m \\\\computer][/t xxx][/d [p|u:]xx:yy [/c \"comment\"]]\n"
+        );
+    }
+    let delay: u64 = value(args, "t").and_then(|t| t.parse().ok()).unwrap_or(30);
+    if delay == 0 {
+        String::new()
+    } else {
+        format!("You're about to be signed out\nWindows will shut down in {} minute(s).\n", delay.div_ceil(60))
+    }
+}
+
+fn cd(console: &mut Console, args: &[String]) -> String {
+    let target: String = args.iter()
+        .filter(|arg| !arg.eq_ignore_ascii_case("/d"))
+        .cloned()
+        .collect::<Vec<String>>()
+        .join(" ");
+    if target.is_empty() {
+        return format!("{}\n", console.cwd);
+    }
+    match console.resolve_dir(&target) {
+        Some(path) => {
+            console.cwd = path.to_string();
+            String::new()
+        },
+        None => String::from("The system cannot find the path specified.\n"),
+    }
+}
+
+fn dir(console: &Console, args: &[String]) -> String {
+    let path: String = operands(args).first().map_or(console.cwd.clone(), |path| path.to_string());
+    let dir: &Directory = match console.resolve_dir(&path).and_then(directory) {
+        Some(dir) => dir,
+        None => return String::from(" Volume in drive C has no label.\n Volume Serial Number is 4A1E-93B2\n\nFile Not Found\n"),
+    };
+    let bare: bool = has(args, "b");
+    let mut output: String = String::new();
+    if !bare {
+        output.push_str(&format!(
+            " Volume in drive C has no label.\n Volume Serial Number is 4A1E-93B2\n\n Directory of {}\n\n", dir.path
+        ));
+    }
+    let (mut files, mut dirs, mut bytes) = (0, 0, 0);
+    for (name, size) in dir.entries {
+        if bare {
+            output.push_str(&format!("{}\n", name));
+            continue;
+        }
+        match size {
+            Some(size) => {
+                files += 1;
+                bytes += size;
+                output.push_str(&format!("10/12/2026  08:15 AM    {:>14} {}\n", with_commas(*size), name));
+            },
+            None => {
+                dirs += 1;
+                output.push_str(&format!("10/12/2026  08:15 AM    <DIR>          {}\n", name));
+            },
+        }
+    }
+    if !bare {
+        output.push_str(&format!(
+            "{:>16} File(s) {:>14} bytes\n{:>16} Dir(s)  187,204,345,856 bytes free\n",
+            files, with_commas(bytes), dirs,
+        ));
+    }
+    output
+}
@@ -0,0 +1,139 @@
+pub mod cmd;
+pub mod powershell;
+pub mod syntax;
+
+use crate::windows::syntax::{cmdlet_spec, tokenize};
+
+/// The Windows command interpreters that can be simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpreter {
+    /// The Command Prompt, `cmd.exe`.
+    Cmd,
+    /// Windows PowerShell.
+    PowerShell,
+}
+
+/// A directory on the simulated machine, with the entries listed by `dir` and `Get-ChildItem`.
+/// Each entry is its name and its size, or `None` for a directory.
+#[derive(Debug)]
+pub struct Directory {
+    pub path: &'static str,
+    pub entries: &'static [(&'static str, Option<u64>)],
+}
+
+/// The simulated machine's filesystem. Paths are compared without regard to case.
+pub const DIRECTORIES: &[Directory] = &[
+    Directory { path: "C:\\", entries: &[
+        ("PerfLogs", None), ("Program Files", None), ("Program Files (x86)", None), ("Users", None),
+        ("Windows", None),
+    ] },
+    Directory { path: "C:\\Users", entries: &[("Public", None), ("student", None)] },
+    Directory { path: "C:\\Users\\student", entries: &[
+        ("Desktop", None), ("Documents", None), ("Downloads", None), ("Pictures", None),
+    ] },
+    Directory { path: "C:\\Users\\student\\Desktop", entries: &[("notes.txt", Some(1_204))] },
+    Directory { path: "C:\\Users\\student\\Documents", entries: &[
+        ("Reports", None), ("budget.xlsx", Some(48_312)), ("resume.docx", Some(23_877)),
+    ] },
+    Directory { path: "C:\\Users\\student\\Documents\\Reports", entries: &[("q3-report.pdf", Some(512_044))] },
+    Directory { path: "C:\\Users\\student\\Downloads", entries: &[("setup.exe", Some(3_581_952))] },
+    Directory { path: "C:\\Users\\student\\Pictures", entries: &[] },
+    Directory { path: "C:\\Windows", entries: &[("System32", None), ("Temp", None), ("explorer.exe", Some(4_874_168))] },
+    Directory { path: "C:\\Windows\\System32", entries: &[
+        ("drivers", None), ("cmd.exe", Some(289_792)), ("ipconfig.exe", Some(35_840)), ("notepad.exe", Some(201_216)),
+    ] },
+    Directory { path: "C:\\Windows\\Temp", entries: &[] },
+];
+
+/// Looks up a directory of the simulated filesystem.
+pub fn directory(path: &str) -> Option<&'static Directory> {
+    DIRECTORIES.iter().find(|dir| dir.path.eq_ignore_ascii_case(path))
+}
+
+/// A simulated Windows console, used for questions that are answered by entering a Windows
+/// command. All output is canned, so it runs anywhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Console {
+    pub interpreter: Interpreter,
+    pub hostname: String,
+    pub user: String,
+    pub cwd: String,
+}
+
+impl Console {
+    pub fn new(interpreter: Interpreter) -> Self {
+        Console {
+            interpreter,
+            hostname: String::from("DESKTOP-7Q2K9LM"),
+            user: String::from("student"),
+            cwd: String::from("C:\\Users\\student"),
+        }
+    }
+
+    /// The prompt shown before each command, such as `C:\Users\student>`.
+    pub fn prompt(&self) -> String {
+        match self.interpreter {
+            Interpreter::Cmd => format!("{}>", self.cwd),
+            Interpreter::PowerShell => format!("PS {}> ", self.cwd),
+        }
+    }
+
+    /// Resolves `path` against the current directory, returning the simulated directory's path
+    /// with its proper case if it exists.
+    pub fn resolve_dir(&self, path: &str) -> Option<&'static str> {
+        let path: String = path.replace('/', "\\");
+        let mut parts: Vec<String> = if path.len() >= 2 && path.as_bytes()[1] == b':' {
+            vec![path[..2].to_ascii_uppercase()]
+        } else if path.starts_with('\\') {
+            vec![String::from("C:")]
+        } else {
+            self.cwd.trim_end_matches('\\').split('\\').map(String::from).collect()
+        };
+        let rest: &str = if path.len() >= 2 && path.as_bytes()[1] == b':' { &path[2..] } else { &path };
+        for part in rest.split('\\').filter(|part| !part.is_empty() && *part != ".") {
+            if part == ".." {
+                if parts.len() > 1 {
+                    parts.pop();
+                }
+            } else {
+                parts.push(part.to_string());
+            }
+        }
+        let joined: String = if parts.len() == 1 { format!("{}\\", parts[0]) } else { parts.join("\\") };
+        directory(&joined).map(|dir| dir.path)
+    }
+
+    /// Runs a command line, returning its canned output.
+    pub fn execute(&mut self, line: &str) -> String {
+        let words: Vec<String> = match tokenize(line, self.interpreter) {
+            Ok(words) => words,
+            Err(e) => return format!("{}\n", e),
+        };
+        let first: Vec<String> = words.split(|word| word == "|").next().unwrap_or(&[]).to_vec();
+        let (name, args) = match first.split_first() {
+            Some(parts) => parts,
+            None => return String::new(),
+        };
+        // Later commands in a pipeline only filter or format, so the first command's output is
+        // shown as-is.
+        match self.interpreter {
+            Interpreter::PowerShell if cmdlet_spec(name).is_some() => powershell::run(self, name, args),
+            Interpreter::PowerShell => match cmd::run(self, name, args) {
+                Some(output) => output,
+                None => format!(
+                    "{0} : The term '{0}' is not recognized as the name of a cmdlet, function, script file, or \
+                    operable program. Check the spelling of the name, or if a path was included, verify that \
+                    the path is correct and try again.\n",
+                    name,
+                ),
+            },
+            Interpreter::Cmd => match cmd::run(self, name, args) {
+                Some(output) => output,
+                None => format!(
+                    "'{}' is not recognized as an internal or external command,\noperable program or batch file.\n",
+                    name,
+                ),
+            },
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use crate::windows::{directory, Console, Directory};
+use crate::windows::cmd::PROCESSES;
+use crate::windows::syntax::cmdlet_spec;
+
+/// The simulated machine's services, as `(status, name, display name)`.
+const SERVICES: &[(&str, &str, &str)] = &[
+    ("Running", "BITS", "Background Intelligent Transfer Ser..."),
+    ("Running", "Dhcp", "DHCP Client"),
+    ("Running", "Dnscache", "DNS Client"),
+    ("Running", "EventLog", "Windows Event Log"),
+    ("Running", "LanmanServer", "Server"),
+    ("Running", "LanmanWorkstation", "Workstation"),
+    ("Stopped", "RemoteRegistry", "Remote Registry"),
+    ("Running", "Spooler", "Print Spooler"),
+    ("Running", "TermService", "Remote Desktop Services"),
+    ("Running", "W32Time", "Windows Time"),
+    ("Running", "WinDefend", "Microsoft Defender Antivirus Service"),
+    ("Stopped", "wuauserv", "Windows Update"),
+];
+
+/// The value given for the parameter `param`, by name (`-Name x`, abbreviated or not) or by
+/// position.
+fn param<'a>(name: &str, args: &'a [String], param: &str) -> Option<&'a String> {
+    let spec = cmdlet_spec(name)?;
+    let mut positional = spec.positional.iter();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.strip_prefix('-') {
+            Some(written) if written.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                let takes_value: bool = spec.params.iter()
+                    .find(|(name, _)| name.to_ascii_lowercase().starts_with(&written.to_ascii_lowercase()))
+                    .is_some_and(|(_, takes_value)| *takes_value);
+                let is_param: bool = param.to_ascii_lowercase().starts_with(&written.to_ascii_lowercase());
+                if takes_value {
+                    let value = iter.next();
+                    if is_param {
+                        return value;
+                    }
+                }
+            },
+            _ => {
+                if positional.next().is_some_and(|name| name.eq_ignore_ascii_case(param)) {
+                    return Some(arg);
+                }
+            },
+        }
+    }
+    None
+}
+
+/// Whether the switch parameter `param` was given, abbreviated or not.
+fn switch(args: &[String], param: &str) -> bool {
+    args.iter().any(|arg| {
+        arg.strip_prefix('-').is_some_and(|written| {
+            !written.is_empty() && param.to_ascii_lowercase().starts_with(&written.to_ascii_lowercase())
+        })
+    })
+}
+
+/// Whether `name` matches the `pattern`, which may contain `*` wildcards, ignoring case.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: String = pattern.to_ascii_lowercase();
+    let name: String = name.to_ascii_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let mut rest: &str = &name;
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(after) => rest = after,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(index) => rest = &rest[index + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Runs a cmdlet, returning its canned output.
+pub fn run(console: &mut Console, name: &str, args: &[String]) -> String {
+    let spec = cmdlet_spec(name).expect("Only known cmdlets should be run");
+    match spec.name {
+        "Get-ChildItem" => get_child_item(console, name, args),
+        "Get-Location" => format!("\nPath\n----\n{}\n\n", console.cwd),
+        "Set-Location" => {
+            let target: String = param(name, args, "Path").cloned().unwrap_or_else(|| String::from("~"));
+            let target: String = if target == "~" { String::from("C:\\Users\\student") } else { target };
+            match console.resolve_dir(&target) {
+                Some(path) => {
+                    console.cwd = path.to_string();
+                    String::new()
+                },
+                None => format!(
+                    "Set-Location : Cannot find path '{}' because it does not exist.\n", target
+                ),
+            }
+        },
+        "Get-Process" => get_process(name, args),
+        "Stop-Process" => {
+            let by_name = param(name, args, "Name");
+            let by_id = param(name, args, "Id");
+            let found: bool = PROCESSES.iter().any(|(image, pid, ..)| {
+                by_name.is_some_and(|n| image.trim_end_matches(".exe").eq_ignore_ascii_case(n))
+                    || by_id.is_some_and(|id| pid.to_string() == *id)
+            });
+            if found {
+                String::new()
+            } else {
+                format!(
+                    "Stop-Process : Cannot find a process with the name \"{}\". Verify the process name and call \
+                    the cmdlet again.\n",
+                    by_name.or(by_id).map_or("", |target| target.as_str()),
+                )
+            }
+        },
+        "Get-Service" => get_service(name, args),
+        "Start-Service" | "Stop-Service" | "Restart-Service" | "Set-Service" => {
+            let service: Option<&String> = param(name, args, "Name");
+            match service {
+                Some(service) if SERVICES.iter().any(|(_, n, _)| n.eq_ignore_ascii_case(service)) => {
+                    if spec.name == "Restart-Service" {
+                        format!("WARNING: Waiting for service '{}' to start...\n", service)
+                    } else {
+                        String::new()
+                    }
+                },
+                Some(service) => format!(
+                    "{} : Cannot find any service with service name '{}'.\n", spec.name, service
+                ),
+                None => format!("{} : Missing an argument for parameter 'Name'.\n", spec.name),
+            }
+        },
+        "Get-NetIPAddress" => String::from(
+            "\nIPAddress         : 192.168.1.25\nInterfaceIndex    : 12\nInterfaceAlias    : Ethernet0\n\
+            AddressFamily     : IPv4\nType              : Unicast\nPrefixLength      : 24\n\
+            PrefixOrigin      : Dhcp\nSuffixOrigin      : Dhcp\nAddressState      : Preferred\n\n"
+        ),
+        "Get-NetIPConfiguration" => String::from(
+            "\nInterfaceAlias       : Ethernet0\nInterfaceIndex       : 12\n\
+            InterfaceDescription : Intel(R) Ethernet Connection I219-V\nNetProfile.Name      : home.lan\n\
+            IPv4Address          : 192.168.1.25\nIPv4DefaultGateway   : 192.168.1.1\n\
+            DNSServer            : 192.168.1.1\n                       8.8.8.8\n\n"
+        ),
+        "Get-NetAdapter" => String::from(
+            "\nName                      InterfaceDescription                    ifIndex Status       MacAddress             LinkSpeed\n\
+            ----                      --------------------                    ------- ------       ----------             ---------\n\
+            Ethernet0                 Intel(R) Ethernet Connection I219-V          12 Up           00-15-5D-4A-3B-2C         1 Gbps\n\n"
+        ),
+        "Test-Connection" => {
+            let target: String = param(name, args, "ComputerName").cloned().unwrap_or_default();
+            if switch(args, "Quiet") {
+                return String::from("True\n");
+            }
+            let count: usize = param(name, args, "Count").and_then(|c| c.parse().ok()).unwrap_or(4);
+            let mut output: String = String::from(
+                "\nSource        Destination     IPV4Address      IPV6Address                              Bytes    Time(ms)\n\
+                ------        -----------     -----------      -----------                              -----    --------\n"
+            );
+            for i in 0..count {
+                output.push_str(&format!(
+                    "{:<14}{:<16}{:<17}{:<41}{:<9}{}\n", console.hostname, target, "142.250.72.14", "", 32, 12 + i % 3
+                ));
+            }
+            output
+        },
+        "Test-NetConnection" => {
+            let target: String = param(name, args, "ComputerName").cloned().unwrap_or_else(|| String::from("internetbeacon.msedge.net"));
+            let mut output: String = format!(
+                "\nComputerName     : {}\nRemoteAddress    : 142.250.72.14\n", target
+            );
+            match param(name, args, "Port") {
+                Some(port) => output.push_str(&format!(
+                    "RemotePort       : {}\nInterfaceAlias   : Ethernet0\nSourceAddress    : 192.168.1.25\n\
+                    TcpTestSucceeded : True\n\n",
+                    port,
+                )),
+                None => output.push_str(
+                    "InterfaceAlias         : Ethernet0\nSourceAddress          : 192.168.1.25\n\
+                    PingSucceeded          : True\nPingReplyDetails (RTT) : 12 ms\n\n"
+                ),
+            }
+            output
+        },
+        "Resolve-DnsName" => {
+            let target: String = param(name, args, "Name").cloned().unwrap_or_default();
+            format!(
+                "\nName                                           Type   TTL   Section    IPAddress\n\
+                ----                                           ----   ---   -------    ---------\n\
+                {:<47}A      142   Answer     142.250.72.14\n\n",
+                target,
+            )
+        },
+        "Get-ExecutionPolicy" => String::from("RemoteSigned\n"),
+        "Set-ExecutionPolicy" => match param(name, args, "ExecutionPolicy") {
+            Some(_) => String::new(),
+            None => String::from("Set-ExecutionPolicy : Missing an argument for parameter 'ExecutionPolicy'.\n"),
+        },
+        "Restart-Computer" | "Stop-Computer" | "Clear-Host" => String::new(),
+        "Get-ComputerInfo" => format!(
+            "\nWindowsBuildLabEx        : 19041.1.amd64fre.vb_release.191206-1406\nWindowsProductName       : Windows 10 Pro\n\
+            WindowsVersion           : 2009\nCsName                   : {}\nCsDomain                 : CORP\n\
+            OsArchitecture           : 64-bit\nOsTotalVisibleMemorySize : 16646772\n\n",
+            console.hostname,
+        ),
+        "Get-EventLog" => match param(name, args, "LogName") {
+            Some(log) => format!(
+                "\n   Index Time          EntryType   Source                 InstanceID Message\n   \
+                ----- ----          ---------   ------                 ---------- -------\n   \
+                48213 Oct 18 09:02  Information Service Control M...   1073748860 The {0} service entered the running state.\n   \
+                48212 Oct 18 08:58  Warning     DCOM                        10016 The application-specific permission settings do not grant...\n   \
+                48211 Oct 18 08:55  Error       Microsoft-Windows...          41 The system has rebooted without cleanly shutting down first.\n\n",
+                log,
+            ),
+            None => String::from("Get-EventLog : Missing an argument for parameter 'LogName'.\n"),
+        },
+        "Get-Help" | "Get-Command" => {
+            let topic: String = param(name, args, "Name").cloned().unwrap_or_else(|| String::from("Get-Help"));
+            match cmdlet_spec(&topic) {
+                Some(topic) => format!(
+                    "\nNAME\n    {}\n\nALIASES\n    {}\n\nPARAMETERS\n    {}\n\n",
+                    topic.name,
+                    if topic.aliases.is_empty() { String::from("None") } else { topic.aliases.join(", ") },
+                    topic.params.iter().map(|(p, _)| format!("-{}", p)).collect::<Vec<String>>().join(" "),
+                ),
+                None => format!("{} : The term '{}' is not recognized as the name of a cmdlet.\n", spec.name, topic),
+            }
+        },
+        // Filtering and formatting cmdlets only make sense later in a pipeline
+        _ => String::new(),
+    }
+}
+
+fn get_child_item(console: &Console, name: &str, args: &[String]) -> String {
+    let path: String = param(name, args, "Path").cloned().unwrap_or_else(|| console.cwd.clone());
+    let dir: &Directory = match console.resolve_dir(&path).and_then(directory) {
+        Some(dir) => dir,
+        None => return format!("Get-ChildItem : Cannot find path '{}' because it does not exist.\n", path),
+    };
+    let filter: Option<&String> = param(name, args, "Filter");
+    let mut output: String = String::new();
+    let mut listed: Vec<&Directory> = vec![dir];
+    if switch(args, "Recurse") {
+        let prefix: String = format!("{}\\", dir.path.trim_end_matches('\\')).to_ascii_lowercase();
+        listed.extend(crate::windows::DIRECTORIES.iter().filter(|d| d.path.to_ascii_lowercase().starts_with(&prefix)));
+    }
+    for dir in listed {
+        let entries: Vec<&(&str, Option<u64>)> = dir.entries.iter()
+            .filter(|(entry, size)| {
+                filter.is_none_or(|f| wildcard_match(f, entry))
+                    && !(switch(args, "File") && size.is_none())
+                    && !(switch(args, "Directory") && size.is_some())
+            })
+            .collect();
+        if entries.is_empty() {
+            continue;
+        }
+        output.push_str(&format!(
+            "\n\n    Directory: {}\n\n\nMode                 LastWriteTime         Length Name\n\
+            ----                 -------------         ------ ----\n",
+            dir.path,
+        ));
+        for (entry, size) in entries {
+            match size {
+                Some(size) => output.push_str(&format!("-a----        10/12/2026   8:15 AM {:>14} {}\n", size, entry)),
+                None => output.push_str(&format!("d-----        10/12/2026   8:15 AM                {}\n", entry)),
+            }
+        }
+    }
+    output.push('\n');
+    output
+}
+
+fn get_process(name: &str, args: &[String]) -> String {
+    let filter: Option<&String> = param(name, args, "Name");
+    let id: Option<&String> = param(name, args, "Id");
+    let processes: Vec<&(&str, u32, &str, u64)> = PROCESSES.iter()
+        .filter(|(image, pid, ..)| {
+            filter.is_none_or(|f| wildcard_match(f, image.trim_end_matches(".exe")))
+                && id.is_none_or(|id| pid.to_string() == *id)
+        })
+        .collect();
+    if processes.is_empty() {
+        return format!(
+            "Get-Process : Cannot find a process with the name \"{}\". Verify the process name and call the \
+            cmdlet again.\n",
+            filter.or(id).map_or("", |target| target.as_str()),
+        );
+    }
+    let mut output: String = String::from(
+        "\nHandles  NPM(K)    PM(K)      WS(K)     CPU(s)     Id  SI ProcessName\n\
+        -------  ------    -----      -----     ------     --  -- -----------\n"
+    );
+    for (image, pid, session, memory) in processes {
+        let session_number: u32 = if *session == "Console" { 1 } else { 0 };
+        output.push_str(&format!(
+            "{:>7} {:>7} {:>8} {:>10} {:>10.2} {:>6} {:>3} {}\n",
+            200 + pid % 700, 12 + pid % 30, memory / 2, memory, *pid as f64 / 97.0, pid, session_number,
+            image.trim_end_matches(".exe"),
+        ));
+    }
+    output.push('\n');
+    output
+}
+
+fn get_service(name: &str, args: &[String]) -> String {
+    let filter: Option<&String> = param(name, args, "Name");
+    let services: Vec<&(&str, &str, &str)> = SERVICES.iter()
+        .filter(|(_, service, _)| filter.is_none_or(|f| wildcard_match(f, service)))
+        .collect();
+    if services.is_empty() {
+        return format!(
+            "Get-Service : Cannot find any service with service name '{}'.\n",
+            filter.map_or("", |f| f.as_str()),
+        );
+    }
+    let mut output: String = String::from("\nStatus   Name               DisplayName\n------   ----               -----------\n");
+    for (status, service, display) in services {
+        output.push_str(&format!("{:<9}{:<19}{}\n", status, service, display));
+    }
+    output.push('\n');
+    output
+}
@@ -0,0 +1,433 @@
+use crate::windows::Interpreter;
+
+/// How a `cmd` program takes its switches.
+#[derive(Debug)]
+pub struct ProgramSpec {
+    pub name: &'static str,
+    /// Other names for the program, e.g., `chdir` for `cd`.
+    pub aliases: &'static [&'static str],
+    /// Whether single-letter switches may be bundled after a dash, as in `netstat -ano`.
+    pub clusters: bool,
+    /// Switches that take the following argument as their value, as in `shutdown /t 60`.
+    pub value_switches: &'static [&'static str],
+}
+
+const fn program(name: &'static str, value_switches: &'static [&'static str]) -> ProgramSpec {
+    ProgramSpec { name, aliases: &[], clusters: false, value_switches }
+}
+
+const PROGRAMS: &[ProgramSpec] = &[
+    program("ipconfig", &[]),
+    ProgramSpec { name: "netstat", aliases: &[], clusters: true, value_switches: &["p"] },
+    program("ping", &["n", "l", "w", "i"]),
+    program("tracert", &["h", "w"]),
+    program("pathping", &["h", "q", "w"]),
+    program("nslookup", &[]),
+    program("sfc", &["scanfile", "verifyfile", "offbootdir", "offwindir"]),
+    program("chkdsk", &["l"]),
+    program("gpupdate", &["target", "wait"]),
+    program("gpresult", &["s", "u", "p", "scope", "user", "h"]),
+    program("robocopy", &[]),
+    program("xcopy", &[]),
+    program("copy", &[]),
+    ProgramSpec { name: "del", aliases: &["erase"], clusters: false, value_switches: &[] },
+    ProgramSpec { name: "md", aliases: &["mkdir"], clusters: false, value_switches: &[] },
+    ProgramSpec { name: "rd", aliases: &["rmdir"], clusters: false, value_switches: &[] },
+    ProgramSpec { name: "cd", aliases: &["chdir"], clusters: false, value_switches: &[] },
+    program("dir", &[]),
+    program("shutdown", &["t", "c", "m", "d"]),
+    program("tasklist", &["fi", "s", "u", "p", "fo", "m"]),
+    program("taskkill", &["pid", "im", "fi", "s", "u", "p"]),
+    program("hostname", &[]),
+    program("whoami", &[]),
+    program("ver", &[]),
+    ProgramSpec { name: "cls", aliases: &[], clusters: false, value_switches: &[] },
+    program("net", &[]),
+    program("format", &["fs", "v", "a"]),
+    program("diskpart", &["s"]),
+    program("bootrec", &[]),
+    program("systeminfo", &["s", "u", "p", "fo"]),
+    program("winver", &[]),
+];
+
+/// How a PowerShell cmdlet takes its parameters.
+#[derive(Debug)]
+pub struct CmdletSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// Each parameter's name and whether it takes a value.
+    pub params: &'static [(&'static str, bool)],
+    /// The parameters that unnamed arguments are bound to, in order.
+    pub positional: &'static [&'static str],
+}
+
+/// Parameters that every cmdlet accepts.
+const COMMON_PARAMS: &[(&str, bool)] = &[
+    ("Verbose", false), ("Debug", false), ("ErrorAction", true), ("WarningAction", true), ("WhatIf", false),
+    ("Confirm", false), ("OutVariable", true),
+];
+
+const CMDLETS: &[CmdletSpec] = &[
+    CmdletSpec {
+        name: "Get-ChildItem",
+        aliases: &["gci", "ls", "dir"],
+        params: &[
+            ("Path", true), ("Filter", true), ("Include", true), ("Exclude", true), ("Recurse", false),
+            ("Force", false), ("File", false), ("Directory", false), ("Name", false), ("Depth", true),
+        ],
+        positional: &["Path", "Filter"],
+    },
+    CmdletSpec { name: "Get-Location", aliases: &["gl", "pwd"], params: &[], positional: &[] },
+    CmdletSpec { name: "Set-Location", aliases: &["sl", "cd", "chdir"], params: &[("Path", true)], positional: &["Path"] },
+    CmdletSpec {
+        name: "Copy-Item",
+        aliases: &["cpi", "cp", "copy"],
+        params: &[("Path", true), ("Destination", true), ("Recurse", false), ("Force", false)],
+        positional: &["Path", "Destination"],
+    },
+    CmdletSpec {
+        name: "Move-Item",
+        aliases: &["mi", "mv", "move"],
+        params: &[("Path", true), ("Destination", true), ("Force", false)],
+        positional: &["Path", "Destination"],
+    },
+    CmdletSpec {
+        name: "Remove-Item",
+        aliases: &["ri", "rm", "del", "erase", "rd", "rmdir"],
+        params: &[("Path", true), ("Recurse", false), ("Force", false)],
+        positional: &["Path"],
+    },
+    CmdletSpec {
+        name: "New-Item",
+        aliases: &["ni"],
+        params: &[("Path", true), ("Name", true), ("ItemType", true), ("Value", true), ("Force", false)],
+        positional: &["Path"],
+    },
+    CmdletSpec { name: "Get-Content", aliases: &["gc", "cat", "type"], params: &[("Path", true), ("Tail", true), ("TotalCount", true), ("Wait", false)], positional: &["Path"] },
+    CmdletSpec { name: "Get-Process", aliases: &["gps", "ps"], params: &[("Name", true), ("Id", true)], positional: &["Name"] },
+    CmdletSpec { name: "Stop-Process", aliases: &["spps", "kill"], params: &[("Name", true), ("Id", true), ("Force", false)], positional: &["Id"] },
+    CmdletSpec { name: "Get-Service", aliases: &["gsv"], params: &[("Name", true), ("DisplayName", true)], positional: &["Name"] },
+    CmdletSpec { name: "Start-Service", aliases: &["sasv"], params: &[("Name", true)], positional: &["Name"] },
+    CmdletSpec { name: "Stop-Service", aliases: &["spsv"], params: &[("Name", true), ("Force", false)], positional: &["Name"] },
+    CmdletSpec { name: "Restart-Service", aliases: &[], params: &[("Name", true), ("Force", false)], positional: &["Name"] },
+    CmdletSpec {
+        name: "Set-Service",
+        aliases: &[],
+        params: &[("Name", true), ("StartupType", true), ("Status", true)],
+        positional: &["Name"],
+    },
+    CmdletSpec { name: "Get-NetIPAddress", aliases: &[], params: &[("AddressFamily", true), ("InterfaceAlias", true)], positional: &[] },
+    CmdletSpec { name: "Get-NetIPConfiguration", aliases: &["gip"], params: &[("Detailed", false), ("All", false)], positional: &[] },
+    CmdletSpec { name: "Get-NetAdapter", aliases: &[], params: &[("Name", true)], positional: &["Name"] },
+    CmdletSpec {
+        name: "Test-Connection",
+        aliases: &[],
+        params: &[("ComputerName", true), ("Count", true), ("Quiet", false)],
+        positional: &["ComputerName"],
+    },
+    CmdletSpec {
+        name: "Test-NetConnection",
+        aliases: &["tnc"],
+        params: &[("ComputerName", true), ("Port", true), ("TraceRoute", false), ("InformationLevel", true)],
+        positional: &["ComputerName"],
+    },
+    CmdletSpec { name: "Resolve-DnsName", aliases: &[], params: &[("Name", true), ("Type", true), ("Server", true)], positional: &["Name", "Type"] },
+    CmdletSpec { name: "Get-ExecutionPolicy", aliases: &[], params: &[("Scope", true), ("List", false)], positional: &[] },
+    CmdletSpec {
+        name: "Set-ExecutionPolicy",
+        aliases: &[],
+        params: &[("ExecutionPolicy", true), ("Scope", true), ("Force", false)],
+        positional: &["ExecutionPolicy", "Scope"],
+    },
+    CmdletSpec { name: "Restart-Computer", aliases: &[], params: &[("ComputerName", true), ("Force", false)], positional: &["ComputerName"] },
+    CmdletSpec { name: "Stop-Computer", aliases: &[], params: &[("ComputerName", true), ("Force", false)], positional: &["ComputerName"] },
+    CmdletSpec { name: "Get-ComputerInfo", aliases: &["gin"], params: &[("Property", true)], positional: &["Property"] },
+    CmdletSpec {
+        name: "Get-EventLog",
+        aliases: &[],
+        params: &[("LogName", true), ("Newest", true), ("EntryType", true), ("Source", true)],
+        positional: &["LogName"],
+    },
+    CmdletSpec { name: "Get-Help", aliases: &["help"], params: &[("Name", true), ("Full", false), ("Examples", false), ("Detailed", false)], positional: &["Name"] },
+    CmdletSpec { name: "Get-Command", aliases: &["gcm"], params: &[("Name", true), ("Verb", true), ("Noun", true), ("Module", true)], positional: &["Name"] },
+    CmdletSpec { name: "Sort-Object", aliases: &["sort"], params: &[("Property", true), ("Descending", false), ("Unique", false)], positional: &["Property"] },
+    CmdletSpec { name: "Select-Object", aliases: &["select"], params: &[("Property", true), ("First", true), ("Last", true)], positional: &["Property"] },
+    CmdletSpec { name: "Where-Object", aliases: &["where", "?"], params: &[("FilterScript", true)], positional: &["FilterScript"] },
+    CmdletSpec { name: "Format-List", aliases: &["fl"], params: &[("Property", true)], positional: &["Property"] },
+    CmdletSpec { name: "Format-Table", aliases: &["ft"], params: &[("Property", true), ("AutoSize", false)], positional: &["Property"] },
+    CmdletSpec { name: "Clear-Host", aliases: &["cls", "clear"], params: &[], positional: &[] },
+];
+
+/// Looks up a `cmd` program by name or alias, ignoring case and a trailing `.exe`.
+pub fn program_spec(name: &str) -> Option<&'static ProgramSpec> {
+    let name: String = name.to_ascii_lowercase();
+    let name: &str = name.strip_suffix(".exe").unwrap_or(&name);
+    PROGRAMS.iter().find(|spec| spec.name == name || spec.aliases.contains(&name))
+}
+
+/// Looks up a PowerShell cmdlet by name or alias, ignoring case.
+pub fn cmdlet_spec(name: &str) -> Option<&'static CmdletSpec> {
+    CMDLETS.iter().find(|spec| {
+        spec.name.eq_ignore_ascii_case(name) || spec.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Splits a command line into words and `|`s. Double quotes group words in both interpreters,
+/// and PowerShell also accepts single quotes. `{ ... }` script blocks are kept as one word.
+pub fn tokenize(line: &str, interpreter: Interpreter) -> Result<Vec<String>, String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    let mut in_word: bool = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            },
+            '|' => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+                words.push(String::from("|"));
+            },
+            '"' | '\'' if c == '"' || interpreter == Interpreter::PowerShell => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => current.push(ch),
+                        None if c == '"' && interpreter == Interpreter::Cmd => break,
+                        None => return Err(String::from("The string is missing the terminator.")),
+                    }
+                }
+            },
+            '{' if interpreter == Interpreter::PowerShell => {
+                in_word = true;
+                current.push(c);
+                let mut depth: usize = 1;
+                for ch in chars.by_ref() {
+                    current.push(ch);
+                    match ch {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {},
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                if depth != 0 {
+                    return Err(String::from("Missing closing '}' in statement block."));
+                }
+            },
+            _ => {
+                in_word = true;
+                current.push(c);
+            },
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+/// One command from a Windows command line, normalized so that equivalent ways of writing it
+/// compare equal. Names and switches are lowercased (Windows ignores case), `/x` and `-x` are the
+/// same switch, bundled switches are separated, cmdlet aliases are expanded, abbreviated
+/// parameters are completed, and positional arguments are bound to their parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinCommand {
+    pub program: String,
+    /// Each switch or parameter with its value, sorted and without duplicates.
+    pub switches: Vec<(String, Option<String>)>,
+    /// Arguments that aren't switches, lowercased.
+    pub operands: Vec<String>,
+}
+
+impl WinCommand {
+    /// Normalizes a `cmd` program's arguments.
+    fn from_program(name: &str, args: &[String]) -> Self {
+        let spec: Option<&ProgramSpec> = program_spec(name);
+        let program: String = match spec {
+            Some(spec) => spec.name.to_string(),
+            None => name.to_ascii_lowercase().trim_end_matches(".exe").to_string(),
+        };
+        let mut command: WinCommand = WinCommand { program, switches: Vec::new(), operands: Vec::new() };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let lower: String = arg.to_ascii_lowercase();
+            let body: &str = match lower.strip_prefix('/').or_else(|| lower.strip_prefix('-')) {
+                Some(body) if !body.is_empty() && !arg.contains('\\') => body,
+                _ => {
+                    command.operands.push(lower);
+                    continue;
+                },
+            };
+
+            if spec.is_some_and(|spec| spec.clusters) {
+                for c in body.chars() {
+                    let name: String = c.to_string();
+                    let takes_value: bool = spec.is_some_and(|spec| spec.value_switches.contains(&name.as_str()));
+                    let value: Option<String> = if takes_value { iter.next().map(|v| v.to_ascii_lowercase()) } else { None };
+                    command.switches.push((name, value));
+                }
+                continue;
+            }
+
+            let (name, value) = match body.split_once(':') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None if spec.is_some_and(|spec| spec.value_switches.contains(&body)) => {
+                    (body.to_string(), iter.next().map(|v| v.to_ascii_lowercase()))
+                },
+                None => (body.to_string(), None),
+            };
+            command.switches.push((name, value));
+        }
+        command.switches.sort();
+        command.switches.dedup();
+        command
+    }
+
+    /// Normalizes a cmdlet's arguments.
+    fn from_cmdlet(spec: &CmdletSpec, args: &[String]) -> Result<Self, String> {
+        let mut command: WinCommand = WinCommand {
+            program: spec.name.to_ascii_lowercase(),
+            switches: Vec::new(),
+            operands: Vec::new(),
+        };
+        let params: Vec<&(&str, bool)> = spec.params.iter().chain(COMMON_PARAMS).collect();
+        let mut positional = spec.positional.iter();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let written: &str = match arg.strip_prefix('-') {
+                Some(written) if written.starts_with(|c: char| c.is_ascii_alphabetic()) => written,
+                _ => {
+                    match positional.next() {
+                        Some(param) => command.switches.push((param.to_ascii_lowercase(), Some(arg.to_ascii_lowercase()))),
+                        None => command.operands.push(arg.to_ascii_lowercase()),
+                    }
+                    continue;
+                },
+            };
+            let (written, inline_value) = match written.split_once(':') {
+                Some((name, value)) => (name, Some(value.to_ascii_lowercase())),
+                None => (written, None),
+            };
+
+            let exact = params.iter().find(|(name, _)| name.eq_ignore_ascii_case(written));
+            let matches: Vec<&&(&str, bool)> = params.iter()
+                .filter(|(name, _)| name.to_ascii_lowercase().starts_with(&written.to_ascii_lowercase()))
+                .collect();
+            let (name, takes_value) = match (exact, matches.as_slice()) {
+                (Some(param), _) => **param,
+                (None, [param]) => ***param,
+                (None, []) => {
+                    command.switches.push((written.to_ascii_lowercase(), inline_value));
+                    continue;
+                },
+                (None, _) => {
+                    return Err(format!(
+                        "{} : Parameter cannot be processed because the parameter name '{}' is ambiguous.",
+                        spec.name, written,
+                    ));
+                },
+            };
+            let value: Option<String> = match (takes_value, inline_value) {
+                (_, Some(value)) => Some(value),
+                (true, None) => match iter.next() {
+                    Some(value) => Some(value.to_ascii_lowercase()),
+                    None => return Err(format!("{} : Missing an argument for parameter '{}'.", spec.name, name)),
+                },
+                (false, None) => None,
+            };
+            command.switches.push((name.to_ascii_lowercase(), value));
+        }
+        command.switches.sort();
+        command.switches.dedup();
+        Ok(command)
+    }
+}
+
+/// Parses and normalizes a Windows command line into the commands of its pipeline. In
+/// PowerShell, cmdlets and their aliases are recognized; anything else is treated as a `cmd`
+/// program, since PowerShell runs those too.
+pub fn parse_line(line: &str, interpreter: Interpreter) -> Result<Vec<WinCommand>, String> {
+    let words: Vec<String> = tokenize(line, interpreter)?;
+    let mut commands: Vec<WinCommand> = Vec::new();
+    for segment in words.split(|word| word == "|") {
+        let (name, args) = match segment.split_first() {
+            Some(parts) => parts,
+            None => return Err(String::from("An empty pipe element is not allowed.")),
+        };
+        let command: WinCommand = match cmdlet_spec(name) {
+            Some(spec) if interpreter == Interpreter::PowerShell => WinCommand::from_cmdlet(spec, args)?,
+            _ => WinCommand::from_program(name, args),
+        };
+        commands.push(command);
+    }
+    Ok(commands)
+}
+
+/// Grades a Windows command line `response` against the `CorrectAnswer` of a question, where each
+/// line of `answers` is an acceptable answer. If the response isn't equivalent to any of them,
+/// the problems found when comparing against the closest answer are returned.
+pub fn grade(answers: &str, response: &str, interpreter: Interpreter) -> Result<(), Vec<String>> {
+    let response: Vec<WinCommand> = match parse_line(response, interpreter) {
+        Ok(commands) if !commands.is_empty() => commands,
+        Ok(_) => return Err(vec![String::from("no command was entered")]),
+        Err(e) => return Err(vec![e]),
+    };
+
+    let mut closest: Option<Vec<String>> = None;
+    for answer in answers.lines().map(str::trim).filter(|answer| !answer.is_empty()) {
+        let expected: Vec<WinCommand> = match parse_line(answer, interpreter) {
+            Ok(commands) => commands,
+            Err(e) => return Err(vec![format!("the correct answer can't be parsed ({})", e)]),
+        };
+        let problems: Vec<String> = compare(&expected, &response);
+        if problems.is_empty() {
+            return Ok(());
+        }
+        if closest.as_ref().is_none_or(|closest| problems.len() < closest.len()) {
+            closest = Some(problems);
+        }
+    }
+    Err(closest.unwrap_or_else(|| vec![String::from("the question has no correct answer")]))
+}
+
+/// Compares the commands of a response against those of one expected answer.
+fn compare(expected: &[WinCommand], actual: &[WinCommand]) -> Vec<String> {
+    if expected.len() != actual.len() {
+        return vec![format!(
+            "the answer should be a pipeline of {} command(s), but {} were given", expected.len(), actual.len(),
+        )];
+    }
+    let mut problems: Vec<String> = Vec::new();
+    for (expected, actual) in expected.iter().zip(actual) {
+        if expected.program != actual.program {
+            problems.push(format!("expected {} but found {}", expected.program, actual.program));
+            continue;
+        }
+        for (name, value) in &expected.switches {
+            match actual.switches.iter().find(|(other, _)| other == name) {
+                None => problems.push(format!("{} is missing the {} switch", expected.program, name)),
+                Some((_, other)) if other != value => {
+                    problems.push(format!("{} has the wrong value for {}", expected.program, name));
+                },
+                Some(_) => {},
+            }
+        }
+        for (name, _) in actual.switches.iter().filter(|(name, _)| !expected.switches.iter().any(|(n, _)| n == name)) {
+            problems.push(format!("{} doesn't need the {} switch", expected.program, name));
+        }
+        if expected.operands != actual.operands {
+            problems.push(format!("the arguments to {} aren't right", expected.program));
+        }
+    }
+    problems
+}